@@ -29,7 +29,7 @@ extern "C" {
 /// Check if the app has accessibility permissions without prompting
 pub fn check_accessibility() -> bool {
     let result = unsafe { AXIsProcessTrusted() };
-    println!("[Permissions] AXIsProcessTrusted() = {}", result);
+    crate::logs::debug(&format!("[Permissions] AXIsProcessTrusted() = {}", result));
     result
 }
 
@@ -51,7 +51,7 @@ pub fn request_accessibility() -> bool {
 /// Check if the app has screen recording permissions without prompting
 pub fn check_screen_recording() -> bool {
     let result = unsafe { CGPreflightScreenCaptureAccess() };
-    println!("[Permissions] CGPreflightScreenCaptureAccess() = {}", result);
+    crate::logs::debug(&format!("[Permissions] CGPreflightScreenCaptureAccess() = {}", result));
     result
 }
 
@@ -1,7 +1,6 @@
 /// Browser automation module via AppleScript
 /// Supports Chrome, Safari, Arc, and Firefox
 
-use std::process::Command;
 use serde::{Serialize, Deserialize};
 
 /// Supported browsers
@@ -295,19 +294,7 @@ pub fn close_tab(browser: Browser) -> Result<(), String> {
 /// Execute AppleScript and return output
 #[cfg(target_os = "macos")]
 fn execute_applescript(script: &str) -> Result<String, String> {
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .map_err(|e| format!("Failed to execute AppleScript: {}", e))?;
-
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        Ok(stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        Err(format!("AppleScript error: {}", stderr))
-    }
+    crate::native_applescript::run(script, crate::native_applescript::DEFAULT_TIMEOUT)
 }
 
 /// Detect which browser is currently active
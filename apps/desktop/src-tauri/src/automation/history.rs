@@ -0,0 +1,387 @@
+/// Automation task history
+/// Bounded, persisted log of executed tasks and their results for the user-facing audit view
+
+use crate::automation::queue::{AutomationTask, TaskCommand, TaskPriority, TaskResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Number of tasks kept in the history
+pub const DEFAULT_HISTORY_CAPACITY: usize = 500;
+
+/// Longest error message kept per entry (characters)
+const MAX_ERROR_CHARS: usize = 500;
+
+/// One executed task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHistoryEntry {
+    pub task_id: String,
+    /// Command type (click, type, browser_navigate, ...)
+    pub task_type: String,
+    /// Redacted description - never includes typed text, scripts or parameters
+    pub summary: String,
+    pub priority: TaskPriority,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub started_at: DateTime<Utc>,
+}
+
+impl TaskHistoryEntry {
+    pub fn new(task: &AutomationTask, result: &TaskResult, started_at: DateTime<Utc>, duration_ms: u64) -> Self {
+        let (task_type, summary) = describe_command(&task.command);
+        Self {
+            task_id: task.id.clone(),
+            task_type: task_type.to_string(),
+            summary,
+            priority: task.priority,
+            success: result.success,
+            error: result
+                .error
+                .as_deref()
+                .map(|e| crate::text::truncate_chars(e, MAX_ERROR_CHARS).to_string()),
+            duration_ms,
+            started_at,
+        }
+    }
+}
+
+impl TaskHistoryEntry {
+    /// Audit entry for a trust level change (not a task)
+    pub fn trust_change(level: crate::automation::trust::TrustLevel, note: &str, at: DateTime<Utc>) -> Self {
+        Self {
+            task_id: uuid::Uuid::new_v4().to_string(),
+            task_type: "trust_level".to_string(),
+            summary: format!("{} ({})", level.to_str(), note),
+            priority: TaskPriority::Normal,
+            success: true,
+            error: None,
+            duration_ms: 0,
+            started_at: at,
+        }
+    }
+}
+
+/// Type name and a redacted summary of a command
+fn describe_command(command: &TaskCommand) -> (&'static str, String) {
+    match command {
+        TaskCommand::Click { x, y, button } => ("click", format!("{} click at ({}, {})", button, x, y)),
+        TaskCommand::Type { text } => ("type", format!("{} characters", text.chars().count())),
+        TaskCommand::Hotkey { modifiers, key } => {
+            let mut keys = modifiers.clone();
+            keys.push(key.clone());
+            ("hotkey", keys.join("+"))
+        }
+        TaskCommand::Screenshot { .. } => ("screenshot", String::new()),
+        TaskCommand::ScreenshotWindow { app_name, .. } => ("screenshot_window", app_name.clone()),
+        TaskCommand::BrowserNavigate { browser, url } => {
+            ("browser_navigate", format!("{} -> {}", browser, redact_url(url)))
+        }
+        TaskCommand::BrowserGetUrl { browser } => ("browser_get_url", browser.clone()),
+        TaskCommand::Wait { milliseconds } => ("wait", format!("{}ms", milliseconds)),
+        TaskCommand::Custom { name, .. } => ("custom", name.clone()),
+        TaskCommand::ClickText { text, occurrence, .. } => {
+            ("click_text", format!("\"{}\" #{}", text, occurrence))
+        }
+    }
+}
+
+/// Keep only scheme, host and path - query strings and credentials may hold secrets
+fn redact_url(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => format!(
+            "{}://{}{}",
+            parsed.scheme(),
+            parsed.host_str().unwrap_or(""),
+            parsed.path()
+        ),
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// Recent task history, newest first
+/// Persisted as JSON lines, oldest first: each entry is appended, and the file
+/// is only rewritten once it holds twice the capacity
+pub struct TaskHistory {
+    entries: VecDeque<TaskHistoryEntry>,
+    capacity: usize,
+    /// Where the history is persisted (None = memory only)
+    path: Option<PathBuf>,
+    /// Entries in the file, including ones already dropped from `entries`
+    file_entries: usize,
+}
+
+impl TaskHistory {
+    fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("observer")
+            .join("task_history.json")
+    }
+
+    /// History that is not persisted
+    pub fn in_memory(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            path: None,
+            file_entries: 0,
+        }
+    }
+
+    /// Load the history from `path` (empty if missing or unreadable)
+    /// A file in the old format (one JSON array, newest first) is rewritten as lines
+    pub fn load(path: PathBuf, capacity: usize) -> Self {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let legacy = content.trim_start().starts_with('[');
+        let (entries, file_entries): (VecDeque<TaskHistoryEntry>, usize) = if legacy {
+            let entries: VecDeque<TaskHistoryEntry> = serde_json::from_str(&content).unwrap_or_default();
+            let count = entries.len();
+            (entries, count)
+        } else {
+            let lines: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+            let entries = lines
+                .iter()
+                .rev()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect();
+            (entries, lines.len())
+        };
+
+        let mut history = Self {
+            entries,
+            capacity,
+            path: Some(path),
+            file_entries,
+        };
+        history.entries.truncate(capacity);
+        if legacy {
+            if let Err(e) = history.compact() {
+                crate::logs::error(&format!("[History] {}", e));
+            }
+        }
+        history
+    }
+
+    /// Load the history from config_dir/observer/task_history.json
+    pub fn load_default() -> Self {
+        Self::load(Self::default_path(), DEFAULT_HISTORY_CAPACITY)
+    }
+
+    /// Add an entry, dropping the oldest beyond capacity
+    pub fn record(&mut self, entry: TaskHistoryEntry) {
+        let persisted = self.append(&entry);
+        self.entries.push_front(entry);
+        self.entries.truncate(self.capacity);
+
+        let persisted = persisted.and_then(|()| {
+            if self.file_entries > self.capacity * 2 {
+                self.compact()
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = persisted {
+            crate::logs::error(&format!("[History] {}", e));
+        }
+    }
+
+    /// Up to `limit` most recent entries, newest first
+    pub fn recent(&self, limit: usize) -> Vec<TaskHistoryEntry> {
+        self.entries.iter().take(limit).cloned().collect()
+    }
+
+    /// Add one line to the history file
+    fn append(&mut self, entry: &TaskHistoryEntry) -> Result<(), String> {
+        use std::io::Write;
+
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize task history: {}", e))?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write task history: {}", e))?;
+        self.file_entries += 1;
+        Ok(())
+    }
+
+    /// Rewrite the history file with only the kept entries
+    fn compact(&mut self) -> Result<(), String> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+
+        let mut content = String::new();
+        for entry in self.entries.iter().rev() {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize task history: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        crate::config::write_atomic(path, content.as_bytes())
+            .map_err(|e| format!("Failed to write task history: {}", e))?;
+        self.file_entries = self.entries.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::queue::AutomationQueue;
+
+    fn wait_task(ms: u64) -> AutomationTask {
+        AutomationTask::new(TaskCommand::Wait { milliseconds: ms }, TaskPriority::Normal)
+    }
+
+    #[tokio::test]
+    async fn test_executed_tasks_newest_first() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+
+        let first = wait_task(1);
+        let second = wait_task(2);
+        queue.run_task(first.clone()).await;
+        queue.run_task(second.clone()).await;
+
+        let history = queue.history(10).await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].task_id, second.id);
+        assert_eq!(history[1].task_id, first.id);
+        assert_eq!(history[0].task_type, "wait");
+        assert!(history[0].success);
+        assert!(history[0].started_at >= history[1].started_at);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = TaskHistory::in_memory(3);
+        let tasks: Vec<AutomationTask> = (0..5).map(wait_task).collect();
+        for task in &tasks {
+            let result = TaskResult {
+                task_id: task.id.clone(),
+                success: true,
+                error: None,
+                output: None,
+            };
+            history.record(TaskHistoryEntry::new(task, &result, Utc::now(), 0));
+        }
+
+        let recent = history.recent(10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].task_id, tasks[4].id);
+        assert_eq!(recent[2].task_id, tasks[2].id);
+        assert_eq!(history.recent(1).len(), 1);
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let typed = AutomationTask::new(
+            TaskCommand::Type { text: "hunter2".to_string() },
+            TaskPriority::High,
+        );
+        let navigate = AutomationTask::new(
+            TaskCommand::BrowserNavigate {
+                browser: "safari".to_string(),
+                url: "https://user:pw@example.com/reset?token=abc".to_string(),
+            },
+            TaskPriority::Normal,
+        );
+        let result = TaskResult {
+            task_id: String::new(),
+            success: true,
+            error: None,
+            output: None,
+        };
+
+        let entry = TaskHistoryEntry::new(&typed, &result, Utc::now(), 5);
+        assert_eq!(entry.summary, "7 characters");
+        let entry = TaskHistoryEntry::new(&navigate, &result, Utc::now(), 5);
+        assert_eq!(entry.summary, "safari -> https://example.com/reset");
+    }
+
+    #[test]
+    fn test_history_persists() {
+        let path = std::env::temp_dir().join(format!("observer_history_{}.json", uuid::Uuid::new_v4()));
+        let task = wait_task(1);
+        let result = TaskResult {
+            task_id: task.id.clone(),
+            success: false,
+            error: Some("boom".to_string()),
+            output: None,
+        };
+
+        TaskHistory::load(path.clone(), 10).record(TaskHistoryEntry::new(&task, &result, Utc::now(), 1));
+        let reloaded = TaskHistory::load(path.clone(), 10).recent(10);
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].error.as_deref(), Some("boom"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_history_file_is_appended_then_compacted() {
+        let path = std::env::temp_dir().join(format!("observer_history_{}.json", uuid::Uuid::new_v4()));
+        let result = TaskResult {
+            task_id: String::new(),
+            success: true,
+            error: None,
+            output: None,
+        };
+        let line_count = || std::fs::read_to_string(&path).unwrap().lines().count();
+
+        let mut history = TaskHistory::load(path.clone(), 2);
+        let tasks: Vec<AutomationTask> = (0..5).map(wait_task).collect();
+        for task in &tasks[..4] {
+            history.record(TaskHistoryEntry::new(task, &result, Utc::now(), 0));
+        }
+        // Appended only, up to twice the capacity
+        assert_eq!(line_count(), 4);
+
+        history.record(TaskHistoryEntry::new(&tasks[4], &result, Utc::now(), 0));
+        assert_eq!(line_count(), 2);
+
+        let reloaded = TaskHistory::load(path.clone(), 2).recent(10);
+        assert_eq!(reloaded.len(), 2);
+        assert_eq!(reloaded[0].task_id, tasks[4].id);
+        assert_eq!(reloaded[1].task_id, tasks[3].id);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_old_history_format_is_converted() {
+        let path = std::env::temp_dir().join(format!("observer_history_{}.json", uuid::Uuid::new_v4()));
+        let result = TaskResult {
+            task_id: String::new(),
+            success: true,
+            error: None,
+            output: None,
+        };
+        let (older, newer) = (wait_task(1), wait_task(2));
+        let entries = vec![
+            TaskHistoryEntry::new(&newer, &result, Utc::now(), 0),
+            TaskHistoryEntry::new(&older, &result, Utc::now(), 0),
+        ];
+        std::fs::write(&path, serde_json::to_string(&entries).unwrap()).unwrap();
+
+        let recent = TaskHistory::load(path.clone(), 10).recent(10);
+        assert_eq!(recent[0].task_id, newer.id);
+        assert_eq!(recent[1].task_id, older.id);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert_eq!(TaskHistory::load(path.clone(), 10).recent(10)[0].task_id, newer.id);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -13,6 +13,8 @@ fn create_enigo() -> Result<Enigo, String> {
 
 /// Click at specific screen coordinates
 pub fn click_at(x: i32, y: i32, button: MouseButton) -> Result<(), String> {
+    // Not user activity for idle detection
+    let _synthetic = crate::collector::synthetic_input();
     let mut enigo = create_enigo()?;
 
     // Move to position
@@ -34,6 +36,7 @@ pub fn click_at(x: i32, y: i32, button: MouseButton) -> Result<(), String> {
 
 /// Type text at current cursor position
 pub fn type_text(text: &str) -> Result<(), String> {
+    let _synthetic = crate::collector::synthetic_input();
     let mut enigo = create_enigo()?;
 
     enigo.text(text)
@@ -44,6 +47,7 @@ pub fn type_text(text: &str) -> Result<(), String> {
 
 /// Press a keyboard hotkey (e.g., Command+C, Control+V)
 pub fn press_hotkey(modifiers: &[Modifier], key: &str) -> Result<(), String> {
+    let _synthetic = crate::collector::synthetic_input();
     let mut enigo = create_enigo()?;
 
     // Press modifiers
@@ -80,6 +84,7 @@ pub fn press_hotkey(modifiers: &[Modifier], key: &str) -> Result<(), String> {
 
 /// Move mouse to coordinates without clicking
 pub fn move_mouse(x: i32, y: i32) -> Result<(), String> {
+    let _synthetic = crate::collector::synthetic_input();
     let mut enigo = create_enigo()?;
 
     enigo.move_mouse(x, y, enigo::Coordinate::Abs)
@@ -90,6 +95,7 @@ pub fn move_mouse(x: i32, y: i32) -> Result<(), String> {
 
 /// Scroll the mouse wheel
 pub fn scroll(amount: i32, axis: ScrollAxis) -> Result<(), String> {
+    let _synthetic = crate::collector::synthetic_input();
     let mut enigo = create_enigo()?;
 
     match axis {
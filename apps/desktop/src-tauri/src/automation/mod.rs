@@ -8,6 +8,7 @@ pub mod accessibility_ffi;
 pub mod input;
 pub mod screen;
 pub mod browser;
+pub mod history;
 pub mod queue;
 pub mod trust;
 pub mod ocr;
@@ -17,7 +18,7 @@ pub mod sync;
 #[allow(unused_imports)]
 pub use queue::{AutomationQueue, TaskPriority, QueueStatus};
 #[allow(unused_imports)]
-pub use trust::TrustLevel;
+pub use trust::{TrustLevel, TrustManager};
 #[allow(unused_imports)]
 pub use input::{click_at, type_text, press_hotkey};
 #[allow(unused_imports)]
@@ -1,8 +1,26 @@
 /// OCR module for text extraction from images
-/// Uses local OCR via Swift Vision framework on macOS with cloud fallback
+/// Uses local OCR via Swift Vision framework on macOS, with cloud OCR as an opt-in fallback
 
+use crate::automation::screen::Rect;
 use image::RgbaImage;
+use once_cell::sync::OnceCell;
 use serde::{Serialize, Deserialize};
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Shown when neither local nor cloud OCR can run
+pub const OCR_UNAVAILABLE_MESSAGE: &str =
+    "Local OCR needs the Swift toolchain. Install Xcode Command Line Tools \
+    (xcode-select --install) for local OCR, or turn on cloud OCR and configure an Observer API key.";
+
+/// OCR endpoint path on the Observer server
+const CLOUD_OCR_PATH: &str = "/api/v1/ocr";
+
+/// Word rendered into the cloud OCR test image
+const TEST_IMAGE_TEXT: &str = "OBSERVER";
+
+/// Cached result of the Swift toolchain check
+static SWIFT_TOOLCHAIN: ToolchainCheck = ToolchainCheck::new();
 
 /// OCR result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,7 +31,9 @@ pub struct OcrResult {
     pub bounding_boxes: Vec<BoundingBox>,
 }
 
-/// Text bounding box
+/// Text bounding box, in pixels of the OCR'd image with the origin at its top-left
+/// (Vision's normalized boxes are converted); on HiDPI displays a pixel is
+/// smaller than the points input uses (see `click_point`)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub text: String,
@@ -24,17 +44,196 @@ pub struct BoundingBox {
     pub confidence: f32,
 }
 
-/// Extract text from image using local OCR
+impl BoundingBox {
+    /// Convert a Vision box (normalized 0-1, origin at the bottom-left) to
+    /// pixels with the origin at the top-left of a `width` x `height` image
+    pub fn into_pixels(self, width: u32, height: u32) -> Self {
+        let (width, height) = (width as f32, height as f32);
+        Self {
+            x: self.x * width,
+            y: (1.0 - self.y - self.height) * height,
+            width: self.width * width,
+            height: self.height * height,
+            ..self
+        }
+    }
+
+    /// Center point in logical points, where `input::click_at` clicks, for a box
+    /// in screen pixels on a display with `scale_factor` pixels per point
+    pub fn click_point(&self, scale_factor: f32) -> (i32, i32) {
+        let scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+        (
+            ((self.x + self.width / 2.0) / scale).round() as i32,
+            ((self.y + self.height / 2.0) / scale).round() as i32,
+        )
+    }
+
+    /// Shift a pixel box by the position of the captured region
+    pub fn offset(self, dx: i32, dy: i32) -> Self {
+        Self {
+            x: self.x + dx as f32,
+            y: self.y + dy as f32,
+            ..self
+        }
+    }
+}
+
+/// How `ClickText` compares a label with OCR'd text (both case-insensitive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMatch {
+    /// The whole box text equals the label
+    #[default]
+    Exact,
+    /// The box text contains the label
+    Contains,
+}
+
+impl TextMatch {
+    fn matches(self, box_text: &str, label: &str) -> bool {
+        let (box_text, label) = (box_text.trim().to_lowercase(), label.trim().to_lowercase());
+        match self {
+            TextMatch::Exact => box_text == label,
+            TextMatch::Contains => box_text.contains(&label),
+        }
+    }
+}
+
+/// The `occurrence`-th (1-based) box matching `label`, counting top to bottom,
+/// then left to right
+pub fn find_text_box<'a>(
+    boxes: &'a [BoundingBox],
+    label: &str,
+    mode: TextMatch,
+    occurrence: usize,
+) -> Option<&'a BoundingBox> {
+    if label.trim().is_empty() || occurrence == 0 {
+        return None;
+    }
+
+    let mut matches: Vec<&BoundingBox> = boxes.iter().filter(|bbox| mode.matches(&bbox.text, label)).collect();
+    matches.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    matches.get(occurrence - 1).copied()
+}
+
+/// Lazily performed, cached toolchain availability check
+pub struct ToolchainCheck {
+    available: OnceCell<bool>,
+}
+
+impl ToolchainCheck {
+    pub const fn new() -> Self {
+        Self {
+            available: OnceCell::new(),
+        }
+    }
+
+    /// Run `detect` on first use only; later calls return the cached answer
+    pub fn get(&self, detect: impl FnOnce() -> bool) -> bool {
+        *self.available.get_or_init(detect)
+    }
+}
+
+/// Check if an executable named `binary` exists in any directory of `path_var`
+fn find_on_path(binary: &str, path_var: &OsStr) -> bool {
+    std::env::split_paths(path_var).any(|dir| is_executable(&dir.join(binary)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Whether `swift` is on PATH (checked once per process)
+pub fn swift_available() -> bool {
+    SWIFT_TOOLCHAIN.get(|| {
+        let found = std::env::var_os("PATH")
+            .map(|path| find_on_path("swift", &path))
+            .unwrap_or(false);
+        if !found {
+            crate::logs::error("[OCR] Swift toolchain not found on PATH - local OCR disabled");
+        }
+        found
+    })
+}
+
+/// Where OCR runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcrBackend {
+    /// Vision framework via a Swift script
+    Local,
+    /// Observer server OCR endpoint
+    Cloud,
+}
+
+/// Pick local OCR when the toolchain is present, otherwise cloud if the user
+/// turned it on (`cloud_enabled`) and an API key is set (`cloud_configured`)
+pub fn choose_backend(local_available: bool, cloud_enabled: bool, cloud_configured: bool) -> Result<OcrBackend, String> {
+    if local_available {
+        Ok(OcrBackend::Local)
+    } else if cloud_enabled && cloud_configured {
+        Ok(OcrBackend::Cloud)
+    } else {
+        Err(OCR_UNAVAILABLE_MESSAGE.to_string())
+    }
+}
+
+/// Whether the user allowed images to be sent to the server for OCR
+fn cloud_ocr_enabled() -> bool {
+    crate::commands::get_settings()
+        .map(|settings| settings.cloud_ocr)
+        .unwrap_or(false)
+}
+
+/// Extract text from an image, locally if possible with cloud OCR as fallback
+/// Blocking - call from a blocking context, not from inside an async task
 pub fn extract_text_from_image(image: &RgbaImage) -> Result<OcrResult, String> {
-    #[cfg(target_os = "macos")]
-    {
-        extract_text_macos(image)
+    let local_available = cfg!(target_os = "macos") && swift_available();
+    let api_key = crate::sync::get_api_key();
+
+    match choose_backend(local_available, cloud_ocr_enabled(), api_key.is_some())? {
+        OcrBackend::Local => extract_text_local(image),
+        OcrBackend::Cloud => {
+            let image_base64 = crate::automation::screen::encode_to_base64(image)?;
+            let api_key = api_key.unwrap_or_default();
+            tauri::async_runtime::block_on(extract_text_cloud(&image_base64, &api_key))
+        }
     }
+}
+
+#[cfg(target_os = "macos")]
+fn extract_text_local(image: &RgbaImage) -> Result<OcrResult, String> {
+    extract_text_macos(image)
+}
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        Err("OCR only supported on macOS currently".to_string())
+#[cfg(not(target_os = "macos"))]
+fn extract_text_local(_image: &RgbaImage) -> Result<OcrResult, String> {
+    Err("Local OCR only supported on macOS currently".to_string())
+}
+
+/// OCR the primary monitor (or `region` of it) with bounding boxes in screen
+/// pixels, top-left origin - ready to use as click targets
+/// Blocking - call from a blocking context, not from inside an async task
+pub fn ocr_with_boxes(region: Option<Rect>) -> Result<OcrResult, String> {
+    let image = crate::automation::screen::capture_rect(region)?;
+    let mut result = extract_text_from_image(&image)?;
+
+    if let Some(region) = region {
+        result.bounding_boxes = result
+            .bounding_boxes
+            .into_iter()
+            .map(|bbox| bbox.offset(region.x, region.y))
+            .collect();
     }
+    Ok(result)
 }
 
 /// Extract text from image file path
@@ -45,11 +244,31 @@ pub fn extract_text_from_path(path: &str) -> Result<OcrResult, String> {
     extract_text_from_image(&rgba)
 }
 
-/// Extract text using macOS Vision framework via Swift
+/// Run a Swift script and return its stdout
 #[cfg(target_os = "macos")]
-fn extract_text_macos(image: &RgbaImage) -> Result<OcrResult, String> {
+fn run_swift_script(source: &str) -> Result<String, String> {
     use std::process::Command;
 
+    let script_path = std::env::temp_dir().join(format!("observer_ocr_{}.swift", uuid::Uuid::new_v4()));
+    std::fs::write(&script_path, source)
+        .map_err(|e| format!("Failed to write Swift script: {}", e))?;
+
+    let output = Command::new("swift")
+        .arg(&script_path)
+        .output()
+        .map_err(|e| format!("Failed to execute Swift: {}", e));
+    let _ = std::fs::remove_file(&script_path);
+    let output = output?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Extract text using macOS Vision framework via Swift
+#[cfg(target_os = "macos")]
+fn extract_text_macos(image: &RgbaImage) -> Result<OcrResult, String> {
     // Save image to temporary file
     let temp_dir = std::env::temp_dir();
     let temp_path = temp_dir.join(format!("observer_ocr_{}.png", uuid::Uuid::new_v4()));
@@ -124,27 +343,9 @@ do {{
         temp_path.display()
     );
 
-    // Write Swift script to temp file
-    let script_path = temp_dir.join(format!("observer_ocr_{}.swift", uuid::Uuid::new_v4()));
-    std::fs::write(&script_path, swift_script)
-        .map_err(|e| format!("Failed to write Swift script: {}", e))?;
-
-    // Execute Swift script
-    let output = Command::new("swift")
-        .arg(&script_path)
-        .output()
-        .map_err(|e| format!("Failed to execute Swift: {}", e))?;
-
-    // Clean up temp files
+    let output = run_swift_script(&swift_script);
     let _ = std::fs::remove_file(&temp_path);
-    let _ = std::fs::remove_file(&script_path);
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("OCR failed: {}", stderr));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = output.map_err(|e| format!("OCR failed: {}", e))?;
 
     // Parse JSON result
     let json: serde_json::Value = serde_json::from_str(stdout.trim())
@@ -177,18 +378,179 @@ do {{
                         height: box_val.get("height")?.as_f64()? as f32,
                     })
                 })
+                // Vision reports normalized, bottom-left-origin boxes
+                .map(|bbox| bbox.into_pixels(image.width(), image.height()))
                 .collect()
         })
         .unwrap_or_default();
 
     Ok(OcrResult {
-        text,
+        text: crate::text::truncate_string(text, crate::text::MAX_OCR_CHARS),
         confidence,
         language: None,
         bounding_boxes: boxes,
     })
 }
 
+/// Languages offered when Vision can't be asked (cloud OCR), as BCP-47 codes
+pub const FALLBACK_OCR_LANGUAGES: &[&str] = &[
+    "en-US", "fr-FR", "it-IT", "de-DE", "es-ES", "pt-BR", "zh-Hans", "zh-Hant", "ja-JP", "ko-KR",
+    "ru-RU", "uk-UA",
+];
+
+/// Vision's supported languages, looked up once per process (compiling the helper is slow)
+static OCR_LANGUAGES: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Prints `{"languages": [...]}` with Vision's supported recognition languages
+#[cfg(target_os = "macos")]
+const LANGUAGES_SCRIPT: &str = r#"
+import Foundation
+import Vision
+
+let request = VNRecognizeTextRequest()
+request.recognitionLevel = .accurate
+do {
+    let languages = try request.supportedRecognitionLanguages()
+    let data = try JSONSerialization.data(withJSONObject: ["languages": languages], options: [])
+    print(String(data: data, encoding: .utf8)!)
+} catch {
+    let data = try! JSONSerialization.data(withJSONObject: ["error": error.localizedDescription], options: [])
+    print(String(data: data, encoding: .utf8)!)
+    exit(1)
+}
+"#;
+
+/// Language codes from the helper's `{"languages": [...]}` output
+pub fn parse_languages(output: &str) -> Result<Vec<String>, String> {
+    let json: serde_json::Value = serde_json::from_str(output.trim())
+        .map_err(|e| format!("Failed to parse OCR languages: {}", e))?;
+    if let Some(error) = json.get("error") {
+        return Err(format!("OCR languages error: {}", error));
+    }
+
+    let languages: Vec<String> = json
+        .get("languages")
+        .and_then(|v| v.as_array())
+        .ok_or("OCR languages output has no language list")?
+        .iter()
+        .filter_map(|language| language.as_str())
+        .map(|language| language.trim().to_string())
+        .filter(|language| !language.is_empty())
+        .collect();
+    Ok(languages)
+}
+
+#[cfg(target_os = "macos")]
+fn query_vision_languages() -> Result<Vec<String>, String> {
+    parse_languages(&run_swift_script(LANGUAGES_SCRIPT)?)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn query_vision_languages() -> Result<Vec<String>, String> {
+    Err("Vision is only available on macOS".to_string())
+}
+
+fn fallback_languages() -> Vec<String> {
+    FALLBACK_OCR_LANGUAGES.iter().map(|language| language.to_string()).collect()
+}
+
+/// BCP-47 codes of the languages OCR can recognize on this machine
+/// Blocking - the first local lookup compiles a Swift helper
+pub fn supported_languages() -> Result<Vec<String>, String> {
+    let local_available = cfg!(target_os = "macos") && swift_available();
+    let cloud_configured = crate::sync::get_api_key().is_some();
+
+    match choose_backend(local_available, cloud_ocr_enabled(), cloud_configured)? {
+        OcrBackend::Local => {
+            if let Some(languages) = OCR_LANGUAGES.get() {
+                return Ok(languages.clone());
+            }
+            match query_vision_languages() {
+                Ok(languages) if !languages.is_empty() => Ok(OCR_LANGUAGES.get_or_init(|| languages).clone()),
+                Ok(_) => Ok(fallback_languages()),
+                Err(e) => {
+                    crate::logs::error(&format!("[OCR] Failed to list Vision languages, using defaults: {}", e));
+                    Ok(fallback_languages())
+                }
+            }
+        }
+        OcrBackend::Cloud => Ok(fallback_languages()),
+    }
+}
+
+/// Cloud OCR settings as shown in the settings window
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudOcrConfig {
+    /// The `cloudOcr` setting: used as a fallback only when on
+    pub enabled: bool,
+    pub endpoint: String,
+    /// The key itself is never sent to the frontend
+    pub api_key_configured: bool,
+}
+
+/// OCR endpoint of an Observer server
+pub fn cloud_ocr_endpoint(server_url: &str) -> String {
+    format!("{}{}", server_url.trim_end_matches('/'), CLOUD_OCR_PATH)
+}
+
+/// Current cloud OCR endpoint and whether an API key is set
+pub fn cloud_ocr_config() -> CloudOcrConfig {
+    CloudOcrConfig {
+        enabled: cloud_ocr_enabled(),
+        endpoint: cloud_ocr_endpoint(&crate::sync::get_server_url()),
+        api_key_configured: crate::sync::get_api_key().is_some(),
+    }
+}
+
+/// 5x7 glyphs for the letters of TEST_IMAGE_TEXT (one byte per row, low 5 bits)
+fn glyph(letter: char) -> [u8; 7] {
+    match letter {
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        _ => [0; 7],
+    }
+}
+
+/// Small black-on-white image of TEST_IMAGE_TEXT for checking cloud OCR
+pub fn test_image() -> RgbaImage {
+    const SCALE: u32 = 4;
+    const MARGIN: u32 = 8;
+    let letters: Vec<char> = TEST_IMAGE_TEXT.chars().collect();
+    let width = MARGIN * 2 + letters.len() as u32 * 6 * SCALE;
+    let height = MARGIN * 2 + 7 * SCALE;
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let ink = x >= MARGIN && y >= MARGIN && {
+            let (col, row) = ((x - MARGIN) / SCALE, (y - MARGIN) / SCALE);
+            let (index, bit) = ((col / 6) as usize, col % 6);
+            row < 7
+                && bit < 5
+                && letters
+                    .get(index)
+                    .is_some_and(|&letter| glyph(letter)[row as usize] & (0b10000 >> bit) != 0)
+        };
+        if ink {
+            image::Rgba([0, 0, 0, 255])
+        } else {
+            image::Rgba([255, 255, 255, 255])
+        }
+    })
+}
+
+/// Run cloud OCR on the built-in test image
+/// Lets users confirm their server and API key support OCR before relying on it
+pub async fn test_cloud_ocr() -> Result<OcrResult, String> {
+    let api_key = crate::sync::get_api_key()
+        .ok_or("Cloud OCR needs an Observer API key - set it in settings first")?;
+    let image_base64 = crate::automation::screen::encode_to_base64(&test_image())?;
+    extract_text_cloud(&image_base64, &api_key).await
+}
+
 /// Extract text using cloud OCR service via Observer server
 pub async fn extract_text_cloud(image_base64: &str, api_key: &str) -> Result<OcrResult, String> {
     // Get server URL from configuration
@@ -199,16 +561,24 @@ pub async fn extract_text_cloud(image_base64: &str, api_key: &str) -> Result<Ocr
         return Err(
             "Cloud OCR requires Observer server configuration. \
             Please set OBSERVER_SERVER_URL environment variable or \
-            configure server URL in ~/.config/observer/server.txt".to_string()
+            set server_url in ~/.config/observer/config.json".to_string()
         );
     }
 
     let client = reqwest::Client::new();
-    let ocr_endpoint = format!("{}/api/v1/ocr", server_url);
+    request_cloud_ocr(&client, &cloud_ocr_endpoint(&server_url), image_base64, api_key).await
+}
 
+/// POST an image to an OCR endpoint and parse the response
+async fn request_cloud_ocr(
+    client: &reqwest::Client,
+    ocr_endpoint: &str,
+    image_base64: &str,
+    api_key: &str,
+) -> Result<OcrResult, String> {
     // Make request to Observer server's OCR endpoint
     let response = client
-        .post(&ocr_endpoint)
+        .post(ocr_endpoint)
         .header("Authorization", format!("Bearer {}", api_key))
         .json(&serde_json::json!({
             "image": image_base64,
@@ -265,7 +635,7 @@ pub async fn extract_text_cloud(image_base64: &str, api_key: &str) -> Result<Ocr
         .unwrap_or_default();
 
     Ok(OcrResult {
-        text,
+        text: crate::text::truncate_string(text, crate::text::MAX_OCR_CHARS),
         confidence,
         language,
         bounding_boxes,
@@ -276,6 +646,231 @@ pub async fn extract_text_cloud(image_base64: &str, api_key: &str) -> Result<Ocr
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_toolchain_check_is_cached() {
+        let check = ToolchainCheck::new();
+        let calls = std::cell::Cell::new(0);
+
+        let detect = || {
+            calls.set(calls.get() + 1);
+            false
+        };
+        assert!(!check.get(detect));
+        assert!(!check.get(|| panic!("detection must not run twice")));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_find_on_path() {
+        let dir = std::env::temp_dir().join(format!("observer_path_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path_var = std::env::join_paths([dir.clone()]).unwrap();
+        assert!(!find_on_path("swift", &path_var));
+
+        let binary = dir.join("swift");
+        std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(find_on_path("swift", &path_var));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_backend_fallback_decision() {
+        assert_eq!(choose_backend(true, true, true), Ok(OcrBackend::Local));
+        assert_eq!(choose_backend(true, false, false), Ok(OcrBackend::Local));
+        assert_eq!(choose_backend(false, true, true), Ok(OcrBackend::Cloud));
+
+        // A configured API key alone doesn't send images to the server
+        assert!(choose_backend(false, false, true).is_err());
+        assert!(choose_backend(false, true, false).is_err());
+
+        let error = choose_backend(false, false, false).unwrap_err();
+        assert!(error.contains("Xcode Command Line Tools"));
+        assert!(error.contains("cloud OCR"));
+    }
+
+    /// Serve one HTTP request with `body`, returning the raw request text
+    async fn serve_once(listener: tokio::net::TcpListener, body: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 8192];
+        loop {
+            let read = socket.read(&mut chunk).await.unwrap();
+            request.extend_from_slice(&chunk[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+            if read == 0 {
+                break;
+            }
+        }
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_cloud_ocr_posts_to_endpoint() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(
+            listener,
+            r#"{"text":"OBSERVER","confidence":0.93,"language":"en","bounding_boxes":[{"text":"OBSERVER","confidence":0.93,"x":8,"y":8,"width":184,"height":28}]}"#,
+        ));
+
+        let image_base64 = crate::automation::screen::encode_to_base64(&test_image()).unwrap();
+        let result = request_cloud_ocr(
+            &reqwest::Client::new(),
+            &cloud_ocr_endpoint(&server_url),
+            &image_base64,
+            "test-key",
+        )
+        .await
+        .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /api/v1/ocr HTTP/1.1"));
+        assert!(request.to_lowercase().contains("authorization: bearer test-key"));
+        assert!(request.contains(&image_base64));
+
+        assert_eq!(result.text, "OBSERVER");
+        assert!((result.confidence - 0.93).abs() < 1e-6);
+        assert_eq!(result.language.as_deref(), Some("en"));
+        assert_eq!(result.bounding_boxes.len(), 1);
+        assert_eq!(result.bounding_boxes[0].width, 184.0);
+    }
+
+    #[test]
+    fn test_test_image_has_text() {
+        let image = test_image();
+        assert_eq!(image.dimensions(), (208, 44));
+        let ink = image.pixels().filter(|p| p.0 == [0, 0, 0, 255]).count();
+        assert!(ink > 0 && ink < (image.width() * image.height()) as usize / 2);
+    }
+
+    fn vision_box(x: f32, y: f32, width: f32, height: f32) -> BoundingBox {
+        BoundingBox {
+            text: "Submit".to_string(),
+            x,
+            y,
+            width,
+            height,
+            confidence: 0.9,
+        }
+    }
+
+    fn assert_box(bbox: &BoundingBox, expected: (f32, f32, f32, f32)) {
+        let actual = [bbox.x, bbox.y, bbox.width, bbox.height];
+        let expected = [expected.0, expected.1, expected.2, expected.3];
+        let close = actual.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-3);
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_normalized_box_to_pixels() {
+        // y flips: a box 20% up from the bottom edge ends 70% down from the top
+        let bbox = vision_box(0.1, 0.2, 0.3, 0.1).into_pixels(1000, 500);
+        assert_box(&bbox, (100.0, 350.0, 300.0, 50.0));
+        assert_eq!(bbox.text, "Submit");
+        assert_eq!(bbox.confidence, 0.9);
+
+        // Corners
+        assert_box(&vision_box(0.0, 0.0, 1.0, 1.0).into_pixels(1920, 1080), (0.0, 0.0, 1920.0, 1080.0));
+        assert_box(&vision_box(0.0, 0.9, 0.5, 0.1).into_pixels(1920, 1080), (0.0, 0.0, 960.0, 108.0));
+        assert_box(&vision_box(0.5, 0.0, 0.5, 0.1).into_pixels(1920, 1080), (960.0, 972.0, 960.0, 108.0));
+
+        // Region captures shift by the region's screen position
+        let bbox = vision_box(0.5, 0.5, 0.25, 0.25).into_pixels(400, 200).offset(100, 300);
+        assert_box(&bbox, (300.0, 350.0, 100.0, 50.0));
+    }
+
+    fn label(text: &str, x: f32, y: f32) -> BoundingBox {
+        BoundingBox {
+            text: text.to_string(),
+            x,
+            y,
+            width: 80.0,
+            height: 20.0,
+            confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_find_text_box() {
+        let boxes = vec![
+            label("Cancel", 200.0, 500.0),
+            label("OK", 300.0, 500.0),
+            label("Press OK to continue", 100.0, 100.0),
+            label("ok", 50.0, 800.0),
+            label("OK", 20.0, 500.0),
+        ];
+        let position = |bbox: Option<&BoundingBox>| bbox.map(|bbox| (bbox.x, bbox.y));
+
+        // Exact ignores case and surrounding whitespace; nth counts in reading order
+        assert_eq!(position(find_text_box(&boxes, " ok", TextMatch::Exact, 1)), Some((20.0, 500.0)));
+        assert_eq!(position(find_text_box(&boxes, "OK", TextMatch::Exact, 2)), Some((300.0, 500.0)));
+        assert_eq!(position(find_text_box(&boxes, "OK", TextMatch::Exact, 3)), Some((50.0, 800.0)));
+        assert_eq!(find_text_box(&boxes, "OK", TextMatch::Exact, 4), None);
+
+        // Contains also finds labels inside longer lines
+        assert_eq!(position(find_text_box(&boxes, "OK", TextMatch::Contains, 1)), Some((100.0, 100.0)));
+        assert_eq!(position(find_text_box(&boxes, "continue", TextMatch::Contains, 1)), Some((100.0, 100.0)));
+        assert_eq!(find_text_box(&boxes, "continue", TextMatch::Exact, 1), None);
+
+        assert_eq!(find_text_box(&boxes, "OK", TextMatch::Exact, 0), None);
+        assert_eq!(find_text_box(&boxes, "  ", TextMatch::Contains, 1), None);
+
+        assert_eq!(label("OK", 300.0, 500.0).click_point(1.0), (340, 510));
+    }
+
+    #[test]
+    fn test_click_point_in_logical_points() {
+        // Retina: 2 pixels per point, so the pixel center is halved
+        assert_eq!(label("OK", 300.0, 500.0).click_point(2.0), (170, 255));
+        // A region box shifted to screen pixels first, then scaled
+        let bbox = vision_box(0.5, 0.5, 0.25, 0.25).into_pixels(400, 200).offset(100, 300);
+        assert_eq!(bbox.click_point(2.0), (175, 188));
+        // A bogus factor is treated as 1
+        assert_eq!(label("OK", 300.0, 500.0).click_point(0.0), (340, 510));
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        let output = r#"{"languages":["en-US","fr-FR","zh-Hans"," ",7]}
+"#;
+        assert_eq!(parse_languages(output).unwrap(), vec!["en-US", "fr-FR", "zh-Hans"]);
+
+        assert_eq!(parse_languages(r#"{"languages":[]}"#).unwrap(), Vec::<String>::new());
+        assert!(parse_languages(r#"{"error":"Vision unavailable"}"#)
+            .unwrap_err()
+            .contains("Vision unavailable"));
+        assert!(parse_languages("swift: command not found").is_err());
+        assert!(parse_languages(r#"{"text":""}"#).is_err());
+    }
+
     #[test]
     fn test_ocr_result_creation() {
         let result = OcrResult {
@@ -1,19 +1,91 @@
 /// Task queue system for automation with priorities and pause/resume
-/// Minimum 100ms interval between tasks
+/// Minimum 100ms interval between tasks, plus a per-minute execution cap and a
+/// pending-task cap so a runaway controller can't flood the machine with input
 
 use serde::{Serialize, Deserialize};
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, VecDeque};
 use std::cmp::Ordering;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::time::{Duration, sleep, timeout};
 use tokio::process::Command;
 use uuid::Uuid;
 use regex::Regex;
+use crate::automation::history::{TaskHistory, TaskHistoryEntry};
 
 const MIN_TASK_INTERVAL: Duration = Duration::from_millis(100);
 const CUSTOM_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Default cap on tasks executed per minute
+pub const DEFAULT_MAX_TASKS_PER_MINUTE: u32 = 120;
+/// Default cap on tasks waiting in the queue
+pub const DEFAULT_MAX_PENDING_TASKS: usize = 100;
+
+/// Window the per-minute cap is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Longest single sleep while rate limited, so pause and clear stay responsive
+const MAX_RATE_LIMIT_SLEEP: Duration = Duration::from_secs(1);
+
+/// Safety limits on task execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueLimits {
+    pub max_tasks_per_minute: u32,
+    pub max_pending_tasks: usize,
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self {
+            max_tasks_per_minute: DEFAULT_MAX_TASKS_PER_MINUTE,
+            max_pending_tasks: DEFAULT_MAX_PENDING_TASKS,
+        }
+    }
+}
+
+/// Sliding-window execution counter
+#[derive(Debug)]
+struct RateLimiter {
+    window: Duration,
+    executed: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            executed: VecDeque::new(),
+        }
+    }
+
+    /// How long to wait before the next task may run (None = run now)
+    /// A max of 0 lets nothing run
+    fn delay(&mut self, now: Instant, max_per_window: u32) -> Option<Duration> {
+        if max_per_window == 0 {
+            return Some(self.window);
+        }
+
+        while let Some(&oldest) = self.executed.front() {
+            if now.duration_since(oldest) >= self.window {
+                self.executed.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.executed.len() < max_per_window as usize {
+            return None;
+        }
+        self.executed
+            .front()
+            .map(|&oldest| (oldest + self.window).saturating_duration_since(now))
+    }
+
+    fn record(&mut self, now: Instant) {
+        self.executed.push_back(now);
+    }
+}
+
 /// Task priority levels
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TaskPriority {
@@ -91,12 +163,37 @@ pub enum TaskCommand {
     BrowserGetUrl { browser: String },
     Wait { milliseconds: u64 },
     Custom { name: String, params: serde_json::Value },
+    /// OCR the screen (or `region`) and click the center of the matching text
+    ClickText {
+        text: String,
+        #[serde(default)]
+        region: Option<crate::automation::screen::Rect>,
+        #[serde(rename = "match", default)]
+        match_mode: crate::automation::ocr::TextMatch,
+        /// Which match to click, 1-based in reading order
+        #[serde(default = "default_occurrence")]
+        occurrence: usize,
+    },
+    /// Capture one app window (focused or not) by app name and title
+    ScreenshotWindow {
+        app_name: String,
+        #[serde(default)]
+        window_title: String,
+    },
+}
+
+fn default_occurrence() -> usize {
+    1
 }
 
 /// Queue status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueStatus {
+    /// Tasks are not being run (queue paused or automation paused)
     pub is_paused: bool,
+    /// Remote tasks are rejected (automation paused, collection unaffected)
+    #[serde(default)]
+    pub automation_paused: bool,
     pub pending_tasks: usize,
     pub completed_tasks: usize,
     pub failed_tasks: usize,
@@ -162,7 +259,7 @@ mod custom_commands {
         command: &str,
         args: Vec<String>,
     ) -> Result<String, String> {
-        println!("Executing shell command: {} with args: {:?}", command, args);
+        crate::logs::info(&format!("[Automation] Executing shell command: {} with args: {:?}", command, args));
 
         // Determine shell based on platform
         #[cfg(target_os = "windows")]
@@ -192,20 +289,20 @@ mod custom_commands {
             Ok(Ok(output)) => {
                 if output.status.success() {
                     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                    println!("Command succeeded: {}", stdout.trim());
+                    crate::logs::info(&format!("[Automation] Command succeeded: {}", stdout.trim()));
                     Ok(stdout)
                 } else {
                     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                    eprintln!("Command failed: {}", stderr);
+                    crate::logs::error(&format!("[Automation] Command failed: {}", stderr));
                     Err(format!("Command failed: {}", stderr))
                 }
             }
             Ok(Err(e)) => {
-                eprintln!("Failed to execute command: {}", e);
+                crate::logs::error(&format!("[Automation] Failed to execute command: {}", e));
                 Err(format!("Failed to execute command: {}", e))
             }
             Err(_) => {
-                eprintln!("Command timed out after {:?}", CUSTOM_COMMAND_TIMEOUT);
+                crate::logs::error(&format!("[Automation] Command timed out after {:?}", CUSTOM_COMMAND_TIMEOUT));
                 Err(format!("Command execution timed out after {:?}", CUSTOM_COMMAND_TIMEOUT))
             }
         }
@@ -214,7 +311,7 @@ mod custom_commands {
     /// Execute AppleScript (macOS only)
     #[cfg(target_os = "macos")]
     pub async fn execute_applescript(script: &str) -> Result<String, String> {
-        println!("Executing AppleScript: {}", script.trim());
+        crate::logs::info(&format!("[Automation] Executing AppleScript: {}", script.trim()));
 
         // Sanitize script
         if script.contains('\0') {
@@ -225,35 +322,22 @@ mod custom_commands {
             return Err("AppleScript exceeds maximum length".to_string());
         }
 
-        // Execute with timeout
-        let result = timeout(CUSTOM_COMMAND_TIMEOUT, async {
-            Command::new("osascript")
-                .arg("-e")
-                .arg(script)
-                .output()
-                .await
+        // Execute with timeout (the script is killed if it hangs)
+        let script = script.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            crate::native_applescript::run(&script, CUSTOM_COMMAND_TIMEOUT)
         })
-        .await;
+        .await
+        .map_err(|e| format!("Failed to execute AppleScript: {}", e))?;
 
         match result {
-            Ok(Ok(output)) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                    println!("AppleScript succeeded: {}", stdout.trim());
-                    Ok(stdout)
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                    eprintln!("AppleScript failed: {}", stderr);
-                    Err(format!("AppleScript failed: {}", stderr))
-                }
+            Ok(stdout) => {
+                crate::logs::info(&format!("[Automation] AppleScript succeeded: {}", stdout.trim()));
+                Ok(stdout)
             }
-            Ok(Err(e)) => {
-                eprintln!("Failed to execute AppleScript: {}", e);
-                Err(format!("Failed to execute AppleScript: {}", e))
-            }
-            Err(_) => {
-                eprintln!("AppleScript timed out after {:?}", CUSTOM_COMMAND_TIMEOUT);
-                Err(format!("AppleScript execution timed out after {:?}", CUSTOM_COMMAND_TIMEOUT))
+            Err(e) => {
+                crate::logs::error(&format!("[Automation] {}", e));
+                Err(e)
             }
         }
     }
@@ -268,47 +352,122 @@ mod custom_commands {
 pub struct AutomationQueue {
     tasks: Arc<Mutex<BinaryHeap<AutomationTask>>>,
     is_paused: Arc<RwLock<bool>>,
+    automation_paused: Arc<RwLock<bool>>,
     completed_count: Arc<RwLock<usize>>,
     failed_count: Arc<RwLock<usize>>,
     current_task: Arc<RwLock<Option<String>>>,
     result_tx: mpsc::UnboundedSender<TaskResult>,
+    history: Arc<Mutex<TaskHistory>>,
+    /// Tasks the local trust level wants confirmed are not run
+    trust: Arc<crate::automation::TrustManager>,
+    limits: std::sync::Mutex<QueueLimits>,
+    rate_limiter: std::sync::Mutex<RateLimiter>,
 }
 
 impl AutomationQueue {
     /// Create a new automation queue
-    pub fn new() -> (Self, mpsc::UnboundedReceiver<TaskResult>) {
+    pub fn new(trust: Arc<crate::automation::TrustManager>) -> (Self, mpsc::UnboundedReceiver<TaskResult>) {
+        Self::with_history(TaskHistory::load_default(), trust)
+    }
+
+    /// Create a queue recording executed tasks into `history`
+    pub fn with_history(
+        history: TaskHistory,
+        trust: Arc<crate::automation::TrustManager>,
+    ) -> (Self, mpsc::UnboundedReceiver<TaskResult>) {
         let (result_tx, result_rx) = mpsc::unbounded_channel();
 
         let queue = Self {
             tasks: Arc::new(Mutex::new(BinaryHeap::new())),
             is_paused: Arc::new(RwLock::new(false)),
+            automation_paused: Arc::new(RwLock::new(false)),
             completed_count: Arc::new(RwLock::new(0)),
             failed_count: Arc::new(RwLock::new(0)),
             current_task: Arc::new(RwLock::new(None)),
             result_tx,
+            history: Arc::new(Mutex::new(history)),
+            trust,
+            limits: std::sync::Mutex::new(QueueLimits::default()),
+            rate_limiter: std::sync::Mutex::new(RateLimiter::new(RATE_WINDOW)),
         };
 
         (queue, result_rx)
     }
 
     /// Add task to queue
+    /// Rejected once `max_pending_tasks` tasks are already waiting
     pub async fn add_task(&self, task: AutomationTask) -> Result<String, String> {
+        let max_pending = self.limits().max_pending_tasks;
         let task_id = task.id.clone();
         let mut tasks = self.tasks.lock().await;
+        if tasks.len() >= max_pending {
+            return Err(format!(
+                "Automation queue is full ({} pending tasks)",
+                max_pending
+            ));
+        }
         tasks.push(task);
         Ok(task_id)
     }
 
+    /// Pending tasks in execution order (highest priority first, then oldest)
+    pub async fn export_tasks(&self) -> Vec<AutomationTask> {
+        let tasks = self.tasks.lock().await.clone();
+        let mut ordered = tasks.into_sorted_vec();
+        ordered.reverse();
+        ordered
+    }
+
+    /// Add previously exported tasks back
+    /// All or nothing: rejected if any task is already queued or the tasks don't
+    /// fit under `max_pending_tasks`. Trust levels are not exported, so imported
+    /// tasks get the default.
+    pub async fn import_tasks(&self, imported: Vec<AutomationTask>) -> Result<Vec<String>, String> {
+        let max_pending = self.limits().max_pending_tasks;
+        let mut tasks = self.tasks.lock().await;
+        if tasks.len() + imported.len() > max_pending {
+            return Err(format!(
+                "Importing {} tasks would exceed the queue limit ({} pending, max {})",
+                imported.len(),
+                tasks.len(),
+                max_pending
+            ));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for task in &imported {
+            if !seen.insert(task.id.as_str()) || tasks.iter().any(|queued| queued.id == task.id) {
+                return Err(format!("Task {} is already queued", task.id));
+            }
+        }
+
+        let ids: Vec<String> = imported.iter().map(|task| task.id.clone()).collect();
+        tasks.extend(imported);
+        Ok(ids)
+    }
+
+    /// Current safety limits
+    pub fn limits(&self) -> QueueLimits {
+        *self.limits.lock().unwrap()
+    }
+
+    /// Update the safety limits (tasks already queued are kept)
+    pub fn set_limits(&self, limits: QueueLimits) {
+        *self.limits.lock().unwrap() = limits;
+    }
+
     /// Get queue status
     pub async fn status(&self) -> QueueStatus {
         let tasks = self.tasks.lock().await;
-        let is_paused = *self.is_paused.read().await;
+        let automation_paused = *self.automation_paused.read().await;
+        let is_paused = *self.is_paused.read().await || automation_paused;
         let completed = *self.completed_count.read().await;
         let failed = *self.failed_count.read().await;
         let current = self.current_task.read().await.clone();
 
         QueueStatus {
             is_paused,
+            automation_paused,
             pending_tasks: tasks.len(),
             completed_tasks: completed,
             failed_tasks: failed,
@@ -328,6 +487,18 @@ impl AutomationQueue {
         *is_paused = false;
     }
 
+    /// Pause or resume automation as a whole
+    /// While paused the queue doesn't run and remote tasks are rejected.
+    /// Separate from `pause`, so resuming automation keeps a paused queue paused.
+    pub async fn set_automation_paused(&self, paused: bool) {
+        *self.automation_paused.write().await = paused;
+    }
+
+    /// Whether remote tasks are currently rejected
+    pub async fn is_automation_paused(&self) -> bool {
+        *self.automation_paused.read().await
+    }
+
     /// Clear all pending tasks
     pub async fn clear(&self) {
         let mut tasks = self.tasks.lock().await;
@@ -337,58 +508,111 @@ impl AutomationQueue {
     /// Process queue (should be run in background task)
     pub async fn process(&self) {
         loop {
-            // Check if paused
-            if *self.is_paused.read().await {
-                sleep(Duration::from_millis(100)).await;
-                continue;
-            }
+            crate::supervisor::heartbeat(crate::supervisor::QUEUE_TASK);
+            let wait = self.process_next(Instant::now()).await;
+            sleep(wait).await;
+        }
+    }
 
-            // Get next task
-            let task = {
-                let mut tasks = self.tasks.lock().await;
-                tasks.pop()
-            };
+    /// One step of `process`: run the next task if the queue isn't paused and
+    /// the rate limit allows it at `now`. Returns how long to wait before the next step.
+    async fn process_next(&self, now: Instant) -> Duration {
+        // Check if paused
+        if *self.is_paused.read().await || *self.automation_paused.read().await {
+            return Duration::from_millis(100);
+        }
 
-            if let Some(task) = task {
-                // Update current task
-                {
-                    let mut current = self.current_task.write().await;
-                    *current = Some(task.id.clone());
-                }
+        // Stay under the per-minute cap
+        let max_per_minute = self.limits().max_tasks_per_minute;
+        let delay = self.rate_limiter.lock().unwrap().delay(now, max_per_minute);
+        if let Some(delay) = delay {
+            return delay.clamp(MIN_TASK_INTERVAL, MAX_RATE_LIMIT_SLEEP);
+        }
 
-                // Execute task
-                let result = self.execute_task(&task).await;
+        // Get next task
+        let task = {
+            let mut tasks = self.tasks.lock().await;
+            tasks.pop()
+        };
 
-                // Update counters
-                if result.success {
-                    let mut completed = self.completed_count.write().await;
-                    *completed += 1;
-                } else {
-                    let mut failed = self.failed_count.write().await;
-                    *failed += 1;
-                }
+        if let Some(task) = task {
+            self.rate_limiter.lock().unwrap().record(now);
+            self.run_task(task).await;
 
-                // Send result
-                let _ = self.result_tx.send(result);
+            // Minimum interval between tasks
+            MIN_TASK_INTERVAL
+        } else {
+            // No tasks, wait a bit
+            Duration::from_millis(100)
+        }
+    }
 
-                // Clear current task
-                {
-                    let mut current = self.current_task.write().await;
-                    *current = None;
-                }
+    /// Run one task: execute it, update counters and history, and send the result
+    pub async fn run_task(&self, task: AutomationTask) -> TaskResult {
+        // Update current task
+        {
+            let mut current = self.current_task.write().await;
+            *current = Some(task.id.clone());
+        }
 
-                // Minimum interval between tasks
-                sleep(MIN_TASK_INTERVAL).await;
-            } else {
-                // No tasks, wait a bit
-                sleep(Duration::from_millis(100)).await;
-            }
+        // Execute task
+        let started_at = chrono::Utc::now();
+        let started = std::time::Instant::now();
+        let result = self.execute_task(&task).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        // Update counters
+        if result.success {
+            let mut completed = self.completed_count.write().await;
+            *completed += 1;
+        } else {
+            let mut failed = self.failed_count.write().await;
+            *failed += 1;
         }
+
+        self.history
+            .lock()
+            .await
+            .record(TaskHistoryEntry::new(&task, &result, started_at, duration_ms));
+
+        // Send result
+        let _ = self.result_tx.send(result.clone());
+
+        // Clear current task
+        {
+            let mut current = self.current_task.write().await;
+            *current = None;
+        }
+
+        result
+    }
+
+    /// Add a non-task entry (e.g. a trust level change) to the audit history
+    pub async fn record_history(&self, entry: TaskHistoryEntry) {
+        self.history.lock().await.record(entry);
+    }
+
+    /// Up to `limit` most recently executed tasks, newest first
+    pub async fn history(&self, limit: usize) -> Vec<TaskHistoryEntry> {
+        self.history.lock().await.recent(limit)
     }
 
     /// Execute a single task
     async fn execute_task(&self, task: &AutomationTask) -> TaskResult {
-        use crate::automation::{input, screen, browser};
+        use crate::automation::{input, ocr, screen, browser};
+
+        // There is no one to confirm a queued task, so it is refused instead
+        if self.trust.requires_confirmation(&task.command) {
+            return TaskResult {
+                task_id: task.id.clone(),
+                success: false,
+                error: Some(format!(
+                    "Task needs confirmation at the current trust level ({})",
+                    self.trust.get_level().description()
+                )),
+                output: None,
+            };
+        }
 
         let result = match &task.command {
             TaskCommand::Click { x, y, button } => {
@@ -463,6 +687,36 @@ impl AutomationQueue {
             TaskCommand::Custom { name, params } => {
                 self.execute_custom_command(name, params).await
             }
+            TaskCommand::ClickText { text, region, match_mode, occurrence } => {
+                let region = *region;
+                let scan = tokio::task::spawn_blocking(move || -> Result<_, String> {
+                    let scan = ocr::ocr_with_boxes(region)?;
+                    Ok((scan, screen::primary_scale_factor()?))
+                })
+                .await;
+                let scan = match scan {
+                    Ok(scan) => scan,
+                    Err(e) => Err(format!("OCR task failed: {}", e)),
+                };
+                scan.and_then(|(scan, scale_factor)| {
+                    let target = ocr::find_text_box(&scan.bounding_boxes, text, *match_mode, *occurrence)
+                        .ok_or_else(|| format!("No text matching \"{}\" on screen (occurrence {})", text, occurrence))?;
+                    // Boxes are in screen pixels, clicks in points
+                    let (x, y) = target.click_point(scale_factor);
+                    input::click_at(x, y, input::MouseButton::Left)?;
+                    Ok(Some(serde_json::json!({ "text": target.text, "x": x, "y": y })))
+                })
+            }
+            TaskCommand::ScreenshotWindow { app_name, window_title } => {
+                let (app_name, window_title) = (app_name.clone(), window_title.clone());
+                let capture = tokio::task::spawn_blocking(move || screen::capture_window(&app_name, &window_title)).await;
+                match capture {
+                    Ok(capture) => capture
+                        .and_then(|img| screen::encode_to_base64(&img))
+                        .map(|base64| Some(serde_json::json!({ "base64": base64 }))),
+                    Err(e) => Err(format!("Window capture task failed: {}", e)),
+                }
+            }
         };
 
         match result {
@@ -538,7 +792,7 @@ impl AutomationQueue {
         name: &str,
         params: &serde_json::Value,
     ) -> Result<Option<serde_json::Value>, String> {
-        println!("Executing custom command: {} with params: {}", name, params);
+        crate::logs::info(&format!("[Automation] Executing custom command: {} with params: {}", name, params));
 
         // Parse parameters
         let params_obj = params.as_object().ok_or("Custom command params must be an object")?;
@@ -646,7 +900,7 @@ impl AutomationQueue {
                     .await
                     .map_err(|e| format!("Failed to read response body: {}", e))?;
 
-                println!("HTTP request completed with status: {}", status);
+                crate::logs::info(&format!("[Automation] HTTP request completed with status: {}", status));
 
                 Ok(Some(serde_json::json!({
                     "status": status,
@@ -696,9 +950,208 @@ mod tests {
         assert_eq!(first.id, high.id);
     }
 
+    fn wait_task() -> AutomationTask {
+        AutomationTask::new(TaskCommand::Wait { milliseconds: 0 }, TaskPriority::Normal)
+    }
+
+    #[tokio::test]
+    async fn test_add_task_rejects_past_pending_cap() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        queue.set_limits(QueueLimits {
+            max_pending_tasks: 2,
+            ..QueueLimits::default()
+        });
+
+        assert!(queue.add_task(wait_task()).await.is_ok());
+        assert!(queue.add_task(wait_task()).await.is_ok());
+        assert!(queue.add_task(wait_task()).await.is_err());
+        assert_eq!(queue.status().await.pending_tasks, 2);
+
+        queue.clear().await;
+        assert!(queue.add_task(wait_task()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        let mut tasks = vec![
+            AutomationTask::new(TaskCommand::Wait { milliseconds: 1 }, TaskPriority::Low),
+            AutomationTask::new(
+                TaskCommand::Type { text: "hello".to_string() },
+                TaskPriority::High,
+            ),
+            AutomationTask::new(
+                TaskCommand::Click { x: 10, y: 20, button: "left".to_string() },
+                TaskPriority::Normal,
+            ),
+            AutomationTask::new(TaskCommand::Wait { milliseconds: 2 }, TaskPriority::High),
+        ];
+        // Distinct creation times so the FIFO order within a priority is deterministic
+        let start = chrono::Utc::now();
+        for (index, task) in tasks.iter_mut().enumerate() {
+            task.created_at = start + chrono::Duration::seconds(index as i64);
+        }
+        for task in &tasks {
+            queue.add_task(task.clone()).await.unwrap();
+        }
+
+        let exported = queue.export_tasks().await;
+        let order: Vec<&str> = exported.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(order, vec![&tasks[1].id, &tasks[3].id, &tasks[2].id, &tasks[0].id]);
+        // Exporting leaves the queue untouched
+        assert_eq!(queue.status().await.pending_tasks, 4);
+
+        // Through JSON, as the frontend would store it
+        let json = serde_json::to_string(&exported).unwrap();
+        let restored: Vec<AutomationTask> = serde_json::from_str(&json).unwrap();
+
+        let (other, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        let ids = other.import_tasks(restored).await.unwrap();
+        assert_eq!(ids.len(), 4);
+
+        let reimported = other.export_tasks().await;
+        assert_eq!(
+            serde_json::to_value(&reimported).unwrap(),
+            serde_json::to_value(&exported).unwrap()
+        );
+        assert!(reimported
+            .iter()
+            .all(|task| task.trust_level == crate::automation::trust::TrustLevel::AskDangerous));
+    }
+
+    #[tokio::test]
+    async fn test_import_respects_caps() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        queue.set_limits(QueueLimits {
+            max_pending_tasks: 3,
+            ..QueueLimits::default()
+        });
+        let queued = wait_task();
+        queue.add_task(queued.clone()).await.unwrap();
+
+        // Too many: nothing is added
+        let too_many = vec![wait_task(), wait_task(), wait_task()];
+        assert!(queue.import_tasks(too_many).await.is_err());
+        assert_eq!(queue.status().await.pending_tasks, 1);
+
+        // Duplicate of a queued task: nothing is added
+        assert!(queue.import_tasks(vec![wait_task(), queued]).await.is_err());
+        assert_eq!(queue.status().await.pending_tasks, 1);
+
+        assert!(queue.import_tasks(vec![wait_task(), wait_task()]).await.is_ok());
+        assert_eq!(queue.status().await.pending_tasks, 3);
+    }
+
+    #[tokio::test]
+    async fn test_automation_pause_keeps_queue_pause() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        queue.add_task(wait_task()).await.unwrap();
+
+        queue.pause().await;
+        queue.set_automation_paused(true).await;
+        queue.set_automation_paused(false).await;
+        assert!(queue.status().await.is_paused);
+        queue.process_next(Instant::now()).await;
+        assert_eq!(queue.status().await.pending_tasks, 1);
+
+        // And resuming the queue doesn't undo the automation pause
+        queue.set_automation_paused(true).await;
+        queue.resume().await;
+        assert!(queue.status().await.is_paused);
+        queue.process_next(Instant::now()).await;
+        assert_eq!(queue.status().await.pending_tasks, 1);
+
+        queue.set_automation_paused(false).await;
+        queue.process_next(Instant::now()).await;
+        assert_eq!(queue.status().await.completed_tasks, 1);
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        // Try to run a task every 100ms for two minutes
+        let mut executed = Vec::new();
+        for tick in 0..1200u64 {
+            let now = start + Duration::from_millis(tick * 100);
+            if limiter.delay(now, 5).is_none() {
+                limiter.record(now);
+                executed.push(now);
+            }
+        }
+
+        // 5 in each of the two windows
+        assert_eq!(executed.len(), 10);
+        for (i, at) in executed.iter().enumerate().skip(5) {
+            assert!(at.duration_since(executed[i - 5]) >= Duration::from_secs(60));
+        }
+
+        let mut limiter = RateLimiter::new(Duration::from_secs(60));
+        limiter.record(start);
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(20), 1),
+            Some(Duration::from_secs(40))
+        );
+
+        // A cap of 0 runs nothing rather than everything
+        let mut limiter = RateLimiter::new(Duration::from_secs(60));
+        assert!(limiter.delay(start, 0).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_process_respects_rate_limit() {
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
+        queue.set_limits(QueueLimits {
+            max_tasks_per_minute: 3,
+            ..QueueLimits::default()
+        });
+        for _ in 0..5 {
+            queue.add_task(wait_task()).await.unwrap();
+        }
+
+        // One step every 100ms for a second
+        let start = Instant::now();
+        for step in 0..10u64 {
+            queue.process_next(start + Duration::from_millis(step * 100)).await;
+        }
+        let status = queue.status().await;
+        assert_eq!(status.completed_tasks, 3);
+        assert_eq!(status.pending_tasks, 2);
+
+        // The window has moved on a minute later
+        let wait = queue.process_next(start + Duration::from_secs(60)).await;
+        assert_eq!(wait, MIN_TASK_INTERVAL);
+        assert_eq!(queue.status().await.completed_tasks, 4);
+    }
+
+    #[tokio::test]
+    async fn test_tasks_needing_confirmation_are_refused() {
+        use crate::automation::trust::TrustLevel;
+
+        let trust = Arc::new(crate::automation::TrustManager::with_level(TrustLevel::AskAlways));
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), trust.clone());
+
+        let refused = queue.run_task(wait_task()).await;
+        assert!(!refused.success);
+        assert!(refused.error.unwrap().contains("confirmation"));
+        assert_eq!(queue.status().await.failed_tasks, 1);
+
+        // A dangerous command still needs confirmation at the default level
+        trust.set_level(TrustLevel::AskDangerous);
+        assert!(queue.run_task(wait_task()).await.success);
+        let custom = AutomationTask::new(
+            TaskCommand::Custom { name: "shell".to_string(), params: serde_json::json!({ "command": "true" }) },
+            TaskPriority::Normal,
+        );
+        assert!(!queue.run_task(custom).await.success);
+        assert_eq!(queue.status().await.completed_tasks, 1);
+        assert_eq!(queue.status().await.failed_tasks, 2);
+    }
+
     #[tokio::test]
     async fn test_queue_status() {
-        let (queue, _rx) = AutomationQueue::new();
+        let (queue, _rx) = AutomationQueue::with_history(TaskHistory::in_memory(10), Default::default());
         let status = queue.status().await;
         assert_eq!(status.pending_tasks, 0);
         assert_eq!(status.completed_tasks, 0);
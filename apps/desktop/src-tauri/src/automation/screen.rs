@@ -1,15 +1,23 @@
 /// Screen capture module with caching
 /// Uses xcap for cross-platform screenshot support with 500ms cache TTL
 
+use crate::jpeg::JpegOptions;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use image::{ImageBuffer, ImageEncoder, RgbaImage};
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
-use xcap::Monitor;
+use xcap::{Monitor, Window};
 
 const CACHE_TTL: Duration = Duration::from_millis(500);
 
+/// Largest allowed preview edge in pixels
+pub const MAX_PREVIEW_DIM: u32 = 640;
+/// Smallest allowed preview edge in pixels
+const MIN_PREVIEW_DIM: u32 = 16;
+/// JPEG quality of monitor previews
+const PREVIEW_JPEG_QUALITY: u8 = 70;
+
 /// Cached screenshot data
 struct CachedScreenshot {
     image: RgbaImage,
@@ -79,6 +87,24 @@ pub fn capture_monitor(monitor_index: usize) -> Result<RgbaImage, String> {
     Ok(image)
 }
 
+/// Screen rectangle in pixels, origin at the top-left of the primary monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Capture `rect` of the primary monitor, or all of it
+pub fn capture_rect(rect: Option<Rect>) -> Result<RgbaImage, String> {
+    match rect {
+        Some(rect) if rect.width == 0 || rect.height == 0 => Err("Region must not be empty".to_string()),
+        Some(rect) => capture_region(rect.x, rect.y, rect.width, rect.height),
+        None => capture_screenshot(),
+    }
+}
+
 /// Capture screenshot of specific region
 pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> Result<RgbaImage, String> {
     let full_screenshot = capture_screenshot()?;
@@ -101,6 +127,87 @@ pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> Result<RgbaIma
     Ok(cropped)
 }
 
+/// On-screen window, as listed by the window server
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct WindowInfo {
+    pub id: u32,
+    pub app_name: String,
+    pub title: String,
+    pub is_minimized: bool,
+}
+
+/// Window of `app_name` whose title contains `window_title` (both case-insensitive),
+/// preferring an exact title; an empty title matches the app's first window
+/// Minimized windows are skipped since they can't be captured
+pub fn find_window<'a>(windows: &'a [WindowInfo], app_name: &str, window_title: &str) -> Option<&'a WindowInfo> {
+    let app_name = app_name.trim().to_lowercase();
+    let window_title = window_title.trim().to_lowercase();
+
+    let candidates: Vec<&WindowInfo> = windows
+        .iter()
+        .filter(|window| !window.is_minimized && window.app_name.to_lowercase() == app_name)
+        .collect();
+
+    candidates
+        .iter()
+        .find(|window| window.title.to_lowercase() == window_title)
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|window| window.title.to_lowercase().contains(&window_title))
+        })
+        .copied()
+}
+
+/// Capture one window, even if it isn't focused or is partly covered
+/// On macOS xcap finds it with `CGWindowListCopyWindowInfo` and captures it
+/// by window ID with `CGWindowListCreateImage`
+pub fn capture_window(app_name: &str, window_title: &str) -> Result<RgbaImage, String> {
+    let windows = Window::all().map_err(|e| format!("Failed to list windows: {}", e))?;
+    let infos: Vec<WindowInfo> = windows
+        .iter()
+        .map(|window| WindowInfo {
+            id: window.id(),
+            app_name: window.app_name().to_string(),
+            title: window.title().to_string(),
+            is_minimized: window.is_minimized(),
+        })
+        .collect();
+
+    let target = find_window(&infos, app_name, window_title)
+        .ok_or_else(|| format!("No window matching {} \"{}\"", app_name, window_title))?;
+    let window = windows
+        .iter()
+        .find(|window| window.id() == target.id)
+        .ok_or_else(|| format!("Window {} closed", target.id))?;
+
+    window
+        .capture_image()
+        .map_err(|e| format!("Failed to capture window: {}", e))
+}
+
+/// Pixels per logical point of the primary monitor (2.0 on Retina displays)
+/// Captures are in pixels while input coordinates are in points
+pub fn primary_scale_factor() -> Result<f32, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
+    monitors
+        .first()
+        .map(|monitor| monitor.scale_factor())
+        .ok_or_else(|| "No monitors found".to_string())
+}
+
+fn monitor_info(index: usize, monitor: &Monitor) -> MonitorInfo {
+    MonitorInfo {
+        index,
+        name: monitor.name().to_string(),
+        width: monitor.width(),
+        height: monitor.height(),
+        x: monitor.x(),
+        y: monitor.y(),
+        is_primary: monitor.is_primary(),
+    }
+}
+
 /// Get list of available monitors
 pub fn get_monitors() -> Result<Vec<MonitorInfo>, String> {
     let monitors = Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
@@ -108,20 +215,68 @@ pub fn get_monitors() -> Result<Vec<MonitorInfo>, String> {
     let info = monitors
         .iter()
         .enumerate()
-        .map(|(index, monitor)| MonitorInfo {
-            index,
-            name: monitor.name().to_string(),
-            width: monitor.width(),
-            height: monitor.height(),
-            x: monitor.x(),
-            y: monitor.y(),
-            is_primary: monitor.is_primary(),
-        })
+        .map(|(index, monitor)| monitor_info(index, monitor))
         .collect();
 
     Ok(info)
 }
 
+/// Capture a small preview of every monitor, at most `max_dim` pixels on the long edge
+pub fn get_monitor_previews(max_dim: u32) -> Result<Vec<MonitorPreview>, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
+
+    let info = monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| monitor_info(index, monitor))
+        .collect();
+
+    Ok(build_previews(info, max_dim, |index| {
+        monitors[index]
+            .capture_image()
+            .map_err(|e| format!("Failed to capture screenshot: {}", e))
+    }))
+}
+
+/// One preview per monitor using `capture`; monitors that fail to capture get no image
+fn build_previews<F>(monitors: Vec<MonitorInfo>, max_dim: u32, mut capture: F) -> Vec<MonitorPreview>
+where
+    F: FnMut(usize) -> Result<RgbaImage, String>,
+{
+    let max_dim = max_dim.clamp(MIN_PREVIEW_DIM, MAX_PREVIEW_DIM);
+
+    monitors
+        .into_iter()
+        .map(|monitor| {
+            let preview = capture(monitor.index).and_then(|image| {
+                let small = image::DynamicImage::ImageRgba8(image)
+                    .thumbnail(max_dim, max_dim)
+                    .to_rgba8();
+                let encoded = encode_to_base64_jpeg(&small, JpegOptions::new(PREVIEW_JPEG_QUALITY))?;
+                Ok((encoded, small.width(), small.height()))
+            });
+
+            match preview {
+                Ok((image, preview_width, preview_height)) => MonitorPreview {
+                    monitor,
+                    image: Some(image),
+                    preview_width,
+                    preview_height,
+                },
+                Err(e) => {
+                    crate::logs::error(&format!("[Screen] Failed to preview monitor {}: {}", monitor.index, e));
+                    MonitorPreview {
+                        monitor,
+                        image: None,
+                        preview_width: 0,
+                        preview_height: 0,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
 /// Encode image to base64 PNG
 pub fn encode_to_base64(image: &RgbaImage) -> Result<String, String> {
     let mut buffer = Vec::new();
@@ -140,7 +295,7 @@ pub fn encode_to_base64(image: &RgbaImage) -> Result<String, String> {
 }
 
 /// Encode image to base64 JPEG
-pub fn encode_to_base64_jpeg(image: &RgbaImage, quality: u8) -> Result<String, String> {
+pub fn encode_to_base64_jpeg(image: &RgbaImage, options: JpegOptions) -> Result<String, String> {
     // Convert RGBA to RGB
     let rgb_image: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(
         image.width(),
@@ -151,18 +306,7 @@ pub fn encode_to_base64_jpeg(image: &RgbaImage, quality: u8) -> Result<String, S
         },
     );
 
-    let mut buffer = Vec::new();
-    let mut cursor = std::io::Cursor::new(&mut buffer);
-
-    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, quality)
-        .write_image(
-            rgb_image.as_raw(),
-            rgb_image.width(),
-            rgb_image.height(),
-            image::ColorType::Rgb8.into(),
-        )
-        .map_err(|e| format!("Failed to encode JPEG: {}", e))?;
-
+    let buffer = crate::jpeg::encode(&rgb_image, options)?;
     Ok(BASE64.encode(&buffer))
 }
 
@@ -185,10 +329,103 @@ pub struct MonitorInfo {
     pub is_primary: bool,
 }
 
+/// Small capture of one monitor for the monitor picker
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MonitorPreview {
+    #[serde(flatten)]
+    pub monitor: MonitorInfo,
+    /// Base64 JPEG (None if the monitor couldn't be captured)
+    pub image: Option<String>,
+    pub preview_width: u32,
+    pub preview_height: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn monitor(index: usize, width: u32, height: u32, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            index,
+            name: format!("Display {}", index),
+            width,
+            height,
+            x,
+            y: 0,
+            is_primary: index == 0,
+        }
+    }
+
+    #[test]
+    fn test_one_preview_per_monitor() {
+        let monitors = vec![monitor(0, 1920, 1080, 0), monitor(1, 1080, 1920, 1920)];
+        let geometry = monitors.clone();
+
+        let mut captured = Vec::new();
+        let previews = build_previews(monitors, 160, |index| {
+            captured.push(index);
+            let m = &geometry[index];
+            Ok(RgbaImage::from_pixel(m.width, m.height, image::Rgba([index as u8 * 100, 0, 0, 255])))
+        });
+
+        assert_eq!(captured, vec![0, 1]);
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].monitor.index, 0);
+        assert_eq!(previews[1].monitor.index, 1);
+        assert_eq!(previews[1].monitor.x, 1920);
+        assert_eq!((previews[0].preview_width, previews[0].preview_height), (160, 90));
+        assert_eq!((previews[1].preview_width, previews[1].preview_height), (90, 160));
+        assert!(previews.iter().all(|p| p.image.is_some()));
+    }
+
+    #[test]
+    fn test_preview_size_is_bounded() {
+        let previews = build_previews(vec![monitor(0, 4000, 2000, 0)], 100_000, |_| {
+            Ok(RgbaImage::new(4000, 2000))
+        });
+        assert_eq!(previews[0].preview_width, MAX_PREVIEW_DIM);
+
+        // A failed capture still lists the monitor
+        let previews = build_previews(vec![monitor(0, 800, 600, 0)], 100, |_| {
+            Err("capture failed".to_string())
+        });
+        assert_eq!(previews.len(), 1);
+        assert!(previews[0].image.is_none());
+    }
+
+    fn window(id: u32, app_name: &str, title: &str, is_minimized: bool) -> WindowInfo {
+        WindowInfo {
+            id,
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            is_minimized,
+        }
+    }
+
+    #[test]
+    fn test_find_window() {
+        let windows = vec![
+            window(1, "Safari", "Slack | general", false),
+            window(2, "Slack", "Slack | random - Acme", true),
+            window(3, "Slack", "Slack | general - Acme", false),
+            window(4, "Slack", "General", false),
+        ];
+        let id = |app: &str, title: &str| find_window(&windows, app, title).map(|w| w.id);
+
+        // App must match exactly; title is a case-insensitive substring
+        assert_eq!(id("slack", "general - acme"), Some(3));
+        // An exact title wins over an earlier partial match
+        assert_eq!(id("Slack", "general"), Some(4));
+        // Any window of the app when no title is given
+        assert_eq!(id("Slack", ""), Some(3));
+        assert_eq!(id("Safari", ""), Some(1));
+
+        // Minimized windows can't be captured
+        assert_eq!(id("Slack", "random"), None);
+        assert_eq!(id("Sla", "general"), None);
+        assert_eq!(id("Mail", ""), None);
+    }
+
     #[test]
     fn test_get_monitors() {
         let result = get_monitors();
@@ -4,7 +4,7 @@
 use futures_util::{stream::SplitSink, SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio::time::{sleep, Duration};
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
@@ -45,7 +45,12 @@ pub enum WsMessage {
 
     /// Authentication
     #[serde(rename = "auth")]
-    Auth { token: String },
+    Auth {
+        token: String,
+        /// Friendly device name shown on the dashboard
+        #[serde(default)]
+        device_name: Option<String>,
+    },
 
     #[serde(rename = "auth_success")]
     AuthSuccess { device_id: String },
@@ -53,6 +58,23 @@ pub enum WsMessage {
     #[serde(rename = "auth_error")]
     AuthError { message: String },
 
+    /// Server turns one collector on or off (e.g. screenshots during a compliance hold)
+    #[serde(rename = "set_collector")]
+    SetCollector {
+        request_id: String,
+        collector: crate::collector::CollectorKind,
+        enabled: bool,
+    },
+
+    /// Client reports the outcome of a collector change and the resulting switches
+    #[serde(rename = "collector_ack")]
+    CollectorAck {
+        request_id: String,
+        success: bool,
+        error: Option<String>,
+        toggles: crate::collector::CollectorToggles,
+    },
+
     /// Server sends automation suggestion for user approval
     #[serde(rename = "automation_suggestion")]
     AutomationSuggestion {
@@ -60,24 +82,45 @@ pub enum WsMessage {
     },
 }
 
+/// Why a connection ended
+#[derive(Debug, PartialEq, Eq)]
+enum Disconnect {
+    Closed,
+    /// New credentials were set; reconnect right away
+    CredentialsChanged,
+}
+
 /// WebSocket connection manager
 pub struct AutomationSync {
-    ws_url: String,
+    /// Includes the API key, so it changes when the token is replaced
+    ws_url: std::sync::RwLock<String>,
+    reconnect: Notify,
     auth_token: Option<String>,
     is_connected: Arc<Mutex<bool>>,
     queue: Arc<crate::automation::queue::AutomationQueue>,
+    /// The local trust level decides what the server may change unattended
+    trust: Arc<crate::automation::TrustManager>,
+    /// Switches remote collector changes are applied to
+    toggles: &'static crate::collector::CollectorSwitches,
     ws_writer: Arc<Mutex<Option<WsWriter>>>,
     app_handle: Option<tauri::AppHandle>,
 }
 
 impl AutomationSync {
     /// Create new sync manager
-    pub fn new(ws_url: String, queue: Arc<crate::automation::queue::AutomationQueue>) -> Self {
+    pub fn new(
+        ws_url: String,
+        queue: Arc<crate::automation::queue::AutomationQueue>,
+        trust: Arc<crate::automation::TrustManager>,
+    ) -> Self {
         Self {
-            ws_url,
+            ws_url: std::sync::RwLock::new(ws_url),
+            reconnect: Notify::new(),
             auth_token: None,
             is_connected: Arc::new(Mutex::new(false)),
             queue,
+            trust,
+            toggles: crate::collector::global_switches(),
             ws_writer: Arc::new(Mutex::new(None)),
             app_handle: None,
         }
@@ -88,6 +131,12 @@ impl AutomationSync {
         self.auth_token = Some(token);
     }
 
+    /// Drop the current connection and reconnect to `ws_url` (e.g. with a new token)
+    pub fn reconnect_to(&self, ws_url: String) {
+        *self.ws_url.write().unwrap() = ws_url;
+        self.reconnect.notify_one();
+    }
+
     /// Set app handle for notifications
     pub fn set_app_handle(&mut self, app: tauri::AppHandle) {
         self.app_handle = Some(app);
@@ -101,12 +150,17 @@ impl AutomationSync {
     /// Start WebSocket connection with auto-reconnect
     pub async fn start(self: Arc<Self>) {
         loop {
-            match self.connect().await {
-                Ok(_) => {
-                    println!("WebSocket connection closed, reconnecting...");
+            crate::supervisor::heartbeat(crate::supervisor::WEBSOCKET_TASK);
+            let result = self.connect().await;
+            match &result {
+                Ok(Disconnect::Closed) => {
+                    crate::logs::info("[Automation] WebSocket connection closed, reconnecting...");
+                }
+                Ok(Disconnect::CredentialsChanged) => {
+                    crate::logs::info("[Automation] WebSocket credentials changed, reconnecting...");
                 }
                 Err(e) => {
-                    eprintln!("WebSocket connection error: {}", e);
+                    crate::logs::error(&format!("[Automation] WebSocket connection error: {}", e));
                 }
             }
 
@@ -116,16 +170,22 @@ impl AutomationSync {
                 *connected = false;
             }
 
-            // Wait before reconnecting
-            sleep(RECONNECT_DELAY).await;
+            // Wait before reconnecting, unless new credentials are waiting to be tried
+            if result != Ok(Disconnect::CredentialsChanged) {
+                tokio::select! {
+                    _ = sleep(RECONNECT_DELAY) => {}
+                    _ = self.reconnect.notified() => {}
+                }
+            }
         }
     }
 
     /// Connect to WebSocket server
-    async fn connect(&self) -> Result<(), String> {
-        println!("Connecting to WebSocket: {}", self.ws_url);
+    async fn connect(&self) -> Result<Disconnect, String> {
+        let ws_url = self.ws_url.read().unwrap().clone();
+        crate::logs::info(&format!("[Automation] Connecting to WebSocket: {}", ws_url.split('?').next().unwrap_or_default()));
 
-        let (ws_stream, _) = connect_async(&self.ws_url)
+        let (ws_stream, _) = connect_async(&ws_url)
             .await
             .map_err(|e| format!("Failed to connect: {}", e))?;
 
@@ -147,6 +207,7 @@ impl AutomationSync {
         if let Some(token) = &self.auth_token {
             let auth_msg = WsMessage::Auth {
                 token: token.clone(),
+                device_name: Some(crate::config::device_name()),
             };
             let json = serde_json::to_string(&auth_msg)
                 .map_err(|e| format!("Failed to serialize auth: {}", e))?;
@@ -182,17 +243,32 @@ impl AutomationSync {
             }
         });
 
-        // Handle incoming messages
-        while let Some(msg) = read.next().await {
+        // Handle incoming messages until the connection ends or the credentials change
+        let disconnect = loop {
+            let msg = tokio::select! {
+                msg = read.next() => msg,
+                _ = self.reconnect.notified() => {
+                    let mut writer = self.ws_writer.lock().await;
+                    if let Some(w) = writer.as_mut() {
+                        let _ = w.close().await;
+                    }
+                    break Disconnect::CredentialsChanged;
+                }
+            };
+            let Some(msg) = msg else {
+                break Disconnect::Closed;
+            };
+
+            crate::supervisor::heartbeat(crate::supervisor::WEBSOCKET_TASK);
             match msg {
                 Ok(Message::Text(text)) => {
                     if let Err(e) = self.handle_message(&text).await {
-                        eprintln!("Error handling message: {}", e);
+                        crate::logs::error(&format!("[Automation] Error handling message: {}", e));
                     }
                 }
                 Ok(Message::Close(_)) => {
-                    println!("WebSocket closed by server");
-                    break;
+                    crate::logs::info("[Automation] WebSocket closed by server");
+                    break Disconnect::Closed;
                 }
                 Ok(Message::Ping(data)) => {
                     let mut writer = self.ws_writer.lock().await;
@@ -201,12 +277,12 @@ impl AutomationSync {
                     }
                 }
                 Err(e) => {
-                    eprintln!("WebSocket error: {}", e);
-                    break;
+                    crate::logs::error(&format!("[Automation] WebSocket error: {}", e));
+                    break Disconnect::Closed;
                 }
                 _ => {}
             }
-        }
+        };
 
         // Clear write handle on disconnect
         {
@@ -214,7 +290,7 @@ impl AutomationSync {
             *writer = None;
         }
 
-        Ok(())
+        Ok(disconnect)
     }
 
     /// Handle incoming WebSocket message
@@ -227,24 +303,50 @@ impl AutomationSync {
 
         match msg {
             WsMessage::AutomationTask { task } => {
+                if self.queue.is_automation_paused().await {
+                    crate::logs::info(&format!("[Automation] Paused, rejecting task: {}", task.id));
+                    let result = crate::automation::queue::TaskResult {
+                        task_id: task.id,
+                        success: false,
+                        error: Some("Automation is paused on this device".to_string()),
+                        output: None,
+                    };
+                    if let Err(e) = self.send_result(result).await {
+                        crate::logs::error(&format!("[Automation] Failed to report rejected task: {}", e));
+                    }
+                    return Ok(());
+                }
+
                 // Add task to queue
                 match self.queue.add_task(task).await {
                     Ok(task_id) => {
-                        println!("Added task to queue: {}", task_id);
+                        crate::logs::info(&format!("[Automation] Added task to queue: {}", task_id));
                     }
                     Err(e) => {
-                        eprintln!("Failed to add task: {}", e);
+                        crate::logs::error(&format!("[Automation] Failed to add task: {}", e));
                     }
                 }
             }
+            WsMessage::SetCollector { request_id, collector, enabled } => {
+                let outcome = self.apply_collector_change(collector, enabled).await;
+                let ack = WsMessage::CollectorAck {
+                    request_id,
+                    success: outcome.is_ok(),
+                    error: outcome.err(),
+                    toggles: self.toggles.get(),
+                };
+                if let Err(e) = self.send_message(&ack).await {
+                    crate::logs::error(&format!("[Automation] Failed to acknowledge collector change: {}", e));
+                }
+            }
             WsMessage::Pong { timestamp } => {
-                println!("Received pong: {}", timestamp);
+                crate::logs::debug(&format!("[Automation] Received pong: {}", timestamp));
             }
             WsMessage::AuthSuccess { device_id } => {
-                println!("Authentication successful: {}", device_id);
+                crate::logs::info(&format!("[Automation] Authentication successful: {}", device_id));
             }
             WsMessage::AuthError { message } => {
-                eprintln!("Authentication error: {}", message);
+                crate::logs::error(&format!("[Automation] Authentication error: {}", message));
             }
             WsMessage::AutomationSuggestion { suggestion } => {
                 let title = suggestion.get("title")
@@ -257,8 +359,8 @@ impl AutomationSync {
                     .and_then(|v| v.as_str())
                     .unwrap_or("unknown");
 
-                println!("[Suggestion] Received: {} - {}", title, description);
-                println!("[Suggestion] ID: {} | Full data: {:?}", suggestion_id, suggestion);
+                crate::logs::info(&format!("[Suggestion] Received: {} - {}", title, description));
+                crate::logs::debug(&format!("[Suggestion] ID: {} | Full data: {:?}", suggestion_id, suggestion));
 
                 // Show macOS notification
                 if let Some(app) = &self.app_handle {
@@ -268,20 +370,62 @@ impl AutomationSync {
                         description,
                         suggestion_id,
                     ) {
-                        eprintln!("[Suggestion] Failed to show notification: {}", e);
+                        crate::logs::error(&format!("[Suggestion] Failed to show notification: {}", e));
                     }
                 } else {
-                    eprintln!("[Suggestion] No app handle available for notification");
+                    crate::logs::error("[Suggestion] No app handle available for notification");
                 }
             }
             _ => {
-                println!("Received unknown message type");
+                crate::logs::info("[Automation] Received unknown message type");
             }
         }
 
         Ok(())
     }
 
+    /// Apply a remote collector change, if automation is enabled and the local
+    /// trust level lets it run unattended
+    async fn apply_collector_change(
+        &self,
+        collector: crate::collector::CollectorKind,
+        enabled: bool,
+    ) -> Result<(), String> {
+        if self.queue.is_automation_paused().await {
+            return Err("Automation is paused on this device".to_string());
+        }
+        if self.trust.get_level().requires_confirmation(crate::automation::trust::DangerLevel::Moderate) {
+            return Err("Collector changes need confirmation at this trust level".to_string());
+        }
+
+        self.toggles.set(collector, enabled);
+        crate::logs::info(&format!(
+            "[Automation] Remote {} {:?} collector",
+            if enabled { "enabled" } else { "disabled" },
+            collector
+        ));
+        Ok(())
+    }
+
+    /// Send a message to the server
+    async fn send_message(&self, msg: &WsMessage) -> Result<(), String> {
+        let mut writer_guard = self.ws_writer.lock().await;
+
+        let writer = writer_guard
+            .as_mut()
+            .ok_or_else(|| "WebSocket not connected".to_string())?;
+
+        let json = serde_json::to_string(msg)
+            .map_err(|e| format!("Failed to serialize message: {}", e))?;
+
+        writer
+            .send(Message::Text(json))
+            .await
+            .map_err(|e| format!("Failed to send message: {}", e))?;
+
+        Ok(())
+    }
+
     /// Send task result to server
     pub async fn send_result(&self, result: crate::automation::queue::TaskResult) -> Result<(), String> {
         let mut writer_guard = self.ws_writer.lock().await;
@@ -325,7 +469,7 @@ impl AutomationSync {
     }
 }
 
-/// Configuration from file
+/// Automation WebSocket configuration
 #[derive(Debug, Clone, Default)]
 pub struct AutomationConfig {
     pub ws_url: String,
@@ -333,66 +477,32 @@ pub struct AutomationConfig {
     pub device_id: String,
 }
 
-/// Read configuration from file
+/// Read configuration from the unified config file
+/// OBSERVER_WS_URL, OBSERVER_API_KEY and OBSERVER_DEVICE_ID override it
 pub fn read_config() -> AutomationConfig {
-    let mut config = AutomationConfig {
-        ws_url: "ws://localhost:8000".to_string(),
-        api_key: None,
-        device_id: get_device_id(),
-    };
-
-    // Try environment variables first
-    if let Ok(url) = std::env::var("OBSERVER_WS_URL") {
-        config.ws_url = url;
-    }
-    if let Ok(key) = std::env::var("OBSERVER_API_KEY") {
-        config.api_key = Some(key);
+    let file = crate::config::Config::load().unwrap_or_default();
+
+    AutomationConfig {
+        ws_url: crate::config::env_override("OBSERVER_WS_URL")
+            .or(file.ws_url)
+            .unwrap_or_else(|| "ws://localhost:8000".to_string()),
+        api_key: crate::sync::get_api_key(),
+        device_id: crate::config::env_override("OBSERVER_DEVICE_ID")
+            .unwrap_or_else(crate::config::device_id),
     }
-
-    // Try to read from config file
-    if let Ok(home) = std::env::var("HOME") {
-        let config_path = format!("{}/.observer/config.json", home);
-        if let Ok(content) = std::fs::read_to_string(&config_path) {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(url) = json.get("ws_url").and_then(|v| v.as_str()) {
-                    config.ws_url = url.to_string();
-                }
-                if let Some(key) = json.get("api_key").and_then(|v| v.as_str()) {
-                    config.api_key = Some(key.to_string());
-                }
-                if let Some(id) = json.get("device_id").and_then(|v| v.as_str()) {
-                    config.device_id = id.to_string();
-                }
-            }
-        }
-    }
-
-    config
-}
-
-/// Get unique device ID
-fn get_device_id() -> String {
-    // Try to get from environment
-    if let Ok(id) = std::env::var("OBSERVER_DEVICE_ID") {
-        return id;
-    }
-
-    // Generate from machine ID or hostname
-    if let Ok(hostname) = std::env::var("HOSTNAME") {
-        return format!("mac-{}", hostname);
-    }
-
-    // Fallback to random ID
-    format!("mac-{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("unknown"))
 }
 
 /// Get WebSocket URL with authentication
 pub fn get_websocket_url() -> String {
-    let config = read_config();
+    websocket_url(&read_config())
+}
+
+/// WebSocket URL for `config`, with its API key as a query parameter
+pub fn websocket_url(config: &AutomationConfig) -> String {
     let base_url = format!("{}/ws/automation/{}", config.ws_url, config.device_id);
 
     // Add API key as query parameter if available
-    if let Some(api_key) = config.api_key {
+    if let Some(api_key) = &config.api_key {
         format!("{}?api_key={}", base_url, api_key)
     } else {
         base_url
@@ -409,6 +519,50 @@ mod tests {
         assert!(url.starts_with("ws://") || url.starts_with("wss://"));
     }
 
+    #[tokio::test]
+    async fn test_new_token_reconnects_with_it() {
+        use crate::automation::queue::AutomationQueue;
+        use tokio::net::TcpListener;
+        use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+        /// Accept one WebSocket connection and return it with the requested URI
+        async fn accept(listener: &TcpListener) -> (WebSocketStream<tokio::net::TcpStream>, String) {
+            let (stream, _) = tokio::time::timeout(Duration::from_secs(5), listener.accept())
+                .await
+                .expect("no connection within 5s")
+                .unwrap();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let record_uri = move |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+                tx.send(request.uri().to_string()).unwrap();
+                Ok(response)
+            };
+            let ws = tokio_tungstenite::accept_hdr_async(stream, record_uri).await.unwrap();
+            (ws, rx.recv().unwrap())
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = |api_key: &str| AutomationConfig {
+            ws_url: format!("ws://{}", listener.local_addr().unwrap()),
+            api_key: Some(api_key.to_string()),
+            device_id: "device-1".to_string(),
+        };
+
+        let trust = Arc::new(crate::automation::TrustManager::new());
+        let (queue, _rx) = AutomationQueue::with_history(crate::automation::history::TaskHistory::in_memory(10), trust.clone());
+        let sync = Arc::new(AutomationSync::new(websocket_url(&config("old-token")), Arc::new(queue), trust));
+        let task = tokio::spawn(sync.clone().start());
+
+        let (_first, uri) = accept(&listener).await;
+        assert_eq!(uri, "/ws/automation/device-1?api_key=old-token");
+
+        // The open connection is dropped and replaced without waiting out the reconnect delay
+        sync.reconnect_to(websocket_url(&config("new-token")));
+        let (_second, uri) = accept(&listener).await;
+        assert_eq!(uri, "/ws/automation/device-1?api_key=new-token");
+
+        task.abort();
+    }
+
     #[test]
     fn test_ws_message_serialization() {
         let ping = WsMessage::Ping { timestamp: 12345 };
@@ -416,4 +570,89 @@ mod tests {
         assert!(json.contains("ping"));
         assert!(json.contains("12345"));
     }
+
+    #[tokio::test]
+    async fn test_paused_automation_rejects_tasks_but_collection_continues() {
+        use crate::automation::queue::{AutomationQueue, AutomationTask, TaskCommand, TaskPriority};
+
+        let trust = Arc::new(crate::automation::TrustManager::new());
+        let (queue, _rx) = AutomationQueue::with_history(crate::automation::history::TaskHistory::in_memory(10), trust.clone());
+        let queue = Arc::new(queue);
+        let sync = AutomationSync::new("ws://localhost:8000/ws".to_string(), queue.clone(), trust);
+
+        let db = Arc::new(crate::db::EventDatabase::in_memory().unwrap());
+        let mut state = crate::AppState::new(db, Vec::new());
+
+        queue.set_automation_paused(true).await;
+
+        let task = AutomationTask::new(TaskCommand::Wait { milliseconds: 10 }, TaskPriority::Normal);
+        let message = serde_json::to_string(&WsMessage::AutomationTask { task }).unwrap();
+        sync.handle_message(&message).await.unwrap();
+
+        let status = queue.status().await;
+        assert_eq!(status.pending_tasks, 0);
+        assert!(status.is_paused);
+        assert!(status.automation_paused);
+
+        // The collector is independent of the automation pause
+        assert!(state.collecting);
+        crate::collector::push_event_with(
+            &mut state,
+            crate::collector::Event::new("app_focus", Some("Code".to_string()), None),
+            &Default::default(),
+        );
+        assert_eq!(state.events_buffer.len(), 1);
+        assert_eq!(state.events_today, 1);
+
+        // Resuming accepts tasks again
+        queue.set_automation_paused(false).await;
+        sync.handle_message(&message).await.unwrap();
+        assert_eq!(queue.status().await.pending_tasks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_remote_collector_toggle() {
+        use crate::automation::queue::AutomationQueue;
+        use crate::automation::trust::TrustLevel;
+        use crate::collector::{CollectorKind, CollectorSwitches};
+
+        let trust = Arc::new(crate::automation::TrustManager::new());
+        let (queue, _rx) = AutomationQueue::with_history(crate::automation::history::TaskHistory::in_memory(10), trust.clone());
+        let queue = Arc::new(queue);
+        let mut sync = AutomationSync::new("ws://localhost:8000/ws".to_string(), queue.clone(), trust.clone());
+        let switches: &'static CollectorSwitches = Box::leak(Box::new(CollectorSwitches::new()));
+        sync.toggles = switches;
+        let set_messages = |enabled: bool| {
+            format!(
+                r#"{{"type":"set_collector","request_id":"hold-1","collector":"messages","enabled":{}}}"#,
+                enabled
+            )
+        };
+
+        // A trust level sent by the server is not part of the message and is ignored
+        let spoofed = r#"{"type":"set_collector","request_id":"hold-1","collector":"messages","enabled":false,"trust_level":"FullTrust"}"#;
+        assert!(matches!(
+            serde_json::from_str::<WsMessage>(spoofed).unwrap(),
+            WsMessage::SetCollector { collector: CollectorKind::Messages, enabled: false, .. }
+        ));
+
+        assert!(switches.get().messages);
+        sync.handle_message(&set_messages(false)).await.unwrap();
+        assert!(!switches.get().messages);
+        assert!(switches.get().screenshots);
+
+        // Ignored while automation is paused or when every action needs confirmation locally
+        queue.set_automation_paused(true).await;
+        sync.handle_message(&set_messages(true)).await.unwrap();
+        assert!(!switches.get().messages);
+        queue.set_automation_paused(false).await;
+        trust.set_level(TrustLevel::AskAlways);
+        sync.handle_message(spoofed).await.unwrap();
+        sync.handle_message(&set_messages(true)).await.unwrap();
+        assert!(!switches.get().messages);
+
+        trust.set_level(TrustLevel::AskDangerous);
+        sync.handle_message(&set_messages(true)).await.unwrap();
+        assert!(switches.get().messages);
+    }
 }
@@ -1,8 +1,12 @@
 /// Trust level management for automation commands
 /// Controls which actions require user confirmation
 
+use chrono::{DateTime, Duration, Utc};
 use serde::{Serialize, Deserialize};
 
+/// Minutes `FullTrust` stays on before reverting to `AskDangerous` by default
+pub const DEFAULT_FULL_TRUST_EXPIRY_MINUTES: u64 = 60;
+
 /// Trust level for automation commands
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TrustLevel {
@@ -20,6 +24,16 @@ impl Default for TrustLevel {
     }
 }
 
+/// Trust level as saved in the config file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedTrust {
+    pub level: TrustLevel,
+    /// When a saved `FullTrust` reverts to the default level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
 /// Command danger level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DangerLevel {
@@ -77,6 +91,7 @@ pub fn classify_command_danger(command: &crate::automation::queue::TaskCommand)
     match command {
         // Safe commands
         TaskCommand::Screenshot { .. } => DangerLevel::Safe,
+        TaskCommand::ScreenshotWindow { .. } => DangerLevel::Safe,
         TaskCommand::BrowserGetUrl { .. } => DangerLevel::Safe,
         TaskCommand::Wait { .. } => DangerLevel::Safe,
 
@@ -91,6 +106,7 @@ pub fn classify_command_danger(command: &crate::automation::queue::TaskCommand)
             }
         }
         TaskCommand::BrowserNavigate { .. } => DangerLevel::Moderate,
+        TaskCommand::ClickText { .. } => DangerLevel::Moderate,
 
         // Dangerous commands
         TaskCommand::Hotkey { modifiers, key } => {
@@ -125,32 +141,113 @@ fn is_dangerous_hotkey(modifiers: &[String], key: &str) -> bool {
 /// Trust settings manager
 pub struct TrustManager {
     current_level: std::sync::RwLock<TrustLevel>,
+    /// When an activated `FullTrust` reverts to the default level
+    full_trust_expires_at: std::sync::RwLock<Option<DateTime<Utc>>>,
 }
 
 impl TrustManager {
     /// Create new trust manager with default level
     pub fn new() -> Self {
-        Self {
-            current_level: std::sync::RwLock::new(TrustLevel::default()),
-        }
+        Self::with_level(TrustLevel::default())
     }
 
     /// Create with specific trust level
     pub fn with_level(level: TrustLevel) -> Self {
         Self {
             current_level: std::sync::RwLock::new(level),
+            full_trust_expires_at: std::sync::RwLock::new(None),
+        }
+    }
+
+    /// Restore a saved level; a `FullTrust` that expired in the meantime is not restored
+    pub fn from_saved(saved: SavedTrust, now: DateTime<Utc>) -> Self {
+        let manager = Self::with_level(saved.level);
+        if saved.level == TrustLevel::FullTrust {
+            *manager.full_trust_expires_at.write().unwrap() = saved.expires_at;
         }
+        manager.expire(now);
+        manager
+    }
+
+    /// Restore the level saved in the config file
+    pub fn load() -> Self {
+        let saved = crate::config::Config::load()
+            .ok()
+            .and_then(|config| config.trust)
+            .unwrap_or_default();
+        Self::from_saved(saved, Utc::now())
+    }
+
+    /// Current level and expiry, for saving
+    pub fn saved(&self) -> SavedTrust {
+        SavedTrust {
+            level: self.get_level(),
+            expires_at: self.full_trust_expires_at(),
+        }
+    }
+
+    /// Save the current level to the config file so it survives a restart
+    pub fn save(&self) -> Result<(), String> {
+        let saved = self.saved();
+        crate::config::Config::update(|config| {
+            config.trust = Some(saved);
+            Ok(())
+        })
     }
 
-    /// Get current trust level
+    /// Get current trust level (an expired `FullTrust` has already reverted)
     pub fn get_level(&self) -> TrustLevel {
+        self.expire(Utc::now());
         *self.current_level.read().unwrap()
     }
 
     /// Set trust level
     pub fn set_level(&self, level: TrustLevel) {
-        let mut current = self.current_level.write().unwrap();
-        *current = level;
+        *self.current_level.write().unwrap() = level;
+        *self.full_trust_expires_at.write().unwrap() = None;
+    }
+
+    /// Change the trust level from the UI
+    /// `FullTrust` is only enabled with `confirmed`, and lasts `expires_after`
+    /// (None = until changed). Returns when it expires.
+    pub fn change_level(
+        &self,
+        level: TrustLevel,
+        confirmed: bool,
+        expires_after: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, String> {
+        if level != TrustLevel::FullTrust {
+            self.set_level(level);
+            return Ok(None);
+        }
+        if !confirmed {
+            return Err("Full trust lets remote commands run without asking; confirm to enable it".to_string());
+        }
+
+        let expires_at = expires_after.map(|duration| now + duration);
+        *self.current_level.write().unwrap() = TrustLevel::FullTrust;
+        *self.full_trust_expires_at.write().unwrap() = expires_at;
+        Ok(expires_at)
+    }
+
+    /// When the active `FullTrust` expires, if it does
+    pub fn full_trust_expires_at(&self) -> Option<DateTime<Utc>> {
+        *self.full_trust_expires_at.read().unwrap()
+    }
+
+    /// Revert `FullTrust` to the default level once it has expired
+    /// Returns true if it expired just now
+    pub fn expire(&self, now: DateTime<Utc>) -> bool {
+        let mut expires_at = self.full_trust_expires_at.write().unwrap();
+        match *expires_at {
+            Some(at) if at <= now => {
+                *expires_at = None;
+                *self.current_level.write().unwrap() = TrustLevel::default();
+                true
+            }
+            _ => false,
+        }
     }
 
     /// Check if command requires confirmation
@@ -197,12 +294,96 @@ mod tests {
         let screenshot = TaskCommand::Screenshot { save_path: None };
         assert_eq!(classify_command_danger(&screenshot), DangerLevel::Safe);
 
+        let screenshot_window = TaskCommand::ScreenshotWindow {
+            app_name: "Slack".to_string(),
+            window_title: String::new(),
+        };
+        assert_eq!(classify_command_danger(&screenshot_window), DangerLevel::Safe);
+
         let click = TaskCommand::Click {
             x: 100,
             y: 100,
             button: "left".to_string(),
         };
         assert_eq!(classify_command_danger(&click), DangerLevel::Moderate);
+
+        let click_text = TaskCommand::ClickText {
+            text: "OK".to_string(),
+            region: None,
+            match_mode: crate::automation::ocr::TextMatch::Exact,
+            occurrence: 1,
+        };
+        assert_eq!(classify_command_danger(&click_text), DangerLevel::Moderate);
+    }
+
+    #[test]
+    fn test_full_trust_requires_confirmation() {
+        let manager = TrustManager::new();
+        let now = Utc::now();
+
+        assert!(manager.change_level(TrustLevel::FullTrust, false, None, now).is_err());
+        assert_eq!(manager.get_level(), TrustLevel::AskDangerous);
+
+        // Other levels don't need it
+        assert_eq!(manager.change_level(TrustLevel::AskAlways, false, None, now), Ok(None));
+        assert_eq!(manager.get_level(), TrustLevel::AskAlways);
+
+        assert_eq!(manager.change_level(TrustLevel::FullTrust, true, None, now), Ok(None));
+        assert_eq!(manager.get_level(), TrustLevel::FullTrust);
+    }
+
+    #[test]
+    fn test_full_trust_expires() {
+        let manager = TrustManager::new();
+        let now = Utc::now();
+
+        let expires_at = manager
+            .change_level(TrustLevel::FullTrust, true, Some(Duration::minutes(30)), now)
+            .unwrap();
+        assert_eq!(expires_at, Some(now + Duration::minutes(30)));
+        assert_eq!(manager.full_trust_expires_at(), expires_at);
+
+        assert!(!manager.expire(now + Duration::minutes(29)));
+        assert_eq!(manager.get_level(), TrustLevel::FullTrust);
+
+        assert!(manager.expire(now + Duration::minutes(30)));
+        assert_eq!(manager.get_level(), TrustLevel::AskDangerous);
+        assert_eq!(manager.full_trust_expires_at(), None);
+        // Only reported once
+        assert!(!manager.expire(now + Duration::minutes(31)));
+
+        // Leaving full trust early clears the expiry
+        manager
+            .change_level(TrustLevel::FullTrust, true, Some(Duration::minutes(30)), now)
+            .unwrap();
+        manager.change_level(TrustLevel::AskAlways, false, None, now).unwrap();
+        assert!(!manager.expire(now + Duration::hours(1)));
+        assert_eq!(manager.get_level(), TrustLevel::AskAlways);
+    }
+
+    #[test]
+    fn test_saved_level_is_restored_until_it_expires() {
+        let now = Utc::now();
+        let manager = TrustManager::new();
+        manager
+            .change_level(TrustLevel::FullTrust, true, Some(Duration::minutes(30)), now)
+            .unwrap();
+
+        let json = serde_json::to_string(&manager.saved()).unwrap();
+        let saved: SavedTrust = serde_json::from_str(&json).unwrap();
+        assert_eq!(saved.expires_at, Some(now + Duration::minutes(30)));
+
+        let restored = TrustManager::from_saved(saved, now + Duration::minutes(10));
+        assert_eq!(restored.get_level(), TrustLevel::FullTrust);
+        assert_eq!(restored.full_trust_expires_at(), saved.expires_at);
+
+        let expired = TrustManager::from_saved(saved, now + Duration::minutes(31));
+        assert_eq!(expired.get_level(), TrustLevel::AskDangerous);
+        assert_eq!(expired.full_trust_expires_at(), None);
+
+        let ask_always = SavedTrust { level: TrustLevel::AskAlways, expires_at: None };
+        assert_eq!(TrustManager::from_saved(ask_always, now).get_level(), TrustLevel::AskAlways);
+        assert_eq!(TrustManager::from_saved(SavedTrust::default(), now).get_level(), TrustLevel::AskDangerous);
     }
 
     #[test]
@@ -0,0 +1,326 @@
+// OCR backfill module
+// Re-runs OCR over stored screenshots that have no text yet, as a cancellable
+// background job that reports progress to the frontend
+
+use crate::collector::Event;
+use crate::AppState;
+use chrono::{NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Frontend event carrying `BackfillProgress`
+pub const PROGRESS_EVENT: &str = "ocr-backfill-progress";
+
+/// Pause between screenshots so the backfill doesn't pin the CPU
+const OCR_THROTTLE: Duration = Duration::from_millis(500);
+
+/// Cancellation token of the running backfill (at most one at a time)
+static RUNNING_JOB: Lazy<std::sync::Mutex<Option<CancellationToken>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+/// Final counts of a backfill run
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct BackfillSummary {
+    pub processed: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+/// Progress update emitted after each screenshot
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillProgress {
+    pub total: usize,
+    pub processed: usize,
+    pub updated: usize,
+    pub failed: usize,
+}
+
+/// Whether an event has a screenshot but no (or empty) OCR text
+pub fn needs_ocr(event: &Event) -> bool {
+    if event.screenshot_path.is_none() {
+        return false;
+    }
+
+    event
+        .data
+        .get("ocr_text")
+        .and_then(|text| text.as_str())
+        .map_or(true, |text| text.trim().is_empty())
+}
+
+/// Events in the local days `from..=to` (timezone `tz`) that need OCR
+pub fn eligible_events<Tz: TimeZone>(
+    events: &[Event],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Vec<Event> {
+    events
+        .iter()
+        .filter(|e| {
+            let date = e.timestamp.with_timezone(tz).date_naive();
+            date >= from && date <= to && needs_ocr(e)
+        })
+        .cloned()
+        .collect()
+}
+
+/// OCR a stored screenshot (delta frames are reconstructed first)
+fn ocr_screenshot(path: &Path) -> Result<String, String> {
+    let image = crate::collector::load_screenshot(path)?;
+    crate::automation::ocr::extract_text_from_image(&image.to_rgba8()).map(|result| result.text)
+}
+
+/// Run OCR over `events` and store the text (blocking)
+///
+/// Events whose OCR returns no text count as processed but not updated.
+/// `on_progress` is called after every screenshot.
+pub fn run_backfill<O, P>(
+    state: &Mutex<AppState>,
+    events: &[Event],
+    ocr: O,
+    cancel: &CancellationToken,
+    throttle: Duration,
+    mut on_progress: P,
+) -> BackfillSummary
+where
+    O: Fn(&Path) -> Result<String, String>,
+    P: FnMut(BackfillProgress),
+{
+    let mut summary = BackfillSummary::default();
+
+    for (index, event) in events.iter().enumerate() {
+        if cancel.is_cancelled() {
+            summary.cancelled = true;
+            break;
+        }
+        if index > 0 && !throttle.is_zero() {
+            std::thread::sleep(throttle);
+        }
+
+        let Some(ref path) = event.screenshot_path else {
+            continue;
+        };
+
+        summary.processed += 1;
+        let started = std::time::Instant::now();
+        let result = ocr(Path::new(path));
+        crate::collector::record_timing(crate::collector::CollectorStep::Ocr, started.elapsed());
+        match result {
+            Ok(text) if text.trim().is_empty() => {}
+            Ok(text) => match store_ocr_text(state, event, &text) {
+                Ok(()) => summary.updated += 1,
+                Err(e) => {
+                    crate::logs::error(&format!("[Backfill] {}", e));
+                    summary.failed += 1;
+                }
+            },
+            Err(e) => {
+                crate::logs::error(&format!("[Backfill] OCR failed for {}: {}", path, e));
+                summary.failed += 1;
+            }
+        }
+
+        on_progress(BackfillProgress {
+            total: events.len(),
+            processed: summary.processed,
+            updated: summary.updated,
+            failed: summary.failed,
+        });
+    }
+
+    summary
+}
+
+/// Write OCR text into the event's data, in both the database and the buffer
+fn store_ocr_text(state: &Mutex<AppState>, event: &Event, text: &str) -> Result<(), String> {
+    let text = crate::text::truncate_chars(text, crate::text::MAX_OCR_CHARS);
+    let mut state = state.blocking_lock();
+
+    let mut data = state
+        .events_buffer
+        .iter()
+        .find(|e| e.id == event.id)
+        .map(|e| e.data.clone())
+        .unwrap_or_else(|| event.data.clone());
+    if !data.is_object() {
+        data = serde_json::json!({});
+    }
+    data["ocr_text"] = serde_json::json!(text);
+
+    state
+        .db
+        .update_data(&event.id, &data)
+        .map_err(|e| format!("Failed to update event {}: {}", event.id, e))?;
+
+    if let Some(buffered) = state.events_buffer.iter_mut().find(|e| e.id == event.id) {
+        buffered.data = data;
+    }
+    Ok(())
+}
+
+/// Backfill OCR text for screenshots in the local days `from..=to`
+/// Only one backfill runs at a time; progress is emitted as `PROGRESS_EVENT`
+pub async fn backfill_ocr(
+    app: AppHandle,
+    state: Arc<Mutex<AppState>>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BackfillSummary, String> {
+    let cancel = CancellationToken::new();
+    {
+        let mut running = RUNNING_JOB.lock().unwrap();
+        if running.is_some() {
+            return Err("OCR backfill is already running".to_string());
+        }
+        *running = Some(cancel.clone());
+    }
+
+    let events = {
+        let state = state.lock().await;
+        eligible_events(&state.events_buffer, from, to, &chrono::Local)
+    };
+    crate::logs::info(&format!("[Backfill] {} screenshots need OCR ({} to {})", events.len(), from, to));
+
+    let result = tokio::task::spawn_blocking(move || {
+        run_backfill(&state, &events, ocr_screenshot, &cancel, OCR_THROTTLE, |progress| {
+            let _ = app.emit(PROGRESS_EVENT, progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("OCR backfill failed: {}", e));
+
+    *RUNNING_JOB.lock().unwrap() = None;
+
+    let summary = result?;
+    crate::logs::info(&format!(
+        "[Backfill] Done: {} processed, {} updated, {} failed{}",
+        summary.processed,
+        summary.updated,
+        summary.failed,
+        if summary.cancelled { " (cancelled)" } else { "" }
+    ));
+    Ok(summary)
+}
+
+/// Cancel the running backfill
+/// Returns false if none was running
+pub fn cancel_backfill() -> bool {
+    match RUNNING_JOB.lock().unwrap().as_ref() {
+        Some(cancel) => {
+            cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventDatabase;
+    use chrono::Utc;
+
+    fn screenshot_event(day: u32, path: Option<&str>, ocr_text: Option<&str>) -> Event {
+        let mut event = Event::new("app_focus", Some("Code".to_string()), None);
+        event.timestamp = Utc.with_ymd_and_hms(2026, 1, day, 12, 0, 0).unwrap();
+        event.screenshot_path = path.map(|p| p.to_string());
+        if let Some(text) = ocr_text {
+            event.data = serde_json::json!({ "ocr_text": text });
+        }
+        event
+    }
+
+    #[test]
+    fn test_selects_eligible_events() {
+        let events = vec![
+            screenshot_event(8, Some("/s/a.jpg"), None),
+            screenshot_event(8, Some("/s/b.jpg"), Some("  ")),
+            screenshot_event(8, Some("/s/c.jpg"), Some("already read")),
+            screenshot_event(8, None, None),
+            screenshot_event(9, Some("/s/d.jpg"), None),
+            screenshot_event(10, Some("/s/e.jpg"), None),
+        ];
+
+        let from = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 1, 9).unwrap();
+        let eligible = eligible_events(&events, from, to, &Utc);
+
+        let ids: Vec<&str> = eligible.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec![events[0].id.as_str(), events[1].id.as_str(), events[4].id.as_str()]);
+    }
+
+    #[test]
+    fn test_backfill_updates_db_and_buffer() {
+        let mut with_data = screenshot_event(8, Some("/s/a.jpg"), None);
+        with_data.data = serde_json::json!({ "selected_text": "keep me" });
+        let events = vec![
+            with_data,
+            screenshot_event(8, Some("/s/blank.jpg"), None),
+            screenshot_event(8, Some("/s/broken.jpg"), None),
+        ];
+
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        for event in &events {
+            db.insert_event(event).unwrap();
+        }
+        let state = Mutex::new(AppState::new(db.clone(), events.clone()));
+
+        let mut progress_calls = 0;
+        let summary = run_backfill(
+            &state,
+            &events,
+            |path| match path.to_str().unwrap() {
+                "/s/a.jpg" => Ok("fn main() {}".to_string()),
+                "/s/blank.jpg" => Ok(String::new()),
+                _ => Err("corrupt image".to_string()),
+            },
+            &CancellationToken::new(),
+            Duration::ZERO,
+            |_| progress_calls += 1,
+        );
+
+        assert_eq!(
+            summary,
+            BackfillSummary { processed: 3, updated: 1, failed: 1, cancelled: false }
+        );
+        assert_eq!(progress_calls, 3);
+
+        let stored = db.load_all_events().unwrap();
+        let updated = stored.iter().find(|e| e.id == events[0].id).unwrap();
+        assert_eq!(updated.data["ocr_text"], "fn main() {}");
+        assert_eq!(updated.data["selected_text"], "keep me");
+        assert!(needs_ocr(stored.iter().find(|e| e.id == events[1].id).unwrap()));
+
+        let buffered = &state.blocking_lock().events_buffer[0];
+        assert_eq!(buffered.data["ocr_text"], "fn main() {}");
+    }
+
+    #[test]
+    fn test_cancelled_backfill_stops() {
+        let events = vec![screenshot_event(8, Some("/s/a.jpg"), None)];
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        let state = Mutex::new(AppState::new(db, events.clone()));
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let summary = run_backfill(
+            &state,
+            &events,
+            |_| panic!("OCR should not run after cancel"),
+            &cancel,
+            Duration::ZERO,
+            |_| {},
+        );
+
+        assert!(summary.cancelled);
+        assert_eq!(summary.processed, 0);
+    }
+}
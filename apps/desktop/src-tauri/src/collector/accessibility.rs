@@ -79,7 +79,7 @@ pub mod macos {
 
         rx.recv().unwrap_or_else(|_| {
             // This should never happen unless the main thread callback panicked
-            eprintln!("Warning: Main thread callback failed, returning default value");
+            crate::logs::error("[Accessibility] Warning: Main thread callback failed, returning default value");
             panic!("Failed to receive result from main thread - callback may have panicked")
         })
     }
@@ -94,6 +94,35 @@ pub mod macos {
     const K_AX_SELECTED_TEXT_ATTRIBUTE: &str = "AXSelectedText";
     const K_AX_VALUE_ATTRIBUTE: &str = "AXValue";
     const K_AX_ROLE_ATTRIBUTE: &str = "AXRole";
+    const K_AX_SUBROLE_ATTRIBUTE: &str = "AXSubrole";
+    const K_AX_SECURE_TEXT_FIELD: &str = "AXSecureTextField";
+
+    /// Check if an element is a secure (password) text field
+    ///
+    /// # Safety
+    /// `element` must be a valid AXUIElementRef
+    unsafe fn is_secure_element(element: *mut c_void) -> bool {
+        [K_AX_ROLE_ATTRIBUTE, K_AX_SUBROLE_ATTRIBUTE].iter().any(|attribute| {
+            let attr_name = CFString::new(attribute);
+            let mut value: *mut c_void = std::ptr::null_mut();
+
+            let result = AXUIElementCopyAttributeValue(
+                element,
+                attr_name.as_concrete_TypeRef() as *const c_void,
+                &mut value,
+            );
+
+            if result == K_AX_ERROR_SUCCESS && !value.is_null() {
+                let cf_string = CFString::wrap_under_create_rule(value as _);
+                cf_string.to_string() == K_AX_SECURE_TEXT_FIELD
+            } else {
+                if !value.is_null() {
+                    CFRelease(value);
+                }
+                false
+            }
+        })
+    }
 
     /// Get information about the currently focused UI element (internal implementation)
     /// Returns (app_name, window_title) if successful
@@ -255,6 +284,12 @@ pub mod macos {
                 return None;
             }
 
+            // Never read selections from password fields
+            if is_secure_element(focused_element) {
+                CFRelease(focused_element);
+                return None;
+            }
+
             // Get selected text
             let selected_text_attr = CFString::new(K_AX_SELECTED_TEXT_ATTRIBUTE);
             let mut selected_text: *mut c_void = std::ptr::null_mut();
@@ -567,6 +602,61 @@ pub mod macos {
         })
     }
 
+    /// Check if the focused UI element is a secure (password) text field (internal implementation)
+    ///
+    /// # Thread Safety
+    /// This function must be called from the main thread only.
+    ///
+    /// # Safety
+    /// Uses unsafe FFI calls to Core Foundation and Accessibility APIs
+    fn is_focused_element_secure_impl() -> bool {
+        assert_main_thread();
+
+        unsafe {
+            let system_wide = AXUIElementCreateSystemWide();
+            if system_wide.is_null() {
+                return false;
+            }
+
+            let attr_name = CFString::new(K_AX_FOCUSED_APPLICATION_ATTRIBUTE);
+            let mut focused_app: *mut c_void = std::ptr::null_mut();
+            let result = AXUIElementCopyAttributeValue(
+                system_wide,
+                attr_name.as_concrete_TypeRef() as *const c_void,
+                &mut focused_app,
+            );
+            CFRelease(system_wide);
+            if result != K_AX_ERROR_SUCCESS || focused_app.is_null() {
+                return false;
+            }
+
+            let focused_attr = CFString::new(K_AX_FOCUSED_UI_ELEMENT_ATTRIBUTE);
+            let mut focused_element: *mut c_void = std::ptr::null_mut();
+            let element_result = AXUIElementCopyAttributeValue(
+                focused_app,
+                focused_attr.as_concrete_TypeRef() as *const c_void,
+                &mut focused_element,
+            );
+            CFRelease(focused_app);
+            if element_result != K_AX_ERROR_SUCCESS || focused_element.is_null() {
+                return false;
+            }
+
+            let secure = is_secure_element(focused_element);
+            CFRelease(focused_element);
+            secure
+        }
+    }
+
+    /// Check if the focused UI element is a secure (password) text field
+    ///
+    /// # Thread Safety
+    /// This function is thread-safe. It can be called from any thread.
+    /// If not on the main thread, it will automatically dispatch to the main thread.
+    pub fn is_focused_element_secure() -> bool {
+        run_on_main_thread(is_focused_element_secure_impl)
+    }
+
     /// Check if the app has accessibility permission
     ///
     /// # Thread Safety
@@ -691,6 +781,11 @@ pub mod macos {
         None
     }
 
+    /// Check if the focused UI element is a secure (password) text field
+    pub fn is_focused_element_secure() -> bool {
+        false
+    }
+
     /// Check if the app has accessibility permission
     pub fn has_accessibility_permission() -> bool {
         true
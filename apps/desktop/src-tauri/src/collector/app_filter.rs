@@ -0,0 +1,226 @@
+// System app filter
+// macOS system processes (login window, Dock, menu bar agents, helpers) briefly
+// take focus and would otherwise show up as app switches and in top apps.
+// Terminals and editors rewrite their window title on every command or file
+// change; for those only app switches are recorded.
+
+use super::{classify_transition, FocusTransition};
+use serde::{Deserialize, Serialize};
+
+/// Built-in system processes that are never tracked (matched case-insensitively, whole name)
+pub const SYSTEM_APPS: &[&str] = &[
+    "loginwindow",
+    "WindowServer",
+    "Dock",
+    "SystemUIServer",
+    "ControlCenter",
+    "NotificationCenter",
+    "UserNotificationCenter",
+    "ScreenSaverEngine",
+    "CoreServicesUIAgent",
+    "SecurityAgent",
+    "coreautha",
+    "universalAccessAuthWarn",
+    "talagent",
+    "AirPlayUIAgent",
+    "TextInputMenuAgent",
+    "Spotlight",
+];
+
+/// Apps whose window title changes are ignored by default
+pub const DEFAULT_TITLE_CHURN_APPS: &[&str] = &["iTerm2", "Terminal", "Code", "Visual Studio Code"];
+
+/// Which focused apps the collector ignores, stored in the app settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SystemAppFilter {
+    pub enabled: bool,
+    /// Extra process names to ignore
+    pub extra: Vec<String>,
+    /// Built-in names to track anyway
+    pub allow: Vec<String>,
+}
+
+impl Default for SystemAppFilter {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            extra: Vec::new(),
+            allow: Vec::new(),
+        }
+    }
+}
+
+impl SystemAppFilter {
+    /// Whether focus changes to `app_name` should be skipped
+    pub fn is_filtered(&self, app_name: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let app_name = app_name.trim();
+        if self.allow.iter().any(|name| name.eq_ignore_ascii_case(app_name)) {
+            return false;
+        }
+
+        SYSTEM_APPS.iter().any(|name| name.eq_ignore_ascii_case(app_name))
+            || self.extra.iter().any(|name| name.trim().eq_ignore_ascii_case(app_name))
+            || is_helper_process(app_name)
+    }
+}
+
+/// Whether title-only changes in `app_name` should be skipped
+pub fn ignores_title_changes(title_churn_apps: &[String], app_name: &str) -> bool {
+    let app_name = app_name.trim();
+    title_churn_apps.iter().any(|name| name.trim().eq_ignore_ascii_case(app_name))
+}
+
+/// What the collector does with one focus reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusReading {
+    /// A system process briefly took focus; the reading is skipped entirely
+    Filtered,
+    /// Nothing worth an event changed (including title churn in flagged apps)
+    Unchanged,
+    Changed(FocusTransition),
+}
+
+/// Classify a focus reading against the previous one, applying the system app
+/// filter and the title churn list
+pub fn read_focus(
+    filter: &SystemAppFilter,
+    title_churn_apps: &[String],
+    last: (Option<&str>, Option<&str>),
+    current: (Option<&str>, Option<&str>),
+) -> FocusReading {
+    let (current_app, current_title) = current;
+    if current_app.is_some_and(|app| filter.is_filtered(app)) {
+        return FocusReading::Filtered;
+    }
+
+    classify_transition(last.0, last.1, current_app, current_title)
+        .filter(|&transition| {
+            transition == FocusTransition::AppSwitch
+                || !current_app.is_some_and(|app| ignores_title_changes(title_churn_apps, app))
+        })
+        .map_or(FocusReading::Unchanged, FocusReading::Changed)
+}
+
+/// Helper processes such as "Google Chrome Helper" or "Slack Helper (Renderer)"
+fn is_helper_process(app_name: &str) -> bool {
+    app_name.ends_with(" Helper") || app_name.contains(" Helper (")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Events `read_focus` produces for a sequence of readings
+    /// The previous reading only moves on when an event is recorded, as in the collector
+    fn events_for(
+        filter: &SystemAppFilter,
+        title_churn_apps: &[String],
+        readings: &[(&str, &str)],
+    ) -> Vec<(String, FocusTransition)> {
+        let mut last: (Option<&str>, Option<&str>) = (None, None);
+        let mut events = Vec::new();
+
+        for &(app, title) in readings {
+            let current = (Some(app), Some(title));
+            if let FocusReading::Changed(transition) = read_focus(filter, title_churn_apps, last, current) {
+                events.push((app.to_string(), transition));
+                last = current;
+            }
+        }
+
+        events
+    }
+
+    #[test]
+    fn test_system_apps_produce_no_events() {
+        let readings = [
+            ("Code", "main.rs"),
+            ("Dock", ""),
+            ("Code", "main.rs"),
+            ("loginwindow", ""),
+            ("Google Chrome Helper (Renderer)", ""),
+            ("Safari", "Docs"),
+            ("NotificationCenter", ""),
+            ("Safari", "Docs"),
+        ];
+
+        let events = events_for(&SystemAppFilter::default(), &[], &readings);
+        assert_eq!(
+            events,
+            vec![
+                ("Code".to_string(), FocusTransition::AppSwitch),
+                ("Safari".to_string(), FocusTransition::AppSwitch),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legitimate_apps_are_tracked() {
+        let filter = SystemAppFilter::default();
+        for app in [
+            "Docker",
+            "Docker Desktop",
+            "Finder",
+            "Help Viewer",
+            "Spotlight Search Pro",
+            "Google Chrome",
+            "System Settings",
+        ] {
+            assert!(!filter.is_filtered(app), "{} should be tracked", app);
+        }
+        assert!(filter.is_filtered("dock"));
+        assert!(filter.is_filtered("Slack Helper"));
+    }
+
+    #[test]
+    fn test_filter_is_editable() {
+        let filter = SystemAppFilter {
+            enabled: true,
+            extra: vec!["Bartender 5".to_string()],
+            allow: vec!["Spotlight".to_string()],
+        };
+        assert!(filter.is_filtered("Bartender 5"));
+        assert!(!filter.is_filtered("Spotlight"));
+        assert!(filter.is_filtered("Dock"));
+
+        let disabled = SystemAppFilter {
+            enabled: false,
+            ..SystemAppFilter::default()
+        };
+        assert!(!disabled.is_filtered("loginwindow"));
+    }
+
+    #[test]
+    fn test_title_churn_apps_only_record_app_switches() {
+        let churn: Vec<String> = DEFAULT_TITLE_CHURN_APPS.iter().map(|app| app.to_string()).collect();
+        let readings = [
+            ("iTerm2", "~/src (zsh)"),
+            ("iTerm2", "~/src/observer (git:main)"),
+            ("iTerm2", "cargo test"),
+            ("Code", "main.rs — observer"),
+            ("Code", "db.rs — observer"),
+            ("Safari", "Docs"),
+            ("Safari", "News"),
+            ("iTerm2", "vim"),
+        ];
+
+        let events = events_for(&SystemAppFilter::default(), &churn, &readings);
+        assert_eq!(
+            events,
+            vec![
+                ("iTerm2".to_string(), FocusTransition::AppSwitch),
+                ("Code".to_string(), FocusTransition::AppSwitch),
+                ("Safari".to_string(), FocusTransition::AppSwitch),
+                ("Safari".to_string(), FocusTransition::TitleChange),
+                ("iTerm2".to_string(), FocusTransition::AppSwitch),
+            ]
+        );
+
+        assert!(ignores_title_changes(&[" terminal ".to_string()], "Terminal"));
+        assert!(!ignores_title_changes(&churn, "Safari"));
+    }
+}
@@ -3,7 +3,7 @@
 
 #[cfg(target_os = "macos")]
 pub fn get_active_window() -> (Option<String>, Option<String>) {
-    use std::process::Command;
+    use crate::native_applescript;
 
     // Use osascript to get the active application name and window title
     let app_script = r#"
@@ -24,28 +24,10 @@ pub fn get_active_window() -> (Option<String>, Option<String>) {
         end tell
     "#;
 
-    let app_name = Command::new("osascript")
-        .arg("-e")
-        .arg(app_script)
-        .output()
-        .ok()
-        .and_then(|output| {
-            String::from_utf8(output.stdout)
-                .ok()
-                .map(|s| s.trim().to_string())
-        })
+    let app_name = native_applescript::collect(app_script)
         .filter(|s| !s.is_empty());
 
-    let window_title = Command::new("osascript")
-        .arg("-e")
-        .arg(title_script)
-        .output()
-        .ok()
-        .and_then(|output| {
-            String::from_utf8(output.stdout)
-                .ok()
-                .map(|s| s.trim().to_string())
-        })
+    let window_title = native_applescript::collect(title_script)
         .filter(|s| !s.is_empty());
 
     (app_name, window_title)
@@ -313,7 +295,7 @@ pub fn get_active_window() -> (Option<String>, Option<String>) {
 pub fn get_running_apps() -> Vec<String> {
     #[cfg(target_os = "macos")]
     {
-        use std::process::Command;
+        use crate::native_applescript;
 
         let script = r#"
             tell application "System Events"
@@ -322,12 +304,8 @@ pub fn get_running_apps() -> Vec<String> {
             end tell
         "#;
 
-        Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
+        native_applescript::run(script, native_applescript::DEFAULT_TIMEOUT)
             .ok()
-            .and_then(|output| String::from_utf8(output.stdout).ok())
             .map(|s| {
                 s.trim()
                     .split(", ")
@@ -0,0 +1,373 @@
+// Accessibility tree dump
+// Serializes the AX hierarchy of the frontmost app for debugging automation
+// targeting. The walk is bounded by depth, node count and time like the
+// messenger traversal, so huge windows can't produce huge dumps.
+
+use super::messenger::TraversalBudget;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Hard limits on what a caller may request
+pub const MAX_DUMP_DEPTH: usize = 50;
+pub const MAX_DUMP_NODES: usize = 10_000;
+
+/// Wall-clock budget for one dump
+const DUMP_TIME_BUDGET: Duration = Duration::from_secs(2);
+
+/// Longest value kept per node (characters)
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Role or subrole of password fields, whose values are never dumped
+const SECURE_TEXT_FIELD_ROLE: &str = "AXSecureTextField";
+
+/// An element of an accessibility hierarchy
+pub trait AxNode: Sized {
+    fn role(&self) -> Option<String>;
+    fn subrole(&self) -> Option<String>;
+    fn title(&self) -> Option<String>;
+    fn value(&self) -> Option<String>;
+    fn position(&self) -> Option<(f64, f64)>;
+    fn children(&self) -> Vec<Self>;
+}
+
+/// Serialize the tree under `root`, at most `max_depth` levels below it and
+/// `max_nodes` nodes in total
+///
+/// Returns `{"tree": node, "nodes": count, "truncated": bool}` where each node is
+/// `{"role", "title", "value", "position": {"x", "y"}, "children": [...]}`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn dump_tree<N: AxNode>(root: &N, max_depth: usize, max_nodes: usize) -> Value {
+    let mut budget = TraversalBudget::new(
+        DUMP_TIME_BUDGET,
+        max_nodes.clamp(1, MAX_DUMP_NODES),
+    );
+    let mut truncated = false;
+    let tree = dump_node(
+        root,
+        0,
+        max_depth.min(MAX_DUMP_DEPTH),
+        &mut budget,
+        &mut truncated,
+    );
+
+    json!({
+        "tree": tree,
+        "nodes": budget.visited(),
+        "truncated": truncated,
+    })
+}
+
+fn dump_node<N: AxNode>(
+    node: &N,
+    depth: usize,
+    max_depth: usize,
+    budget: &mut TraversalBudget,
+    truncated: &mut bool,
+) -> Option<Value> {
+    if !budget.visit() {
+        *truncated = true;
+        return None;
+    }
+
+    let role = node.role();
+    let secure = role.as_deref() == Some(SECURE_TEXT_FIELD_ROLE)
+        || node.subrole().as_deref() == Some(SECURE_TEXT_FIELD_ROLE);
+    let value = if secure {
+        None
+    } else {
+        node.value()
+            .map(|value| crate::text::truncate_string(value, MAX_VALUE_CHARS))
+    };
+
+    let children = node.children();
+    let mut dumped = Vec::new();
+    if depth < max_depth {
+        for child in &children {
+            match dump_node(child, depth + 1, max_depth, budget, truncated) {
+                Some(value) => dumped.push(value),
+                None => break,
+            }
+        }
+    } else if !children.is_empty() {
+        *truncated = true;
+    }
+
+    Some(json!({
+        "role": role,
+        "title": node.title(),
+        "value": value,
+        "position": node.position().map(|(x, y)| json!({ "x": x, "y": y })),
+        "children": dumped,
+    }))
+}
+
+/// Dump the AX tree of the frontmost application
+pub fn dump_focused_app_tree(max_depth: usize, max_nodes: usize) -> Result<Value, String> {
+    if !super::has_accessibility_permission() {
+        return Err("Accessibility permission is required to dump the AX tree".to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let app = macos::AxElement::focused_application()
+            .ok_or("Failed to get the focused application")?;
+        Ok(dump_tree(&app, max_depth, max_nodes))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (max_depth, max_nodes);
+        Err("AX tree dumps are only supported on macOS".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use macos::AxElement;
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::AxNode;
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::{CFGetTypeID, CFRelease, CFRetain, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    type AXUIElementRef = *const c_void;
+
+    /// kAXValueCGPointType
+    const K_AX_VALUE_CG_POINT_TYPE: u32 = 1;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct CGPoint {
+        x: f64,
+        y: f64,
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+        fn AXUIElementCopyAttributeValue(
+            element: AXUIElementRef,
+            attribute: CFStringRef,
+            value: *mut CFTypeRef,
+        ) -> i32;
+        fn AXValueGetValue(value: CFTypeRef, the_type: u32, value_ptr: *mut c_void) -> bool;
+    }
+
+    /// Owned (retained) AXUIElement
+    pub struct AxElement(AXUIElementRef);
+
+    impl Drop for AxElement {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0) };
+        }
+    }
+
+    impl AxElement {
+        /// Wrap an element the caller keeps owning, taking an extra retain
+        ///
+        /// # Safety
+        /// `element` must be a valid, non-null AXUIElementRef
+        pub unsafe fn retain(element: AXUIElementRef) -> Self {
+            CFRetain(element);
+            AxElement(element)
+        }
+
+        /// The frontmost application
+        pub fn focused_application() -> Option<Self> {
+            let system = unsafe { AXUIElementCreateSystemWide() };
+            if system.is_null() {
+                return None;
+            }
+            let system = AxElement(system);
+            system
+                .copy_attribute("AXFocusedApplication")
+                .map(|app| AxElement(app as AXUIElementRef))
+        }
+
+        /// Copy an attribute value (caller owns the result)
+        fn copy_attribute(&self, name: &str) -> Option<CFTypeRef> {
+            let attr_name = CFString::new(name);
+            let mut value: CFTypeRef = std::ptr::null();
+            let result = unsafe {
+                AXUIElementCopyAttributeValue(self.0, attr_name.as_concrete_TypeRef(), &mut value)
+            };
+
+            if result == 0 && !value.is_null() {
+                Some(value)
+            } else {
+                None
+            }
+        }
+
+        fn string_attribute(&self, name: &str) -> Option<String> {
+            let value = self.copy_attribute(name)?;
+            unsafe {
+                if CFGetTypeID(value) == CFString::type_id() {
+                    Some(CFString::wrap_under_create_rule(value as CFStringRef).to_string())
+                } else {
+                    CFRelease(value);
+                    None
+                }
+            }
+        }
+    }
+
+    impl AxNode for AxElement {
+        fn role(&self) -> Option<String> {
+            self.string_attribute("AXRole")
+        }
+
+        fn subrole(&self) -> Option<String> {
+            self.string_attribute("AXSubrole")
+        }
+
+        fn title(&self) -> Option<String> {
+            self.string_attribute("AXTitle")
+        }
+
+        fn value(&self) -> Option<String> {
+            self.string_attribute("AXValue")
+        }
+
+        fn position(&self) -> Option<(f64, f64)> {
+            let value = self.copy_attribute("AXPosition")?;
+            let mut point = CGPoint::default();
+            let ok = unsafe {
+                let ok = AXValueGetValue(
+                    value,
+                    K_AX_VALUE_CG_POINT_TYPE,
+                    &mut point as *mut CGPoint as *mut c_void,
+                );
+                CFRelease(value);
+                ok
+            };
+            ok.then_some((point.x, point.y))
+        }
+
+        fn children(&self) -> Vec<Self> {
+            let Some(value) = self.copy_attribute("AXChildren") else {
+                return Vec::new();
+            };
+
+            let children =
+                unsafe { CFArray::<AXUIElementRef>::wrap_under_create_rule(value as CFArrayRef) };
+            children
+                .iter()
+                .filter(|child| !child.is_null())
+                .map(|child| {
+                    unsafe { CFRetain(*child) };
+                    AxElement(*child)
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mock AX element
+    struct MockNode {
+        role: &'static str,
+        value: Option<&'static str>,
+        children: Vec<MockNode>,
+    }
+
+    impl MockNode {
+        fn new(role: &'static str, children: Vec<MockNode>) -> Self {
+            Self { role, value: None, children }
+        }
+    }
+
+    impl<'a> AxNode for &'a MockNode {
+        fn role(&self) -> Option<String> {
+            Some(self.role.to_string())
+        }
+
+        fn subrole(&self) -> Option<String> {
+            None
+        }
+
+        fn title(&self) -> Option<String> {
+            None
+        }
+
+        fn value(&self) -> Option<String> {
+            self.value.map(|value| value.to_string())
+        }
+
+        fn position(&self) -> Option<(f64, f64)> {
+            Some((10.0, 20.0))
+        }
+
+        fn children(&self) -> Vec<Self> {
+            let node: &'a MockNode = self;
+            node.children.iter().collect()
+        }
+    }
+
+    fn window() -> MockNode {
+        MockNode::new(
+            "AXApplication",
+            vec![MockNode::new(
+                "AXWindow",
+                vec![
+                    MockNode::new("AXButton", Vec::new()),
+                    MockNode {
+                        role: "AXTextField",
+                        value: Some("hello"),
+                        children: Vec::new(),
+                    },
+                    MockNode {
+                        role: SECURE_TEXT_FIELD_ROLE,
+                        value: Some("hunter2"),
+                        children: Vec::new(),
+                    },
+                ],
+            )],
+        )
+    }
+
+    #[test]
+    fn test_dump_full_tree() {
+        let root = window();
+        let dump = dump_tree(&&root, 10, 100);
+
+        assert_eq!(dump["nodes"], 5);
+        assert_eq!(dump["truncated"], false);
+
+        let tree = &dump["tree"];
+        assert_eq!(tree["role"], "AXApplication");
+        assert_eq!(tree["position"]["x"], 10.0);
+        let window = &tree["children"][0];
+        assert_eq!(window["role"], "AXWindow");
+        assert_eq!(window["children"].as_array().unwrap().len(), 3);
+        assert_eq!(window["children"][1]["value"], "hello");
+        assert!(window["children"][2]["value"].is_null());
+    }
+
+    #[test]
+    fn test_dump_bounded_by_depth() {
+        let root = window();
+        let dump = dump_tree(&&root, 1, 100);
+
+        assert_eq!(dump["nodes"], 2);
+        assert_eq!(dump["truncated"], true);
+        assert!(dump["tree"]["children"][0]["children"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dump_bounded_by_node_count() {
+        let root = window();
+        let dump = dump_tree(&&root, 10, 3);
+
+        assert_eq!(dump["nodes"], 3);
+        assert_eq!(dump["truncated"], true);
+        let buttons = dump["tree"]["children"][0]["children"].as_array().unwrap();
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0]["role"], "AXButton");
+    }
+}
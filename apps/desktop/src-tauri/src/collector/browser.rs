@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Default window in which an unchanged (app, url) is not stored again
+pub const DEFAULT_URL_DEDUP_SECS: u64 = 60;
 
 /// Represents information about a browser tab
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +29,69 @@ pub struct BrowserState {
     pub all_tabs: Vec<BrowserTab>,
 }
 
+/// Separates tabs in the all-tabs AppleScript output (ASCII record separator)
+const TAB_RECORD_SEPARATOR: char = '\u{1e}';
+/// Separates URL and title within a tab record (ASCII unit separator)
+const TAB_FIELD_SEPARATOR: char = '\u{1f}';
+/// Tabs parsed per browser; the rest of a pathological tab list is ignored
+pub const MAX_PARSED_TABS: usize = 500;
+
+/// AppleScript listing every tab as URL<US>title<RS>
+/// Control-character separators can't appear in titles, unlike ", "
+fn all_tabs_script(app: &str, title_property: &str) -> String {
+    format!(
+        r#"
+            tell application "{}"
+                set tabText to ""
+                repeat with w in windows
+                    repeat with t in tabs of w
+                        set tabText to tabText & (URL of t) & (ASCII character 31) & ({} of t) & (ASCII character 30)
+                    end repeat
+                end repeat
+                return tabText
+            end tell
+        "#,
+        app, title_property
+    )
+}
+
+/// Parse the all-tabs AppleScript output, dropping malformed and duplicate tabs
+/// Every record ends with a separator, so an unterminated tail is a record cut
+/// off by the output cap and is skipped. At most `MAX_PARSED_TABS` are returned.
+fn parse_tab_list(browser: &str, output: &str) -> Vec<BrowserTab> {
+    let mut seen = std::collections::HashSet::new();
+    let complete = output.rfind(TAB_RECORD_SEPARATOR).map_or("", |end| &output[..end]);
+
+    let mut tabs: Vec<BrowserTab> = complete
+        .split(TAB_RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let record = record.trim_matches(|c| c == '\n' || c == '\r');
+            let (url, title) = record.split_once(TAB_FIELD_SEPARATOR)?;
+            if url.is_empty() {
+                return None;
+            }
+            Some((url.to_string(), title.to_string()))
+        })
+        .filter(|tab| seen.insert(tab.clone()))
+        .map(|(url, title)| BrowserTab {
+            browser: browser.to_string(),
+            url,
+            title,
+            visible_text: None,
+        })
+        .take(MAX_PARSED_TABS + 1)
+        .collect();
+
+    if tabs.len() > MAX_PARSED_TABS {
+        tabs.truncate(MAX_PARSED_TABS);
+        crate::logs::error(&format!(
+            "[Browser] Warning: {} has more than {} tabs, ignoring the rest",
+            browser, MAX_PARSED_TABS
+        ));
+    }
+    tabs
+}
+
 /// Browser monitor for macOS
 #[allow(dead_code)]
 pub struct BrowserMonitor;
@@ -145,14 +212,18 @@ impl BrowserMonitor {
     /// Helper function to run AppleScript NATIVELY and parse tab information
     /// Uses NSAppleScript directly so macOS prompts for Automation permissions for Observer itself
     fn run_applescript_tab(&self, script: &str, browser_name: &str) -> Option<BrowserTab> {
-        let output_str = crate::native_applescript::execute(script)?;
+        let output_str = crate::native_applescript::collect(script)?;
 
         if output_str.is_empty() {
             return None;
         }
 
         // Log for debugging
-        println!("[Browser] {} returned: {}", browser_name, output_str);
+        crate::logs::debug(&format!(
+            "[Browser] {} returned: {}",
+            browser_name,
+            crate::text::truncate_chars(&output_str, crate::text::MAX_TITLE_CHARS)
+        ));
 
         // Parse the output format: "URL|||Title"
         let parts: Vec<&str> = output_str.split("|||").collect();
@@ -164,109 +235,34 @@ impl BrowserMonitor {
                 visible_text: None,
             })
         } else {
-            println!("[Browser] {} unexpected format: {}", browser_name, output_str);
+            crate::logs::info(&format!(
+                "[Browser] {} unexpected format: {}",
+                browser_name,
+                crate::text::truncate_chars(&output_str, crate::text::MAX_TITLE_CHARS)
+            ));
             None
         }
     }
 
     /// Get all open tabs from a specific browser
     pub fn get_all_tabs(&self, browser: &str) -> Vec<BrowserTab> {
-        let script = match browser.to_lowercase().as_str() {
-            "chrome" => r#"
-                tell application "Google Chrome"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & title of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
-            "safari" => r#"
-                tell application "Safari"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & name of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
-            "firefox" => r#"
-                tell application "Firefox"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & name of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
-            "edge" => r#"
-                tell application "Microsoft Edge"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & title of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
-            "arc" => r#"
-                tell application "Arc"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & title of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
-            "brave" => r#"
-                tell application "Brave Browser"
-                    set tabList to {}
-                    repeat with w in windows
-                        repeat with t in tabs of w
-                            set end of tabList to (URL of t & "|||" & title of t)
-                        end repeat
-                    end repeat
-                    return tabList
-                end tell
-            "#,
+        let (app, title_property) = match browser.to_lowercase().as_str() {
+            "chrome" => ("Google Chrome", "title"),
+            "safari" => ("Safari", "name"),
+            "firefox" => ("Firefox", "name"),
+            "edge" => ("Microsoft Edge", "title"),
+            "arc" => ("Arc", "title"),
+            "brave" => ("Brave Browser", "title"),
             _ => return vec![],
         };
 
-        let output_str = match crate::native_applescript::execute(script) {
+        let script = all_tabs_script(app, title_property);
+        let output_str = match crate::native_applescript::collect(&script) {
             Some(s) => s,
             None => return vec![],
         };
 
-        if output_str.is_empty() {
-            return vec![];
-        }
-
-        // Parse the output - AppleScript returns comma-separated list
-        output_str
-            .split(", ")
-            .filter_map(|item| {
-                let parts: Vec<&str> = item.split("|||").collect();
-                if parts.len() == 2 {
-                    Some(BrowserTab {
-                        browser: browser.to_string(),
-                        url: parts[0].to_string(),
-                        title: parts[1].to_string(),
-                        visible_text: None,
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect()
+        parse_tab_list(browser, &output_str)
     }
 
     /// Get the current browser state with active browser and all tabs
@@ -301,7 +297,7 @@ impl BrowserMonitor {
             browser_name
         );
 
-        crate::native_applescript::execute(&script)
+        crate::native_applescript::collect(&script)
             .map(|s| s.trim().to_lowercase() == "true")
             .unwrap_or(false)
     }
@@ -331,6 +327,45 @@ impl Default for BrowserMonitor {
     }
 }
 
+/// Guard against storing the same active tab over and over
+///
+/// Title-only changes (notification counters, spinners) re-report the same URL;
+/// those are dropped while the (app, url) pair was stored within the window.
+/// A different URL is a genuine navigation and always passes, and so is coming
+/// back to the tab after another app had focus (see `reset`).
+pub struct UrlDedup {
+    window: Duration,
+    last: Option<(String, String, Instant)>,
+}
+
+impl UrlDedup {
+    /// A zero window disables deduplication
+    pub fn new(window: Duration) -> Self {
+        Self { window, last: None }
+    }
+
+    /// Whether an event for `app` on `url` should be stored
+    /// Records the pair when it is
+    pub fn should_store(&mut self, app: &str, url: &str, now: Instant) -> bool {
+        if let Some((ref last_app, ref last_url, stored_at)) = self.last {
+            if last_app == app
+                && last_url == url
+                && now.saturating_duration_since(stored_at) < self.window
+            {
+                return false;
+            }
+        }
+
+        self.last = Some((app.to_string(), url.to_string(), now));
+        true
+    }
+
+    /// Forget the last tab, e.g. when an event for another app was stored
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,4 +397,98 @@ mod tests {
         assert_eq!(tab.browser, "Chrome");
         assert_eq!(tab.url, "https://example.com");
     }
+
+    #[test]
+    fn test_parse_tab_list_with_commas_in_titles() {
+        let output = "https://a.com/?q=1, 2\u{1f}Hello, World - Search\u{1e}\
+                      https://b.com\u{1f}News, Sports, Weather\u{1e}";
+        let tabs = parse_tab_list("Chrome", output);
+
+        assert_eq!(tabs.len(), 2);
+        assert_eq!(tabs[0].url, "https://a.com/?q=1, 2");
+        assert_eq!(tabs[0].title, "Hello, World - Search");
+        assert_eq!(tabs[1].url, "https://b.com");
+        assert_eq!(tabs[1].title, "News, Sports, Weather");
+        assert_eq!(tabs[1].browser, "Chrome");
+    }
+
+    #[test]
+    fn test_parse_tab_list_dedupes_and_skips_malformed() {
+        let output = "https://a.com\u{1f}A\u{1e}garbage\u{1e}https://a.com\u{1f}A\u{1e}\
+                      https://a.com\u{1f}A (2)\u{1e}\u{1f}no url\u{1e}";
+        let tabs = parse_tab_list("Safari", output);
+
+        assert_eq!(tabs.len(), 2);
+        assert_eq!(tabs[0].title, "A");
+        assert_eq!(tabs[1].title, "A (2)");
+    }
+
+    #[test]
+    fn test_url_dedup_passes_navigation() {
+        let mut dedup = UrlDedup::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(dedup.should_store("Safari", "https://a.com", start));
+        // Same tab re-reported while idling
+        assert!(!dedup.should_store("Safari", "https://a.com", start + Duration::from_secs(5)));
+        assert!(!dedup.should_store("Safari", "https://a.com", start + Duration::from_secs(30)));
+
+        // Navigation is always captured
+        assert!(dedup.should_store("Safari", "https://a.com/next", start + Duration::from_secs(31)));
+        // Same URL in another browser is a different pair
+        assert!(dedup.should_store("Arc", "https://a.com/next", start + Duration::from_secs(32)));
+
+        // Window elapsed - stored again
+        assert!(dedup.should_store("Arc", "https://a.com/next", start + Duration::from_secs(100)));
+    }
+
+    #[test]
+    fn test_url_dedup_passes_return_from_other_app() {
+        let mut dedup = UrlDedup::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        // Safari(a.com) -> Code -> Safari(a.com): the switch back is stored
+        assert!(dedup.should_store("Safari", "https://a.com", start));
+        dedup.reset();
+        assert!(dedup.should_store("Safari", "https://a.com", start + Duration::from_secs(5)));
+        assert!(!dedup.should_store("Safari", "https://a.com", start + Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn test_url_dedup_disabled() {
+        let mut dedup = UrlDedup::new(Duration::ZERO);
+        let now = Instant::now();
+        assert!(dedup.should_store("Safari", "https://a.com", now));
+        assert!(dedup.should_store("Safari", "https://a.com", now));
+    }
+
+    #[test]
+    fn test_oversized_tab_list_is_bounded() {
+        let record = |i: usize| format!("https://example.com/{}\u{1f}{}\u{1e}", i, "Title ".repeat(50));
+        let output: String = (0..5_000).map(record).collect();
+        assert!(output.len() > crate::native_applescript::MAX_OUTPUT_BYTES);
+
+        // Capped like osascript stdout, which cuts the last record in half
+        let (kept, truncated) = crate::native_applescript::read_capped(
+            &mut output.as_bytes(),
+            crate::native_applescript::MAX_OUTPUT_BYTES,
+        );
+        assert!(truncated);
+        let kept = String::from_utf8_lossy(&kept);
+        assert!(!kept.ends_with('\u{1e}'));
+
+        let tabs = parse_tab_list("Chrome", &kept);
+        assert_eq!(tabs.len(), MAX_PARSED_TABS);
+        assert_eq!(tabs[0].url, "https://example.com/0");
+        assert!(tabs.iter().all(|tab| tab.title.ends_with("Title ")));
+
+        // A short list ending mid-record keeps its complete records
+        let tabs = parse_tab_list("Chrome", "https://a.com\u{1f}A\u{1e}https://b.co");
+        assert_eq!(tabs.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_tab_list_empty() {
+        assert!(parse_tab_list("Arc", "").is_empty());
+    }
 }
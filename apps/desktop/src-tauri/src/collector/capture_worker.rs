@@ -0,0 +1,328 @@
+// Screenshot store/OCR worker
+// The collector only grabs the frame and hands it off with its event; encoding,
+// dedup, saving and OCR run here so a slow OCR never delays the next focus
+// check. The hand-off queue is bounded: when the workers fall behind, the
+// oldest pending capture is dropped.
+
+use super::screenshots::ScreenshotManager;
+use crate::AppState;
+use image::DynamicImage;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
+
+/// Default number of workers storing captures
+pub const DEFAULT_CAPTURE_WORKERS: usize = 1;
+/// Default number of captures waiting for a worker before the oldest is dropped
+pub const DEFAULT_CAPTURE_QUEUE_SIZE: usize = 4;
+
+/// A captured frame and the event it belongs to
+pub struct CaptureJob {
+    pub event_id: String,
+    pub image: DynamicImage,
+    pub app_name: String,
+    pub window_title: String,
+}
+
+struct QueueState<T> {
+    items: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+/// Bounded hand-off queue that drops the oldest item when full
+pub struct CaptureQueue<T> {
+    capacity: usize,
+    state: std::sync::Mutex<QueueState<T>>,
+    notify: Notify,
+}
+
+impl<T> CaptureQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: std::sync::Mutex::new(QueueState {
+                items: VecDeque::new(),
+                dropped: 0,
+                closed: false,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Queue `item`, returning the oldest item if it had to be dropped to make room
+    pub fn push(&self, item: T) -> Option<T> {
+        let dropped = {
+            let mut state = self.state.lock().unwrap();
+            let dropped = if state.items.len() >= self.capacity {
+                state.dropped += 1;
+                state.items.pop_front()
+            } else {
+                None
+            };
+            state.items.push_back(item);
+            dropped
+        };
+        self.notify.notify_one();
+        dropped
+    }
+
+    /// Next item, waiting for one; None once the queue is closed and drained
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            // Created before checking so a push or close in between isn't missed
+            let notified = self.notify.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    return Some(item);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// Stop accepting work; workers exit once the queue is drained
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.notify.notify_waiters();
+    }
+
+    /// Items dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+/// Closes the queue when dropped, so its workers exit with the collector that
+/// feeds them - also when the collector panics and is restarted
+pub struct CloseOnDrop<T>(pub Arc<CaptureQueue<T>>);
+
+impl<T> Drop for CloseOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Take items off `queue` and `process` them one at a time until it closes
+pub async fn run_worker<T, F, Fut>(queue: Arc<CaptureQueue<T>>, mut process: F)
+where
+    F: FnMut(T) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    while let Some(item) = queue.pop().await {
+        process(item).await;
+    }
+}
+
+/// Store a capture, OCR it if enabled, and attach both to its event (blocking)
+/// The event is released for sync afterwards, whether or not that worked
+pub fn process_capture(state: &Mutex<AppState>, manager: &std::sync::Mutex<ScreenshotManager>, job: CaptureJob) {
+    let event_id = job.event_id.clone();
+    store_capture(state, manager, job);
+    release_capture(&mut state.blocking_lock(), &event_id);
+}
+
+/// Let an event whose capture was handled (or lost) sync again
+pub fn release_capture(state: &mut AppState, event_id: &str) {
+    state.pending_captures.remove(event_id);
+}
+
+fn store_capture(state: &Mutex<AppState>, manager: &std::sync::Mutex<ScreenshotManager>, job: CaptureJob) {
+    let ocr_enabled = crate::commands::get_settings()
+        .map(|settings| settings.screenshot_ocr)
+        .unwrap_or(false);
+    let ocr_image = ocr_enabled.then(|| job.image.to_rgba8());
+
+    let Some(screenshot) = manager
+        .lock()
+        .unwrap()
+        .store_image(job.image, job.app_name, job.window_title)
+    else {
+        return;
+    };
+    let path = screenshot.path.to_string_lossy().to_string();
+    if screenshot.reused {
+        crate::logs::info(&format!("[Screenshot] Reused: {}", path));
+    } else {
+        crate::logs::info(&format!("[Screenshot] Saved: {}", path));
+    }
+
+    let ocr_text = ocr_image.and_then(|image| {
+        let result = super::timings::timed(super::CollectorStep::Ocr, || {
+            crate::automation::ocr::extract_text_from_image(&image)
+        });
+        match result {
+            Ok(result) if !result.text.trim().is_empty() => {
+                Some(crate::text::truncate_chars(&result.text, crate::text::MAX_OCR_CHARS).to_string())
+            }
+            Ok(_) => None,
+            Err(e) => {
+                crate::logs::error(&format!("[OCR] Error: {}", e));
+                None
+            }
+        }
+    });
+
+    let thumbnail_path = screenshot
+        .thumbnail_path
+        .map(|thumbnail| thumbnail.to_string_lossy().to_string());
+    attach_screenshot(
+        &mut state.blocking_lock(),
+        &job.event_id,
+        &path,
+        thumbnail_path.as_deref(),
+        ocr_text.as_deref(),
+    );
+}
+
+/// Set the screenshot and thumbnail paths (and OCR text) of an event in the
+/// database and the buffer
+pub fn attach_screenshot(
+    state: &mut AppState,
+    event_id: &str,
+    path: &str,
+    thumbnail_path: Option<&str>,
+    ocr_text: Option<&str>,
+) {
+    if let Err(e) = state.db.update_screenshot_path(event_id, path, thumbnail_path) {
+        crate::logs::error(&format!("[DB] Error: Failed to attach screenshot to {}: {}", event_id, e));
+    }
+    if let Some(event) = state.events_buffer.iter_mut().find(|e| e.id == event_id) {
+        event.screenshot_path = Some(path.to_string());
+        event.thumbnail_path = thumbnail_path.map(str::to_string);
+    }
+
+    let Some(text) = ocr_text else {
+        return;
+    };
+    let mut data = match super::find_event(state, event_id) {
+        Ok(Some(event)) if event.data.is_object() => event.data,
+        Ok(Some(_)) => serde_json::json!({}),
+        // Already synced or deleted
+        Ok(None) => return,
+        Err(e) => {
+            crate::logs::error(&format!("[DB] Error: {}", e));
+            return;
+        }
+    };
+    data["ocr_text"] = serde_json::json!(text);
+
+    if let Err(e) = state.db.update_data(event_id, &data) {
+        crate::logs::error(&format!("[DB] Error: Failed to store OCR text for {}: {}", event_id, e));
+    }
+    if let Some(event) = state.events_buffer.iter_mut().find(|e| e.id == event_id) {
+        event.data = data;
+    }
+}
+
+/// Start `workers` tasks storing captures from `queue`
+pub fn spawn_workers(
+    workers: usize,
+    queue: Arc<CaptureQueue<CaptureJob>>,
+    state: Arc<Mutex<AppState>>,
+    manager: Arc<std::sync::Mutex<ScreenshotManager>>,
+) {
+    for _ in 0..workers.max(1) {
+        let (queue, state, manager) = (queue.clone(), state.clone(), manager.clone());
+        tokio::spawn(run_worker(queue, move |job| {
+            let (state, manager) = (state.clone(), manager.clone());
+            async move {
+                let event_id = job.event_id.clone();
+                let worker_state = state.clone();
+                let result = tokio::task::spawn_blocking(move || process_capture(&worker_state, &manager, job)).await;
+                if let Err(e) = result {
+                    crate::logs::error(&format!("[Screenshot] Worker failed: {}", e));
+                    release_capture(&mut *state.lock().await, &event_id);
+                }
+            }
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_worker_processes_handed_off_items() {
+        let queue = Arc::new(CaptureQueue::new(8));
+        let processed = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let worker = {
+            let processed = processed.clone();
+            tokio::spawn(run_worker(queue.clone(), move |item: u32| {
+                let processed = processed.clone();
+                async move { processed.lock().unwrap().push(item) }
+            }))
+        };
+
+        for item in 1..=3 {
+            assert!(queue.push(item).is_none());
+        }
+        queue.close();
+        worker.await.unwrap();
+
+        assert_eq!(*processed.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(queue.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_worker_exits_when_feeder_panics() {
+        let queue = Arc::new(CaptureQueue::new(8));
+        let worker = tokio::spawn(run_worker(queue.clone(), |_: u32| async {}));
+
+        // A collector that dies still closes its queue on the way out
+        let feeder = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let _close = CloseOnDrop(queue.clone());
+                queue.push(1);
+                panic!("collector failed");
+            })
+        };
+        assert!(feeder.await.is_err());
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), worker)
+            .await
+            .expect("worker should exit once the queue closes")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_drops_oldest() {
+        // No worker running: the queue fills up
+        let queue = CaptureQueue::new(2);
+        assert_eq!(queue.push(1), None);
+        assert_eq!(queue.push(2), None);
+        assert_eq!(queue.push(3), Some(1));
+        assert_eq!(queue.push(4), Some(2));
+        assert_eq!(queue.dropped(), 2);
+
+        queue.close();
+        assert_eq!(queue.pop().await, Some(3));
+        assert_eq!(queue.pop().await, Some(4));
+        assert_eq!(queue.pop().await, None);
+    }
+
+    #[test]
+    fn test_attach_screenshot_updates_buffer_and_database() {
+        let db = Arc::new(crate::db::EventDatabase::in_memory().unwrap());
+        let mut state = AppState::new(db.clone(), Vec::new());
+        let event = super::super::Event::new("app_focus", Some("Code".to_string()), None);
+        super::super::push_event_with(&mut state, event.clone(), &Default::default());
+
+        attach_screenshot(&mut state, &event.id, "/shots/a.jpg", Some("/shots/a_thumb.jpg"), Some("fn main"));
+
+        assert_eq!(state.events_buffer[0].screenshot_path.as_deref(), Some("/shots/a.jpg"));
+        assert_eq!(state.events_buffer[0].thumbnail_path.as_deref(), Some("/shots/a_thumb.jpg"));
+        assert_eq!(state.events_buffer[0].data["ocr_text"], "fn main");
+        let stored = db.load_unsynced_events(10).unwrap();
+        assert_eq!(stored[0].screenshot_path.as_deref(), Some("/shots/a.jpg"));
+        assert_eq!(stored[0].thumbnail_path.as_deref(), Some("/shots/a_thumb.jpg"));
+        assert_eq!(stored[0].data["ocr_text"], "fn main");
+    }
+}
@@ -0,0 +1,212 @@
+// Category rules module
+// User-defined app -> category overrides on top of the built-in categorization
+
+use crate::AppState;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Categories an event can be assigned to
+pub const KNOWN_CATEGORIES: &[&str] = &[
+    "coding",
+    "browsing",
+    "communication",
+    "writing",
+    "design",
+    "system",
+    "other",
+];
+
+/// File holding the user rules inside the config directory
+pub const CATEGORY_RULES_FILE: &str = "category_rules.json";
+
+static CATEGORY_RULES: Lazy<RwLock<CategoryRules>> =
+    Lazy::new(|| RwLock::new(CategoryRules::load()));
+
+/// Check if a category is one of the known categories
+pub fn is_known_category(category: &str) -> bool {
+    KNOWN_CATEGORIES.contains(&category)
+}
+
+/// A single app -> category override
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryRule {
+    pub app_name: String,
+    pub category: String,
+}
+
+/// User category rules, persisted to config_dir/observer/category_rules.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryRules {
+    pub rules: Vec<CategoryRule>,
+}
+
+impl CategoryRules {
+    fn config_path() -> PathBuf {
+        crate::config::Config::dir().join(CATEGORY_RULES_FILE)
+    }
+
+    /// Load rules from disk (empty if missing or unreadable)
+    pub fn load() -> Self {
+        Self::load_from(&Self::config_path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist rules to disk
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&Self::config_path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize category rules: {}", e))?;
+        crate::config::write_atomic(path, content.as_bytes())
+            .map_err(|e| format!("Failed to write category rules: {}", e))
+    }
+
+    /// Check that every rule names an app and a known category
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in &self.rules {
+            if rule.app_name.trim().is_empty() {
+                return Err("Category rule has an empty app name".to_string());
+            }
+            if !is_known_category(&rule.category) {
+                return Err(format!(
+                    "Unknown category for {}: {}",
+                    rule.app_name, rule.category
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Category override for an app (case-insensitive exact match)
+    pub fn category_for(&self, app_name: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.app_name.eq_ignore_ascii_case(app_name))
+            .map(|rule| rule.category.as_str())
+    }
+
+    /// Add or replace the rule for an app
+    pub fn learn(&mut self, app_name: &str, category: &str) {
+        self.rules
+            .retain(|rule| !rule.app_name.eq_ignore_ascii_case(app_name));
+        self.rules.push(CategoryRule {
+            app_name: app_name.to_string(),
+            category: category.to_string(),
+        });
+    }
+}
+
+/// Category override for an app from the user rules
+pub fn user_category(app_name: &str) -> Option<String> {
+    CATEGORY_RULES
+        .read()
+        .unwrap()
+        .category_for(app_name)
+        .map(|c| c.to_string())
+}
+
+/// Replace the in-memory rules after they were saved elsewhere
+pub fn reload_category_rules() {
+    *CATEGORY_RULES.write().unwrap() = CategoryRules::load();
+}
+
+/// Learn a correction so future events from the app get the category
+pub fn learn_rule(app_name: &str, category: &str) -> Result<(), String> {
+    let mut rules = CATEGORY_RULES.write().unwrap();
+    rules.learn(app_name, category);
+    rules.save()
+}
+
+/// Change the category of an event in both the buffer and the database
+/// Returns the event's app name so the caller can learn a rule from it
+pub fn recategorize_event(
+    state: &mut AppState,
+    event_id: &str,
+    category: &str,
+) -> Result<Option<String>, String> {
+    if !is_known_category(category) {
+        return Err(format!("Unknown category: {}", category));
+    }
+
+    let updated_in_db = state
+        .db
+        .update_category(event_id, category)
+        .map_err(|e| format!("Failed to update event: {}", e))?;
+
+    let mut app_name = None;
+    let mut found_in_buffer = false;
+    if let Some(event) = state.events_buffer.iter_mut().find(|e| e.id == event_id) {
+        event.category = Some(category.to_string());
+        app_name = event.app_name.clone();
+        found_in_buffer = true;
+    }
+
+    if !updated_in_db && !found_in_buffer {
+        return Err(format!("Event not found: {}", event_id));
+    }
+
+    Ok(app_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::Event;
+    use crate::db::EventDatabase;
+    use std::sync::Arc;
+
+    fn state_with_event(event: &Event) -> AppState {
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(event).unwrap();
+        AppState::new(db, vec![event.clone()])
+    }
+
+    #[test]
+    fn test_recategorize_updates_buffer_and_db() {
+        let event = Event::new("app_focus", Some("Zed".to_string()), None).with_category("other");
+        let mut state = state_with_event(&event);
+
+        let app_name = recategorize_event(&mut state, &event.id, "coding").unwrap();
+        assert_eq!(app_name.as_deref(), Some("Zed"));
+        assert_eq!(state.events_buffer[0].category.as_deref(), Some("coding"));
+
+        let stored = state.db.load_all_events().unwrap();
+        assert_eq!(stored[0].category.as_deref(), Some("coding"));
+    }
+
+    #[test]
+    fn test_recategorize_rejects_unknown_category() {
+        let event = Event::new("app_focus", Some("Zed".to_string()), None);
+        let mut state = state_with_event(&event);
+
+        assert!(recategorize_event(&mut state, &event.id, "gaming").is_err());
+        assert!(recategorize_event(&mut state, "missing-id", "coding").is_err());
+    }
+
+    #[test]
+    fn test_learn_rule() {
+        let mut rules = CategoryRules::default();
+        assert_eq!(rules.category_for("Zed"), None);
+
+        rules.learn("Zed", "writing");
+        rules.learn("zed", "coding");
+
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.category_for("ZED"), Some("coding"));
+    }
+}
@@ -0,0 +1,324 @@
+// Server-backed categorization (opt-in)
+// The built-in heuristic only looks at the app name, so a browser tab with a
+// code review is "browsing". When enabled, buffered events are sent in batches
+// to the Observer server (`/api/v1/categorize`) and the answers are cached by
+// (app, title, url). User rules still win, and events keep their local
+// category whenever the server can't answer.
+
+use super::Event;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// How often buffered events are checked for new (app, title, url) combinations
+const CATEGORIZE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Most keys sent to the server in one request
+const MAX_BATCH: usize = 50;
+
+/// The cache is cleared when it grows past this many keys
+const MAX_CACHED: usize = 5_000;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+static CACHE: Lazy<RwLock<CategoryCache>> = Lazy::new(|| RwLock::new(CategoryCache::default()));
+
+/// What the server categorizes by
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct CategoryKey {
+    /// Lowercased
+    pub app_name: String,
+    pub window_title: String,
+    /// Without query string or fragment
+    pub url: Option<String>,
+}
+
+impl CategoryKey {
+    pub fn new(app_name: &str, window_title: Option<&str>, url: Option<&str>) -> Self {
+        Self {
+            app_name: app_name.trim().to_lowercase(),
+            window_title: window_title.unwrap_or_default().trim().to_string(),
+            url: url.map(strip_query).filter(|url| !url.is_empty()),
+        }
+    }
+
+    pub fn for_event(event: &Event) -> Option<Self> {
+        let app_name = event.app_name.as_deref()?;
+        Some(Self::new(app_name, event.window_title.as_deref(), event.url.as_deref()))
+    }
+}
+
+/// Drop the query and fragment: they rarely change the category and often carry tokens
+fn strip_query(url: &str) -> String {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    url[..end].trim().to_string()
+}
+
+/// Server answers by key; `None` means the server had no category, so it isn't asked again
+#[derive(Debug, Default)]
+pub struct CategoryCache {
+    categories: HashMap<CategoryKey, Option<String>>,
+}
+
+impl CategoryCache {
+    pub fn contains(&self, key: &CategoryKey) -> bool {
+        self.categories.contains_key(key)
+    }
+
+    /// Cached server category for an event
+    pub fn category_for(&self, event: &Event) -> Option<&str> {
+        let key = CategoryKey::for_event(event)?;
+        self.categories.get(&key)?.as_deref()
+    }
+
+    pub fn insert(&mut self, key: CategoryKey, category: Option<String>) {
+        if self.categories.len() >= MAX_CACHED {
+            self.categories.clear();
+        }
+        self.categories.insert(key, category);
+    }
+
+    /// Keys of `events` the server hasn't been asked about (deduplicated, at most one batch)
+    pub fn uncached_keys(&self, events: &[Event]) -> Vec<CategoryKey> {
+        let mut keys = Vec::new();
+        for key in events.iter().filter_map(CategoryKey::for_event) {
+            if keys.len() >= MAX_BATCH {
+                break;
+            }
+            if !self.contains(&key) && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+/// Ask `fetch` for the categories of `keys` (one per key, in order) and cache them
+/// Unknown categories are cached as no answer. On error nothing is cached.
+pub async fn refresh<F, Fut>(cache: &RwLock<CategoryCache>, keys: Vec<CategoryKey>, fetch: F) -> Result<usize, String>
+where
+    F: FnOnce(Vec<CategoryKey>) -> Fut,
+    Fut: Future<Output = Result<Vec<Option<String>>, String>>,
+{
+    let categories = fetch(keys.clone()).await?;
+    if categories.len() != keys.len() {
+        return Err(format!(
+            "Server returned {} categories for {} items",
+            categories.len(),
+            keys.len()
+        ));
+    }
+
+    let mut cache = cache.write().unwrap();
+    let mut categorized = 0;
+    for (key, category) in keys.into_iter().zip(categories) {
+        let category = category.filter(|category| super::is_known_category(category));
+        if category.is_some() {
+            categorized += 1;
+        }
+        cache.insert(key, category);
+    }
+    Ok(categorized)
+}
+
+/// Cached server category for a new event, unless a user rule covers its app
+pub fn cached_category(event: &Event) -> Option<String> {
+    let app_name = event.app_name.as_deref()?;
+    if super::categories::user_category(app_name).is_some() {
+        return None;
+    }
+    CACHE.read().unwrap().category_for(event).map(str::to_string)
+}
+
+/// Apply cached server categories to buffered events (buffer and database)
+/// Returns how many events changed
+pub fn apply_cached(state: &mut AppState, cache: &CategoryCache) -> usize {
+    let mut updated = 0;
+    for event in state.events_buffer.iter_mut() {
+        let Some(category) = cache.category_for(event) else {
+            continue;
+        };
+        if event.category.as_deref() == Some(category) {
+            continue;
+        }
+        let has_user_rule = event
+            .app_name
+            .as_deref()
+            .is_some_and(|app_name| super::categories::user_category(app_name).is_some());
+        if has_user_rule {
+            continue;
+        }
+
+        if let Err(e) = state.db.update_category(&event.id, category) {
+            crate::logs::error(&format!("[Categorizer] Failed to update event {}: {}", event.id, e));
+            continue;
+        }
+        event.category = Some(category.to_string());
+        updated += 1;
+    }
+    updated
+}
+
+#[derive(Debug, Deserialize)]
+struct CategorizeResponse {
+    categories: Vec<Option<String>>,
+}
+
+/// POST `{ "items": [{ app_name, window_title, url }] }` to the Observer server
+async fn fetch_from_server(keys: Vec<CategoryKey>) -> Result<Vec<Option<String>>, String> {
+    let client = crate::sync::shared_http_client().map_err(|e| e.to_string())?;
+    let mut request = client
+        .post(format!("{}/api/v1/categorize", crate::sync::get_server_url()))
+        .timeout(REQUEST_TIMEOUT)
+        .json(&serde_json::json!({ "items": keys }));
+    if let Some(key) = crate::sync::get_api_key() {
+        request = request.header("X-API-Key", key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Categorize request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server returned status: {}", response.status()));
+    }
+
+    response
+        .json::<CategorizeResponse>()
+        .await
+        .map(|response| response.categories)
+        .map_err(|e| format!("Invalid categorize response: {}", e))
+}
+
+/// Periodically categorize new (app, title, url) combinations while `serverCategorization` is on
+pub async fn start_server_categorizer(state: Arc<Mutex<AppState>>, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(CATEGORIZE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let enabled = crate::commands::get_settings()
+            .map(|settings| settings.server_categorization)
+            .unwrap_or(false);
+        if !enabled {
+            continue;
+        }
+
+        let keys = {
+            let state = state.lock().await;
+            CACHE.read().unwrap().uncached_keys(&state.events_buffer)
+        };
+        if !keys.is_empty() {
+            let count = keys.len();
+            match refresh(&CACHE, keys, fetch_from_server).await {
+                Ok(categorized) => crate::logs::info(&format!(
+                    "[Categorizer] Server categorized {} of {} new items",
+                    categorized, count
+                )),
+                Err(e) => {
+                    crate::logs::error(&format!("[Categorizer] Keeping local categories: {}", e));
+                    continue;
+                }
+            }
+        }
+
+        let mut state = state.lock().await;
+        let cache = CACHE.read().unwrap();
+        let updated = apply_cached(&mut state, &cache);
+        if updated > 0 {
+            crate::logs::info(&format!("[Categorizer] Recategorized {} events", updated));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventDatabase;
+
+    fn tab_event(title: &str, url: &str) -> Event {
+        let mut event = Event::new(
+            "app_focus",
+            Some("Google Chrome".to_string()),
+            Some(title.to_string()),
+        )
+        .with_category("browsing");
+        event.url = Some(url.to_string());
+        event
+    }
+
+    #[test]
+    fn test_cache_keying() {
+        let key = CategoryKey::new(" Google Chrome", Some("PR #12 "), Some("https://github.com/o/r/pull/12?tab=files#diff"));
+        assert_eq!(key.app_name, "google chrome");
+        assert_eq!(key.window_title, "PR #12");
+        assert_eq!(key.url.as_deref(), Some("https://github.com/o/r/pull/12"));
+
+        // Case and query string don't make a new key; title and path do
+        assert_eq!(
+            key,
+            CategoryKey::new("GOOGLE CHROME", Some("PR #12"), Some("https://github.com/o/r/pull/12?tab=commits"))
+        );
+        assert_ne!(key, CategoryKey::new("Google Chrome", Some("PR #13"), Some("https://github.com/o/r/pull/12")));
+        assert_ne!(key, CategoryKey::new("Google Chrome", Some("PR #12"), Some("https://github.com/o/r/pull/13")));
+        assert_eq!(CategoryKey::new("Zed", None, Some("")).url, None);
+
+        let events = vec![
+            tab_event("PR #12", "https://github.com/o/r/pull/12?tab=files"),
+            tab_event("PR #12", "https://github.com/o/r/pull/12"),
+            tab_event("Inbox", "https://mail.example.com/"),
+            Event::new("idle", None, None),
+        ];
+        let mut cache = CategoryCache::default();
+        assert_eq!(cache.uncached_keys(&events).len(), 2);
+
+        cache.insert(CategoryKey::for_event(&events[2]).unwrap(), None);
+        assert_eq!(cache.uncached_keys(&events), vec![key]);
+    }
+
+    #[tokio::test]
+    async fn test_local_category_kept_when_server_fails() {
+        let event = tab_event("PR #12", "https://github.com/o/r/pull/12");
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(&event).unwrap();
+        let mut state = AppState::new(db, vec![event.clone()]);
+        let cache = RwLock::new(CategoryCache::default());
+
+        let keys = cache.read().unwrap().uncached_keys(&state.events_buffer);
+        let result = refresh(&cache, keys.clone(), |_| async { Err("connection refused".to_string()) }).await;
+        assert!(result.is_err());
+        assert_eq!(apply_cached(&mut state, &cache.read().unwrap()), 0);
+        assert_eq!(state.events_buffer[0].category.as_deref(), Some("browsing"));
+        // Not cached, so the next round asks again
+        assert_eq!(cache.read().unwrap().uncached_keys(&state.events_buffer).len(), 1);
+
+        // Once the server answers, the event is recategorized
+        let categorized = refresh(&cache, keys, |_| async { Ok(vec![Some("coding".to_string())]) }).await;
+        assert_eq!(categorized, Ok(1));
+        assert_eq!(apply_cached(&mut state, &cache.read().unwrap()), 1);
+        assert_eq!(state.events_buffer[0].category.as_deref(), Some("coding"));
+        let stored = state.db.get_event(&event.id).unwrap().unwrap();
+        assert_eq!(stored.category.as_deref(), Some("coding"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_server_category_ignored() {
+        let cache = RwLock::new(CategoryCache::default());
+        let key = CategoryKey::new("Steam", None, None);
+        let result = refresh(&cache, vec![key.clone()], |_| async { Ok(vec![Some("gaming".to_string())]) }).await;
+
+        assert_eq!(result, Ok(0));
+        let cache = cache.read().unwrap();
+        assert!(cache.contains(&key));
+        assert_eq!(cache.category_for(&Event::new("app_focus", Some("Steam".to_string()), None)), None);
+    }
+}
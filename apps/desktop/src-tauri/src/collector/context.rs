@@ -0,0 +1,291 @@
+// Current context snapshot
+// Bundles focus, system metrics, the active browser tab and optionally an OCR
+// of the focused window into one struct for the "what am I doing" widget.
+
+use super::{BrowserTab, FocusInfo, SystemMetrics, SystemMetricsCollector};
+use crate::automation::ocr::OcrResult;
+use chrono::{DateTime, Utc};
+use image::RgbaImage;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Longest side of the image handed to OCR (pixels)
+const MAX_OCR_DIMENSION: u32 = 1600;
+
+/// Wall-clock budget for capturing and OCR-ing the focused window
+const OCR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Shared so CPU usage is measured against the previous call, not from zero
+static METRICS: Lazy<SystemMetricsCollector> = Lazy::new(SystemMetricsCollector::new);
+
+/// Set while an OCR thread is running, including one that already timed out
+static OCR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// The single OCR thread slot; released when dropped
+struct OcrSlot;
+
+impl OcrSlot {
+    /// None while another OCR (possibly a timed-out one) still runs
+    fn acquire() -> Option<Self> {
+        OCR_RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| OcrSlot)
+    }
+}
+
+impl Drop for OcrSlot {
+    fn drop(&mut self) {
+        OCR_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// What the user is doing right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentContext {
+    pub focus: Option<FocusInfo>,
+    pub metrics: Option<SystemMetrics>,
+    pub browser_tab: Option<BrowserTab>,
+    /// OCR of the focused window (only when requested)
+    pub ocr: Option<OcrResult>,
+    /// Why OCR was requested but is missing
+    pub ocr_error: Option<String>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Where the parts of a context come from
+pub trait ContextSource {
+    fn focus(&self) -> Option<FocusInfo>;
+    fn metrics(&self) -> Option<SystemMetrics>;
+    fn browser_tab(&self, app_name: &str) -> Option<BrowserTab>;
+    fn ocr_focused_window(&self, focus: &FocusInfo) -> Result<OcrResult, String>;
+}
+
+/// Build a context from `source`
+/// Blocking - call from a blocking context when `include_ocr` is set
+pub fn compose<S: ContextSource>(source: &S, include_ocr: bool) -> CurrentContext {
+    let focus = source.focus();
+    let metrics = source.metrics();
+    let browser_tab = focus
+        .as_ref()
+        .filter(|focus| super::is_browser(&focus.app_name))
+        .and_then(|focus| source.browser_tab(&focus.app_name));
+
+    let (ocr, ocr_error) = match (include_ocr, focus.as_ref()) {
+        (false, _) => (None, None),
+        (true, None) => (None, Some("No focused window".to_string())),
+        (true, Some(focus)) => match source.ocr_focused_window(focus) {
+            Ok(mut result) => {
+                result.text = crate::text::truncate_string(result.text, crate::text::MAX_OCR_CHARS);
+                (Some(result), None)
+            }
+            Err(e) => (None, Some(e)),
+        },
+    };
+
+    CurrentContext {
+        focus,
+        metrics,
+        browser_tab,
+        ocr,
+        ocr_error,
+        captured_at: Utc::now(),
+    }
+}
+
+/// The live system
+pub struct SystemContext;
+
+impl ContextSource for SystemContext {
+    fn focus(&self) -> Option<FocusInfo> {
+        super::get_current_focus()
+    }
+
+    fn metrics(&self) -> Option<SystemMetrics> {
+        METRICS.collect().ok()
+    }
+
+    fn browser_tab(&self, app_name: &str) -> Option<BrowserTab> {
+        super::browser::BrowserMonitor::new().get_active_tab(app_name)
+    }
+
+    fn ocr_focused_window(&self, focus: &FocusInfo) -> Result<OcrResult, String> {
+        // A timed-out thread can't be stopped, so no new one starts until it ends
+        let slot = OcrSlot::acquire().ok_or("Previous OCR is still running")?;
+        let focus = focus.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _slot = slot;
+            let result = capture_focused_window(&focus)
+                .map(|image| downscale(image, MAX_OCR_DIMENSION))
+                .and_then(|image| crate::automation::ocr::extract_text_from_image(&image));
+            let _ = tx.send(result);
+        });
+
+        rx.recv_timeout(OCR_TIMEOUT)
+            .map_err(|_| format!("OCR timed out after {}s", OCR_TIMEOUT.as_secs()))?
+    }
+}
+
+/// Capture the focused app's frontmost window, or the primary screen if it can't be found
+fn capture_focused_window(focus: &FocusInfo) -> Result<RgbaImage, String> {
+    let windows = xcap::Window::all().map_err(|e| format!("Failed to list windows: {}", e))?;
+    let window = windows
+        .iter()
+        .filter(|window| !window.is_minimized() && window.app_name() == focus.app_name)
+        .find(|window| window.title() == focus.window_title)
+        .or_else(|| {
+            windows
+                .iter()
+                .find(|window| !window.is_minimized() && window.app_name() == focus.app_name)
+        });
+
+    match window {
+        Some(window) => window
+            .capture_image()
+            .map_err(|e| format!("Failed to capture window: {}", e)),
+        None => crate::automation::screen::capture_screenshot(),
+    }
+}
+
+/// Shrink `image` so its longest side is at most `max_dimension`
+fn downscale(image: RgbaImage, max_dimension: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let longest = width.max(height);
+    if longest <= max_dimension {
+        return image;
+    }
+
+    let scale = max_dimension as f64 / longest as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+    image::imageops::thumbnail(&image, new_width, new_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Mock source returning fixed parts and counting OCR calls
+    struct MockSource {
+        focus: Option<FocusInfo>,
+        ocr: Result<OcrResult, String>,
+        ocr_calls: Cell<usize>,
+    }
+
+    impl MockSource {
+        fn new(app_name: &str) -> Self {
+            Self {
+                focus: Some(FocusInfo {
+                    app_name: app_name.to_string(),
+                    window_title: "Inbox".to_string(),
+                    selected_text: None,
+                    url: None,
+                }),
+                ocr: Ok(OcrResult {
+                    text: "hello".to_string(),
+                    confidence: 0.9,
+                    language: None,
+                    bounding_boxes: Vec::new(),
+                }),
+                ocr_calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl ContextSource for MockSource {
+        fn focus(&self) -> Option<FocusInfo> {
+            self.focus.clone()
+        }
+
+        fn metrics(&self) -> Option<SystemMetrics> {
+            Some(SystemMetrics {
+                cpu_usage: 12.5,
+                ram_used: 4,
+                ram_total: 8,
+                ram_usage_percent: 50.0,
+                cpu_usage_smoothed: None,
+                ram_usage_percent_smoothed: None,
+            })
+        }
+
+        fn browser_tab(&self, app_name: &str) -> Option<BrowserTab> {
+            Some(BrowserTab {
+                browser: app_name.to_string(),
+                url: "https://mail.example.com".to_string(),
+                title: "Inbox".to_string(),
+                visible_text: None,
+            })
+        }
+
+        fn ocr_focused_window(&self, _focus: &FocusInfo) -> Result<OcrResult, String> {
+            self.ocr_calls.set(self.ocr_calls.get() + 1);
+            self.ocr.clone()
+        }
+    }
+
+    #[test]
+    fn test_composed_from_sources() {
+        let source = MockSource::new("Google Chrome");
+        let context = compose(&source, true);
+
+        assert_eq!(context.focus.unwrap().window_title, "Inbox");
+        assert_eq!(context.metrics.unwrap().cpu_usage, 12.5);
+        let tab = context.browser_tab.unwrap();
+        assert_eq!(tab.browser, "Google Chrome");
+        assert_eq!(tab.url, "https://mail.example.com");
+        assert_eq!(context.ocr.unwrap().text, "hello");
+        assert!(context.ocr_error.is_none());
+        assert_eq!(source.ocr_calls.get(), 1);
+    }
+
+    #[test]
+    fn test_ocr_only_when_requested() {
+        let source = MockSource::new("Mail");
+        let context = compose(&source, false);
+
+        assert!(context.ocr.is_none());
+        assert!(context.ocr_error.is_none());
+        assert_eq!(source.ocr_calls.get(), 0);
+        // Not a browser, so no tab lookup
+        assert!(context.browser_tab.is_none());
+    }
+
+    #[test]
+    fn test_ocr_failure_is_reported() {
+        let mut source = MockSource::new("Mail");
+        source.ocr = Err("OCR timed out after 5s".to_string());
+        let context = compose(&source, true);
+
+        assert!(context.ocr.is_none());
+        assert_eq!(context.ocr_error.as_deref(), Some("OCR timed out after 5s"));
+        assert!(context.focus.is_some());
+
+        source.focus = None;
+        let context = compose(&source, true);
+        assert_eq!(context.ocr_error.as_deref(), Some("No focused window"));
+        assert_eq!(source.ocr_calls.get(), 1);
+    }
+
+    #[test]
+    fn test_downscale_bounds_longest_side() {
+        let image = RgbaImage::new(3200, 1000);
+        assert_eq!(downscale(image, 1600).dimensions(), (1600, 500));
+
+        let small = RgbaImage::new(800, 600);
+        assert_eq!(downscale(small, 1600).dimensions(), (800, 600));
+    }
+
+    #[test]
+    fn test_one_ocr_thread_at_a_time() {
+        let slot = OcrSlot::acquire().unwrap();
+        assert!(OcrSlot::acquire().is_none());
+
+        drop(slot);
+        assert!(OcrSlot::acquire().is_some());
+    }
+}
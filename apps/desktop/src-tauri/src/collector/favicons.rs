@@ -0,0 +1,281 @@
+// Site favicons (opt-in)
+// Browser events get the tab's domain in `data.domain`, and the domain's
+// favicon is fetched in the background so the dashboard can show site icons.
+// Icons are small data URIs kept in a bounded in-memory cache; fetches are
+// rate limited and failures are remembered so a site is not retried on
+// every focus change.
+
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Most domains whose icon (or failed lookup) is remembered
+pub const MAX_CACHED_FAVICONS: usize = 64;
+/// Larger icons are not cached
+const MAX_FAVICON_BYTES: usize = 32 * 1024;
+/// Minimum time between two favicon requests
+const FETCH_INTERVAL: Duration = Duration::from_secs(2);
+/// A domain whose icon couldn't be fetched is retried after this long
+const FAILED_RETRY_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most redirects followed for one icon request
+const MAX_REDIRECTS: usize = 5;
+/// Well-known icon locations, tried in order
+const FAVICON_PATHS: &[&str] = &["/favicon.ico", "/apple-touch-icon.png"];
+
+static CACHE: Lazy<Mutex<FaviconCache>> = Lazy::new(|| Mutex::new(FaviconCache::new(MAX_CACHED_FAVICONS)));
+
+/// Client for icon fetches; every redirect hop is validated before it is followed,
+/// so a public site can't bounce the request to a local or private address
+static CLIENT: Lazy<Result<reqwest::Client, String>> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(e) = crate::sync::validate_url(attempt.url().as_str()) {
+                attempt.error(e)
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+});
+
+/// Domain of a web page, as used for the icon cache key
+/// Lowercased without a leading "www.". None for non-web URLs, IP addresses
+/// and single-label (intranet) hosts, which are never fetched from.
+pub fn domain_of(url: &str) -> Option<String> {
+    let domain = crate::stats::url_domain(url.trim(), false)?;
+    let domain = domain.trim_end_matches('.');
+    let is_ip = domain.starts_with('[') || domain.parse::<std::net::IpAddr>().is_ok();
+    (!is_ip && domain.contains('.')).then(|| domain.to_string())
+}
+
+/// Cached lookup result for one domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CachedIcon {
+    /// None when the site has no usable icon
+    data_uri: Option<String>,
+    fetched_at: Instant,
+}
+
+/// Favicons by domain, least recently used evicted first
+#[derive(Debug)]
+pub struct FaviconCache {
+    capacity: usize,
+    icons: HashMap<String, CachedIcon>,
+    /// Least recently used first
+    order: VecDeque<String>,
+    in_flight: HashSet<String>,
+    last_fetch: Option<Instant>,
+}
+
+impl FaviconCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            icons: HashMap::new(),
+            order: VecDeque::new(),
+            in_flight: HashSet::new(),
+            last_fetch: None,
+        }
+    }
+
+    /// Icon for `domain`, if one was fetched
+    pub fn get(&mut self, domain: &str) -> Option<String> {
+        let data_uri = self.icons.get(domain)?.data_uri.clone();
+        self.touch(domain);
+        data_uri
+    }
+
+    /// Claim a fetch for `domain` if it isn't cached, already being fetched or
+    /// over the rate limit
+    pub fn begin_fetch(&mut self, domain: &str, now: Instant) -> bool {
+        let cached = self.icons.get(domain).is_some_and(|icon| {
+            icon.data_uri.is_some() || now.saturating_duration_since(icon.fetched_at) < FAILED_RETRY_AFTER
+        });
+        let rate_limited = self
+            .last_fetch
+            .is_some_and(|last| now.saturating_duration_since(last) < FETCH_INTERVAL);
+        if cached || rate_limited || self.in_flight.contains(domain) {
+            return false;
+        }
+
+        self.last_fetch = Some(now);
+        self.in_flight.insert(domain.to_string());
+        true
+    }
+
+    /// Store the outcome of a fetch, evicting the least recently used domain when full
+    pub fn finish_fetch(&mut self, domain: &str, data_uri: Option<String>, now: Instant) {
+        self.in_flight.remove(domain);
+        self.icons.insert(domain.to_string(), CachedIcon { data_uri, fetched_at: now });
+        self.touch(domain);
+
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.icons.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, domain: &str) {
+        self.order.retain(|cached| cached != domain);
+        self.order.push_back(domain.to_string());
+    }
+}
+
+/// Fetch the icon of the page at `url` in the background unless it is cached
+/// Returns the domain, to be stored on the event
+pub fn prefetch(url: &str) -> Option<String> {
+    let domain = domain_of(url)?;
+    if CACHE.lock().unwrap().begin_fetch(&domain, Instant::now()) {
+        let fetched = domain.clone();
+        tauri::async_runtime::spawn(async move {
+            let data_uri = fetch_icon(&fetched).await;
+            CACHE.lock().unwrap().finish_fetch(&fetched, data_uri, Instant::now());
+        });
+    }
+    Some(domain)
+}
+
+/// Cached icons (data URIs) of the given domains; domains without one are left out
+pub fn cached_icons(domains: &[String]) -> HashMap<String, String> {
+    let mut cache = CACHE.lock().unwrap();
+    domains
+        .iter()
+        .filter_map(|domain| {
+            let key = domain_of(&format!("https://{}", domain.trim()))?;
+            Some((domain.clone(), cache.get(&key)?))
+        })
+        .collect()
+}
+
+/// First well-known icon of `domain` that is a small image
+async fn fetch_icon(domain: &str) -> Option<String> {
+    for path in FAVICON_PATHS {
+        match fetch_icon_at(&format!("https://{}{}", domain, path)).await {
+            Ok(data_uri) => return Some(data_uri),
+            Err(e) => crate::logs::debug(&format!("[Favicons] {}{}: {}", domain, path, e)),
+        }
+    }
+    None
+}
+
+async fn fetch_icon_at(url: &str) -> Result<String, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let url = crate::sync::validate_url(url)?;
+    let client = CLIENT.as_ref().map_err(|e| e.clone())?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Server returned status: {}", response.status()));
+    }
+    if response.content_length().is_some_and(|length| length as usize > MAX_FAVICON_BYTES) {
+        return Err("Icon too large".to_string());
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or_default().trim().to_lowercase())
+        .unwrap_or_default();
+    if !content_type.starts_with("image/") {
+        return Err(format!("Not an image ({})", content_type));
+    }
+
+    // Content-Length may be missing or wrong, so the body is capped while it is read
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read icon: {}", e))? {
+        append_capped(&mut bytes, &chunk, MAX_FAVICON_BYTES)?;
+    }
+    if bytes.is_empty() {
+        return Err("Icon empty".to_string());
+    }
+    Ok(format!("data:{};base64,{}", content_type, BASE64.encode(&bytes)))
+}
+
+/// Append a body chunk, failing once the body would exceed `limit` bytes
+fn append_capped(body: &mut Vec<u8>, chunk: &[u8], limit: usize) -> Result<(), String> {
+    if body.len() + chunk.len() > limit {
+        return Err("Icon too large".to_string());
+    }
+    body.extend_from_slice(chunk);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_capped_while_streaming() {
+        let mut body = Vec::new();
+        append_capped(&mut body, &[1; 6], 10).unwrap();
+        append_capped(&mut body, &[2; 4], 10).unwrap();
+        assert_eq!(body.len(), 10);
+
+        // The chunk that would cross the limit is rejected, not truncated
+        assert!(append_capped(&mut body, &[3], 10).is_err());
+        assert_eq!(body.len(), 10);
+    }
+
+    #[test]
+    fn test_domain_extraction() {
+        assert_eq!(domain_of("https://www.GitHub.com/pulls?q=1"), Some("github.com".to_string()));
+        assert_eq!(domain_of("http://docs.rs:8080/chrono"), Some("docs.rs".to_string()));
+        assert_eq!(domain_of("https://news.ycombinator.com./item"), Some("news.ycombinator.com".to_string()));
+        assert_eq!(domain_of("https://www2.example.co.uk"), Some("www2.example.co.uk".to_string()));
+
+        // Nothing to fetch from
+        assert_eq!(domain_of("chrome://settings"), None);
+        assert_eq!(domain_of("file:///Users/me/index.html"), None);
+        assert_eq!(domain_of("http://192.168.1.1/admin"), None);
+        assert_eq!(domain_of("http://[::1]:3000"), None);
+        assert_eq!(domain_of("http://localhost:3000"), None);
+        assert_eq!(domain_of("http://intranet/wiki"), None);
+        assert_eq!(domain_of("not a url"), None);
+    }
+
+    #[test]
+    fn test_cache_keying_rate_limit_and_eviction() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut cache = FaviconCache::new(2);
+        let key = |url: &str| domain_of(url).unwrap();
+
+        // Different URLs of the same site share one entry
+        assert!(cache.begin_fetch(&key("https://www.github.com/a"), at(0)));
+        assert!(!cache.begin_fetch(&key("https://GITHUB.com/b"), at(10)));
+        cache.finish_fetch("github.com", Some("data:image/png;base64,AA==".to_string()), at(10));
+        assert!(!cache.begin_fetch("github.com", at(20)));
+        assert_eq!(cache.get("github.com").as_deref(), Some("data:image/png;base64,AA=="));
+
+        // One request per interval
+        assert!(cache.begin_fetch("docs.rs", at(20)));
+        assert!(!cache.begin_fetch("example.com", at(21)));
+        cache.finish_fetch("docs.rs", None, at(21));
+        assert_eq!(cache.get("docs.rs"), None);
+        // Failures are retried only after a while
+        assert!(!cache.begin_fetch("docs.rs", at(30)));
+        assert!(cache.begin_fetch("docs.rs", at(30) + FAILED_RETRY_AFTER));
+        cache.finish_fetch("docs.rs", None, at(40) + FAILED_RETRY_AFTER);
+
+        // github.com was used more recently than docs.rs, so docs.rs goes
+        cache.get("github.com");
+        assert!(cache.begin_fetch("example.com", at(50) + FAILED_RETRY_AFTER));
+        cache.finish_fetch("example.com", Some("data:image/x-icon;base64,AA==".to_string()), at(50));
+        assert_eq!(cache.icons.len(), 2);
+        assert!(cache.icons.contains_key("github.com"));
+        assert!(cache.icons.contains_key("example.com"));
+        assert!(!cache.icons.contains_key("docs.rs"));
+    }
+}
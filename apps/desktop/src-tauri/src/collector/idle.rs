@@ -0,0 +1,349 @@
+// Idle detection
+// When nobody touches the keyboard or mouse for a while the collector records a
+// single `idle_start`, stops producing focus events, and records `idle_end` once
+// input resumes. Input the app posts itself (automation clicks and typing) is
+// not user activity and doesn't end or postpone an idle period.
+// The threshold depends on the category of the focused app: reading in a
+// browser goes much longer without input than working in a terminal.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default seconds without input before the user counts as idle
+pub const DEFAULT_IDLE_THRESHOLD_SECS: u64 = 180;
+/// Default per-category thresholds; other categories use the global one
+pub const DEFAULT_CATEGORY_IDLE_THRESHOLDS: &[(&str, u64)] = &[("browsing", 480), ("writing", 600)];
+/// Input this close to an automation action is attributed to it
+const SYNTHETIC_INPUT_GRACE: Duration = Duration::from_secs(2);
+
+static SYNTHETIC_INPUT: Mutex<SyntheticInput> = Mutex::new(SyntheticInput::new());
+
+/// Change in the user's idle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTransition {
+    /// No user input for `idle_for` (at least the threshold)
+    Start { idle_for: Duration },
+    /// Input resumed
+    End,
+}
+
+impl IdleTransition {
+    /// Event type stored on the collected event
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            IdleTransition::Start { .. } => "idle_start",
+            IdleTransition::End => "idle_end",
+        }
+    }
+}
+
+/// Idle threshold while an app of `category` is focused
+/// Categories match case-insensitively; without an entry `default_secs` applies.
+/// A zero threshold turns idle detection off for that category.
+pub fn threshold_for(category: Option<&str>, by_category: &HashMap<String, u64>, default_secs: u64) -> Duration {
+    let seconds = category
+        .and_then(|category| {
+            by_category
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(category))
+                .map(|(_, seconds)| *seconds)
+        })
+        .unwrap_or(default_secs);
+    Duration::from_secs(seconds)
+}
+
+/// When the app itself last posted input events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticInput {
+    active: usize,
+    started: Option<Instant>,
+    ended: Option<Instant>,
+}
+
+impl SyntheticInput {
+    pub const fn new() -> Self {
+        Self {
+            active: 0,
+            started: None,
+            ended: None,
+        }
+    }
+
+    fn begin(&mut self, now: Instant) {
+        if self.active == 0 {
+            self.started = Some(now);
+        }
+        self.active += 1;
+    }
+
+    fn end(&mut self, now: Instant) {
+        self.active = self.active.saturating_sub(1);
+        self.ended = Some(now);
+    }
+
+    /// Whether input seen at `at` was (probably) posted by the app
+    pub fn covers(&self, at: Instant) -> bool {
+        let Some(started) = self.started else {
+            return false;
+        };
+        let from = started.checked_sub(SYNTHETIC_INPUT_GRACE).unwrap_or(started);
+        if self.active > 0 {
+            return at >= from;
+        }
+        self.ended.is_some_and(|ended| at >= from && at <= ended + SYNTHETIC_INPUT_GRACE)
+    }
+}
+
+impl Default for SyntheticInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marks input posted by the app for as long as it is alive
+pub struct SyntheticInputGuard(());
+
+impl Drop for SyntheticInputGuard {
+    fn drop(&mut self) {
+        SYNTHETIC_INPUT.lock().unwrap().end(Instant::now());
+    }
+}
+
+/// Call before posting input events; keep the guard until they're sent
+pub fn synthetic_input() -> SyntheticInputGuard {
+    SYNTHETIC_INPUT.lock().unwrap().begin(Instant::now());
+    SyntheticInputGuard(())
+}
+
+fn synthetic_input_state() -> SyntheticInput {
+    *SYNTHETIC_INPUT.lock().unwrap()
+}
+
+/// Tracks the last user input and whether the user is idle
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+    last_user_input: Option<Instant>,
+    idle: bool,
+}
+
+impl IdleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Update with the time since the last input of any kind
+    /// A `threshold` of zero turns idle detection off (and ends a running idle period).
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        since_input: Duration,
+        threshold: Duration,
+        synthetic: &SyntheticInput,
+    ) -> Option<IdleTransition> {
+        let last_input = now.checked_sub(since_input).unwrap_or(now);
+        match self.last_user_input {
+            // Automation input doesn't count as the user coming back
+            Some(_) if synthetic.covers(last_input) => {}
+            Some(last) if last >= last_input => {}
+            _ => self.last_user_input = Some(last_input),
+        }
+
+        let idle_for = now.saturating_duration_since(self.last_user_input.unwrap_or(now));
+        let idle = !threshold.is_zero() && idle_for >= threshold;
+        if idle == self.idle {
+            return None;
+        }
+
+        self.idle = idle;
+        Some(if idle {
+            IdleTransition::Start { idle_for }
+        } else {
+            IdleTransition::End
+        })
+    }
+
+    /// Poll with the system's input timer; None where it can't be read
+    pub fn poll_system(&mut self, threshold: Duration) -> Option<IdleTransition> {
+        let since_input = seconds_since_input()?;
+        self.poll(
+            Instant::now(),
+            Duration::from_secs_f64(since_input.max(0.0)),
+            threshold,
+            &synthetic_input_state(),
+        )
+    }
+}
+
+/// Seconds since the last keyboard or mouse input (macOS)
+#[cfg(target_os = "macos")]
+fn seconds_since_input() -> Option<f64> {
+    const HID_SYSTEM_STATE: i32 = 1;
+    const ANY_INPUT_EVENT_TYPE: u32 = u32::MAX;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(source_state: i32, event_type: u32) -> f64;
+    }
+
+    let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(HID_SYSTEM_STATE, ANY_INPUT_EVENT_TYPE) };
+    seconds.is_finite().then_some(seconds)
+}
+
+/// Seconds since the last keyboard or mouse input (Windows)
+#[cfg(target_os = "windows")]
+fn seconds_since_input() -> Option<f64> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both tick counts wrap after ~49 days
+        Some(GetTickCount().wrapping_sub(info.dwTime) as f64 / 1000.0)
+    }
+}
+
+/// Seconds since the last keyboard or mouse input (Linux, X11 screen saver extension)
+#[cfg(target_os = "linux")]
+fn seconds_since_input() -> Option<f64> {
+    use std::ptr;
+    use x11::xlib::{XCloseDisplay, XDefaultRootWindow, XFree, XOpenDisplay};
+    use x11::xss::{XScreenSaverAllocInfo, XScreenSaverQueryInfo};
+
+    unsafe {
+        let display = XOpenDisplay(ptr::null());
+        if display.is_null() {
+            return None;
+        }
+
+        let info = XScreenSaverAllocInfo();
+        let idle_ms = if !info.is_null() && XScreenSaverQueryInfo(display, XDefaultRootWindow(display), info) != 0 {
+            Some((*info).idle)
+        } else {
+            None
+        };
+
+        if !info.is_null() {
+            XFree(info as *mut _);
+        }
+        XCloseDisplay(display);
+        idle_ms.map(|ms| ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn seconds_since_input() -> Option<f64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(180);
+
+    #[test]
+    fn test_idle_start_and_end_are_emitted_once() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let none = SyntheticInput::new();
+        let mut tracker = IdleTracker::new();
+
+        assert_eq!(tracker.poll(at(0), Duration::ZERO, THRESHOLD, &none), None);
+        assert_eq!(tracker.poll(at(100), Duration::from_secs(100), THRESHOLD, &none), None);
+        assert_eq!(
+            tracker.poll(at(180), Duration::from_secs(180), THRESHOLD, &none),
+            Some(IdleTransition::Start { idle_for: THRESHOLD })
+        );
+        assert!(tracker.is_idle());
+        assert_eq!(tracker.poll(at(600), Duration::from_secs(600), THRESHOLD, &none), None);
+
+        // The user touches the mouse
+        assert_eq!(tracker.poll(at(601), Duration::ZERO, THRESHOLD, &none), Some(IdleTransition::End));
+        assert!(!tracker.is_idle());
+        assert_eq!(tracker.poll(at(602), Duration::from_secs(1), THRESHOLD, &none), None);
+
+        // A zero threshold turns detection off
+        assert_eq!(tracker.poll(at(2000), Duration::from_secs(1399), Duration::ZERO, &none), None);
+        assert_eq!(IdleTransition::End.event_type(), "idle_end");
+    }
+
+    #[test]
+    fn test_threshold_depends_on_focused_category() {
+        let by_category: HashMap<String, u64> =
+            [("browsing".to_string(), 480), ("Coding".to_string(), 120), ("design".to_string(), 0)]
+                .into_iter()
+                .collect();
+        let threshold = |category: Option<&str>| threshold_for(category, &by_category, 180);
+
+        assert_eq!(threshold(Some("browsing")), Duration::from_secs(480));
+        assert_eq!(threshold(Some("coding")), Duration::from_secs(120));
+        // Unlisted or unknown categories fall back to the global threshold
+        assert_eq!(threshold(Some("communication")), THRESHOLD);
+        assert_eq!(threshold(None), THRESHOLD);
+
+        // Five minutes without input: idle in a terminal, still reading in the browser
+        let start = Instant::now();
+        let none = SyntheticInput::new();
+        let idle_after = |category: &str| {
+            let mut tracker = IdleTracker::new();
+            tracker.poll(start, Duration::ZERO, threshold(Some(category)), &none);
+            tracker.poll(
+                start + Duration::from_secs(300),
+                Duration::from_secs(300),
+                threshold(Some(category)),
+                &none,
+            )
+        };
+        assert_eq!(
+            idle_after("coding"),
+            Some(IdleTransition::Start { idle_for: Duration::from_secs(300) })
+        );
+        assert_eq!(idle_after("browsing"), None);
+        assert_eq!(
+            idle_after("other"),
+            Some(IdleTransition::Start { idle_for: Duration::from_secs(300) })
+        );
+        // Zero turns detection off for the category
+        assert_eq!(idle_after("design"), None);
+    }
+
+    #[test]
+    fn test_automation_input_is_not_user_activity() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut tracker = IdleTracker::new();
+        let mut synthetic = SyntheticInput::new();
+
+        tracker.poll(at(0), Duration::ZERO, THRESHOLD, &synthetic);
+
+        // Automation types for a while before the user has been away long enough
+        synthetic.begin(at(100));
+        assert_eq!(tracker.poll(at(105), Duration::ZERO, THRESHOLD, &synthetic), None);
+        synthetic.end(at(110));
+        // The system's input timer now points at the automation, not the user
+        assert_eq!(
+            tracker.poll(at(200), Duration::from_secs(90), THRESHOLD, &synthetic),
+            Some(IdleTransition::Start { idle_for: Duration::from_secs(200) })
+        );
+
+        // Automation while idle doesn't end the idle period
+        synthetic.begin(at(300));
+        synthetic.end(at(301));
+        assert_eq!(tracker.poll(at(301), Duration::ZERO, THRESHOLD, &synthetic), None);
+        assert!(tracker.is_idle());
+
+        // Real input afterwards does
+        assert_eq!(tracker.poll(at(320), Duration::ZERO, THRESHOLD, &synthetic), Some(IdleTransition::End));
+    }
+}
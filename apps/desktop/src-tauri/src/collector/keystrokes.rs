@@ -0,0 +1,487 @@
+// Global typed-text capture (opt-in)
+// Records what is typed in any app, not just browser text fields, for users who
+// explicitly asked for it. A listen-only keyboard event tap (macOS, needs
+// accessibility) feeds keystrokes in; the collector points the capture at the
+// focused app every tick and stores the text as a "typed_text" event when the
+// app changes. Nothing is recorded while a password field is focused, while the
+// system has secure input on, or in a denylisted app. Each keystroke is checked
+// against the process the capture points at, so keys typed into another app
+// before the next tick are dropped rather than misattributed. Typed text never leaves
+// the device (sync drops the field), and a menu bar marker stays on while
+// capture is enabled.
+
+use super::Event;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with the new on/off state when capture is switched
+pub const TYPED_TEXT_CAPTURE_EVENT: &str = "typed-text-capture";
+/// Text typed into one app is stored at least this often
+pub const TYPED_TEXT_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Characters held before the text is stored early
+pub const MAX_TYPED_TEXT_CHARS: usize = 5_000;
+
+/// Apps whose keystrokes are never recorded (case-insensitive, whole name),
+/// on top of the user's `typedTextDenylist`
+pub const TYPED_TEXT_DENYLIST: &[&str] = &[
+    "1Password",
+    "1Password 7",
+    "Bitwarden",
+    "Dashlane",
+    "KeePassXC",
+    "LastPass",
+    "Keychain Access",
+    "Passwords",
+    "SecurityAgent",
+    "loginwindow",
+    "coreautha",
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CAPTURE: Mutex<KeystrokeCapture> = Mutex::new(KeystrokeCapture::new());
+
+/// One keystroke as seen by the event tap
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyInput {
+    Text(String),
+    Backspace,
+    /// Return or tab
+    Break,
+}
+
+/// Text typed into one app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedText {
+    pub app_name: String,
+    pub text: String,
+}
+
+impl TypedText {
+    pub fn into_event(self) -> Event {
+        let category = super::categorize_app(&self.app_name);
+        let mut event = Event::new("typed_text", Some(self.app_name), None).with_category(&category);
+        event.typed_text = Some(self.text);
+        event
+    }
+}
+
+/// Whether keystrokes in `app_name` may be recorded
+pub fn typing_allowed(app_name: &str, secure_field: bool, denylist: &[String]) -> bool {
+    let app_name = app_name.trim();
+    !secure_field
+        && !TYPED_TEXT_DENYLIST.iter().any(|name| name.eq_ignore_ascii_case(app_name))
+        && !denylist.iter().any(|name| name.trim().eq_ignore_ascii_case(app_name))
+}
+
+/// Keystrokes of the app currently being recorded
+#[derive(Debug)]
+pub struct KeystrokeCapture {
+    /// App keystrokes are attributed to; None while nothing may be recorded
+    target: Option<String>,
+    /// Process of `target`; keystrokes sent to any other process are dropped
+    target_pid: Option<i32>,
+    text: String,
+    started: Option<Instant>,
+}
+
+impl KeystrokeCapture {
+    pub const fn new() -> Self {
+        Self {
+            target: None,
+            target_pid: None,
+            text: String::new(),
+            started: None,
+        }
+    }
+
+    /// Point the capture at the focused app, running as process `pid` if known
+    /// Returns the text typed into the previous app when recording moves or stops
+    pub fn focus(
+        &mut self,
+        app_name: Option<&str>,
+        pid: Option<i32>,
+        secure_field: bool,
+        denylist: &[String],
+    ) -> Option<TypedText> {
+        let next = app_name
+            .filter(|app| typing_allowed(app, secure_field, denylist))
+            .map(str::to_string);
+        if next == self.target {
+            if next.is_some() && pid.is_some() {
+                self.target_pid = pid;
+            }
+            return None;
+        }
+
+        let finished = self.take();
+        self.target_pid = next.as_ref().and(pid);
+        self.target = next;
+        finished
+    }
+
+    /// Add a keystroke sent to process `event_pid` to the current app's text
+    /// Dropped while `secure_input` is on, or when the key went to another
+    /// process than the one recorded (focus moved since the last tick)
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn record(&mut self, key: KeyInput, secure_input: bool, event_pid: Option<i32>, now: Instant) {
+        if secure_input || self.target.is_none() {
+            return;
+        }
+        if let (Some(event_pid), Some(target_pid)) = (event_pid, self.target_pid) {
+            if event_pid != target_pid {
+                return;
+            }
+        }
+
+        match key {
+            KeyInput::Text(text) => self.text.push_str(&text),
+            KeyInput::Backspace => {
+                self.text.pop();
+            }
+            KeyInput::Break => {
+                if !self.text.is_empty() && !self.text.ends_with('\n') {
+                    self.text.push('\n');
+                }
+            }
+        }
+        self.started.get_or_insert(now);
+    }
+
+    /// Text typed so far, if any; recording continues for the same app
+    pub fn take(&mut self) -> Option<TypedText> {
+        self.started = None;
+        let text = std::mem::take(&mut self.text);
+        let app_name = self.target.clone()?;
+        let text = text.trim();
+        (!text.is_empty()).then(|| TypedText {
+            app_name,
+            text: text.to_string(),
+        })
+    }
+
+    /// Text typed so far once it has been held for `max_age` or grew too long
+    pub fn take_due(&mut self, now: Instant, max_age: Duration) -> Option<TypedText> {
+        let due = self.started.is_some_and(|started| now.duration_since(started) >= max_age)
+            || self.text.chars().count() >= MAX_TYPED_TEXT_CHARS;
+        if due {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// Stop recording and discard anything not stored yet
+    pub fn stop(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for KeystrokeCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether global typed-text capture is on
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Turn global typed-text capture on or off and update the menu bar marker
+/// Turning it off discards text that was not stored yet
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if enabled {
+        tap::start()?;
+    } else {
+        CAPTURE.lock().unwrap().stop();
+    }
+    ENABLED.store(enabled, Ordering::SeqCst);
+
+    crate::tray::set_typing_indicator(app, enabled);
+    let _ = app.emit(TYPED_TEXT_CAPTURE_EVENT, enabled);
+    crate::logs::info(&format!(
+        "[TypedText] Global capture {}",
+        if enabled { "enabled" } else { "disabled" }
+    ));
+    Ok(())
+}
+
+/// Point capture at the focused app (None while collection is paused)
+/// Returns the text finished in the previous app, or text held for too long
+pub fn focus(app_name: Option<&str>, secure_field: bool, denylist: &[String]) -> Option<TypedText> {
+    if !is_enabled() {
+        return None;
+    }
+    let pid = app_name.and_then(|_| tap::frontmost_pid());
+    let mut capture = CAPTURE.lock().unwrap();
+    capture
+        .focus(app_name, pid, secure_field, denylist)
+        .or_else(|| capture.take_due(Instant::now(), TYPED_TEXT_FLUSH_INTERVAL))
+}
+
+/// Text not stored yet, e.g. on shutdown
+pub fn take() -> Option<TypedText> {
+    CAPTURE.lock().unwrap().take()
+}
+
+#[cfg(target_os = "macos")]
+mod tap {
+    use super::{KeyInput, CAPTURE};
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use core_graphics::event::{
+        CGEvent, CGEventFlags, CGEventRef, CGEventTap, CGEventTapLocation, CGEventTapOptions,
+        CGEventTapPlacement, CGEventType, EventField,
+    };
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    const KEYCODE_RETURN: i64 = 36;
+    const KEYCODE_TAB: i64 = 48;
+    const KEYCODE_DELETE: i64 = 51;
+    const KEYCODE_KEYPAD_ENTER: i64 = 76;
+
+    static STARTED: AtomicBool = AtomicBool::new(false);
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn CGEventKeyboardGetUnicodeString(
+            event: *const c_void,
+            max_len: usize,
+            actual_len: *mut usize,
+            buffer: *mut u16,
+        );
+    }
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        /// On while any app has a password field focused
+        fn IsSecureEventInputEnabled() -> bool;
+    }
+
+    /// Start the keyboard event tap on its own run loop thread (once)
+    pub fn start() -> Result<(), String> {
+        if STARTED.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("typed-text-tap".to_string())
+            .spawn(move || {
+                let tap = CGEventTap::new(
+                    CGEventTapLocation::Session,
+                    CGEventTapPlacement::HeadInsertEventTap,
+                    CGEventTapOptions::ListenOnly,
+                    vec![CGEventType::KeyDown],
+                    |_proxy, _event_type, event| {
+                        if let Some(key) = key_input(event) {
+                            let secure_input = unsafe { IsSecureEventInputEnabled() };
+                            let pid = event.get_integer_value_field(EventField::EVENT_TARGET_UNIX_PROCESS_ID);
+                            let pid = (pid > 0).then_some(pid as i32);
+                            CAPTURE.lock().unwrap().record(key, secure_input, pid, Instant::now());
+                        }
+                        None
+                    },
+                );
+                let tap = match tap {
+                    Ok(tap) => tap,
+                    Err(()) => {
+                        let _ = tx.send(Err(
+                            "Failed to create keyboard event tap: accessibility permission required".to_string(),
+                        ));
+                        return;
+                    }
+                };
+                let source = match tap.mach_port.create_runloop_source(0) {
+                    Ok(source) => source,
+                    Err(()) => {
+                        let _ = tx.send(Err("Failed to create keyboard event tap run loop source".to_string()));
+                        return;
+                    }
+                };
+
+                unsafe {
+                    CFRunLoop::get_current().add_source(&source, kCFRunLoopCommonModes);
+                }
+                tap.enable();
+                let _ = tx.send(Ok(()));
+                CFRunLoop::run_current();
+            })
+            .map_err(|e| format!("Failed to start keyboard event tap: {}", e))?;
+
+        rx.recv()
+            .map_err(|_| "Failed to start keyboard event tap".to_string())??;
+        STARTED.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Process id of the frontmost app, which receives keystrokes
+    pub fn frontmost_pid() -> Option<i32> {
+        use cocoa::base::{id, nil};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let app: id = msg_send![workspace, frontmostApplication];
+            if app == nil {
+                return None;
+            }
+            let pid: i32 = msg_send![app, processIdentifier];
+            (pid > 0).then_some(pid)
+        }
+    }
+
+    /// Keystroke for a keydown event; shortcuts (command/control) are skipped
+    fn key_input(event: &CGEvent) -> Option<KeyInput> {
+        let flags = event.get_flags();
+        if flags.intersects(CGEventFlags::CGEventFlagCommand | CGEventFlags::CGEventFlagControl) {
+            return None;
+        }
+
+        match event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) {
+            KEYCODE_DELETE => return Some(KeyInput::Backspace),
+            KEYCODE_RETURN | KEYCODE_KEYPAD_ENTER | KEYCODE_TAB => return Some(KeyInput::Break),
+            _ => {}
+        }
+
+        let mut buffer = [0u16; 8];
+        let mut len = 0usize;
+        let event_ref: &CGEventRef = event;
+        unsafe {
+            CGEventKeyboardGetUnicodeString(
+                event_ref as *const CGEventRef as *const c_void,
+                buffer.len(),
+                &mut len,
+                buffer.as_mut_ptr(),
+            );
+        }
+        let text: String = String::from_utf16_lossy(&buffer[..len.min(buffer.len())])
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        (!text.is_empty()).then_some(KeyInput::Text(text))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod tap {
+    pub fn start() -> Result<(), String> {
+        Err("Global typed text capture is only supported on macOS".to_string())
+    }
+
+    pub fn frontmost_pid() -> Option<i32> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(capture: &mut KeystrokeCapture, text: &str, secure_input: bool) {
+        capture.record(KeyInput::Text(text.to_string()), secure_input, None, Instant::now());
+    }
+
+    #[test]
+    fn test_text_is_stored_per_app() {
+        let mut capture = KeystrokeCapture::new();
+        assert_eq!(capture.focus(Some("Notes"), None, false, &[]), None);
+        typed(&mut capture, "helo", false);
+        capture.record(KeyInput::Backspace, false, None, Instant::now());
+        typed(&mut capture, "lo", false);
+        capture.record(KeyInput::Break, false, None, Instant::now());
+        capture.record(KeyInput::Break, false, None, Instant::now());
+        typed(&mut capture, "world", false);
+
+        let finished = capture.focus(Some("Terminal"), None, false, &[]).unwrap();
+        assert_eq!(finished.app_name, "Notes");
+        assert_eq!(finished.text, "hello\nworld");
+
+        typed(&mut capture, "ls", false);
+        assert_eq!(capture.take().unwrap().app_name, "Terminal");
+    }
+
+    #[test]
+    fn test_secure_field_is_never_captured() {
+        let mut capture = KeystrokeCapture::new();
+        capture.focus(Some("Safari"), None, false, &[]);
+        typed(&mut capture, "user@example.com", false);
+
+        // Focusing a password field stores what came before and stops recording
+        let finished = capture.focus(Some("Safari"), None, true, &[]).unwrap();
+        assert_eq!(finished.text, "user@example.com");
+        typed(&mut capture, "hunter2", false);
+        assert_eq!(capture.focus(Some("Safari"), None, false, &[]), None);
+
+        // Secure input reported by the system drops keystrokes even before the next tick
+        typed(&mut capture, "hunter2", true);
+        assert_eq!(capture.take(), None);
+
+        let event = TypedText {
+            app_name: "Safari".to_string(),
+            text: "hi".to_string(),
+        }
+        .into_event();
+        assert_eq!(event.event_type, "typed_text");
+        assert_eq!(event.typed_text.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn test_denylisted_apps_are_never_captured() {
+        let denylist = vec![" Signal ".to_string()];
+        let mut capture = KeystrokeCapture::new();
+
+        for app in ["1password", "Keychain Access", "signal"] {
+            assert_eq!(capture.focus(Some(app), None, false, &denylist), None);
+            typed(&mut capture, "secret", false);
+            assert_eq!(capture.take(), None, "{} must not be captured", app);
+        }
+
+        // Switching from an allowed app to a denylisted one stops recording
+        capture.focus(Some("Notes"), None, false, &denylist);
+        typed(&mut capture, "todo", false);
+        assert_eq!(capture.focus(Some("Bitwarden"), None, false, &denylist).unwrap().text, "todo");
+        typed(&mut capture, "secret", false);
+        assert_eq!(capture.focus(None, None, false, &denylist), None);
+    }
+
+    #[test]
+    fn test_keys_sent_to_another_process_are_dropped() {
+        let key = |capture: &mut KeystrokeCapture, text: &str, pid: i32| {
+            capture.record(KeyInput::Text(text.to_string()), false, Some(pid), Instant::now());
+        };
+        let mut capture = KeystrokeCapture::new();
+        capture.focus(Some("Notes"), Some(100), false, &[]);
+        key(&mut capture, "todo", 100);
+
+        // Focus moved to a password manager between ticks: its keys are not recorded
+        key(&mut capture, "secret", 200);
+        assert_eq!(capture.take().unwrap().text, "todo");
+
+        // Until the next tick retargets the capture
+        capture.focus(Some("Terminal"), Some(300), false, &[]);
+        key(&mut capture, "ls", 300);
+        key(&mut capture, "secret", 200);
+        assert_eq!(capture.take().unwrap().text, "ls");
+    }
+
+    #[test]
+    fn test_long_typing_is_flushed() {
+        let mut capture = KeystrokeCapture::new();
+        capture.focus(Some("Notes"), None, false, &[]);
+        let start = Instant::now();
+        capture.record(KeyInput::Text("a".to_string()), false, None, start);
+
+        assert_eq!(capture.take_due(start + Duration::from_secs(10), TYPED_TEXT_FLUSH_INTERVAL), None);
+        let due = capture.take_due(start + TYPED_TEXT_FLUSH_INTERVAL, TYPED_TEXT_FLUSH_INTERVAL);
+        assert_eq!(due.unwrap().text, "a");
+        // Recording continues for the same app
+        typed(&mut capture, "b", false);
+        assert_eq!(capture.take().unwrap().text, "b");
+    }
+}
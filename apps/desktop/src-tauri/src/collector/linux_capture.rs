@@ -0,0 +1,237 @@
+// Linux screen capture
+// X11 sessions grab the root window with XGetImage. Under Wayland the root
+// window of XWayland only shows X clients, so the compositor's own screenshot
+// tools (grim on wlroots compositors, spectacle on KDE) are tried first and
+// X11 is only the fallback. GNOME only hands out screenshots through an
+// interactive portal dialog, which a background collector can't use.
+// The first backend that works is remembered and tried first from then on.
+
+use super::screenshots::{decode_pixels, ChannelOrder, PixelLayout};
+use image::RgbImage;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a screenshot tool may run before it is killed
+const TOOL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Placeholder for the output file in `WAYLAND_TOOLS` arguments
+const OUTPUT_PLACEHOLDER: &str = "{output}";
+
+/// Command-line screenshot tools for Wayland, in order of preference
+/// Each writes a PNG of the whole screen to the output path without prompting
+const WAYLAND_TOOLS: &[(&str, &[&str])] = &[
+    ("grim", &["-t", "png", OUTPUT_PLACEHOLDER]),
+    ("spectacle", &["-b", "-n", "-f", "-o", OUTPUT_PLACEHOLDER]),
+];
+
+/// Backend that captured last, tried first next time
+static WORKING_BACKEND: Mutex<Option<Backend>> = Mutex::new(None);
+
+/// A way of grabbing the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    X11,
+    /// Index into `WAYLAND_TOOLS`
+    WaylandTool(usize),
+}
+
+/// Backends to try for the current session, best first
+/// A backend that worked before (`working`) goes ahead of the rest
+pub fn backends(wayland_session: bool, working: Option<Backend>) -> Vec<Backend> {
+    let tools = (0..WAYLAND_TOOLS.len()).map(Backend::WaylandTool);
+    let mut backends: Vec<Backend> = if wayland_session {
+        tools.chain([Backend::X11]).collect()
+    } else {
+        [Backend::X11].into_iter().chain(tools).collect()
+    };
+    if let Some(working) = working {
+        backends.retain(|&backend| backend != working);
+        backends.insert(0, working);
+    }
+    backends
+}
+
+/// Capture the whole screen with the first backend that works
+pub fn capture() -> Option<RgbImage> {
+    let wayland_session = std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland");
+    let working = *WORKING_BACKEND.lock().unwrap();
+
+    backends(wayland_session, working).into_iter().find_map(|backend| {
+        let frame = match backend {
+            Backend::X11 => capture_x11(),
+            Backend::WaylandTool(index) => capture_with_tool(WAYLAND_TOOLS[index]),
+        }?;
+        if working != Some(backend) {
+            crate::logs::info(&format!("[Screenshot] Capturing with {:?}", backend));
+            *WORKING_BACKEND.lock().unwrap() = Some(backend);
+        }
+        Some(frame)
+    })
+}
+
+/// Byte order of a 32-bit X11 pixel, from the image's channel masks
+/// None for layouts other than 8 bits per channel
+pub fn channel_order(bits_per_pixel: i32, red_mask: u64, green_mask: u64, blue_mask: u64) -> Option<ChannelOrder> {
+    if bits_per_pixel != 32 || green_mask != 0x00ff00 {
+        return None;
+    }
+    match (red_mask, blue_mask) {
+        (0xff0000, 0x0000ff) => Some(ChannelOrder::Bgra),
+        (0x0000ff, 0xff0000) => Some(ChannelOrder::Rgba),
+        _ => None,
+    }
+}
+
+/// Grab the X11 root window
+fn capture_x11() -> Option<RgbImage> {
+    use std::ptr;
+    use x11::xlib::{
+        XAllPlanes, XCloseDisplay, XDefaultRootWindow, XDestroyImage, XGetImage, XGetWindowAttributes,
+        XOpenDisplay, XWindowAttributes, ZPixmap,
+    };
+
+    unsafe {
+        let display = XOpenDisplay(ptr::null());
+        if display.is_null() {
+            return None;
+        }
+
+        let root = XDefaultRootWindow(display);
+        let mut attributes: XWindowAttributes = std::mem::zeroed();
+        let image = if XGetWindowAttributes(display, root, &mut attributes) != 0 {
+            XGetImage(
+                display,
+                root,
+                0,
+                0,
+                attributes.width as u32,
+                attributes.height as u32,
+                XAllPlanes(),
+                ZPixmap,
+            )
+        } else {
+            ptr::null_mut()
+        };
+
+        let frame = image.as_ref().and_then(|ximage| {
+            let Some(order) = channel_order(
+                ximage.bits_per_pixel,
+                ximage.red_mask as u64,
+                ximage.green_mask as u64,
+                ximage.blue_mask as u64,
+            ) else {
+                crate::logs::error(&format!(
+                    "[Screenshot] Unsupported X11 pixel format ({} bits per pixel)",
+                    ximage.bits_per_pixel
+                ));
+                return None;
+            };
+            let layout = PixelLayout {
+                width: ximage.width as usize,
+                height: ximage.height as usize,
+                bytes_per_row: ximage.bytes_per_line as usize,
+                order,
+                // The fourth byte is padding, not alpha
+                premultiplied: false,
+            };
+            let data = std::slice::from_raw_parts(ximage.data as *const u8, layout.bytes_per_row * layout.height);
+            decode_pixels(data, layout)
+        });
+
+        if !image.is_null() {
+            XDestroyImage(image);
+        }
+        XCloseDisplay(display);
+        frame
+    }
+}
+
+/// Arguments for a screenshot tool writing to `output`
+pub fn tool_args(args: &[&str], output: &Path) -> Vec<String> {
+    args.iter()
+        .map(|arg| arg.replace(OUTPUT_PLACEHOLDER, &output.to_string_lossy()))
+        .collect()
+}
+
+/// Take a screenshot with a Wayland screenshot tool
+fn capture_with_tool((program, args): (&str, &[&str])) -> Option<RgbImage> {
+    let output = std::env::temp_dir().join(format!("observer-capture-{}.png", uuid::Uuid::new_v4()));
+    let mut command = std::process::Command::new(program);
+    command.args(tool_args(args, &output));
+
+    let result = match crate::native_applescript::output_with_timeout(command, TOOL_TIMEOUT) {
+        Ok(result) if result.status.success() => image::open(&output)
+            .map(|image| image.to_rgb8())
+            .map_err(|e| format!("Failed to read {} screenshot: {}", program, e)),
+        Ok(result) => Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )),
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_file(&output);
+
+    match result {
+        Ok(image) => Some(image),
+        // Not installed: quietly try the next backend
+        Err(e) if e.contains("No such file or directory") => None,
+        Err(e) => {
+            crate::logs::error(&format!("[Screenshot] {}", e));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wayland_sessions_try_tools_first() {
+        let (grim, spectacle) = (Backend::WaylandTool(0), Backend::WaylandTool(1));
+        assert_eq!(backends(true, None), [grim, spectacle, Backend::X11]);
+        assert_eq!(backends(false, None), [Backend::X11, grim, spectacle]);
+    }
+
+    #[test]
+    fn test_working_backend_is_tried_first() {
+        let spectacle = Backend::WaylandTool(1);
+        assert_eq!(backends(true, Some(spectacle)), [spectacle, Backend::WaylandTool(0), Backend::X11]);
+        assert_eq!(backends(false, Some(Backend::X11)), backends(false, None));
+        assert_eq!(WAYLAND_TOOLS[1].0, "spectacle");
+    }
+
+    #[test]
+    fn test_x11_channel_order() {
+        assert_eq!(channel_order(32, 0xff0000, 0x00ff00, 0x0000ff), Some(ChannelOrder::Bgra));
+        assert_eq!(channel_order(32, 0x0000ff, 0x00ff00, 0xff0000), Some(ChannelOrder::Rgba));
+        // 16-bit and 30-bit deep color are not supported
+        assert_eq!(channel_order(16, 0xf800, 0x07e0, 0x001f), None);
+        assert_eq!(channel_order(32, 0x3ff00000, 0x000ffc00, 0x000003ff), None);
+    }
+
+    #[test]
+    fn test_x11_frame_decodes_through_shared_pipeline() {
+        // Two BGRX pixels per row, padded to 12 bytes
+        let data = [
+            0, 0, 255, 0, 0, 255, 0, 0, 9, 9, 9, 9, //
+            255, 0, 0, 0, 10, 20, 30, 0, 9, 9, 9, 9,
+        ];
+        let order = channel_order(32, 0xff0000, 0x00ff00, 0x0000ff).unwrap();
+        let layout = PixelLayout { width: 2, height: 2, bytes_per_row: 12, order, premultiplied: false };
+        let image = decode_pixels(&data, layout).unwrap();
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 255, 0]);
+        assert_eq!(image.get_pixel(0, 1).0, [0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [30, 20, 10]);
+    }
+
+    #[test]
+    fn test_tool_args_point_at_output() {
+        let output = Path::new("/tmp/observer-capture.png");
+        assert_eq!(tool_args(&["-t", "png", OUTPUT_PLACEHOLDER], output), vec!["-t", "png", "/tmp/observer-capture.png"]);
+    }
+}
@@ -0,0 +1,136 @@
+// Manual events module
+// Lets integrations and scripts add events (e.g. "started pomodoro" markers)
+// to the timeline through the same path as the collector
+
+use super::screenshots::{category_allows_capture, Screenshot};
+use super::{categorize_app, push_event_with, Event};
+use crate::commands::AppSettings;
+use crate::AppState;
+
+/// Event types that may be added manually
+/// None of them are emitted by the collector, so a manual event can't pass
+/// for collected activity in stats, focus time or sync
+pub const MANUAL_EVENT_TYPES: &[&str] = &["marker", "note"];
+
+/// Largest serialized `data` accepted for a manual event (bytes)
+pub const MAX_MANUAL_DATA_BYTES: usize = 16 * 1024;
+
+/// Build a manual event and store it like a collected one
+/// `data` must be a JSON object (or null); it is tagged with `"source": "manual"`
+pub fn add_manual_event(
+    state: &mut AppState,
+    event_type: &str,
+    app_name: Option<String>,
+    data: serde_json::Value,
+    settings: &AppSettings,
+) -> Result<Event, String> {
+    if !MANUAL_EVENT_TYPES.contains(&event_type) {
+        return Err(format!("Event type not allowed: {}", event_type));
+    }
+
+    let mut data = match data {
+        serde_json::Value::Null => serde_json::json!({}),
+        serde_json::Value::Object(_) => data,
+        _ => return Err("Event data must be a JSON object".to_string()),
+    };
+
+    let size = serde_json::to_string(&data)
+        .map_err(|e| format!("Failed to serialize event data: {}", e))?
+        .len();
+    if size > MAX_MANUAL_DATA_BYTES {
+        return Err(format!(
+            "Event data too large: {} bytes (max {})",
+            size, MAX_MANUAL_DATA_BYTES
+        ));
+    }
+    data["source"] = serde_json::json!("manual");
+
+    let category = match app_name {
+        Some(ref app_name) => categorize_app(app_name),
+        None => "other".to_string(),
+    };
+
+    let mut event = Event::new(event_type, app_name, None).with_category(&category);
+    event.data = data;
+
+    push_event_with(state, event.clone(), settings);
+    Ok(event)
+}
+
+/// Apply the screenshot category settings to a manual capture of `app_name`
+pub fn manual_screenshot_allowed(app_name: &str, allowed_categories: &[String]) -> Result<(), String> {
+    let category = categorize_app(app_name);
+    if category_allows_capture(Some(&category), allowed_categories) {
+        Ok(())
+    } else {
+        Err(format!("Screenshots are disabled for {} ({})", app_name, category))
+    }
+}
+
+/// Record a manually captured screenshot as a timeline event
+pub fn add_manual_screenshot(state: &mut AppState, screenshot: &Screenshot, settings: &AppSettings) -> Event {
+    let mut event = Event::new(
+        "screenshot",
+        Some(screenshot.app_name.clone()),
+        Some(screenshot.window_title.clone()),
+    )
+    .with_category(&categorize_app(&screenshot.app_name));
+    event.screenshot_path = Some(screenshot.path.to_string_lossy().to_string());
+    event.thumbnail_path = screenshot
+        .thumbnail_path
+        .as_ref()
+        .map(|thumbnail| thumbnail.to_string_lossy().to_string());
+    event.data = serde_json::json!({ "source": "manual" });
+
+    push_event_with(state, event.clone(), settings);
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventDatabase;
+    use std::sync::Arc;
+
+    fn empty_state() -> AppState {
+        AppState::new(Arc::new(EventDatabase::in_memory().unwrap()), Vec::new())
+    }
+
+    #[test]
+    fn test_manual_event_appears_in_stats_and_buffer() {
+        let mut state = empty_state();
+
+        let event = add_manual_event(
+            &mut state,
+            "marker",
+            Some("Pomodoro".to_string()),
+            serde_json::json!({ "label": "started pomodoro" }),
+            &AppSettings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(state.events_today, 1);
+        assert_eq!(state.top_apps_cache.get("Pomodoro"), Some(&1));
+        assert_eq!(state.events_buffer.len(), 1);
+        assert_eq!(state.events_buffer[0].id, event.id);
+        assert_eq!(state.events_buffer[0].data["label"], "started pomodoro");
+        assert_eq!(state.events_buffer[0].data["source"], "manual");
+        assert_eq!(state.db.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_manual_event_validation() {
+        let mut state = empty_state();
+        let settings = AppSettings::default();
+
+        assert!(add_manual_event(&mut state, "system_wake", None, serde_json::Value::Null, &settings).is_err());
+        assert!(add_manual_event(&mut state, "app_focus", None, serde_json::Value::Null, &settings).is_err());
+        assert!(add_manual_event(&mut state, "marker", None, serde_json::json!("text"), &settings).is_err());
+
+        let huge = "x".repeat(MAX_MANUAL_DATA_BYTES);
+        assert!(add_manual_event(&mut state, "note", None, serde_json::json!({ "text": huge }), &settings).is_err());
+
+        assert!(state.events_buffer.is_empty());
+        assert!(add_manual_event(&mut state, "note", None, serde_json::Value::Null, &settings).is_ok());
+    }
+}
@@ -2,13 +2,22 @@
 use core_foundation::base::{CFRelease, TCFType};
 #[cfg(target_os = "macos")]
 use core_foundation::string::{CFString, CFStringRef};
-#[cfg(target_os = "macos")]
-use core_foundation::array::{CFArray, CFArrayRef};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use super::ax_tree::AxNode;
+#[cfg(target_os = "macos")]
+use super::ax_tree::AxElement;
+
+/// Default wall-clock budget for one AX traversal
+pub const DEFAULT_TRAVERSAL_TIME_BUDGET: Duration = Duration::from_millis(150);
+
+/// Default maximum number of AX elements visited per traversal
+pub const DEFAULT_TRAVERSAL_MAX_NODES: usize = 2000;
 
 #[cfg(target_os = "macos")]
 type AXUIElementRef = *const std::ffi::c_void;
@@ -43,7 +52,7 @@ impl Message {
             id: Uuid::new_v4().to_string(),
             app,
             sender: None,
-            content,
+            content: crate::text::truncate_string(content, crate::text::MAX_MESSAGE_CHARS),
             timestamp: Utc::now(),
             chat_name: None,
             is_outgoing: false,
@@ -85,10 +94,52 @@ impl MessengerState {
     }
 }
 
+/// Time and node-count limits for a single AX tree traversal
+/// Large chat histories can expose tens of thousands of elements, so the walk
+/// stops early and keeps whatever messages it found so far
+#[derive(Debug)]
+pub struct TraversalBudget {
+    deadline: Instant,
+    max_nodes: usize,
+    visited: usize,
+}
+
+impl TraversalBudget {
+    pub fn new(time_budget: Duration, max_nodes: usize) -> Self {
+        Self {
+            deadline: Instant::now() + time_budget,
+            max_nodes,
+            visited: 0,
+        }
+    }
+
+    /// Account for visiting one node
+    /// Returns false once the budget is exhausted and the traversal should stop
+    pub fn visit(&mut self) -> bool {
+        if self.is_exhausted() {
+            return false;
+        }
+        self.visited += 1;
+        true
+    }
+
+    /// Whether either the node cap or the deadline has been reached
+    pub fn is_exhausted(&self) -> bool {
+        self.visited >= self.max_nodes || Instant::now() >= self.deadline
+    }
+
+    /// Number of nodes visited so far
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+}
+
 /// Monitor for capturing messages from messenger applications
 #[allow(dead_code)]
 pub struct MessengerMonitor {
     supported_messengers: HashSet<String>,
+    time_budget: Duration,
+    max_nodes: usize,
 }
 
 impl MessengerMonitor {
@@ -102,9 +153,19 @@ impl MessengerMonitor {
 
         Self {
             supported_messengers,
+            time_budget: DEFAULT_TRAVERSAL_TIME_BUDGET,
+            max_nodes: DEFAULT_TRAVERSAL_MAX_NODES,
         }
     }
 
+    /// Override the AX traversal limits
+    #[allow(dead_code)]
+    pub fn with_budget(mut self, time_budget: Duration, max_nodes: usize) -> Self {
+        self.time_budget = time_budget;
+        self.max_nodes = max_nodes;
+        self
+    }
+
     /// Check if the given app name is a supported messenger
     pub fn is_messenger(&self, app_name: &str) -> bool {
         self.supported_messengers.contains(app_name)
@@ -184,7 +245,10 @@ impl MessengerMonitor {
         let chat_name = self.get_active_chat_name(app_name);
         let mut messages = Vec::new();
 
-        self.find_message_elements(window, app_name, &mut messages);
+        // Retained by the wrapper, so the release below still balances the copy
+        let window_element = unsafe { AxElement::retain(window) };
+        self.find_message_elements(&window_element, app_name, &mut messages);
+        drop(window_element);
 
         // Release the window element
         unsafe {
@@ -258,98 +322,53 @@ impl MessengerMonitor {
     }
 
     /// Find message elements in the window
-    #[cfg(target_os = "macos")]
-    fn find_message_elements(
-        &self,
-        window: AXUIElementRef,
-        app_name: &str,
-        messages: &mut Vec<Message>,
-    ) {
-        self.traverse_for_messages(window, app_name, messages, 0);
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn find_message_elements<N: AxNode>(&self, window: &N, app_name: &str, messages: &mut Vec<Message>) {
+        let mut budget = TraversalBudget::new(self.time_budget, self.max_nodes);
+        self.traverse_for_messages(window, app_name, messages, 0, &mut budget);
+
+        if budget.is_exhausted() {
+            crate::logs::info(&format!(
+                "[Messenger] Traversal budget hit for {} after {} nodes, returning {} partial messages",
+                app_name,
+                budget.visited(),
+                messages.len()
+            ));
+        }
     }
 
     /// Recursively traverse the AX tree to find messages
-    #[cfg(target_os = "macos")]
-    fn traverse_for_messages(
+    fn traverse_for_messages<N: AxNode>(
         &self,
-        element: AXUIElementRef,
+        element: &N,
         app_name: &str,
         messages: &mut Vec<Message>,
         depth: usize,
+        budget: &mut TraversalBudget,
     ) {
         const MAX_DEPTH: usize = 30;
 
-        if element.is_null() || depth > MAX_DEPTH {
+        if depth > MAX_DEPTH || !budget.visit() {
             return;
         }
 
-        unsafe {
-            // Get the role of the element
-            let role_attr = CFString::new("AXRole");
-            let mut role_value: *const std::ffi::c_void = std::ptr::null();
-
-            let role_result = AXUIElementCopyAttributeValue(
-                element,
-                role_attr.as_concrete_TypeRef(),
-                &mut role_value,
-            );
-
-            let mut is_text_element = false;
-
-            if role_result == 0 && !role_value.is_null() {
-                let role_string = CFString::wrap_under_get_rule(role_value as CFStringRef);
-                let role = role_string.to_string();
-
-                // Look for text-containing elements
-                is_text_element = role.contains("Text")
-                    || role.contains("StaticText")
-                    || role.contains("TextField")
-                    || role == "AXGroup";
-            }
-
-            // Try to get the text value
-            if is_text_element {
-                let value_attr = CFString::new("AXValue");
-                let mut text_value: *const std::ffi::c_void = std::ptr::null();
-
-                let value_result = AXUIElementCopyAttributeValue(
-                    element,
-                    value_attr.as_concrete_TypeRef(),
-                    &mut text_value,
-                );
-
-                if value_result == 0 && !text_value.is_null() {
-                    let text_string = CFString::wrap_under_get_rule(text_value as CFStringRef);
-                    let text = text_string.to_string();
-
-                    if self.looks_like_message(&text, app_name) {
-                        let message = Message::new(app_name.to_string(), text);
-                        messages.push(message);
-                    }
+        // Look for text-containing elements
+        let is_text_element = element
+            .role()
+            .is_some_and(|role| role.contains("Text") || role == "AXGroup");
+        if is_text_element {
+            if let Some(text) = element.value() {
+                if self.looks_like_message(&text, app_name) {
+                    messages.push(Message::new(app_name.to_string(), text));
                 }
             }
+        }
 
-            // Traverse children
-            let children_attr = CFString::new("AXChildren");
-            let mut children_value: *const std::ffi::c_void = std::ptr::null();
-
-            let children_result = AXUIElementCopyAttributeValue(
-                element,
-                children_attr.as_concrete_TypeRef(),
-                &mut children_value,
-            );
-
-            if children_result == 0 && !children_value.is_null() {
-                let children_array = CFArray::<AXUIElementRef>::wrap_under_get_rule(
-                    children_value as CFArrayRef
-                );
-
-                for i in 0..children_array.len() {
-                    if let Some(child) = children_array.get(i) {
-                        self.traverse_for_messages(*child, app_name, messages, depth + 1);
-                    }
-                }
+        for child in element.children() {
+            if budget.is_exhausted() {
+                break;
             }
+            self.traverse_for_messages(&child, app_name, messages, depth + 1, budget);
         }
     }
 
@@ -478,8 +497,6 @@ impl MessengerMonitor {
     /// Get the active chat name from window title
     #[cfg(target_os = "macos")]
     fn get_active_chat_name(&self, app_name: &str) -> Option<String> {
-        use std::process::Command;
-
         // Use AppleScript to get the window title
         let script = format!(
             r#"tell application "System Events"
@@ -494,17 +511,9 @@ impl MessengerMonitor {
             app_name
         );
 
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(&script)
-            .output()
-            .ok()?;
-
-        if output.status.success() {
-            let title = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !title.is_empty() && title != app_name {
-                return Some(title);
-            }
+        let title = crate::native_applescript::collect(&script)?;
+        if !title.is_empty() && title != app_name {
+            return Some(title);
         }
 
         None
@@ -591,4 +600,83 @@ mod tests {
         assert_eq!(state.active_chat, Some("general".to_string()));
         assert_eq!(state.visible_messages.len(), 1);
     }
+
+    /// Synthetic AX element
+    struct Node {
+        role: &'static str,
+        text: Option<&'static str>,
+        children: Vec<Node>,
+    }
+
+    impl<'a> AxNode for &'a Node {
+        fn role(&self) -> Option<String> {
+            Some(self.role.to_string())
+        }
+
+        fn subrole(&self) -> Option<String> {
+            None
+        }
+
+        fn title(&self) -> Option<String> {
+            None
+        }
+
+        fn value(&self) -> Option<String> {
+            self.text.map(|text| text.to_string())
+        }
+
+        fn position(&self) -> Option<(f64, f64)> {
+            None
+        }
+
+        fn children(&self) -> Vec<Self> {
+            let node: &'a Node = self;
+            node.children.iter().collect()
+        }
+    }
+
+    /// A window holding `count` chat messages
+    fn chat_window(count: usize) -> Node {
+        Node {
+            role: "AXWindow",
+            text: None,
+            children: (0..count)
+                .map(|_| Node { role: "AXStaticText", text: Some("See you at lunch"), children: Vec::new() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_messages_found_in_tree() {
+        let mut window = chat_window(2);
+        window.children.push(Node { role: "AXButton", text: Some("Send"), children: Vec::new() });
+
+        let mut messages = Vec::new();
+        MessengerMonitor::new().find_message_elements(&&window, "Slack", &mut messages);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "See you at lunch");
+        assert_eq!(messages[0].app, "Slack");
+    }
+
+    #[test]
+    fn test_traversal_stops_at_node_cap() {
+        let window = chat_window(100);
+        let monitor = MessengerMonitor::new().with_budget(Duration::from_secs(60), 10);
+
+        let mut messages = Vec::new();
+        monitor.find_message_elements(&&window, "Slack", &mut messages);
+
+        // The window plus 9 messages fit in the cap; partial results are kept
+        assert_eq!(messages.len(), 9);
+    }
+
+    #[test]
+    fn test_traversal_stops_at_deadline() {
+        let window = chat_window(100);
+        let monitor = MessengerMonitor::new().with_budget(Duration::ZERO, 1000);
+
+        let mut messages = Vec::new();
+        monitor.find_message_elements(&&window, "Slack", &mut messages);
+        assert!(messages.is_empty());
+    }
 }
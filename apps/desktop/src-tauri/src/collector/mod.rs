@@ -1,16 +1,70 @@
 mod accessibility;
+mod app_filter;
 mod apps;
+mod ax_tree;
 mod browser;
+mod capture_worker;
+mod categories;
+mod categorizer;
+mod context;
+mod favicons;
+mod idle;
+mod keystrokes;
+#[cfg(target_os = "linux")]
+mod linux_capture;
+mod manual;
 mod messenger;
+mod permission_watch;
+mod power;
+mod recording;
+mod screenshot_delta;
 mod screenshots;
 mod system_metrics;
+mod timings;
+mod toggles;
 
 pub use accessibility::macos::*;
-pub use browser::BrowserTab;
+pub use app_filter::{SystemAppFilter, DEFAULT_TITLE_CHURN_APPS};
+pub use ax_tree::dump_focused_app_tree;
+pub use browser::{BrowserTab, DEFAULT_URL_DEDUP_SECS};
+pub use capture_worker::{DEFAULT_CAPTURE_QUEUE_SIZE, DEFAULT_CAPTURE_WORKERS};
+pub use categories::{
+    is_known_category, learn_rule, recategorize_event, reload_category_rules, CategoryRule,
+    CategoryRules, CATEGORY_RULES_FILE,
+};
+pub use categorizer::start_server_categorizer;
+pub use context::{compose as compose_context, CurrentContext, SystemContext};
+pub use favicons::cached_icons as cached_favicons;
+pub use idle::{synthetic_input, DEFAULT_CATEGORY_IDLE_THRESHOLDS, DEFAULT_IDLE_THRESHOLD_SECS};
+pub use keystrokes::{
+    is_enabled as global_typed_text_enabled, set_enabled as set_global_typed_text_enabled,
+};
+pub use manual::{add_manual_event, add_manual_screenshot, manual_screenshot_allowed};
 pub use messenger::Message;
-pub use screenshots::{ScreenshotConfig, ScreenshotManager};
-pub use system_metrics::{SystemMetrics, SystemMetricsCollector};
-
+pub use permission_watch::PermissionDeniedPolicy;
+pub use power::{watch as watch_power, PowerEvent};
+pub use recording::{
+    expire_recording, recording_status, start_recording, stop_recording, RecordingStatus,
+    MAX_RECORDING_MINUTES,
+};
+pub use screenshot_delta::load_screenshot;
+pub use screenshots::{
+    cleanup_screenshot_dirs, live_config as live_screenshot_config, load_thumbnail, screenshot_stats,
+    set_live_config as set_live_screenshot_config, CaptureTrigger, CleanupSummary, Screenshot,
+    FrameCapturer, ScreenshotConfig, ScreenshotFormat, ScreenshotManager, DEFAULT_INTERVAL_SECS as DEFAULT_SCREENSHOT_INTERVAL_SECS,
+    DEFAULT_QUALITY as DEFAULT_SCREENSHOT_QUALITY, DEFAULT_RETENTION_DAYS as DEFAULT_SCREENSHOT_RETENTION_DAYS,
+    DEFAULT_SCALE_FACTOR as DEFAULT_SCREENSHOT_SCALE, DEFAULT_SIMILARITY_THRESHOLD as DEFAULT_SCREENSHOT_SIMILARITY,
+    DEFAULT_THUMBNAIL_WIDTH,
+};
+pub use system_metrics::{
+    SystemMetrics, SystemMetricsCollector, DEFAULT_METRICS_INTERVAL_SECS, DEFAULT_SMOOTHING_ALPHA,
+};
+pub use timings::{collector_timings, record_timing, CollectorStep, StepTimings};
+pub use toggles::{
+    collector_toggles, global_switches, set_collector_enabled, CollectorKind, CollectorSwitches, CollectorToggles,
+};
+
+use crate::commands::AppSettings;
 use crate::AppState;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -44,6 +98,9 @@ pub struct Event {
     pub browser_tab: Option<BrowserTab>,
     pub messages: Option<Vec<Message>>,
     pub screenshot_path: Option<String>,
+    /// Small JPEG stored next to the screenshot, when thumbnails are on
+    #[serde(default)]
+    pub thumbnail_path: Option<String>,
     // System metrics
     pub system_metrics: Option<SystemMetrics>,
     // Browser input capture (text being typed)
@@ -58,10 +115,11 @@ impl Event {
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
-            device_id: get_device_id(),
+            device_id: crate::config::device_id(),
             event_type: event_type.to_string(),
             timestamp: Utc::now(),
             app_name,
+            // Capped by `apply_field_limits` before the event is stored
             window_title,
             url: None,
             data: serde_json::json!({}),
@@ -69,33 +127,125 @@ impl Event {
             browser_tab: None,
             messages: None,
             screenshot_path: None,
+            thumbnail_path: None,
             system_metrics: None,
             typed_text: None,
         }
     }
 
+    /// Minimal compact-mode event: app, category and timestamp only
+    pub fn compact(event_type: &str, app_name: &str, category: &str) -> Self {
+        Self::new(event_type, Some(app_name.to_string()), None).with_category(category)
+    }
+
     pub fn with_category(mut self, category: &str) -> Self {
         self.category = Some(category.to_string());
         self
     }
+
+    /// Cap the window title and URLs at `limits`
+    /// Cut fields are listed in `data.truncated_fields`; large `data:`/`blob:` URLs
+    /// are dropped and flagged with `data.url_skipped`
+    pub fn apply_field_limits(&mut self, limits: &FieldLimits) {
+        let mut truncated = Vec::new();
+        let mut url_skipped = false;
+
+        if let Some(title) = self.window_title.take() {
+            let (title, cut) = cap_chars(title, limits.max_title_chars);
+            if cut {
+                truncated.push("window_title");
+            }
+            self.window_title = Some(title);
+        }
+
+        if let Some(url) = self.url.take() {
+            match url_to_store(url, limits.max_url_chars) {
+                Some((url, cut)) => {
+                    if cut {
+                        truncated.push("url");
+                    }
+                    self.url = Some(url);
+                }
+                None => url_skipped = true,
+            }
+        }
+
+        if let Some(tab) = self.browser_tab.as_mut() {
+            let (title, cut) = cap_chars(std::mem::take(&mut tab.title), limits.max_title_chars);
+            if cut {
+                truncated.push("browser_tab.title");
+            }
+            tab.title = title;
+
+            match url_to_store(std::mem::take(&mut tab.url), limits.max_url_chars) {
+                Some((url, cut)) => {
+                    if cut {
+                        truncated.push("browser_tab.url");
+                    }
+                    tab.url = url;
+                }
+                None => url_skipped = true,
+            }
+        }
+
+        if truncated.is_empty() && !url_skipped {
+            return;
+        }
+        if !self.data.is_object() {
+            self.data = serde_json::json!({});
+        }
+        if !truncated.is_empty() {
+            self.data["truncated_fields"] = serde_json::json!(truncated);
+        }
+        if url_skipped {
+            self.data["url_skipped"] = serde_json::json!(true);
+        }
+    }
 }
 
-fn get_device_id() -> String {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("observer");
+/// Default cap on stored URLs (characters)
+pub const DEFAULT_MAX_URL_CHARS: usize = 2_048;
+
+/// `data:`/`blob:` URLs longer than this are not stored at all (characters)
+pub const MAX_INLINE_URL_CHARS: usize = 256;
+
+/// URL schemes that embed content instead of pointing at a page
+const INLINE_URL_SCHEMES: &[&str] = &["data:", "blob:"];
 
-    std::fs::create_dir_all(&config_dir).ok();
+/// Length caps for free-form event fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLimits {
+    pub max_title_chars: usize,
+    pub max_url_chars: usize,
+}
 
-    let id_file = config_dir.join("device_id");
+impl Default for FieldLimits {
+    fn default() -> Self {
+        Self {
+            max_title_chars: crate::text::MAX_TITLE_CHARS,
+            max_url_chars: DEFAULT_MAX_URL_CHARS,
+        }
+    }
+}
+
+/// `s` capped at `max` characters, and whether anything was cut
+fn cap_chars(s: String, max: usize) -> (String, bool) {
+    let cut = s.chars().nth(max).is_some();
+    (crate::text::truncate_string(s, max), cut)
+}
 
-    if let Ok(id) = std::fs::read_to_string(&id_file) {
-        return id.trim().to_string();
+/// URL to store: None for large inline (`data:`/`blob:`) URLs, otherwise the
+/// URL capped at `max_chars` and whether it was cut
+pub fn url_to_store(url: String, max_chars: usize) -> Option<(String, bool)> {
+    let inline = INLINE_URL_SCHEMES.iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    });
+    if inline && url.chars().nth(MAX_INLINE_URL_CHARS).is_some() {
+        return None;
     }
 
-    let new_id = Uuid::new_v4().to_string();
-    std::fs::write(&id_file, &new_id).ok();
-    new_id
+    Some(cap_chars(url, max_chars))
 }
 
 /// Check if app is a browser
@@ -111,18 +261,47 @@ fn is_browser(app_name: &str) -> bool {
         || app_lower.contains("vivaldi")
 }
 
+/// Default cap on stored selected text (characters)
+pub const DEFAULT_MAX_SELECTED_TEXT_CHARS: usize = 2_000;
+
+/// Selected text to store on an event: None when capture is off or nothing is
+/// selected, truncated to `max_chars` otherwise
+pub fn selected_text_to_store(selected: Option<&str>, capture: bool, max_chars: usize) -> Option<String> {
+    if !capture {
+        return None;
+    }
+
+    selected
+        .filter(|text| !text.is_empty())
+        .map(|text| crate::text::truncate_chars(text, max_chars).to_string())
+}
+
 /// Get the currently focused application and window information
+/// The selection is only read when the saved settings allow it
 pub fn get_current_focus() -> Option<FocusInfo> {
+    let capture_selected_text = crate::commands::get_settings()
+        .map(|settings| settings.capture_selected_text)
+        .unwrap_or(false);
+    current_focus(capture_selected_text)
+}
+
+/// Focused application and window; the selection is read only with `capture_selected_text`
+pub fn current_focus(capture_selected_text: bool) -> Option<FocusInfo> {
     // First try accessibility API for detailed info
     if has_accessibility_permission() {
         if let Some((app_name, window_title)) = get_focused_element_info() {
-            let selected_text = get_selected_text();
+            // Don't even read the selection when the user opted out
+            let selected_text = if capture_selected_text {
+                get_selected_text()
+            } else {
+                None
+            };
 
             // Get URL using AppleScript for browsers
             let url = if is_browser(&app_name) {
                 let browser_monitor = browser::BrowserMonitor::new();
                 browser_monitor.get_active_tab(&app_name).map(|tab| {
-                    println!("[Browser] {} | {}", app_name, tab.url);
+                    crate::logs::debug(&format!("[Browser] {} | {}", app_name, tab.url));
                     tab.url
                 })
             } else {
@@ -152,9 +331,49 @@ pub fn get_current_focus() -> Option<FocusInfo> {
     None
 }
 
+/// Names of the permissions the collector needs but doesn't have
+fn missing_permissions() -> Vec<&'static str> {
+    #[cfg(target_os = "macos")]
+    {
+        use crate::permissions::{check_permission, PermissionType};
+
+        let mut missing = Vec::new();
+        if !has_accessibility_permission() {
+            missing.push("Accessibility");
+        }
+        if !check_permission(PermissionType::ScreenRecording).granted {
+            missing.push("Screen Recording");
+        }
+        missing
+    }
+
+    // Nothing to grant on other platforms
+    #[cfg(not(target_os = "macos"))]
+    {
+        Vec::new()
+    }
+}
+
+/// How long the collector waits for accessibility permission before starting anyway
+#[cfg(target_os = "macos")]
+const ACCESSIBILITY_WAIT_SECS: u64 = 60;
+
+/// Screenshot manager shared by the collector's capture workers and manual captures,
+/// so they see one hash index and one last-capture time
+pub type SharedScreenshotManager = Arc<std::sync::Mutex<ScreenshotManager>>;
+
+/// Manager for the collector, started from the saved screenshot settings
+pub fn new_screenshot_manager() -> SharedScreenshotManager {
+    let settings = crate::commands::get_settings().unwrap_or_default();
+    let config = settings.screenshot_config(ScreenshotConfig::default());
+    Arc::new(std::sync::Mutex::new(ScreenshotManager::new(config)))
+}
+
 pub async fn start_collector(
     state: Arc<Mutex<AppState>>,
-    _app_handle: AppHandle,
+    screenshot_manager: SharedScreenshotManager,
+    mut power_rx: tokio::sync::broadcast::Receiver<PowerEvent>,
+    app_handle: AppHandle,
     shutdown_token: CancellationToken,
 ) {
     let mut last_app: Option<String> = None;
@@ -162,12 +381,29 @@ pub async fn start_collector(
     let mut last_typed_text: Option<String> = None;
 
     // Initialize collectors
-    let metrics_collector = SystemMetricsCollector::new();
-    let mut screenshot_manager = ScreenshotManager::new(ScreenshotConfig::default());
-    let messenger_monitor = messenger::MessengerMonitor::new();
+    let settings = crate::commands::get_settings().unwrap_or_default();
+    let metrics_collector = SystemMetricsCollector::new()
+        .with_smoothing(settings.metrics_smoothing_alpha)
+        .with_cache(std::time::Duration::from_secs(settings.metrics_interval_secs));
+    let mut screenshot_config = screenshots::watch_live_config(settings.screenshot_config(ScreenshotConfig::default()));
+    let initial_screenshot_config = screenshot_config.borrow_and_update().clone();
+    let mut frame_capturer = FrameCapturer::new(initial_screenshot_config.clone());
+    screenshot_manager.lock().unwrap().set_config(initial_screenshot_config);
+    let capture_queue = Arc::new(capture_worker::CaptureQueue::new(settings.screenshot_queue_size));
+    capture_worker::spawn_workers(
+        settings.screenshot_workers,
+        capture_queue.clone(),
+        state.clone(),
+        screenshot_manager.clone(),
+    );
+    let _close_capture_queue = capture_worker::CloseOnDrop(capture_queue.clone());
+    let messenger_monitor = Arc::new(messenger::MessengerMonitor::new());
     let browser_monitor = browser::BrowserMonitor::new();
+    let mut url_dedup =
+        browser::UrlDedup::new(std::time::Duration::from_secs(settings.url_dedup_secs));
+    let mut idle_tracker = idle::IdleTracker::new();
 
-    println!("[Collector] Initialized: ScreenshotManager, MessengerMonitor, BrowserMonitor");
+    crate::logs::info("[Collector] Initialized: ScreenshotManager, MessengerMonitor, BrowserMonitor");
 
     // Request permissions on start
     #[cfg(target_os = "macos")]
@@ -175,12 +411,22 @@ pub async fn start_collector(
         use crate::automation::{request_accessibility, request_screen_recording};
 
         if !has_accessibility_permission() {
-            println!("[Permissions] Requesting accessibility permission...");
+            crate::logs::info("[Permissions] Requesting accessibility permission...");
             let granted = request_accessibility();
             if granted {
-                println!("[Permissions] Accessibility permission granted!");
+                crate::logs::info("[Permissions] Accessibility permission granted!");
             } else {
-                eprintln!("[Permissions] Warning: Accessibility permission not granted.");
+                // Give the user time to respond to the system dialog before collecting
+                crate::logs::info("[Permissions] Waiting for accessibility permission...");
+                let granted = crate::startup::wait_until(
+                    has_accessibility_permission,
+                    crate::startup::GATE_POLL_INTERVAL,
+                    tokio::time::Duration::from_secs(ACCESSIBILITY_WAIT_SECS),
+                )
+                .await;
+                if !granted {
+                    crate::logs::error("[Permissions] Warning: Accessibility permission not granted.");
+                }
             }
         }
 
@@ -188,60 +434,227 @@ pub async fn start_collector(
         request_screen_recording();
     }
 
-    println!("[Collector] Started. Waiting for events...");
+    let mut ax_watch = permission_watch::AccessibilityWatch::new(
+        has_accessibility_permission(),
+        permission_watch::ACCESSIBILITY_CHECK_INTERVAL,
+    );
+    let mut permission_gate = permission_watch::PermissionGate::new(
+        settings.permission_denied_policy,
+        permission_watch::ACCESSIBILITY_CHECK_INTERVAL,
+        permission_watch::PERMISSION_REMINDER_INTERVAL,
+    );
+
+    // Opt-in global typed text, switched on earlier by the user
+    if settings.global_typed_text {
+        if let Err(e) = keystrokes::set_enabled(&app_handle, true) {
+            crate::logs::error(&format!("[TypedText] {}", e));
+        }
+    }
+
+    crate::logs::info("[Collector] Started. Waiting for events...");
 
     loop {
         tokio::select! {
             _ = shutdown_token.cancelled() => {
-                println!("[Collector] Shutdown signal received. Flushing events...");
+                crate::logs::info("[Collector] Shutdown signal received. Flushing events...");
+                capture_queue.close();
+                if let Some(typed) = keystrokes::take() {
+                    push_event(&mut *state.lock().await, typed.into_event());
+                }
                 flush_events(&state).await;
-                println!("[Collector] Shutdown complete.");
+                crate::logs::info("[Collector] Shutdown complete.");
                 break;
             }
+            Ok(power_event) = power_rx.recv() => {
+                // Record sleep/wake even while paused so the gap is explained
+                crate::logs::info(&format!("[Power] {}", power_event.event_type()));
+                let event = Event::new(power_event.event_type(), None, None)
+                    .with_category("system");
+
+                let mut state = state.lock().await;
+                push_event(&mut state, event);
+
+                // Force a fresh focus event after waking up
+                if power_event == PowerEvent::Wake {
+                    last_app = None;
+                    last_title = None;
+                }
+            }
             _ = tokio::time::sleep(tokio::time::Duration::from_millis(500)) => {
-                println!("[Loop] Tick - checking focus...");
+                crate::logs::debug("[Loop] Tick - checking focus...");
+                crate::supervisor::heartbeat(crate::supervisor::COLLECTOR_TASK);
+                // Read once; everything below in this tick uses this copy
+                let tick_settings = crate::commands::get_settings().unwrap_or_default();
 
                 // Check if collection is enabled
                 {
-                    let state = state.lock().await;
+                    let mut state = state.lock().await;
                     if !state.collecting {
+                        // Stop recording typed text while paused
+                        store_typed_text(&mut state, None, false, &tick_settings);
                         continue;
                     }
                 }
 
-                // Get current focus
-                let focus_info = get_current_focus();
+                // Apply the permission-denied policy (no-op for "warn")
+                match permission_gate.poll(std::time::Instant::now(), missing_permissions) {
+                    Some(permission_watch::GateEvent::Notify(missing)) => {
+                        crate::logs::error(&format!(
+                            "[Permissions] Missing: {}{}",
+                            missing.join(", "),
+                            if permission_gate.collection_paused() { " - collection paused" } else { "" }
+                        ));
+                        for name in missing {
+                            let _ = crate::notifications::notify_permission_required(&app_handle, name);
+                        }
+                    }
+                    Some(permission_watch::GateEvent::Granted) => {
+                        crate::logs::info("[Permissions] All permissions granted, resuming full collection");
+                        if let Some(change) = ax_watch.record(has_accessibility_permission()) {
+                            let event = Event::new(change.event_type(), None, None).with_category("system");
+                            let mut state = state.lock().await;
+                            push_event_with(&mut state, event, &tick_settings);
+                        }
+                    }
+                    None => {}
+                }
+                if permission_gate.collection_paused() {
+                    store_typed_text(&mut *state.lock().await, None, false, &tick_settings);
+                    continue;
+                }
 
-                let (current_app, current_title) = if let Some(ref info) = focus_info {
-                    (Some(info.app_name.clone()), Some(info.window_title.clone()))
-                } else {
-                    apps::get_active_window()
-                };
+                // Detect accessibility permission being revoked/restored mid-session
+                if let Some(change) = ax_watch.poll(std::time::Instant::now(), has_accessibility_permission) {
+                    crate::logs::error(&format!("[Permissions] Accessibility {}", change.event_type()));
+                    let event = Event::new(change.event_type(), None, None).with_category("system");
+                    {
+                        let mut state = state.lock().await;
+                        push_event_with(&mut state, event, &tick_settings);
+                    }
+
+                    if change == permission_watch::PermissionChange::Lost {
+                        let _ = crate::notifications::notify_permission_required(&app_handle, "Accessibility");
+                    }
+                }
+                if crate::native_applescript::take_collectors_disabled() {
+                    let _ = crate::notifications::notify_applescript_disabled(&app_handle);
+                }
+                let ax_available = ax_watch.use_accessibility();
+                let compact_mode = tick_settings.compact_mode;
+
+                // Nobody at the keyboard: one idle_start, then no focus events until input resumes
+                // The threshold follows the category of the app focused last
+                let idle_threshold = idle::threshold_for(
+                    last_app.as_deref().map(categorize_app).as_deref(),
+                    &tick_settings.idle_thresholds_by_category,
+                    tick_settings.idle_threshold_seconds,
+                );
+                if let Some(transition) = idle_tracker.poll_system(idle_threshold) {
+                    crate::logs::info(&format!("[Idle] {}", transition.event_type()));
+                    let mut event = Event::new(transition.event_type(), None, None).with_category("system");
+                    match transition {
+                        // Backdated to the last input so the idle gap starts where it really began
+                        idle::IdleTransition::Start { idle_for } => {
+                            event.timestamp -= chrono::Duration::from_std(idle_for).unwrap_or_default();
+                        }
+                        // Force a fresh focus event once the user is back
+                        idle::IdleTransition::End => {
+                            last_app = None;
+                            last_title = None;
+                        }
+                    }
+                    push_event_with(&mut *state.lock().await, event, &tick_settings);
+                }
+                if idle_tracker.is_idle() {
+                    store_typed_text(&mut *state.lock().await, None, false, &tick_settings);
+                    continue;
+                }
+                let toggles = collector_toggles();
+                if screenshot_config.has_changed().unwrap_or(false) {
+                    let config = screenshot_config.borrow_and_update().clone();
+                    frame_capturer.set_config(config.clone());
+                    screenshot_manager.lock().unwrap().set_config(config);
+                    crate::logs::info("[Screenshot] Config updated");
+                }
+
+                // Get current focus (basic window info only without accessibility or in compact mode)
+                let (focus_info, (current_app, current_title)) = timings::timed(CollectorStep::Focus, || {
+                    let focus_info = if ax_available && !compact_mode {
+                        current_focus(tick_settings.capture_selected_text)
+                    } else {
+                        None
+                    };
+                    let window = if let Some(ref info) = focus_info {
+                        (Some(info.app_name.clone()), Some(info.window_title.clone()))
+                    } else {
+                        apps::get_active_window()
+                    };
+                    (focus_info, window)
+                });
+
+                // === GLOBAL TYPED TEXT (opt-in) ===
+                // Retargeted before the system app filter so login and auth dialogs stop recording too
+                if keystrokes::is_enabled() {
+                    let app = current_app.as_deref().filter(|_| ax_available && !compact_mode);
+                    let secure_field = app.is_some() && is_focused_element_secure();
+                    store_typed_text(&mut *state.lock().await, app, secure_field, &tick_settings);
+                }
 
                 // Check if there's a change
-                if current_app != last_app || current_title != last_title {
-                    if let Some(ref app_name) = current_app {
+                // System processes (Dock, login window, helpers) briefly taking focus are
+                // skipped, and title churn in terminals/editors isn't a focus change
+                let transition = match app_filter::read_focus(
+                    &tick_settings.system_app_filter,
+                    &tick_settings.title_churn_apps,
+                    (last_app.as_deref(), last_title.as_deref()),
+                    (current_app.as_deref(), current_title.as_deref()),
+                ) {
+                    app_filter::FocusReading::Filtered => continue,
+                    app_filter::FocusReading::Unchanged => None,
+                    app_filter::FocusReading::Changed(transition) => Some(transition),
+                };
+                if let Some(transition) = transition {
+                    if transition == FocusTransition::AppSwitch && last_app.is_some() {
+                        crate::focus::record_switch(Utc::now());
+                    }
+                    if compact_mode {
+                        // Title changes would repeat the same minimal event
+                        if let (FocusTransition::AppSwitch, Some(app_name)) = (transition, current_app.as_deref()) {
+                            crate::logs::info(&format!("[Focus] {} (compact)", app_name));
+                            let event = Event::compact(transition.event_type(), app_name, &categorize_app(app_name));
+                            let mut state = state.lock().await;
+                            push_event_with(&mut state, event, &tick_settings);
+                        }
+                    } else if let Some(ref app_name) = current_app {
                         let window_title = current_title.clone().unwrap_or_default();
 
                         // === DEBUG LOG: Focus Change ===
-                        println!("[Focus] {} | {}", app_name, window_title);
+                        crate::logs::info(&format!(
+                            "[Focus] {} | {} ({})",
+                            app_name,
+                            window_title,
+                            transition.event_type()
+                        ));
 
                         let mut event = Event::new(
-                            "app_focus",
+                            transition.event_type(),
                             current_app.clone(),
                             current_title.clone(),
                         )
-                        .with_category(categorize_app(app_name));
+                        .with_category(&categorize_app(app_name));
 
                         // === SYSTEM METRICS ===
-                        if let Ok(metrics) = metrics_collector.collect() {
-                            event.system_metrics = Some(metrics);
+                        if toggles.metrics {
+                            if let Ok(metrics) = timings::timed(CollectorStep::Metrics, || metrics_collector.collect()) {
+                                event.system_metrics = Some(metrics);
+                            }
                         }
 
                         // === BROWSER URL ===
-                        if is_browser(app_name) {
-                            if let Some(tab) = browser_monitor.get_active_tab(app_name) {
-                                println!("[Browser] {} | {}", app_name, tab.url);
+                        if toggles.browser_tab && is_browser(app_name) {
+                            let tab = timings::timed(CollectorStep::BrowserTab, || browser_monitor.get_active_tab(app_name));
+                            if let Some(tab) = tab {
+                                crate::logs::debug(&format!("[Browser] {} | {}", app_name, tab.url));
                                 event.url = Some(tab.url.clone());
                                 event.browser_tab = Some(tab);
                             }
@@ -256,47 +669,65 @@ pub async fn start_collector(
 
                         // Add selected text to event data
                         if let Some(ref info) = focus_info {
-                            if let Some(ref selected) = info.selected_text {
-                                if !selected.is_empty() {
-                                    event.data = serde_json::json!({
-                                        "selected_text": selected
-                                    });
+                            if let Some(selected) = selected_text_to_store(
+                                info.selected_text.as_deref(),
+                                tick_settings.capture_selected_text,
+                                tick_settings.max_selected_text_chars,
+                            ) {
+                                event.data = serde_json::json!({
+                                    "selected_text": selected
+                                });
+                            }
+                        }
+
+                        // Site domain for the dashboard, and its icon fetched in the background (opt-in)
+                        if tick_settings.favicons && is_browser(app_name) {
+                            if let Some(domain) = event.url.as_deref().and_then(favicons::prefetch) {
+                                if let Some(data) = event.data.as_object_mut() {
+                                    data.insert("domain".to_string(), serde_json::Value::String(domain));
                                 }
                             }
                         }
 
+                        // Same browser tab as the last stored event (e.g. only the title changed)
+                        // Any other app in between means the tab is visited again
+                        let unchanged_tab = match event.url {
+                            Some(ref url) if is_browser(app_name) => {
+                                !url_dedup.should_store(app_name, url, std::time::Instant::now())
+                            }
+                            _ => {
+                                url_dedup.reset();
+                                false
+                            }
+                        };
+
                         // === SCREENSHOT CAPTURE ===
-                        if let Some(screenshot) = screenshot_manager.maybe_capture(
-                            app_name.clone(),
-                            window_title.clone(),
-                        ).await {
-                            let path_str = screenshot.path.to_string_lossy().to_string();
-                            println!("[Screenshot] Saved: {}", path_str);
-                            event.screenshot_path = Some(path_str.clone());
-
-                            // === OCR DISABLED - blocks entire app (compiles Swift synchronously 5-30 sec) ===
-                            // #[cfg(target_os = "macos")]
-                            // {
-                            //     match crate::automation::ocr::extract_text_from_path(&path_str) {
-                            //         Ok(ocr_result) => {
-                            //             println!("[OCR] Extracted {} chars", ocr_result.text.len());
-                            //             if let serde_json::Value::Object(ref mut map) = event.data {
-                            //                 map.insert("ocr_text".to_string(), serde_json::json!(ocr_result.text));
-                            //             }
-                            //         }
-                            //         Err(e) => {
-                            //             eprintln!("[OCR] Error: {}", e);
-                            //         }
-                            //     }
-                            // }
-                        }
+                        let capture_allowed = screenshots::category_allows_capture(
+                            event.category.as_deref(),
+                            &tick_settings.screenshot_categories,
+                        );
+                        // Only the frame is grabbed here; a worker stores (and OCRs) it once the event is saved
+                        let frame = if toggles.screenshots && capture_allowed && !unchanged_tab {
+                            frame_capturer.capture_frame(transition).await
+                        } else {
+                            None
+                        };
 
                         // === MESSENGER MESSAGES ===
-                        if messenger_monitor.is_messenger(app_name) {
-                            if let Some(msg_state) = messenger_monitor.get_visible_messages(app_name) {
+                        if toggles.messages && ax_available && messenger_monitor.is_messenger(app_name) {
+                            // AX traversal is blocking - keep it off the async collector loop
+                            let monitor = messenger_monitor.clone();
+                            let messenger_app = app_name.clone();
+                            let msg_state = tokio::task::spawn_blocking(move || {
+                                timings::timed(CollectorStep::Messages, || monitor.get_visible_messages(&messenger_app))
+                            })
+                            .await
+                            .ok()
+                            .flatten();
+                            if let Some(msg_state) = msg_state {
                                 let msg_count = msg_state.visible_messages.len();
                                 if msg_count > 0 {
-                                    println!("[Messenger] {} messages from {}", msg_count, app_name);
+                                    crate::logs::info(&format!("[Messenger] {} messages from {}", msg_count, app_name));
                                     event.messages = Some(msg_state.visible_messages);
 
                                     // Store chat name in data
@@ -310,7 +741,7 @@ pub async fn start_collector(
                         }
 
                         // === BROWSER INPUT ===
-                        if is_browser(app_name) {
+                        if ax_available && is_browser(app_name) {
                             if let Some((url, typed_text)) = get_browser_input() {
                                 if Some(&typed_text) != last_typed_text.as_ref() {
                                     event.typed_text = Some(typed_text.clone());
@@ -326,52 +757,42 @@ pub async fn start_collector(
                             last_typed_text = None;
                         }
 
-                        // === SAVE TO DATABASE AND BUFFER ===
-                        let mut state = state.lock().await;
-
-                        let buffer_size = state.events_buffer.len();
-
-                        // Check buffer capacity
-                        if buffer_size >= crate::MAX_BUFFER_SIZE {
-                            state.events_buffer.remove(0);
-                            eprintln!("[Buffer] Warning: Full ({} events). Dropping oldest.", crate::MAX_BUFFER_SIZE);
-                        }
-
-                        if buffer_size >= crate::BUFFER_WARNING_THRESHOLD && !state.buffer_warnings_logged {
-                            eprintln!(
-                                "[Buffer] Warning: {}% full ({}/{} events)",
-                                (buffer_size * 100) / crate::MAX_BUFFER_SIZE,
-                                buffer_size,
-                                crate::MAX_BUFFER_SIZE
-                            );
-                            state.buffer_warnings_logged = true;
-                        }
-
-                        if buffer_size < crate::BUFFER_WARNING_THRESHOLD / 2 {
-                            state.buffer_warnings_logged = false;
+                        // Server category from an earlier batch (opt-in)
+                        if tick_settings.server_categorization {
+                            if let Some(category) = categorizer::cached_category(&event) {
+                                event.category = Some(category);
+                            }
                         }
 
-                        // Persist to database
-                        match state.db.insert_event(&event) {
-                            Ok(_) => {
-                                println!("[DB] Event saved: {} | {} | {}",
-                                    event.id,
-                                    event.app_name.as_deref().unwrap_or("?"),
-                                    event.url.as_deref().unwrap_or("-")
-                                );
-                            }
-                            Err(e) => {
-                                eprintln!("[DB] Error: Failed to save event: {}", e);
+                        // === SAVE TO DATABASE AND BUFFER ===
+                        // Typed text is new content, so an unchanged tab with input is still kept
+                        let redundant = unchanged_tab && event.typed_text.is_none();
+                        if redundant {
+                            crate::logs::debug(&format!("[Browser] Unchanged tab, skipping: {}", event.url.as_deref().unwrap_or("-")));
+                        } else {
+                            let mut state = state.lock().await;
+
+                            // Hand the frame to a capture worker; it attaches the file once the state lock is released
+                            if let Some(image) = frame {
+                                let job = capture_worker::CaptureJob {
+                                    event_id: event.id.clone(),
+                                    image,
+                                    app_name: app_name.clone(),
+                                    window_title: window_title.clone(),
+                                };
+                                // Held back from sync until the worker has attached the screenshot
+                                state.pending_captures.insert(event.id.clone());
+                                if let Some(dropped) = capture_queue.push(job) {
+                                    state.pending_captures.remove(&dropped.event_id);
+                                    crate::logs::info(&format!(
+                                        "[Screenshot] Workers behind, dropped oldest capture ({} so far)",
+                                        capture_queue.dropped()
+                                    ));
+                                }
                             }
-                        }
 
-                        // Update top_apps_cache
-                        if let Some(ref app_name) = event.app_name {
-                            *state.top_apps_cache.entry(app_name.clone()).or_insert(0) += 1;
+                            push_event_with(&mut state, event, &tick_settings);
                         }
-
-                        state.events_buffer.push(event);
-                        state.events_today += 1;
                     }
 
                     last_app = current_app.clone();
@@ -379,10 +800,10 @@ pub async fn start_collector(
                 } else {
                     // No focus change, but check for browser input changes
                     if let Some(ref app_name) = current_app {
-                        if is_browser(app_name) {
+                        if ax_available && !compact_mode && is_browser(app_name) {
                             if let Some((url, typed_text)) = get_browser_input() {
                                 if Some(&typed_text) != last_typed_text.as_ref() && !typed_text.is_empty() {
-                                    println!("[BrowserInput] {} | {}", app_name, typed_text);
+                                    crate::logs::debug(&format!("[BrowserInput] {} | {}", app_name, typed_text));
 
                                     let mut event = Event::new(
                                         "browser_input",
@@ -391,33 +812,17 @@ pub async fn start_collector(
                                     )
                                     .with_category("browsing");
 
-                                    if let Ok(metrics) = metrics_collector.collect() {
-                                        event.system_metrics = Some(metrics);
+                                    if toggles.metrics {
+                                        if let Ok(metrics) = timings::timed(CollectorStep::Metrics, || metrics_collector.collect()) {
+                                            event.system_metrics = Some(metrics);
+                                        }
                                     }
 
                                     event.typed_text = Some(typed_text.clone());
                                     event.url = url;
                                     last_typed_text = Some(typed_text);
 
-                                    let mut state = state.lock().await;
-
-                                    let buffer_size = state.events_buffer.len();
-
-                                    if buffer_size >= crate::MAX_BUFFER_SIZE {
-                                        state.events_buffer.remove(0);
-                                    }
-
-                                    match state.db.insert_event(&event) {
-                                        Ok(_) => println!("[DB] Browser input saved: {}", event.id),
-                                        Err(e) => eprintln!("[DB] Error: {}", e),
-                                    }
-
-                                    if let Some(ref app_name) = event.app_name {
-                                        *state.top_apps_cache.entry(app_name.clone()).or_insert(0) += 1;
-                                    }
-
-                                    state.events_buffer.push(event);
-                                    state.events_today += 1;
+                                    push_event_with(&mut *state.lock().await, event, &tick_settings);
                                 }
                             } else if last_typed_text.is_some() {
                                 last_typed_text = None;
@@ -430,17 +835,255 @@ pub async fn start_collector(
     }
 }
 
+/// Kind of focus change between two collector ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusTransition {
+    /// Switched to a different app
+    AppSwitch,
+    /// Stayed in the same app, but the window title changed
+    TitleChange,
+}
+
+impl FocusTransition {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            FocusTransition::AppSwitch => "app_focus",
+            FocusTransition::TitleChange => "window_title_changed",
+        }
+    }
+}
+
+/// Classify the change from the previous app/title to the current one
+/// Returns None when nothing changed
+pub fn classify_transition(
+    last_app: Option<&str>,
+    last_title: Option<&str>,
+    current_app: Option<&str>,
+    current_title: Option<&str>,
+) -> Option<FocusTransition> {
+    if current_app != last_app {
+        Some(FocusTransition::AppSwitch)
+    } else if current_title != last_title {
+        Some(FocusTransition::TitleChange)
+    } else {
+        None
+    }
+}
+
+/// Persist an event and append it to the in-memory buffer
+/// Drops the oldest buffered event when the buffer is full and updates the stats caches
+pub fn push_event(state: &mut AppState, event: Event) {
+    let settings = crate::commands::get_settings().unwrap_or_default();
+    push_event_with(state, event, &settings);
+}
+
+/// `push_event` with settings the caller already read (once per collector tick)
+/// Every collected event is inserted here, and field limits are applied here only
+pub fn push_event_with(state: &mut AppState, mut event: Event, settings: &AppSettings) {
+    event.apply_field_limits(&settings.field_limits());
+
+    let buffer_size = state.events_buffer.len();
+    if buffer_size >= crate::MAX_BUFFER_SIZE {
+        state.events_buffer.remove(0);
+        crate::logs::error(&format!("[Buffer] Warning: Full ({} events). Dropping oldest.", crate::MAX_BUFFER_SIZE));
+    }
+    if buffer_size >= crate::BUFFER_WARNING_THRESHOLD && !state.buffer_warnings_logged {
+        crate::logs::error(&format!(
+            "[Buffer] Warning: {}% full ({}/{} events)",
+            (buffer_size * 100) / crate::MAX_BUFFER_SIZE,
+            buffer_size,
+            crate::MAX_BUFFER_SIZE
+        ));
+        state.buffer_warnings_logged = true;
+    }
+    if buffer_size < crate::BUFFER_WARNING_THRESHOLD / 2 {
+        state.buffer_warnings_logged = false;
+    }
+
+    match state.db.insert_event(&event) {
+        Ok(_) => crate::logs::debug(&format!(
+            "[DB] Event saved: {} | {} | {}",
+            event.id,
+            event.app_name.as_deref().unwrap_or("?"),
+            event.url.as_deref().unwrap_or("-")
+        )),
+        Err(e) => crate::logs::error(&format!("[DB] Error: Failed to save event: {}", e)),
+    }
+
+    if let Some(ref app_name) = event.app_name {
+        *state.top_apps_cache.entry(app_name.clone()).or_insert(0) += 1;
+    }
+
+    crate::event_hooks::dispatch(&settings.event_hooks, &event);
+
+    state.events_buffer.push(event);
+    state.events_today += 1;
+}
+
+/// Point global typed-text capture at `app_name` and store the text finished in the previous app
+fn store_typed_text(state: &mut AppState, app_name: Option<&str>, secure_field: bool, settings: &AppSettings) {
+    if let Some(typed) = keystrokes::focus(app_name, secure_field, &settings.typed_text_denylist) {
+        crate::logs::info(&format!("[TypedText] {} ({} chars)", typed.app_name, typed.text.chars().count()));
+        push_event_with(state, typed.into_event(), settings);
+    }
+}
+
+/// Find an event by ID in the buffer, falling back to the database
+pub fn find_event(state: &AppState, event_id: &str) -> Result<Option<Event>, String> {
+    if let Some(event) = state.events_buffer.iter().find(|event| event.id == event_id) {
+        return Ok(Some(event.clone()));
+    }
+
+    state
+        .db
+        .get_event(event_id)
+        .map_err(|e| format!("Failed to load event: {}", e))
+}
+
+/// Buffered events in `category` (case-insensitive) and not older than `since`
+pub fn buffered_matching(state: &AppState, category: Option<&str>, since: Option<DateTime<Utc>>) -> Vec<Event> {
+    state
+        .events_buffer
+        .iter()
+        .filter(|event| {
+            category.map_or(true, |category| {
+                event.category.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(category))
+            }) && since.map_or(true, |since| event.timestamp >= since)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Latest `limit` events (at most `db::MAX_QUERY_LIMIT`) in `category` and not
+/// older than `since`, newest first. `buffered` are the matching buffered events
+/// (see `buffered_matching`), merged in case some of them failed to persist.
+pub fn recent_events(
+    db: &crate::db::EventDatabase,
+    buffered: Vec<Event>,
+    limit: usize,
+    category: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<Event>, String> {
+    let limit = limit.min(crate::db::MAX_QUERY_LIMIT);
+    let query = crate::db::EventQuery {
+        from: since,
+        category: category.map(str::to_string),
+        limit: Some(limit),
+        ..Default::default()
+    };
+    let stored = db
+        .query_events(&query)
+        .map_err(|e| format!("Failed to load events: {}", e))?;
+
+    let mut events = buffered;
+    let buffered_ids: std::collections::HashSet<String> = events.iter().map(|e| e.id.clone()).collect();
+    events.extend(stored.into_iter().filter(|e| !buffered_ids.contains(&e.id)));
+    events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    events.truncate(limit);
+    Ok(events)
+}
+
+/// Delete an event from the buffer and the database, along with its screenshot
+/// unless another event shares it. Returns false if no such event exists.
+pub fn delete_event(state: &mut AppState, event_id: &str) -> Result<bool, String> {
+    let buffered = state
+        .events_buffer
+        .iter()
+        .position(|event| event.id == event_id)
+        .map(|index| state.events_buffer.remove(index));
+    let event = match buffered {
+        Some(event) => Some(event),
+        None => state
+            .db
+            .get_event(event_id)
+            .map_err(|e| format!("Failed to load event: {}", e))?,
+    };
+
+    let deleted = state
+        .db
+        .delete_events(&[event_id.to_string()])
+        .map_err(|e| format!("Failed to delete event: {}", e))?;
+
+    let Some(event) = event else {
+        return Ok(deleted > 0);
+    };
+
+    if let Some(ref app_name) = event.app_name {
+        if let Some(count) = state.top_apps_cache.get_mut(app_name) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.top_apps_cache.remove(app_name);
+            }
+        }
+    }
+    // Only today's events are in the daily counter
+    if event.timestamp.with_timezone(&chrono::Local).date_naive() == state.counting_day {
+        state.events_today = state.events_today.saturating_sub(1);
+    }
+
+    if let Some(ref path) = event.screenshot_path {
+        let shared = state
+            .events_buffer
+            .iter()
+            .any(|other| other.screenshot_path.as_ref() == Some(path))
+            || state
+                .db
+                .screenshot_references(path)
+                .map_err(|e| format!("Failed to check screenshot references: {}", e))?
+                > 0;
+        if !shared {
+            screenshots::delete_screenshot(std::path::Path::new(path))?;
+        }
+    }
+
+    Ok(true)
+}
+
 /// Flush all remaining events in the buffer
 async fn flush_events(state: &Arc<Mutex<AppState>>) {
     let state = state.lock().await;
     let event_count = state.events_buffer.len();
 
     if event_count > 0 {
-        println!("[Collector] Flushing {} remaining events...", event_count);
+        crate::logs::info(&format!("[Collector] Flushing {} remaining events...", event_count));
+    }
+    match persist_buffer(&state) {
+        Ok(0) => {}
+        Ok(written) => crate::logs::info(&format!("[Collector] Saved {} buffered events missing from the database", written)),
+        Err(e) => crate::logs::error(&format!("[DB] Error: {}", e)),
     }
 }
 
-fn categorize_app(app_name: &str) -> &'static str {
+/// Write buffered events that never made it to the database (e.g. a failed insert)
+/// Returns how many were written
+pub fn persist_buffer(state: &AppState) -> Result<usize, String> {
+    let mut written = 0;
+    for event in &state.events_buffer {
+        let stored = state
+            .db
+            .get_event(&event.id)
+            .map_err(|e| format!("Failed to load event: {}", e))?;
+        if stored.is_none() {
+            state
+                .db
+                .insert_event(event)
+                .map_err(|e| format!("Failed to save event: {}", e))?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+fn categorize_app(app_name: &str) -> String {
+    // User corrections take priority over the built-in rules
+    if let Some(category) = categories::user_category(app_name) {
+        return category;
+    }
+
+    builtin_category(app_name).to_string()
+}
+
+fn builtin_category(app_name: &str) -> &'static str {
     let app_lower = app_name.to_lowercase();
 
     if app_lower.contains("code")
@@ -493,3 +1136,257 @@ fn categorize_app(app_name: &str) -> &'static str {
 
     "other"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_text_truncated() {
+        let long = "é".repeat(5_000);
+        let stored = selected_text_to_store(Some(&long), true, 100).unwrap();
+        assert_eq!(stored.chars().count(), 100);
+
+        assert_eq!(
+            selected_text_to_store(Some("short"), true, DEFAULT_MAX_SELECTED_TEXT_CHARS).as_deref(),
+            Some("short")
+        );
+        assert_eq!(selected_text_to_store(Some(""), true, 100), None);
+        assert_eq!(selected_text_to_store(None, true, 100), None);
+    }
+
+    #[test]
+    fn test_selected_text_opt_out() {
+        assert_eq!(selected_text_to_store(Some("secret plan"), false, 100), None);
+    }
+
+    #[test]
+    fn test_long_title_and_url_truncated() {
+        let limits = FieldLimits {
+            max_title_chars: 10,
+            max_url_chars: 30,
+        };
+        let mut event = Event::new("app_focus", Some("Safari".to_string()), Some("日本語".repeat(10)));
+        event.url = Some(format!("https://example.com/{}", "a".repeat(100)));
+        event.apply_field_limits(&limits);
+
+        assert_eq!(event.window_title.as_deref(), Some("日本語日本語日本語日"));
+        assert_eq!(event.url.as_deref().unwrap().chars().count(), 30);
+        assert_eq!(event.data["truncated_fields"], serde_json::json!(["window_title", "url"]));
+        assert!(event.data.get("url_skipped").is_none());
+
+        let mut short = Event::new("app_focus", Some("Safari".to_string()), Some("Home".to_string()));
+        short.url = Some("https://example.com".to_string());
+        short.apply_field_limits(&limits);
+        assert_eq!(short.window_title.as_deref(), Some("Home"));
+        assert_eq!(short.url.as_deref(), Some("https://example.com"));
+        assert_eq!(short.data, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_large_inline_urls_skipped() {
+        let data_uri = format!("data:image/png;base64,{}", "A".repeat(10_000));
+        assert_eq!(url_to_store(data_uri.clone(), DEFAULT_MAX_URL_CHARS), None);
+        let blob = format!("BLOB:https://example.com/{}", "0".repeat(MAX_INLINE_URL_CHARS));
+        assert_eq!(url_to_store(blob, DEFAULT_MAX_URL_CHARS), None);
+
+        // Small inline URLs and long regular URLs are kept
+        assert_eq!(
+            url_to_store("data:text/plain,hi".to_string(), DEFAULT_MAX_URL_CHARS),
+            Some(("data:text/plain,hi".to_string(), false))
+        );
+        let (long, cut) = url_to_store(format!("https://x.io/{}", "é".repeat(5_000)), 100).unwrap();
+        assert!(cut);
+        assert_eq!(long.chars().count(), 100);
+
+        let mut event = Event::new("app_focus", Some("Chrome".to_string()), None);
+        event.url = Some(data_uri);
+        event.apply_field_limits(&FieldLimits::default());
+        assert_eq!(event.url, None);
+        assert_eq!(event.data["url_skipped"], true);
+    }
+
+    #[test]
+    fn test_compact_event_is_minimal() {
+        let event = Event::compact("app_focus", "Google Chrome", "browsing");
+        assert_eq!(event.app_name.as_deref(), Some("Google Chrome"));
+        assert_eq!(event.category.as_deref(), Some("browsing"));
+        assert_eq!(event.event_type, "app_focus");
+
+        let json = serde_json::to_value(&event).unwrap();
+        let populated: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .iter()
+            .filter(|(_, value)| !value.is_null() && **value != serde_json::json!({}))
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(
+            populated,
+            vec!["app_name", "category", "device_id", "event_type", "id", "timestamp"]
+        );
+    }
+
+    #[test]
+    fn test_classify_transition() {
+        // First event after startup
+        assert_eq!(
+            classify_transition(None, None, Some("Code"), Some("main.rs")),
+            Some(FocusTransition::AppSwitch)
+        );
+        // Different app, even with the same title
+        assert_eq!(
+            classify_transition(Some("Code"), Some("main.rs"), Some("Cursor"), Some("main.rs")),
+            Some(FocusTransition::AppSwitch)
+        );
+        // Same browser, navigated to another page
+        assert_eq!(
+            classify_transition(Some("Safari"), Some("Docs"), Some("Safari"), Some("News")),
+            Some(FocusTransition::TitleChange)
+        );
+        // Nothing changed
+        assert_eq!(
+            classify_transition(Some("Safari"), Some("Docs"), Some("Safari"), Some("Docs")),
+            None
+        );
+        assert_eq!(
+            FocusTransition::TitleChange.event_type(),
+            "window_title_changed"
+        );
+    }
+
+    fn event_with_screenshot(app_name: &str, path: &std::path::Path) -> Event {
+        let mut event = Event::new("app_focus", Some(app_name.to_string()), None);
+        event.screenshot_path = Some(path.to_string_lossy().to_string());
+        event
+    }
+
+    #[test]
+    fn test_find_event_returns_full_event() {
+        let mut event = Event::new("app_focus", Some("Telegram".to_string()), Some("Team chat".to_string()))
+            .with_category("communication");
+        event.url = Some("https://t.me/team".to_string());
+        event.data = serde_json::json!({ "chat_name": "Team", "ocr_text": "standup at 10" });
+        event.messages = Some(vec![Message::new("Telegram".to_string(), "hi all".to_string())]);
+        event.browser_tab = Some(BrowserTab {
+            browser: "Safari".to_string(),
+            url: "https://t.me/team".to_string(),
+            title: "Team".to_string(),
+            visible_text: Some("hi all".to_string()),
+        });
+        event.system_metrics = Some(SystemMetrics {
+            cpu_usage: 12.5,
+            ram_used: 4,
+            ram_total: 8,
+            ram_usage_percent: 50.0,
+            cpu_usage_smoothed: Some(11.0),
+            ram_usage_percent_smoothed: None,
+        });
+        event.screenshot_path = Some("/shots/a.jpg".to_string());
+        event.typed_text = Some("hello".to_string());
+
+        // Only in the database, as after the buffer dropped it
+        let state = AppState::new(Arc::new(crate::db::EventDatabase::in_memory().unwrap()), Vec::new());
+        state.db.insert_event(&event).unwrap();
+
+        let found = find_event(&state, &event.id).unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&found).unwrap(),
+            serde_json::to_value(&event).unwrap()
+        );
+        assert!(find_event(&state, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_recent_events_filters_and_sorts() {
+        let now = Utc::now();
+        let at = |minutes_ago: i64, category: &str| {
+            let mut event = Event::new("app_focus", Some("App".to_string()), None).with_category(category);
+            event.timestamp = now - chrono::Duration::minutes(minutes_ago);
+            event
+        };
+        let old_coding = at(120, "coding");
+        let dropped = at(30, "coding");
+        let buffered = at(10, "coding");
+        let browsing = at(5, "browsing");
+
+        // `dropped` is only in the database; `buffered` is in both
+        let db = Arc::new(crate::db::EventDatabase::in_memory().unwrap());
+        for event in [&old_coding, &dropped, &buffered] {
+            db.insert_event(event).unwrap();
+        }
+        let state = AppState::new(db, vec![buffered.clone(), browsing.clone()]);
+        let ids = |events: Vec<Event>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        let recent = |limit: usize, category: Option<&str>, since: Option<DateTime<Utc>>| {
+            recent_events(&state.db, buffered_matching(&state, category, since), limit, category, since).unwrap()
+        };
+
+        let all = recent(10, None, None);
+        assert_eq!(ids(all), vec![browsing.id.clone(), buffered.id.clone(), dropped.id.clone(), old_coding.id.clone()]);
+
+        let coding = recent(10, Some("Coding"), Some(now - chrono::Duration::hours(1)));
+        assert_eq!(ids(coding), vec![buffered.id.clone(), dropped.id.clone()]);
+
+        assert_eq!(ids(recent(1, None, None)), vec![browsing.id.clone()]);
+        assert!(recent(10, Some("music"), None).is_empty());
+    }
+
+    #[test]
+    fn test_delete_event_everywhere() {
+        let dir = std::env::temp_dir().join(format!("observer_delete_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let screenshot = dir.join("120000_abcd.jpg");
+        let thumbnail = screenshots::thumbnail_path_for(&screenshot);
+        std::fs::write(&screenshot, b"jpeg").unwrap();
+        std::fs::write(&thumbnail, b"jpeg").unwrap();
+
+        let mut state = AppState::new(Arc::new(crate::db::EventDatabase::in_memory().unwrap()), Vec::new());
+        let event = event_with_screenshot("Slack", &screenshot);
+        let other = Event::new("app_focus", Some("Slack".to_string()), None);
+        push_event_with(&mut state, event.clone(), &AppSettings::default());
+        push_event_with(&mut state, other.clone(), &AppSettings::default());
+
+        assert!(delete_event(&mut state, &event.id).unwrap());
+        assert!(state.events_buffer.iter().all(|e| e.id != event.id));
+        assert!(state.db.get_event(&event.id).unwrap().is_none());
+        assert!(state.db.get_event(&other.id).unwrap().is_some());
+        assert!(!screenshot.exists());
+        assert!(!thumbnail.exists());
+        assert_eq!(state.events_today, 1);
+        assert_eq!(state.top_apps_cache.get("Slack"), Some(&1));
+
+        // Deleting an event from an earlier day leaves today's count alone
+        let mut old = Event::new("app_focus", Some("Code".to_string()), None);
+        old.timestamp -= chrono::Duration::days(2);
+        state.db.insert_event(&old).unwrap();
+        assert!(delete_event(&mut state, &old.id).unwrap());
+        assert_eq!(state.events_today, 1);
+
+        assert!(!delete_event(&mut state, &event.id).unwrap());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_delete_event_keeps_shared_screenshot() {
+        let dir = std::env::temp_dir().join(format!("observer_delete_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let screenshot = dir.join("120000_abcd.jpg");
+        std::fs::write(&screenshot, b"jpeg").unwrap();
+
+        let mut state = AppState::new(Arc::new(crate::db::EventDatabase::in_memory().unwrap()), Vec::new());
+        let first = event_with_screenshot("Code", &screenshot);
+        let second = event_with_screenshot("Code", &screenshot);
+        push_event_with(&mut state, first.clone(), &AppSettings::default());
+        push_event_with(&mut state, second.clone(), &AppSettings::default());
+        // Only in the database, as after the buffer dropped it
+        state.events_buffer.retain(|e| e.id != second.id);
+
+        assert!(delete_event(&mut state, &first.id).unwrap());
+        assert!(screenshot.exists());
+
+        assert!(delete_event(&mut state, &second.id).unwrap());
+        assert!(!screenshot.exists());
+        assert!(state.top_apps_cache.is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,272 @@
+// Accessibility permission watch
+// Tracks whether accessibility permission is still granted during a session so
+// the collector can fall back to basic window tracking when it is revoked, and
+// applies the configured policy while startup permissions are missing
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often the collector re-checks accessibility permission
+pub const ACCESSIBILITY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the user is reminded about missing permissions
+pub const PERMISSION_REMINDER_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// What the collector does while required permissions are denied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionDeniedPolicy {
+    /// Log a warning and collect whatever is available
+    #[default]
+    Warn,
+    /// Keep collecting, re-check periodically and remind the user until granted
+    NotifyAndRetry,
+    /// Stop collecting until granted, reminding the user periodically
+    PauseUntilGranted,
+}
+
+/// Outcome of a permission gate check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GateEvent {
+    /// Prompt the user to grant these permissions
+    Notify(Vec<&'static str>),
+    /// Everything is granted again - resume full collection
+    Granted,
+}
+
+/// Applies a `PermissionDeniedPolicy` to periodic permission checks
+pub struct PermissionGate {
+    policy: PermissionDeniedPolicy,
+    /// Permissions missing at the last check
+    missing: Vec<&'static str>,
+    last_check: Option<Instant>,
+    check_interval: Duration,
+    last_notified: Option<Instant>,
+    reminder_interval: Duration,
+}
+
+impl PermissionGate {
+    pub fn new(policy: PermissionDeniedPolicy, check_interval: Duration, reminder_interval: Duration) -> Self {
+        Self {
+            policy,
+            missing: Vec::new(),
+            last_check: None,
+            check_interval,
+            last_notified: None,
+            reminder_interval,
+        }
+    }
+
+    /// Whether collection should be skipped for now
+    pub fn collection_paused(&self) -> bool {
+        self.policy == PermissionDeniedPolicy::PauseUntilGranted && !self.missing.is_empty()
+    }
+
+    /// Re-check permissions if the interval elapsed (the first poll always checks)
+    /// `check` returns the names of the missing permissions
+    pub fn poll(&mut self, now: Instant, check: impl FnOnce() -> Vec<&'static str>) -> Option<GateEvent> {
+        if self.policy == PermissionDeniedPolicy::Warn {
+            return None;
+        }
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < self.check_interval)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+        self.record(now, check())
+    }
+
+    /// Record a permission check result
+    pub fn record(&mut self, now: Instant, missing: Vec<&'static str>) -> Option<GateEvent> {
+        let was_missing = !self.missing.is_empty();
+        self.missing = missing;
+
+        if self.policy == PermissionDeniedPolicy::Warn {
+            return None;
+        }
+
+        if self.missing.is_empty() {
+            self.last_notified = None;
+            return was_missing.then_some(GateEvent::Granted);
+        }
+
+        if self
+            .last_notified
+            .is_some_and(|last| now.duration_since(last) < self.reminder_interval)
+        {
+            return None;
+        }
+        self.last_notified = Some(now);
+        Some(GateEvent::Notify(self.missing.clone()))
+    }
+}
+
+/// Change in accessibility permission between checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionChange {
+    Lost,
+    Restored,
+}
+
+impl PermissionChange {
+    /// Event type recorded for the change
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            PermissionChange::Lost => "permission_lost",
+            PermissionChange::Restored => "permission_restored",
+        }
+    }
+}
+
+/// Periodic accessibility permission tracker
+pub struct AccessibilityWatch {
+    granted: bool,
+    last_check: Instant,
+    check_interval: Duration,
+}
+
+impl AccessibilityWatch {
+    pub fn new(granted: bool, check_interval: Duration) -> Self {
+        Self {
+            granted,
+            last_check: Instant::now(),
+            check_interval,
+        }
+    }
+
+    /// Whether AX-dependent captures (focus details, selected text, messages) should run
+    pub fn use_accessibility(&self) -> bool {
+        self.granted
+    }
+
+    /// Re-check permission if the interval elapsed
+    /// Returns the change if the permission state flipped
+    pub fn poll(&mut self, now: Instant, check: impl FnOnce() -> bool) -> Option<PermissionChange> {
+        if now.duration_since(self.last_check) < self.check_interval {
+            return None;
+        }
+        self.last_check = now;
+        self.record(check())
+    }
+
+    /// Record a permission check result
+    pub fn record(&mut self, granted: bool) -> Option<PermissionChange> {
+        let change = match (self.granted, granted) {
+            (true, false) => Some(PermissionChange::Lost),
+            (false, true) => Some(PermissionChange::Restored),
+            _ => None,
+        };
+        self.granted = granted;
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_transitions_drive_code_path() {
+        let mut watch = AccessibilityWatch::new(true, Duration::from_secs(10));
+        assert!(watch.use_accessibility());
+
+        assert_eq!(watch.record(true), None);
+        assert_eq!(watch.record(false), Some(PermissionChange::Lost));
+        assert!(!watch.use_accessibility());
+
+        // Still lost - no repeated events
+        assert_eq!(watch.record(false), None);
+
+        assert_eq!(watch.record(true), Some(PermissionChange::Restored));
+        assert!(watch.use_accessibility());
+    }
+
+    fn gate(policy: PermissionDeniedPolicy) -> PermissionGate {
+        PermissionGate::new(policy, Duration::from_secs(10), Duration::from_secs(60))
+    }
+
+    /// Feed one check result every 10 seconds and collect the gate's reactions
+    fn run(gate: &mut PermissionGate, checks: &[&[&'static str]]) -> Vec<(Option<GateEvent>, bool)> {
+        let start = Instant::now();
+        checks
+            .iter()
+            .enumerate()
+            .map(|(i, missing)| {
+                let now = start + Duration::from_secs(10 * i as u64);
+                let event = gate.poll(now, || missing.to_vec());
+                (event, gate.collection_paused())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_warn_policy_never_checks() {
+        let mut gate = gate(PermissionDeniedPolicy::Warn);
+        assert_eq!(gate.poll(Instant::now(), || panic!("checked under warn policy")), None);
+        assert!(!gate.collection_paused());
+    }
+
+    #[test]
+    fn test_notify_policy_reminds_until_granted() {
+        let mut gate = gate(PermissionDeniedPolicy::NotifyAndRetry);
+        let ax: &[&'static str] = &["Accessibility"];
+        let results = run(&mut gate, &[ax, ax, ax, ax, ax, ax, ax, &[], &[]]);
+
+        let notify = Some(GateEvent::Notify(vec!["Accessibility"]));
+        let events: Vec<_> = results.iter().map(|(event, _)| event.clone()).collect();
+        // Reminded at 0s and again at 60s, then resumed once granted
+        assert_eq!(
+            events,
+            vec![
+                notify.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                notify,
+                Some(GateEvent::Granted),
+                None
+            ]
+        );
+        // Never pauses collection
+        assert!(results.iter().all(|(_, paused)| !paused));
+    }
+
+    #[test]
+    fn test_pause_policy_pauses_until_granted() {
+        let mut gate = gate(PermissionDeniedPolicy::PauseUntilGranted);
+        let both: &[&'static str] = &["Accessibility", "Screen Recording"];
+        let screen: &[&'static str] = &["Screen Recording"];
+        let results = run(&mut gate, &[both, screen, &[], screen]);
+
+        assert_eq!(results[0], (Some(GateEvent::Notify(both.to_vec())), true));
+        // Still paused while anything is missing, reminder not due yet
+        assert_eq!(results[1], (None, true));
+        assert_eq!(results[2], (Some(GateEvent::Granted), false));
+        // Revoked again - the reminder restarts immediately
+        assert_eq!(results[3], (Some(GateEvent::Notify(screen.to_vec())), true));
+    }
+
+    #[test]
+    fn test_granted_from_the_start() {
+        let mut gate = gate(PermissionDeniedPolicy::PauseUntilGranted);
+        let results = run(&mut gate, &[&[], &[]]);
+        assert_eq!(results, vec![(None, false), (None, false)]);
+    }
+
+    #[test]
+    fn test_poll_respects_interval() {
+        let mut watch = AccessibilityWatch::new(true, Duration::from_secs(10));
+        let start = watch.last_check;
+
+        // Too early - the check is not even called
+        assert_eq!(watch.poll(start + Duration::from_secs(1), || panic!("checked too early")), None);
+
+        let change = watch.poll(start + Duration::from_secs(11), || false);
+        assert_eq!(change, Some(PermissionChange::Lost));
+        assert_eq!(PermissionChange::Lost.event_type(), "permission_lost");
+    }
+}
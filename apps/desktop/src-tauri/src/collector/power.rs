@@ -0,0 +1,170 @@
+// Power state tracking module
+// Subscribes to OS sleep/wake notifications so the collector can mark the
+// periods where the machine was off instead of leaving an unexplained gap
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Transitions buffered for a collector that falls behind
+const POWER_CHANNEL_CAPACITY: usize = 16;
+
+/// System power transition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PowerEvent {
+    Sleep,
+    Wake,
+}
+
+impl PowerEvent {
+    /// Event type stored on the collected event
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            PowerEvent::Sleep => "system_sleep",
+            PowerEvent::Wake => "system_wake",
+        }
+    }
+}
+
+/// Start watching for sleep/wake transitions
+/// Call once per process: the OS subscription lives as long as the process.
+/// Every receiver from `subscribe()` on the returned sender yields a PowerEvent
+/// per transition, so a restarted collector just subscribes again.
+/// On unsupported platforms the receivers never yield anything.
+pub fn watch() -> broadcast::Sender<PowerEvent> {
+    let (tx, _) = broadcast::channel(POWER_CHANNEL_CAPACITY);
+
+    if subscribe(tx.clone()) {
+        crate::logs::info("[Power] Watching for sleep/wake notifications");
+    }
+
+    tx
+}
+
+/// Subscribe to NSWorkspace sleep/wake notifications (macOS)
+/// Observers are registered without a queue, so the blocks run on the posting (main) thread
+#[cfg(target_os = "macos")]
+fn subscribe(tx: broadcast::Sender<PowerEvent>) -> bool {
+    use block::ConcreteBlock;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let notifications = [
+        ("NSWorkspaceWillSleepNotification", PowerEvent::Sleep),
+        ("NSWorkspaceDidWakeNotification", PowerEvent::Wake),
+    ];
+
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let center: id = msg_send![workspace, notificationCenter];
+
+        for (name, power_event) in notifications {
+            let tx = tx.clone();
+            let block = ConcreteBlock::new(move |_notification: id| {
+                let _ = tx.send(power_event);
+            });
+            let block = block.copy();
+
+            let ns_name = NSString::alloc(nil).init_str(name);
+            let _: id = msg_send![center,
+                addObserverForName: ns_name
+                object: nil
+                queue: nil
+                usingBlock: &*block];
+
+            // Observers live for the whole process lifetime
+            std::mem::forget(block);
+        }
+    }
+
+    true
+}
+
+/// Subscribe to WM_POWERBROADCAST via a hidden window (Windows)
+/// Broadcast messages are not delivered to message-only windows, so a hidden
+/// top-level window with its own message loop is used instead
+#[cfg(target_os = "windows")]
+fn subscribe(tx: broadcast::Sender<PowerEvent>) -> bool {
+    use once_cell::sync::OnceCell;
+    use windows::core::w;
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        TranslateMessage, HMENU, MSG, WINDOW_EX_STYLE, WINDOW_STYLE, WNDCLASSW,
+    };
+
+    const WM_POWERBROADCAST: u32 = 0x0218;
+    const PBT_APMSUSPEND: usize = 0x0004;
+    const PBT_APMRESUMEAUTOMATIC: usize = 0x0012;
+
+    static POWER_TX: OnceCell<broadcast::Sender<PowerEvent>> = OnceCell::new();
+
+    unsafe extern "system" fn wndproc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_POWERBROADCAST {
+            let power_event = match wparam.0 {
+                PBT_APMSUSPEND => Some(PowerEvent::Sleep),
+                PBT_APMRESUMEAUTOMATIC => Some(PowerEvent::Wake),
+                _ => None,
+            };
+            if let (Some(power_event), Some(tx)) = (power_event, POWER_TX.get()) {
+                let _ = tx.send(power_event);
+            }
+            return LRESULT(1);
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    if POWER_TX.set(tx).is_err() {
+        // Already subscribed
+        return false;
+    }
+
+    std::thread::spawn(|| unsafe {
+        let class_name = w!("ObserverPowerWatcher");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(wndproc),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassW(&class);
+
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class_name,
+            w!(""),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            HWND::default(),
+            HMENU::default(),
+            HINSTANCE::default(),
+            None,
+        );
+
+        if hwnd.is_err() {
+            crate::logs::error("[Power] Failed to create power broadcast window");
+            return;
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+
+    true
+}
+
+#[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+fn subscribe(_tx: broadcast::Sender<PowerEvent>) -> bool {
+    // Unsupported platform
+    false
+}
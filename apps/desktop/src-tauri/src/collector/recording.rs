@@ -0,0 +1,218 @@
+// High-fidelity session recording
+// For demos and detailed reviews: for a fixed time screenshots are taken on
+// every focus change with a short interval and no similarity reuse, and every
+// collector is on. The screenshot config and collector switches in effect
+// before are snapshotted and put back exactly when the recording stops or
+// times out.
+
+use super::screenshots::{self, CaptureTrigger, ScreenshotConfig};
+use super::toggles::{self, CollectorToggles};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Seconds between screenshots while recording
+pub const HIGH_FIDELITY_INTERVAL_SECS: u64 = 1;
+/// Longest recording that can be requested
+pub const MAX_RECORDING_MINUTES: u64 = 240;
+
+static RECORDING: Mutex<HighFidelityRecording> = Mutex::new(HighFidelityRecording::new());
+
+/// Collection settings a recording overrides
+#[derive(Debug, Clone, PartialEq)]
+pub struct FidelitySettings {
+    pub screenshot: ScreenshotConfig,
+    pub toggles: CollectorToggles,
+}
+
+impl FidelitySettings {
+    fn current() -> Self {
+        Self {
+            screenshot: screenshots::live_config(),
+            toggles: toggles::collector_toggles(),
+        }
+    }
+
+    fn apply(&self) -> Result<(), String> {
+        screenshots::set_live_config(self.screenshot.clone())?;
+        toggles::replace_collector_toggles(self.toggles);
+        Ok(())
+    }
+
+    /// `self` with the high-fidelity overrides applied
+    pub fn high_fidelity(&self) -> Self {
+        Self {
+            screenshot: ScreenshotConfig {
+                min_interval_seconds: self.screenshot.min_interval_seconds.min(HIGH_FIDELITY_INTERVAL_SECS),
+                // Only identical frames count as similar, and nothing is reused
+                similarity_threshold: 100,
+                dedup_window_hours: 0,
+                capture_trigger: CaptureTrigger::Both,
+                ..self.screenshot.clone()
+            },
+            toggles: CollectorToggles::ALL_ENABLED,
+        }
+    }
+}
+
+/// Whether a recording is running and until when
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStatus {
+    pub active: bool,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// The running recording, if any, with the settings to restore
+#[derive(Debug)]
+pub struct HighFidelityRecording {
+    original: Option<FidelitySettings>,
+    until: Option<DateTime<Utc>>,
+    /// Bumped on every start so a timer of an earlier recording can't stop a newer one
+    generation: u64,
+}
+
+impl HighFidelityRecording {
+    pub const fn new() -> Self {
+        Self {
+            original: None,
+            until: None,
+            generation: 0,
+        }
+    }
+
+    /// Start (or extend) a recording ending at `until`
+    /// Returns the settings to apply and the recording's generation; an already
+    /// running recording keeps its original snapshot
+    pub fn start(&mut self, current: FidelitySettings, until: DateTime<Utc>) -> (FidelitySettings, u64) {
+        let original = self.original.get_or_insert(current);
+        let overrides = original.high_fidelity();
+        self.until = Some(until);
+        self.generation += 1;
+        (overrides, self.generation)
+    }
+
+    /// Stop the recording, returning the settings to restore
+    pub fn stop(&mut self) -> Option<FidelitySettings> {
+        self.until = None;
+        self.original.take()
+    }
+
+    /// Stop the recording of `generation` if it is still the running one
+    pub fn expire(&mut self, generation: u64) -> Option<FidelitySettings> {
+        if generation == self.generation {
+            self.stop()
+        } else {
+            None
+        }
+    }
+
+    pub fn status(&self) -> RecordingStatus {
+        RecordingStatus {
+            active: self.original.is_some(),
+            until: self.until,
+        }
+    }
+}
+
+impl Default for HighFidelityRecording {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Start recording in high fidelity for `duration`
+/// Returns the status and the generation to pass to `expire_recording` at the end
+pub fn start_recording(duration: Duration) -> Result<(RecordingStatus, u64), String> {
+    let mut recording = RECORDING.lock().unwrap();
+    let (overrides, generation) = recording.start(FidelitySettings::current(), Utc::now() + duration);
+    if let Err(e) = overrides.apply() {
+        if let Some(original) = recording.stop() {
+            let _ = original.apply();
+        }
+        return Err(format!("Failed to start high-fidelity recording: {}", e));
+    }
+    Ok((recording.status(), generation))
+}
+
+/// Stop recording and restore the previous settings
+/// Returns false if no recording was running
+pub fn stop_recording() -> Result<bool, String> {
+    restore(RECORDING.lock().unwrap().stop())
+}
+
+/// Stop the recording of `generation` once its time is up
+pub fn expire_recording(generation: u64) -> Result<bool, String> {
+    restore(RECORDING.lock().unwrap().expire(generation))
+}
+
+fn restore(original: Option<FidelitySettings>) -> Result<bool, String> {
+    match original {
+        Some(original) => original
+            .apply()
+            .map(|_| true)
+            .map_err(|e| format!("Failed to restore collection settings: {}", e)),
+        None => Ok(false),
+    }
+}
+
+pub fn recording_status() -> RecordingStatus {
+    RECORDING.lock().unwrap().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> FidelitySettings {
+        FidelitySettings {
+            screenshot: ScreenshotConfig {
+                min_interval_seconds: 30,
+                similarity_threshold: 85,
+                dedup_window_hours: 12,
+                capture_trigger: CaptureTrigger::OnSwitch,
+                jpeg_quality: 60,
+                ..ScreenshotConfig::default()
+            },
+            toggles: CollectorToggles {
+                screenshots: false,
+                messages: false,
+                ..CollectorToggles::ALL_ENABLED
+            },
+        }
+    }
+
+    #[test]
+    fn test_overrides_apply_and_revert_exactly() {
+        let original = settings();
+        let mut recording = HighFidelityRecording::new();
+        let now = Utc::now();
+
+        let (applied, _) = recording.start(original.clone(), now + Duration::minutes(10));
+        assert_eq!(applied.screenshot.min_interval_seconds, HIGH_FIDELITY_INTERVAL_SECS);
+        assert_eq!(applied.screenshot.similarity_threshold, 100);
+        assert_eq!(applied.screenshot.dedup_window_hours, 0);
+        assert_eq!(applied.screenshot.capture_trigger, CaptureTrigger::Both);
+        assert_eq!(applied.toggles, CollectorToggles::ALL_ENABLED);
+        // Untouched settings carry over
+        assert_eq!(applied.screenshot.jpeg_quality, 60);
+        assert!(applied.screenshot.validate().is_ok());
+        assert_eq!(
+            recording.status(),
+            RecordingStatus { active: true, until: Some(now + Duration::minutes(10)) }
+        );
+
+        // Extending keeps the snapshot taken before the first start, not the overrides
+        let (_, generation) = recording.start(applied, now + Duration::minutes(20));
+        assert_eq!(recording.stop(), Some(original.clone()));
+        assert_eq!(recording.status(), RecordingStatus { active: false, until: None });
+        assert_eq!(recording.stop(), None);
+
+        // A timer from an earlier recording doesn't end a newer one
+        let (_, old) = recording.start(original.clone(), now);
+        let (_, current) = recording.start(original.clone(), now + Duration::minutes(5));
+        assert!(old < current && generation < current);
+        assert_eq!(recording.expire(old), None);
+        assert_eq!(recording.expire(current), Some(original));
+    }
+}
@@ -0,0 +1,235 @@
+// Delta screenshot encoding
+// Stores only the changed region of a screenshot relative to a full base frame.
+// A delta is a lossless PNG patch plus a small JSON file pointing at the base frame.
+
+use image::{DynamicImage, GenericImage, GenericImageView, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Extension of delta metadata files
+pub const DELTA_EXTENSION: &str = "delta.json";
+
+/// Per-channel difference below which pixels count as unchanged
+const PIXEL_TOLERANCE: u8 = 8;
+
+/// Rectangular region of an image
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Share of the full image covered by the region (0-100)
+    pub fn percent_of(&self, width: u32, height: u32) -> f32 {
+        let total = width as f32 * height as f32;
+        if total == 0.0 {
+            return 100.0;
+        }
+        (self.width as f32 * self.height as f32) / total * 100.0
+    }
+}
+
+/// Metadata stored for a delta frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeltaFrame {
+    /// Full frame the patch is applied to
+    pub base_path: PathBuf,
+    /// Lossless patch covering the changed region
+    pub patch_path: PathBuf,
+    pub region: Region,
+}
+
+/// Bounding box of the pixels that differ between two frames
+/// Returns None if the frames are the same, or the full frame if sizes differ
+pub fn changed_region(base: &RgbImage, next: &RgbImage) -> Option<Region> {
+    if base.dimensions() != next.dimensions() {
+        let (width, height) = next.dimensions();
+        return Some(Region { x: 0, y: 0, width, height });
+    }
+
+    let (mut min_x, mut min_y) = (u32::MAX, u32::MAX);
+    let (mut max_x, mut max_y) = (0, 0);
+    let mut changed = false;
+
+    for (x, y, pixel) in next.enumerate_pixels() {
+        let base_pixel = base.get_pixel(x, y);
+        let differs = pixel
+            .0
+            .iter()
+            .zip(base_pixel.0.iter())
+            .any(|(a, b)| a.abs_diff(*b) > PIXEL_TOLERANCE);
+
+        if differs {
+            changed = true;
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+
+    changed.then(|| Region {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x + 1,
+        height: max_y - min_y + 1,
+    })
+}
+
+/// Cut the changed region out of the new frame
+pub fn encode_delta(next: &RgbImage, region: Region) -> RgbImage {
+    next.view(region.x, region.y, region.width, region.height)
+        .to_image()
+}
+
+/// Paste a patch onto the base frame
+pub fn apply_delta(base: &RgbImage, patch: &RgbImage, region: Region) -> Result<RgbImage, String> {
+    let mut image = base.clone();
+    image
+        .copy_from(patch, region.x, region.y)
+        .map_err(|e| format!("Failed to apply delta patch: {}", e))?;
+    Ok(image)
+}
+
+/// Check if a path is a delta metadata file
+pub fn is_delta_path(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(DELTA_EXTENSION)
+}
+
+/// Check if a path is the PNG patch of a delta frame (`<name>.png` next to `<name>.delta.json`)
+/// Full screenshots saved as PNG have no metadata file next to them
+pub fn is_patch_path(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "png") && path.with_extension(DELTA_EXTENSION).exists()
+}
+
+/// Write a delta frame next to `meta_path` (patch is stored as PNG)
+pub fn save_delta(
+    meta_path: &Path,
+    base_path: &Path,
+    patch: &RgbImage,
+    region: Region,
+) -> Result<u64, String> {
+    let patch_path = meta_path.with_extension("").with_extension("png");
+    patch
+        .save_with_format(&patch_path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write delta patch: {}", e))?;
+
+    let frame = DeltaFrame {
+        base_path: base_path.to_path_buf(),
+        patch_path: patch_path.clone(),
+        region,
+    };
+    let content = serde_json::to_string(&frame)
+        .map_err(|e| format!("Failed to serialize delta frame: {}", e))?;
+    std::fs::write(meta_path, &content)
+        .map_err(|e| format!("Failed to write delta frame: {}", e))?;
+
+    let patch_size = std::fs::metadata(&patch_path).map(|m| m.len()).unwrap_or(0);
+    Ok(patch_size + content.len() as u64)
+}
+
+/// Read the metadata of a delta frame
+pub fn read_delta(path: &Path) -> Result<DeltaFrame, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read delta frame: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse delta frame: {}", e))
+}
+
+/// Check if any delta frame next to `base_path` is applied on top of it
+/// Deltas always live in the same directory as their base
+pub fn has_dependent_deltas(base_path: &Path) -> bool {
+    let Some(dir) = base_path.parent() else {
+        return false;
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_delta_path(path))
+        .any(|path| read_delta(&path).is_ok_and(|frame| frame.base_path == base_path))
+}
+
+/// Load a stored screenshot, reconstructing delta frames from their base
+pub fn load_screenshot(path: &Path) -> Result<DynamicImage, String> {
+    if !is_delta_path(path) {
+        return image::open(path).map_err(|e| format!("Failed to open screenshot: {}", e));
+    }
+
+    let frame = read_delta(path)?;
+
+    let base = image::open(&frame.base_path)
+        .map_err(|e| format!("Failed to open base frame: {}", e))?
+        .to_rgb8();
+    let patch = image::open(&frame.patch_path)
+        .map_err(|e| format!("Failed to open delta patch: {}", e))?
+        .to_rgb8();
+
+    Ok(DynamicImage::ImageRgb8(apply_delta(&base, &patch, frame.region)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with_cursor(cursor_x: u32) -> RgbImage {
+        RgbImage::from_fn(64, 48, |x, y| {
+            if (cursor_x..cursor_x + 2).contains(&x) && (10..20).contains(&y) {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([(x * 4) as u8, (y * 5) as u8, 200])
+            }
+        })
+    }
+
+    #[test]
+    fn test_changed_region() {
+        let base = frame_with_cursor(5);
+        assert_eq!(changed_region(&base, &base), None);
+
+        let next = frame_with_cursor(30);
+        let region = changed_region(&base, &next).unwrap();
+        assert_eq!(region, Region { x: 5, y: 10, width: 27, height: 10 });
+        assert!(region.percent_of(64, 48) < 10.0);
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let base = frame_with_cursor(5);
+        let next = frame_with_cursor(30);
+
+        let region = changed_region(&base, &next).unwrap();
+        let patch = encode_delta(&next, region);
+        assert_eq!(patch.dimensions(), (region.width, region.height));
+
+        let reconstructed = apply_delta(&base, &patch, region).unwrap();
+        assert_eq!(reconstructed, next);
+    }
+
+    #[test]
+    fn test_stored_delta_reconstructs_from_disk() {
+        let dir = std::env::temp_dir().join(format!("observer_delta_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Lossless base so the full reconstruction can be compared exactly
+        let base = frame_with_cursor(5);
+        let base_path = dir.join("100000_base.png");
+        base.save(&base_path).unwrap();
+
+        let next = frame_with_cursor(30);
+        let region = changed_region(&base, &next).unwrap();
+        let meta_path = dir.join(format!("100005_next.{}", DELTA_EXTENSION));
+        save_delta(&meta_path, &base_path, &encode_delta(&next, region), region).unwrap();
+
+        assert!(is_delta_path(&meta_path));
+        let loaded = load_screenshot(&meta_path).unwrap().to_rgb8();
+        assert_eq!(loaded, next);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,21 +1,180 @@
+use crate::jpeg::{ChromaSubsampling, JpegOptions};
 use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
 use uuid::Uuid;
 
 #[cfg(target_os = "macos")]
 use core_graphics::display::CGMainDisplayID;
-#[cfg(target_os = "macos")]
-use image::{DynamicImage, ImageBuffer, Rgb};
+use super::screenshot_delta;
+use super::FocusTransition;
+use image::{DynamicImage, RgbImage};
+
+/// File inside the storage directory holding recent content hashes
+const HASH_INDEX_FILE: &str = "hash_index.json";
+/// Upper bound on remembered hashes
+const MAX_HASH_INDEX_ENTRIES: usize = 1000;
+/// Suffix of thumbnail files stored next to full screenshots
+const THUMBNAIL_SUFFIX: &str = "_thumb.jpg";
+/// Default thumbnail width in pixels
+pub const DEFAULT_THUMBNAIL_WIDTH: u32 = 320;
+/// Default seconds between captures
+pub const DEFAULT_INTERVAL_SECS: u64 = 5;
+/// Default JPEG and WebP quality
+pub const DEFAULT_QUALITY: u8 = 60;
+/// Default downscale factor
+pub const DEFAULT_SCALE_FACTOR: f32 = 0.5;
+/// Default days screenshots are kept
+pub const DEFAULT_RETENTION_DAYS: i64 = 7;
+/// Default perceptual similarity above which a frame is a duplicate
+pub const DEFAULT_SIMILARITY_THRESHOLD: u8 = 90;
+/// Extensions of full screenshots, whatever format they were saved in
+const SCREENSHOT_EXTENSIONS: &[&str] = &["jpg", "webp", "png"];
+
+/// Config of the collector's live `ScreenshotManager`; commands update it and
+/// the collector applies changes on its next tick
+static LIVE_CONFIG: Lazy<watch::Sender<ScreenshotConfig>> =
+    Lazy::new(|| watch::channel(ScreenshotConfig::default()).0);
+
+/// Perceptual hash used to detect similar screenshots
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// Average hash: each pixel of an 8x8 thumbnail compared to the mean
+    #[default]
+    AHash,
+    /// Difference hash: each pixel compared to its right neighbour
+    /// Robust to brightness and lighting changes, sensitive to structure
+    DHash,
+}
+
+/// File format of stored screenshots
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenshotFormat {
+    /// Lossy, sized by `jpeg_quality` and `chroma_subsampling`
+    #[default]
+    Jpeg,
+    /// Lossy WebP at `jpeg_quality`; smaller than JPEG on flat UI colors and text
+    WebP,
+    /// Lossless PNG
+    Png,
+}
+
+impl ScreenshotFormat {
+    /// Extension of saved files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::WebP => "webp",
+            ScreenshotFormat::Png => "png",
+        }
+    }
+}
+
+/// Which focus changes capture a screenshot
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureTrigger {
+    /// Any focus change, at most once per `min_interval_seconds`
+    #[default]
+    Interval,
+    /// Only switching to another app, right away (similar frames are still reused)
+    OnSwitch,
+    /// App switches right away, title changes at most once per interval
+    Both,
+}
+
+impl CaptureTrigger {
+    /// Whether a collector tick should capture, given what changed and the time since the last capture
+    pub fn should_capture(
+        self,
+        transition: Option<FocusTransition>,
+        since_last: Option<Duration>,
+        min_interval: Duration,
+    ) -> bool {
+        let interval_elapsed = match since_last {
+            Some(elapsed) => elapsed >= min_interval,
+            None => true,
+        };
+
+        match (self, transition) {
+            // Nothing changed since the last tick
+            (_, None) => false,
+            (CaptureTrigger::OnSwitch | CaptureTrigger::Both, Some(FocusTransition::AppSwitch)) => true,
+            (CaptureTrigger::OnSwitch, Some(FocusTransition::TitleChange)) => false,
+            (CaptureTrigger::Interval, Some(_)) | (CaptureTrigger::Both, Some(FocusTransition::TitleChange)) => {
+                interval_elapsed
+            }
+        }
+    }
+}
+
+impl HashAlgorithm {
+    /// 64-bit hash of the image
+    pub fn hash(&self, image: &DynamicImage) -> u64 {
+        match self {
+            HashAlgorithm::AHash => average_hash(image),
+            HashAlgorithm::DHash => difference_hash(image),
+        }
+    }
+}
+
+/// 8x8 average hash
+fn average_hash(image: &DynamicImage) -> u64 {
+    use image::imageops::FilterType;
+
+    // Resize to 8x8 for hash computation
+    let small = image.resize_exact(8, 8, FilterType::Lanczos3);
+    let gray = small.to_luma8();
+
+    // Calculate average pixel value
+    let mut sum: u32 = 0;
+    for pixel in gray.pixels() {
+        sum += pixel[0] as u32;
+    }
+    let avg = sum / 64;
+
+    // Build hash
+    let mut hash: u64 = 0;
+    for (i, pixel) in gray.pixels().enumerate() {
+        if pixel[0] as u32 > avg {
+            hash |= 1 << i;
+        }
+    }
+
+    hash
+}
+
+/// 8x8 difference hash (from a 9x8 thumbnail)
+fn difference_hash(image: &DynamicImage) -> u64 {
+    use image::imageops::FilterType;
+
+    let small = image.resize_exact(9, 8, FilterType::Lanczos3);
+    let gray = small.to_luma8();
+
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            if gray.get_pixel(x + 1, y)[0] > gray.get_pixel(x, y)[0] {
+                hash |= 1 << (y * 8 + x);
+            }
+        }
+    }
+
+    hash
+}
 
 /// Configuration for screenshot capture behavior
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScreenshotConfig {
     /// Minimum seconds between screenshot captures
     pub min_interval_seconds: u64,
-    /// JPEG quality (0-100)
+    /// JPEG and WebP quality (0-100)
     pub jpeg_quality: u8,
     /// Scale factor for compression (0.0-1.0)
     pub scale_factor: f32,
@@ -25,22 +184,114 @@ pub struct ScreenshotConfig {
     pub storage_path: PathBuf,
     /// Perceptual hash similarity threshold (0-100, higher = more similar)
     pub similarity_threshold: u8,
+    /// How long a stored screenshot can be reused for identical content
+    pub dedup_window_hours: i64,
+    /// Store only the changed region when a frame is mostly the same as the last full frame
+    pub delta_encoding: bool,
+    /// Maximum changed area (percent of the frame) that is still stored as a delta
+    pub delta_max_changed_percent: f32,
+    /// Perceptual hash used for similarity checks
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Also store a small thumbnail next to each full screenshot
+    #[serde(default)]
+    pub thumbnails: bool,
+    /// Thumbnail width in pixels (height keeps the aspect ratio)
+    #[serde(default = "default_thumbnail_width")]
+    pub thumbnail_width: u32,
+    /// Chroma subsampling applied before JPEG encoding (smaller files)
+    #[serde(default)]
+    pub chroma_subsampling: ChromaSubsampling,
+    /// Which focus changes capture a screenshot
+    #[serde(default)]
+    pub capture_trigger: CaptureTrigger,
+    /// File format of full screenshots (thumbnails are always JPEG)
+    #[serde(default)]
+    pub format: ScreenshotFormat,
+}
+
+impl ScreenshotConfig {
+    /// Encoder settings for stored screenshots and thumbnails
+    pub fn jpeg_options(&self) -> JpegOptions {
+        JpegOptions::new(self.jpeg_quality).with_subsampling(self.chroma_subsampling)
+    }
+
+    /// Check values set from the UI
+    pub fn validate(&self) -> Result<(), String> {
+        if self.jpeg_quality > 100 {
+            return Err("jpeg_quality must be between 0 and 100".to_string());
+        }
+        if !(self.scale_factor > 0.0 && self.scale_factor <= 1.0) {
+            return Err("scale_factor must be greater than 0 and at most 1".to_string());
+        }
+        if self.similarity_threshold > 100 {
+            return Err("similarity_threshold must be between 0 and 100".to_string());
+        }
+        if self.retention_days < 1 {
+            return Err("retention_days must be at least 1".to_string());
+        }
+        if self.dedup_window_hours < 0 {
+            return Err("dedup_window_hours cannot be negative".to_string());
+        }
+        if !(0.0..=100.0).contains(&self.delta_max_changed_percent) {
+            return Err("delta_max_changed_percent must be between 0 and 100".to_string());
+        }
+        if self.thumbnail_width == 0 {
+            return Err("thumbnail_width must be at least 1 pixel".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Config the collector's screenshot manager is running with
+pub fn live_config() -> ScreenshotConfig {
+    LIVE_CONFIG.borrow().clone()
+}
+
+/// Validate and apply a new config to the running collector
+/// The storage directory is fixed for the session
+pub fn set_live_config(config: ScreenshotConfig) -> Result<(), String> {
+    config.validate()?;
+    if config.storage_path != LIVE_CONFIG.borrow().storage_path {
+        return Err("storage_path can't be changed while running".to_string());
+    }
+    LIVE_CONFIG.send_replace(config);
+    Ok(())
+}
+
+/// Publish the collector's starting config and watch for later changes
+pub fn watch_live_config(initial: ScreenshotConfig) -> watch::Receiver<ScreenshotConfig> {
+    LIVE_CONFIG.send_replace(initial);
+    LIVE_CONFIG.subscribe()
+}
+
+fn default_thumbnail_width() -> u32 {
+    DEFAULT_THUMBNAIL_WIDTH
 }
 
 impl Default for ScreenshotConfig {
     fn default() -> Self {
         let home = dirs::home_dir().expect("Unable to determine home directory");
         Self {
-            min_interval_seconds: 5,
-            jpeg_quality: 60,
-            scale_factor: 0.5,
-            retention_days: 7,
+            min_interval_seconds: DEFAULT_INTERVAL_SECS,
+            jpeg_quality: DEFAULT_QUALITY,
+            scale_factor: DEFAULT_SCALE_FACTOR,
+            retention_days: DEFAULT_RETENTION_DAYS,
             storage_path: home
                 .join("Library")
                 .join("Application Support")
                 .join("observer")
                 .join("screenshots"),
-            similarity_threshold: 90,
+            similarity_threshold: DEFAULT_SIMILARITY_THRESHOLD,
+            dedup_window_hours: 24,
+            delta_encoding: false,
+            delta_max_changed_percent: 20.0,
+            hash_algorithm: HashAlgorithm::default(),
+            thumbnails: false,
+            thumbnail_width: DEFAULT_THUMBNAIL_WIDTH,
+            chroma_subsampling: ChromaSubsampling::default(),
+            capture_trigger: CaptureTrigger::default(),
+            format: ScreenshotFormat::default(),
         }
     }
 }
@@ -56,6 +307,23 @@ pub struct Screenshot {
     pub size_bytes: u64,
     #[serde(skip)]
     pub hash: Option<u64>,
+    /// True when the path points at a previously stored file with the same content
+    #[serde(default)]
+    pub reused: bool,
+    /// Small preview stored next to the full image (when thumbnails are enabled)
+    #[serde(default)]
+    pub thumbnail_path: Option<PathBuf>,
+}
+
+/// A previously stored screenshot, keyed by its perceptual hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HashIndexEntry {
+    hash: u64,
+    /// Hashes are only comparable when computed with the same algorithm
+    #[serde(default)]
+    algorithm: HashAlgorithm,
+    path: PathBuf,
+    timestamp: DateTime<Utc>,
 }
 
 /// Statistics about screenshot collection
@@ -68,11 +336,358 @@ pub struct ScreenshotStats {
     pub screenshots_by_date: HashMap<String, usize>,
 }
 
-/// Manager for capturing and managing screenshots
-pub struct ScreenshotManager {
+/// Check whether screenshots are enabled for an event category
+/// An empty list allows every category
+pub fn category_allows_capture(category: Option<&str>, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+
+    category
+        .map(|category| allowed.iter().any(|c| c.eq_ignore_ascii_case(category)))
+        .unwrap_or(false)
+}
+
+/// Thumbnail file stored next to a screenshot: `<name>_thumb.jpg`
+pub fn thumbnail_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let stem = name
+        .strip_suffix(screenshot_delta::DELTA_EXTENSION)
+        .map(|stem| stem.trim_end_matches('.'))
+        .or_else(|| path.file_stem().and_then(|stem| stem.to_str()))
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}", stem, THUMBNAIL_SUFFIX))
+}
+
+fn is_thumbnail_path(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(THUMBNAIL_SUFFIX)
+}
+
+/// Full screenshot in any of the supported formats (or a thumbnail or delta patch)
+fn has_screenshot_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SCREENSHOT_EXTENSIONS.contains(&extension))
+}
+
+/// Delete a stored screenshot with its thumbnail, or a delta frame with its patch
+/// Full frames that delta frames still depend on are kept; returns whether anything was removed
+pub fn delete_screenshot(path: &Path) -> Result<bool, String> {
+    if screenshot_delta::is_delta_path(path) {
+        if let Ok(frame) = screenshot_delta::read_delta(path) {
+            fs::remove_file(&frame.patch_path).ok();
+        }
+        fs::remove_file(path).map_err(|e| format!("Failed to delete screenshot: {}", e))?;
+        return Ok(true);
+    }
+
+    if screenshot_delta::has_dependent_deltas(path) {
+        crate::logs::info(&format!("[Screenshots] Keeping {} - delta frames depend on it", path.display()));
+        return Ok(false);
+    }
+
+    fs::remove_file(thumbnail_path_for(path)).ok();
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(format!("Failed to delete screenshot: {}", e)),
+    }
+}
+
+/// Downscale an image to `width` pixels wide (never upscales)
+fn make_thumbnail(image: &DynamicImage, width: u32) -> DynamicImage {
+    if image.width() <= width {
+        return image.clone();
+    }
+    image.thumbnail(width, u32::MAX)
+}
+
+fn encode_jpeg(image: &DynamicImage, options: JpegOptions) -> Result<Vec<u8>, String> {
+    crate::jpeg::encode(&image.to_rgb8(), options)
+}
+
+/// Lossy WebP at `quality` (0-100)
+fn encode_webp(image: &DynamicImage, quality: u8) -> Vec<u8> {
+    let rgb = image.to_rgb8();
+    webp::Encoder::from_rgb(rgb.as_raw(), rgb.width(), rgb.height())
+        .encode(f32::from(quality))
+        .to_vec()
+}
+
+/// Encode a full screenshot in the configured format
+fn encode_screenshot(image: &DynamicImage, config: &ScreenshotConfig) -> Result<Vec<u8>, String> {
+    use image::codecs::png::PngEncoder;
+
+    let mut buffer = Vec::new();
+    let result = match config.format {
+        ScreenshotFormat::Jpeg => return encode_jpeg(image, config.jpeg_options()),
+        ScreenshotFormat::WebP => return Ok(encode_webp(image, config.jpeg_quality)),
+        ScreenshotFormat::Png => image.to_rgb8().write_with_encoder(PngEncoder::new(&mut buffer)),
+    };
+    result
+        .map(|()| buffer)
+        .map_err(|e| format!("Failed to encode screenshot as {:?}: {}", config.format, e))
+}
+
+/// JPEG thumbnail of a stored screenshot inside `config.storage_path`
+/// Serves the stored thumbnail if there is one, otherwise builds it from the full image
+pub fn load_thumbnail(config: &ScreenshotConfig, path: &Path) -> Result<Vec<u8>, String> {
+    let root = config
+        .storage_path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve screenshot directory: {}", e))?;
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve screenshot: {}", e))?;
+    if !path.starts_with(&root) {
+        return Err("Screenshot is outside the screenshot directory".to_string());
+    }
+
+    let thumbnail = thumbnail_path_for(&path);
+    if thumbnail.exists() {
+        return fs::read(&thumbnail).map_err(|e| format!("Failed to read thumbnail: {}", e));
+    }
+
+    let image = screenshot_delta::load_screenshot(&path)?;
+    encode_jpeg(&make_thumbnail(&image, config.thumbnail_width), config.jpeg_options())
+}
+
+/// Byte order of a 32-bit pixel in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    Rgba,
+    Bgra,
+}
+
+/// Memory layout of a 32-bit-per-pixel bitmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelLayout {
+    pub width: usize,
+    pub height: usize,
+    /// Can exceed `width * 4` when rows are padded for alignment
+    pub bytes_per_row: usize,
+    pub order: ChannelOrder,
+    /// Color channels are already multiplied by alpha
+    pub premultiplied: bool,
+}
+
+/// Convert a 32-bit bitmap to RGB, skipping row padding and undoing
+/// premultiplied alpha. None if `data` is too short for `layout`.
+pub fn decode_pixels(data: &[u8], layout: PixelLayout) -> Option<RgbImage> {
+    let row_bytes = layout.width.checked_mul(4)?;
+    if layout.bytes_per_row < row_bytes {
+        return None;
+    }
+    if layout.height > 0 {
+        let needed = (layout.height - 1)
+            .checked_mul(layout.bytes_per_row)?
+            .checked_add(row_bytes)?;
+        if data.len() < needed {
+            return None;
+        }
+    }
+
+    let unpremultiply = |channel: u8, alpha: u8| -> u8 {
+        match alpha {
+            0 => 0,
+            255 => channel,
+            alpha => ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8,
+        }
+    };
+
+    let mut image = RgbImage::new(layout.width as u32, layout.height as u32);
+    for (y, row) in data.chunks(layout.bytes_per_row).take(layout.height).enumerate() {
+        for (x, pixel) in row[..row_bytes].chunks_exact(4).enumerate() {
+            let (r, g, b, a) = match layout.order {
+                ChannelOrder::Rgba => (pixel[0], pixel[1], pixel[2], pixel[3]),
+                ChannelOrder::Bgra => (pixel[2], pixel[1], pixel[0], pixel[3]),
+            };
+            let rgb = if layout.premultiplied {
+                [unpremultiply(r, a), unpremultiply(g, a), unpremultiply(b, a)]
+            } else {
+                [r, g, b]
+            };
+            image.put_pixel(x as u32, y as u32, image::Rgb(rgb));
+        }
+    }
+    Some(image)
+}
+
+/// Capture the screen using macOS Core Graphics
+#[cfg(target_os = "macos")]
+fn capture_screen(scale_factor: f32) -> Option<DynamicImage> {
+    use core_graphics::display::CGDisplay;
+
+    unsafe {
+        // Get main display
+        let display_id = CGMainDisplayID();
+        let display = CGDisplay::new(display_id);
+
+        // Capture display image
+        let cg_image = display.image()?;
+
+        // Convert CGImage to DynamicImage
+        cgimage_to_dynamic_image(&cg_image, scale_factor)
+    }
+}
+
+/// Capture the screen with X11, or a compositor screenshot tool under Wayland
+#[cfg(target_os = "linux")]
+fn capture_screen(scale_factor: f32) -> Option<DynamicImage> {
+    let frame = super::linux_capture::capture();
+    if frame.is_none() {
+        crate::logs::error("[Screenshot] No Linux capture backend could grab the screen");
+    }
+    Some(scale_frame(frame?, scale_factor))
+}
+
+/// Convert CGImage to DynamicImage using CGBitmapContext
+/// The context picks its own (possibly padded) row length, so pixels are
+/// read using the context's `bytes_per_row`, in the format requested here
+#[cfg(target_os = "macos")]
+fn cgimage_to_dynamic_image(
+    cg_image: &core_graphics::image::CGImage,
+    scale_factor: f32,
+) -> Option<DynamicImage> {
+    use core_graphics::base::{kCGBitmapByteOrder32Little, kCGImageAlphaPremultipliedFirst};
+    use core_graphics::color_space::CGColorSpace;
+    use core_graphics::context::CGContext;
+
+    let width = cg_image.width();
+    let height = cg_image.height();
+
+    // Premultiplied BGRA is the native macOS framebuffer layout; a row
+    // length of 0 lets CoreGraphics choose an aligned one
+    let color_space = CGColorSpace::create_device_rgb();
+    let mut context = CGContext::create_bitmap_context(
+        None,
+        width,
+        height,
+        8, // bits per component
+        0,
+        &color_space,
+        kCGImageAlphaPremultipliedFirst | kCGBitmapByteOrder32Little,
+    );
+
+    // Draw the CGImage into the context
+    let rect = core_graphics::geometry::CGRect::new(
+        &core_graphics::geometry::CGPoint::new(0.0, 0.0),
+        &core_graphics::geometry::CGSize::new(width as f64, height as f64),
+    );
+    context.draw_image(rect, cg_image);
+
+    let layout = PixelLayout {
+        width,
+        height,
+        bytes_per_row: context.bytes_per_row(),
+        order: ChannelOrder::Bgra,
+        premultiplied: true,
+    };
+    let img_buffer = match decode_pixels(context.data(), layout) {
+        Some(buffer) => buffer,
+        None => {
+            crate::logs::error(&format!("[Screenshot] Unexpected bitmap layout: {:?}", layout));
+            return None;
+        }
+    };
+
+    Some(scale_frame(img_buffer, scale_factor))
+}
+
+/// Scale a captured frame down by `scale_factor`
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn scale_frame(frame: RgbImage, scale_factor: f32) -> DynamicImage {
+    if scale_factor < 1.0 {
+        let new_width = ((frame.width() as f32) * scale_factor) as u32;
+        let new_height = ((frame.height() as f32) * scale_factor) as u32;
+        DynamicImage::ImageRgb8(frame).resize(
+            new_width,
+            new_height,
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        DynamicImage::ImageRgb8(frame)
+    }
+}
+
+/// Decides when the collector grabs a frame and grabs it
+/// Kept apart from `ScreenshotManager` so grabbing never waits on a worker
+/// storing the previous frame
+pub struct FrameCapturer {
     config: ScreenshotConfig,
     last_capture_time: Option<DateTime<Utc>>,
-    last_hash: Option<u64>,
+}
+
+impl FrameCapturer {
+    pub fn new(config: ScreenshotConfig) -> Self {
+        Self {
+            config,
+            last_capture_time: None,
+        }
+    }
+
+    pub fn set_config(&mut self, config: ScreenshotConfig) {
+        self.config = config;
+    }
+
+    /// Whether the trigger mode and minimum interval allow a capture at `now`
+    fn capture_due(&self, transition: FocusTransition, now: DateTime<Utc>) -> bool {
+        let since_last = self
+            .last_capture_time
+            .map(|last_time| now.signed_duration_since(last_time));
+        let min_interval = Duration::seconds(self.config.min_interval_seconds as i64);
+        self.config
+            .capture_trigger
+            .should_capture(Some(transition), since_last, min_interval)
+    }
+
+    /// Grab a frame if the trigger mode and minimum interval allow it
+    /// The grab runs on a blocking thread (a Linux screenshot tool can take
+    /// seconds); storing the frame (`ScreenshotManager::store_image`) is left to
+    /// the capture worker
+    pub async fn capture_frame(&mut self, transition: FocusTransition) -> Option<DynamicImage> {
+        // Paused while the disk is nearly full
+        if crate::storage::screenshot_capture_suspended() {
+            return None;
+        }
+
+        // Check the trigger mode and minimum interval
+        let now = Utc::now();
+        if !self.capture_due(transition, now) {
+            return None;
+        }
+
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        {
+            let scale_factor = self.config.scale_factor;
+            let image = tokio::task::spawn_blocking(move || {
+                super::timings::timed(super::CollectorStep::Screenshot, || capture_screen(scale_factor))
+            })
+            .await
+            .ok()
+            .flatten()?;
+            self.last_capture_time = Some(now);
+            Some(image)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            crate::logs::error("[Screenshot] Screenshot capture is only supported on macOS and Linux");
+            None
+        }
+    }
+}
+
+/// Manager for storing and managing screenshots
+pub struct ScreenshotManager {
+    config: ScreenshotConfig,
+    /// Recent content hashes across the whole store (persisted)
+    hash_index: Vec<HashIndexEntry>,
+    /// Last full frame and its file, used as the base for delta frames
+    last_full_frame: Option<(RgbImage, PathBuf)>,
 }
 
 impl ScreenshotManager {
@@ -80,178 +695,243 @@ impl ScreenshotManager {
     pub fn new(config: ScreenshotConfig) -> Self {
         // Ensure storage directory exists
         if let Err(e) = fs::create_dir_all(&config.storage_path) {
-            eprintln!("Failed to create screenshot directory: {}", e);
+            crate::logs::error(&format!("[Screenshot] Failed to create screenshot directory: {}", e));
         }
 
+        let hash_index = Self::load_hash_index(&config.storage_path);
+
         Self {
             config,
-            last_capture_time: None,
-            last_hash: None,
+            hash_index,
+            last_full_frame: None,
         }
     }
 
-    /// Attempt to capture a screenshot, only if conditions are met
-    /// Returns Some(Screenshot) if capture was successful, None otherwise
-    pub async fn maybe_capture(
+    pub fn config(&self) -> &ScreenshotConfig {
+        &self.config
+    }
+
+    /// Switch to a new config; a different storage directory gets its own hash index
+    pub fn set_config(&mut self, config: ScreenshotConfig) {
+        if config.storage_path != self.config.storage_path {
+            if let Err(e) = fs::create_dir_all(&config.storage_path) {
+                crate::logs::error(&format!("[Screenshot] Failed to create screenshot directory: {}", e));
+            }
+            self.hash_index = Self::load_hash_index(&config.storage_path);
+            self.last_full_frame = None;
+        }
+        self.config = config;
+    }
+
+    /// Store a captured image, reusing an existing file if the same content was
+    /// already stored within the dedup window
+    pub fn store_image(
         &mut self,
+        image: DynamicImage,
         app_name: String,
         window_title: String,
     ) -> Option<Screenshot> {
-        // Check minimum interval
-        if let Some(last_time) = self.last_capture_time {
-            let elapsed = Utc::now().signed_duration_since(last_time);
-            if elapsed.num_seconds() < self.config.min_interval_seconds as i64 {
-                return None;
-            }
+        let hash = self.compute_image_hash(&image);
+
+        if let Some(existing) = self.find_similar(hash) {
+            let size_bytes = fs::metadata(&existing.path).map(|m| m.len()).unwrap_or(0);
+            return Some(Screenshot {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now(),
+                app_name,
+                window_title,
+                thumbnail_path: Some(thumbnail_path_for(&existing.path))
+                    .filter(|thumbnail| thumbnail.exists()),
+                path: existing.path,
+                size_bytes,
+                hash: Some(hash),
+                reused: true,
+            });
         }
 
-        // Capture screen
-        #[cfg(target_os = "macos")]
-        let image = self.capture_screen()?;
-
-        #[cfg(not(target_os = "macos"))]
-        {
-            eprintln!("Screenshot capture is only supported on macOS");
-            return None;
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            // Compute perceptual hash
-            let hash = self.compute_image_hash(&image);
-
-            // Check if content has changed
-            if let Some(last_hash) = self.last_hash {
-                let similarity = self.hash_similarity(last_hash, hash);
-                if similarity >= self.config.similarity_threshold {
-                    // Content hasn't changed significantly
-                    return None;
+        let screenshot = if self.config.delta_encoding {
+            let frame = image.to_rgb8();
+            match self.save_delta_frame(&frame, &app_name, &window_title, hash) {
+                Some(screenshot) => screenshot,
+                None => {
+                    // Not similar enough for a delta - store a new full base frame
+                    let screenshot = self.save_screenshot(image, app_name, window_title, hash)?;
+                    self.last_full_frame = Some((frame, screenshot.path.clone()));
+                    screenshot
                 }
             }
+        } else {
+            self.save_screenshot(image, app_name, window_title, hash)?
+        };
 
-            // Save screenshot
-            let screenshot = self.save_screenshot(image, app_name, window_title, hash)?;
+        self.index_screenshot(&screenshot, hash);
+        Some(screenshot)
+    }
 
-            // Update tracking
-            self.last_capture_time = Some(screenshot.timestamp);
-            self.last_hash = Some(hash);
+    /// Capture and store a screenshot right away, skipping the trigger mode,
+    /// minimum interval and similarity checks (manual "capture now")
+    pub fn capture_now(&mut self, app_name: String, window_title: String) -> Result<Screenshot, String> {
+        if crate::storage::screenshot_capture_suspended() {
+            return Err("Screenshots are paused while disk space is low".to_string());
+        }
 
-            Some(screenshot)
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        {
+            let image = capture_screen(self.config.scale_factor).ok_or("Failed to capture screen")?;
+            self.store_now(image, app_name, window_title)
+                .ok_or_else(|| "Failed to save screenshot".to_string())
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            let _ = (app_name, window_title);
+            Err("Screenshot capture is only supported on macOS and Linux".to_string())
         }
     }
 
-    /// Capture the screen using macOS Core Graphics
-    #[cfg(target_os = "macos")]
-    fn capture_screen(&self) -> Option<DynamicImage> {
-        use core_graphics::display::CGDisplay;
+    /// Store an image as a new full frame, even if similar content was stored recently
+    pub fn store_now(
+        &mut self,
+        image: DynamicImage,
+        app_name: String,
+        window_title: String,
+    ) -> Option<Screenshot> {
+        let hash = self.compute_image_hash(&image);
+        let frame = self.config.delta_encoding.then(|| image.to_rgb8());
 
-        unsafe {
-            // Get main display
-            let display_id = CGMainDisplayID();
-            let display = CGDisplay::new(display_id);
+        let screenshot = self.save_screenshot(image, app_name, window_title, hash)?;
+        if let Some(frame) = frame {
+            self.last_full_frame = Some((frame, screenshot.path.clone()));
+        }
 
-            // Capture display image
-            let cg_image = display.image()?;
+        self.index_screenshot(&screenshot, hash);
+        Some(screenshot)
+    }
 
-            // Convert CGImage to DynamicImage
-            self.cgimage_to_dynamic_image(&cg_image)
-        }
+    /// Remember a newly stored file so later captures with the same content reuse it
+    fn index_screenshot(&mut self, screenshot: &Screenshot, hash: u64) {
+        self.hash_index.push(HashIndexEntry {
+            hash,
+            algorithm: self.config.hash_algorithm,
+            path: screenshot.path.clone(),
+            timestamp: screenshot.timestamp,
+        });
+        self.prune_hash_index();
+        self.save_hash_index();
     }
 
-    /// Convert CGImage to DynamicImage using CGBitmapContext
-    #[cfg(target_os = "macos")]
-    fn cgimage_to_dynamic_image(
+    /// Store the frame as a delta against the last full frame, if it's mostly the same
+    fn save_delta_frame(
         &self,
-        cg_image: &core_graphics::image::CGImage,
-    ) -> Option<DynamicImage> {
-        use core_graphics::context::CGContext;
-        use core_graphics::color_space::CGColorSpace;
-
-        let width = cg_image.width();
-        let height = cg_image.height();
-
-        // Create a buffer to hold the pixel data (RGBA format)
-        let bytes_per_row = width * 4;
-        let mut buffer: Vec<u8> = vec![0; height * bytes_per_row];
-
-        // Create a bitmap context to render the image
-        let color_space = CGColorSpace::create_device_rgb();
-        let context = CGContext::create_bitmap_context(
-            Some(buffer.as_mut_ptr() as *mut _),
-            width,
-            height,
-            8,                  // bits per component
-            bytes_per_row,
-            &color_space,
-            core_graphics::base::kCGImageAlphaPremultipliedLast, // RGBA
-        );
+        frame: &RgbImage,
+        app_name: &str,
+        window_title: &str,
+        hash: u64,
+    ) -> Option<Screenshot> {
+        let (base, base_path) = self.last_full_frame.as_ref()?;
 
-        // Draw the CGImage into the context
-        let rect = core_graphics::geometry::CGRect::new(
-            &core_graphics::geometry::CGPoint::new(0.0, 0.0),
-            &core_graphics::geometry::CGSize::new(width as f64, height as f64),
-        );
-        context.draw_image(rect, cg_image);
-
-        // Create image buffer from RGBA data
-        let mut img_buffer = ImageBuffer::<Rgb<u8>, Vec<u8>>::new(width as u32, height as u32);
-
-        for y in 0..height {
-            for x in 0..width {
-                let offset = y * bytes_per_row + x * 4;
-                if offset + 3 <= buffer.len() {
-                    // RGBA format
-                    let r = buffer[offset];
-                    let g = buffer[offset + 1];
-                    let b = buffer[offset + 2];
-                    img_buffer.put_pixel(x as u32, y as u32, Rgb([r, g, b]));
-                }
-            }
+        let now = Utc::now();
+        let date_dir = self.config.storage_path.join(now.format("%Y-%m-%d").to_string());
+
+        // Keep deltas in the same date directory as their base so retention
+        // cleanup never removes a base that a delta still depends on
+        if base_path.parent() != Some(date_dir.as_path()) {
+            return None;
         }
 
-        // Scale down if needed
-        let scaled_image = if self.config.scale_factor < 1.0 {
-            let new_width = ((width as f32) * self.config.scale_factor) as u32;
-            let new_height = ((height as f32) * self.config.scale_factor) as u32;
-            DynamicImage::ImageRgb8(img_buffer).resize(
-                new_width,
-                new_height,
-                image::imageops::FilterType::Lanczos3,
-            )
-        } else {
-            DynamicImage::ImageRgb8(img_buffer)
+        let region = screenshot_delta::changed_region(base, frame)?;
+        let (width, height) = frame.dimensions();
+        if region.percent_of(width, height) > self.config.delta_max_changed_percent {
+            return None;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let meta_path = date_dir.join(format!(
+            "{}_{}.{}",
+            now.format("%H%M%S"),
+            crate::text::truncate_chars(&id, 8),
+            screenshot_delta::DELTA_EXTENSION
+        ));
+        let patch = screenshot_delta::encode_delta(frame, region);
+
+        let size_bytes = match screenshot_delta::save_delta(&meta_path, base_path, &patch, region) {
+            Ok(size) => size,
+            Err(e) => {
+                crate::logs::error(&format!("[Screenshot] {}", e));
+                return None;
+            }
         };
 
-        Some(scaled_image)
+        Some(Screenshot {
+            id,
+            timestamp: now,
+            app_name: app_name.to_string(),
+            window_title: window_title.to_string(),
+            path: meta_path,
+            size_bytes,
+            hash: Some(hash),
+            reused: false,
+            thumbnail_path: None,
+        })
     }
 
-    /// Compute a perceptual hash of the image for similarity comparison
-    fn compute_image_hash(&self, image: &DynamicImage) -> u64 {
-        use image::imageops::FilterType;
+    /// Find a stored screenshot within the dedup window with similar content
+    fn find_similar(&self, hash: u64) -> Option<HashIndexEntry> {
+        let cutoff = Utc::now() - Duration::hours(self.config.dedup_window_hours);
+
+        self.hash_index
+            .iter()
+            .rev()
+            .filter(|entry| {
+                entry.timestamp >= cutoff && entry.algorithm == self.config.hash_algorithm
+            })
+            .find(|entry| {
+                self.hash_similarity(entry.hash, hash) >= self.config.similarity_threshold
+                    && entry.path.exists()
+            })
+            .cloned()
+    }
 
-        // Resize to 8x8 for hash computation
-        let small = image.resize_exact(8, 8, FilterType::Lanczos3);
-        let gray = small.to_luma8();
+    /// Drop expired, deleted and excess entries from the hash index
+    fn prune_hash_index(&mut self) {
+        let cutoff = Utc::now() - Duration::hours(self.config.dedup_window_hours);
+        self.hash_index
+            .retain(|entry| entry.timestamp >= cutoff && entry.path.exists());
 
-        // Calculate average pixel value
-        let mut sum: u32 = 0;
-        for pixel in gray.pixels() {
-            sum += pixel[0] as u32;
+        if self.hash_index.len() > MAX_HASH_INDEX_ENTRIES {
+            let excess = self.hash_index.len() - MAX_HASH_INDEX_ENTRIES;
+            self.hash_index.drain(..excess);
         }
-        let avg = sum / 64;
+    }
+
+    /// Load the persisted hash index (empty if missing or unreadable)
+    fn load_hash_index(storage_path: &std::path::Path) -> Vec<HashIndexEntry> {
+        fs::read_to_string(storage_path.join(HASH_INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
 
-        // Build hash
-        let mut hash: u64 = 0;
-        for (i, pixel) in gray.pixels().enumerate() {
-            if pixel[0] as u32 > avg {
-                hash |= 1 << i;
+    /// Persist the hash index next to the screenshots
+    fn save_hash_index(&self) {
+        let path = self.config.storage_path.join(HASH_INDEX_FILE);
+        match serde_json::to_string(&self.hash_index) {
+            Ok(content) => {
+                if let Err(e) = fs::write(&path, content) {
+                    crate::logs::error(&format!("[Screenshot] Failed to write screenshot hash index: {}", e));
+                }
             }
+            Err(e) => crate::logs::error(&format!("[Screenshot] Failed to serialize screenshot hash index: {}", e)),
         }
+    }
 
-        hash
+    /// Compute a perceptual hash of the image for similarity comparison
+    fn compute_image_hash(&self, image: &DynamicImage) -> u64 {
+        self.config.hash_algorithm.hash(image)
     }
 
     /// Calculate similarity between two hashes (0-100, higher = more similar)
+    /// Both algorithms produce 64-bit hashes compared by Hamming distance
     fn hash_similarity(&self, hash1: u64, hash2: u64) -> u8 {
         let diff = (hash1 ^ hash2).count_ones();
         let max_diff = 64;
@@ -259,8 +939,7 @@ impl ScreenshotManager {
         similarity
     }
 
-    /// Save screenshot to disk as JPEG
-    #[cfg(target_os = "macos")]
+    /// Save screenshot to disk in the configured format
     fn save_screenshot(
         &self,
         image: DynamicImage,
@@ -275,34 +954,39 @@ impl ScreenshotManager {
         let date_dir = self.config.storage_path.join(&date_str);
 
         if let Err(e) = fs::create_dir_all(&date_dir) {
-            eprintln!("Failed to create date directory: {}", e);
+            crate::logs::error(&format!("[Screenshot] Failed to create date directory: {}", e));
             return None;
         }
 
         // Generate filename
         let id = Uuid::new_v4().to_string();
-        let filename = format!("{}_{}.jpg", now.format("%H%M%S"), &id[..8]);
+        let filename = format!(
+            "{}_{}.{}",
+            now.format("%H%M%S"),
+            crate::text::truncate_chars(&id, 8),
+            self.config.format.extension()
+        );
         let filepath = date_dir.join(&filename);
 
-        // Save as JPEG
-        let mut buffer = Vec::new();
-        {
-            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
-                &mut buffer,
-                self.config.jpeg_quality,
-            );
-            if let Err(e) = encoder.encode_image(&image) {
-                eprintln!("Failed to encode JPEG: {}", e);
+        let buffer = match encode_screenshot(&image, &self.config) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                crate::logs::error(&format!("[Screenshot] {}", e));
                 return None;
             }
-        }
+        };
 
         if let Err(e) = fs::write(&filepath, &buffer) {
-            eprintln!("Failed to write screenshot file: {}", e);
+            crate::logs::error(&format!("[Screenshot] Failed to write screenshot file: {}", e));
             return None;
         }
 
         let size_bytes = buffer.len() as u64;
+        let thumbnail_path = if self.config.thumbnails {
+            self.save_thumbnail(&image, &filepath)
+        } else {
+            None
+        };
 
         Some(Screenshot {
             id,
@@ -312,128 +996,180 @@ impl ScreenshotManager {
             path: filepath,
             size_bytes,
             hash: Some(hash),
+            reused: false,
+            thumbnail_path,
         })
     }
 
-    /// Delete screenshots older than the retention period
-    pub async fn cleanup_old_screenshots(&self) -> Result<usize, std::io::Error> {
-        let cutoff_date = Utc::now() - Duration::days(self.config.retention_days);
-        let mut deleted_count = 0;
+    /// Write `<name>_thumb.jpg` next to a saved screenshot
+    fn save_thumbnail(&self, image: &DynamicImage, path: &Path) -> Option<PathBuf> {
+        let thumbnail = make_thumbnail(image, self.config.thumbnail_width);
+        let thumbnail_path = thumbnail_path_for(path);
+
+        let result = encode_jpeg(&thumbnail, self.config.jpeg_options()).and_then(|buffer| {
+            fs::write(&thumbnail_path, buffer)
+                .map_err(|e| format!("Failed to write thumbnail: {}", e))
+        });
+        match result {
+            Ok(()) => Some(thumbnail_path),
+            Err(e) => {
+                crate::logs::error(&format!("[Screenshot] {}", e));
+                None
+            }
+        }
+    }
 
-        // Iterate through date directories
-        let entries = fs::read_dir(&self.config.storage_path)?;
+    /// Get statistics about stored screenshots
+    pub fn get_stats(&self) -> ScreenshotStats {
+        screenshot_stats(&self.config.storage_path)
+    }
+}
 
-        for entry in entries {
-            let entry = entry?;
+/// Statistics about the screenshots stored under `storage_path`
+pub fn screenshot_stats(storage_path: &Path) -> ScreenshotStats {
+    let mut stats = ScreenshotStats {
+        total_screenshots: 0,
+        total_size_bytes: 0,
+        oldest_screenshot: None,
+        newest_screenshot: None,
+        screenshots_by_date: HashMap::new(),
+    };
+
+    // Iterate through all screenshot files
+    if let Ok(entries) = fs::read_dir(storage_path) {
+        for entry in entries.flatten() {
             let path = entry.path();
 
             if path.is_dir() {
-                // Check if directory name is a date
                 if let Some(dirname) = path.file_name().and_then(|n| n.to_str()) {
-                    // Try to parse as date
-                    if let Ok(dir_date) = chrono::NaiveDate::parse_from_str(dirname, "%Y-%m-%d") {
-                        // Safely convert to datetime, skip if fails
-                        let Some(naive_dt) = dir_date.and_hms_opt(0, 0, 0) else {
-                            continue;
-                        };
-                        let chrono::LocalResult::Single(dir_datetime) = naive_dt.and_local_timezone(Utc) else {
-                            continue;
-                        };
-
-                        if dir_datetime < cutoff_date {
-                            // Delete entire directory
-                            match fs::remove_dir_all(&path) {
-                                Ok(_) => {
-                                    deleted_count += 1;
-                                    println!("Deleted old screenshot directory: {:?}", path);
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to delete directory {:?}: {}", path, e);
+                    if let Ok(_) = chrono::NaiveDate::parse_from_str(dirname, "%Y-%m-%d") {
+                        // Count files in this date directory
+                        let mut count = 0;
+                        if let Ok(files) = fs::read_dir(&path) {
+                            for file in files.flatten() {
+                                let file_path = file.path();
+
+                                // Delta patches and thumbnails count towards size but not as separate screenshots
+                                if screenshot_delta::is_patch_path(&file_path) || is_thumbnail_path(&file_path) {
+                                    if let Ok(metadata) = file.metadata() {
+                                        stats.total_size_bytes += metadata.len();
+                                    }
+                                    continue;
                                 }
-                            }
-                        }
-                    }
-                }
-            }
-        }
 
-        Ok(deleted_count)
-    }
+                                if has_screenshot_extension(&file_path) || screenshot_delta::is_delta_path(&file_path) {
+                                    count += 1;
+                                    stats.total_screenshots += 1;
 
-    /// Get statistics about stored screenshots
-    pub fn get_stats(&self) -> ScreenshotStats {
-        let mut stats = ScreenshotStats {
-            total_screenshots: 0,
-            total_size_bytes: 0,
-            oldest_screenshot: None,
-            newest_screenshot: None,
-            screenshots_by_date: HashMap::new(),
-        };
+                                    // Get file size
+                                    if let Ok(metadata) = file.metadata() {
+                                        stats.total_size_bytes += metadata.len();
+                                    }
 
-        // Iterate through all screenshot files
-        if let Ok(entries) = fs::read_dir(&self.config.storage_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                if path.is_dir() {
-                    if let Some(dirname) = path.file_name().and_then(|n| n.to_str()) {
-                        if let Ok(_) = chrono::NaiveDate::parse_from_str(dirname, "%Y-%m-%d") {
-                            // Count files in this date directory
-                            let mut count = 0;
-                            if let Ok(files) = fs::read_dir(&path) {
-                                for file in files.flatten() {
-                                    if file.path().extension().and_then(|s| s.to_str()) == Some("jpg") {
-                                        count += 1;
-                                        stats.total_screenshots += 1;
-
-                                        // Get file size
-                                        if let Ok(metadata) = file.metadata() {
-                                            stats.total_size_bytes += metadata.len();
-                                        }
+                                    // Parse timestamp from filename
+                                    if let Some(filename) = file.file_name().to_str() {
+                                        if let Some(date_time_str) = parse_filename_timestamp(dirname, filename) {
+                                            if let Ok(dt) = DateTime::parse_from_rfc3339(&date_time_str) {
+                                                let utc_dt = dt.with_timezone(&Utc);
 
-                                        // Parse timestamp from filename
-                                        if let Some(filename) = file.file_name().to_str() {
-                                            if let Some(date_time_str) = self.parse_filename_timestamp(dirname, filename) {
-                                                if let Ok(dt) = DateTime::parse_from_rfc3339(&date_time_str) {
-                                                    let utc_dt = dt.with_timezone(&Utc);
-
-                                                    if stats.oldest_screenshot.is_none() || Some(utc_dt) < stats.oldest_screenshot {
-                                                        stats.oldest_screenshot = Some(utc_dt);
-                                                    }
-                                                    if stats.newest_screenshot.is_none() || Some(utc_dt) > stats.newest_screenshot {
-                                                        stats.newest_screenshot = Some(utc_dt);
-                                                    }
+                                                if stats.oldest_screenshot.is_none() || Some(utc_dt) < stats.oldest_screenshot {
+                                                    stats.oldest_screenshot = Some(utc_dt);
+                                                }
+                                                if stats.newest_screenshot.is_none() || Some(utc_dt) > stats.newest_screenshot {
+                                                    stats.newest_screenshot = Some(utc_dt);
                                                 }
                                             }
                                         }
                                     }
                                 }
                             }
-                            stats.screenshots_by_date.insert(dirname.to_string(), count);
                         }
+                        stats.screenshots_by_date.insert(dirname.to_string(), count);
                     }
                 }
             }
         }
+    }
+
+    stats
+}
 
-        stats
+/// RFC 3339 timestamp of a screenshot from its date directory and filename
+fn parse_filename_timestamp(date: &str, filename: &str) -> Option<String> {
+    // Filename format: HHMMSS_uuid.<jpg|webp|png>
+    let parts: Vec<&str> = filename.split('_').collect();
+    if parts.len() >= 1 {
+        let time_str = parts[0];
+        if time_str.len() == 6 {
+            let hour = &time_str[0..2];
+            let minute = &time_str[2..4];
+            let second = &time_str[4..6];
+            return Some(format!("{}T{}:{}:{}Z", date, hour, minute, second));
+        }
     }
+    None
+}
+
+/// What a manual screenshot cleanup removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CleanupSummary {
+    pub directories_removed: usize,
+    pub bytes_freed: u64,
+}
+
+/// Remove date directories under `root` (blocking)
+/// First those older than `older_than_days` before `today`, then the oldest
+/// remaining ones until `free_bytes` plus what was freed reaches
+/// `target_free_bytes`. Today's directory is never removed.
+pub fn cleanup_screenshot_dirs(
+    root: &Path,
+    today: chrono::NaiveDate,
+    older_than_days: Option<i64>,
+    free_bytes: u64,
+    target_free_bytes: Option<u64>,
+) -> Result<CleanupSummary, String> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(CleanupSummary::default()),
+        Err(e) => return Err(format!("Failed to read screenshot directory: {}", e)),
+    };
+
+    let mut dated: Vec<(chrono::NaiveDate, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let dirname = path.file_name()?.to_str()?;
+            let date = chrono::NaiveDate::parse_from_str(dirname, "%Y-%m-%d").ok()?;
+            (date < today).then_some((date, path))
+        })
+        .collect();
+    dated.sort();
+
+    let cutoff = older_than_days.map(|days| today - Duration::days(days));
+    let mut summary = CleanupSummary::default();
+
+    for (date, path) in dated {
+        let too_old = cutoff.is_some_and(|cutoff| date < cutoff);
+        let need_space =
+            target_free_bytes.is_some_and(|target| free_bytes + summary.bytes_freed < target);
+        if !too_old && !need_space {
+            // Sorted oldest first, so nothing later qualifies either
+            break;
+        }
 
-    /// Helper to parse timestamp from filename
-    fn parse_filename_timestamp(&self, date: &str, filename: &str) -> Option<String> {
-        // Filename format: HHMMSS_uuid.jpg
-        let parts: Vec<&str> = filename.split('_').collect();
-        if parts.len() >= 1 {
-            let time_str = parts[0];
-            if time_str.len() == 6 {
-                let hour = &time_str[0..2];
-                let minute = &time_str[2..4];
-                let second = &time_str[4..6];
-                return Some(format!("{}T{}:{}:{}Z", date, hour, minute, second));
+        let size = crate::storage::dir_size(&path);
+        match fs::remove_dir_all(&path) {
+            Ok(()) => {
+                crate::logs::info(&format!("[Screenshot] Deleted screenshot directory: {:?}", path));
+                summary.directories_removed += 1;
+                summary.bytes_freed += size;
             }
+            Err(e) => crate::logs::error(&format!("[Screenshot] Failed to delete directory {:?}: {}", path, e)),
         }
-        None
     }
+
+    Ok(summary)
 }
 
 #[cfg(test)]
@@ -459,6 +1195,39 @@ mod tests {
         assert!(similarity > 95);
     }
 
+    #[test]
+    fn test_dhash_robust_to_lighting_change() {
+        // Blocky texture, then the same texture lit darker at the top and brighter at the bottom
+        let texture = |x: u32, y: u32| 70 + ((x / 8) * 37 + (y / 8) * 11) % 61;
+        let lit = |shift: fn(u32) -> i32| {
+            DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+                let v = (texture(x, y) as i32 + shift(y)).clamp(0, 255) as u8;
+                image::Rgb([v, v, v])
+            }))
+        };
+        let original = lit(|_| 0);
+        let relit = lit(|y| y as i32 * 2 - 64);
+
+        let manager = ScreenshotManager::new(ScreenshotConfig::default());
+        let similarity = |algorithm: HashAlgorithm| {
+            manager.hash_similarity(algorithm.hash(&original), algorithm.hash(&relit))
+        };
+
+        let ahash = similarity(HashAlgorithm::AHash);
+        let dhash = similarity(HashAlgorithm::DHash);
+        assert!(ahash < 90, "aHash similarity {}", ahash);
+        assert!(dhash >= 90, "dHash similarity {}", dhash);
+        assert!(dhash > ahash);
+    }
+
+    #[test]
+    fn test_hash_algorithm_config() {
+        assert_eq!(ScreenshotConfig::default().hash_algorithm, HashAlgorithm::AHash);
+
+        let algorithm: HashAlgorithm = serde_json::from_str(r#""dhash""#).unwrap();
+        assert_eq!(algorithm, HashAlgorithm::DHash);
+    }
+
     #[test]
     fn test_config_default() {
         let config = ScreenshotConfig::default();
@@ -467,14 +1236,507 @@ mod tests {
         assert_eq!(config.scale_factor, 0.5);
         assert_eq!(config.retention_days, 7);
         assert_eq!(config.similarity_threshold, 90);
+        assert_eq!(config.dedup_window_hours, 24);
+        assert!(!config.delta_encoding);
     }
 
     #[test]
-    fn test_parse_filename_timestamp() {
+    fn test_delta_mode_stores_changed_region_only() {
+        let mut config = test_config("delta");
+        config.delta_encoding = true;
+        // Disable similarity dedup so the near-identical frame isn't reused
+        config.similarity_threshold = 101;
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config);
+
+        let frame = |cursor_x: u32| {
+            RgbImage::from_fn(64, 48, |x, y| {
+                if (cursor_x..cursor_x + 2).contains(&x) && (10..20).contains(&y) {
+                    image::Rgb([0, 0, 0])
+                } else {
+                    image::Rgb([(x * 4) as u8, (y * 5) as u8, 200])
+                }
+            })
+        };
+
+        let base = manager
+            .store_image(DynamicImage::ImageRgb8(frame(5)), "Code".to_string(), "a".to_string())
+            .unwrap();
+        let delta = manager
+            .store_image(DynamicImage::ImageRgb8(frame(30)), "Code".to_string(), "a".to_string())
+            .unwrap();
+
+        assert!(!screenshot_delta::is_delta_path(&base.path));
+        assert!(screenshot_delta::is_delta_path(&delta.path));
+
+        // The changed region is reconstructed exactly on top of the base JPEG
+        let reconstructed = screenshot_delta::load_screenshot(&delta.path).unwrap().to_rgb8();
+        let expected = frame(30);
+        for y in 10..20 {
+            for x in 5..32 {
+                assert_eq!(reconstructed.get_pixel(x, y), expected.get_pixel(x, y));
+            }
+        }
+        assert_eq!(manager.get_stats().total_screenshots, 2);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_formats_are_saved_and_counted() {
+        let mut config = test_config("formats");
+        // Every frame is stored, even the identical ones
+        config.similarity_threshold = 101;
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config.clone());
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, 120])
+        }));
+
+        let mut saved = Vec::new();
+        for format in [ScreenshotFormat::Jpeg, ScreenshotFormat::WebP, ScreenshotFormat::Png] {
+            manager.set_config(ScreenshotConfig { format, ..config.clone() });
+            let screenshot = manager.store_image(image.clone(), "Code".to_string(), "a".to_string()).unwrap();
+            assert_eq!(screenshot.path.extension().unwrap(), format.extension());
+            saved.push(screenshot);
+        }
+
+        // Lossy formats decode to the same size, PNG to the exact pixels
+        for screenshot in &saved[..2] {
+            let decoded = screenshot_delta::load_screenshot(&screenshot.path).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (image.width(), image.height()));
+        }
+        let decoded = screenshot_delta::load_screenshot(&saved[2].path).unwrap();
+        assert_eq!(decoded.to_rgb8(), image.to_rgb8());
+
+        // A delta patch next to its metadata is not a screenshot of its own
+        let dir = saved[0].path.parent().unwrap();
+        fs::write(dir.join("235959_patch.png"), b"patch").unwrap();
+        fs::write(dir.join("235959_patch.delta.json"), b"{}").unwrap();
+        assert!(screenshot_delta::is_patch_path(&dir.join("235959_patch.png")));
+        assert!(!screenshot_delta::is_patch_path(&saved[2].path));
+
+        let stats = manager.get_stats();
+        assert_eq!(stats.total_screenshots, 4);
+        let saved_bytes: u64 = saved.iter().map(|screenshot| screenshot.size_bytes).sum();
+        assert_eq!(stats.total_size_bytes, saved_bytes + 5 + 2);
+
+        let format: ScreenshotFormat = serde_json::from_str(r#""webp""#).unwrap();
+        assert_eq!(format, ScreenshotFormat::WebP);
+        assert_eq!(ScreenshotConfig::default().format, ScreenshotFormat::Jpeg);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_webp_is_smaller_than_jpeg_on_ui_frame() {
+        // Flat panels, a sidebar and rows of dark "text" strokes, like an app window
+        let frame = DynamicImage::ImageRgb8(RgbImage::from_fn(800, 600, |x, y| {
+            let in_text_row = y % 24 < 12 && (x / 6) % 7 != 0;
+            if x < 200 {
+                if in_text_row && (20..160).contains(&x) {
+                    image::Rgb([90, 90, 96])
+                } else {
+                    image::Rgb([236, 238, 242])
+                }
+            } else if y < 48 {
+                image::Rgb([52, 120, 246])
+            } else if in_text_row && (230..760).contains(&x) {
+                image::Rgb([30, 30, 30])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        }));
         let config = ScreenshotConfig::default();
-        let manager = ScreenshotManager::new(config);
 
-        let result = manager.parse_filename_timestamp("2026-01-08", "143025_abc123def.jpg");
+        let jpeg = encode_screenshot(&frame, &ScreenshotConfig { format: ScreenshotFormat::Jpeg, ..config.clone() }).unwrap();
+        let webp = encode_screenshot(&frame, &ScreenshotConfig { format: ScreenshotFormat::WebP, ..config }).unwrap();
+        assert!(webp.len() < jpeg.len(), "webp {} bytes, jpeg {} bytes", webp.len(), jpeg.len());
+    }
+
+    #[test]
+    fn test_manager_honors_updated_config() {
+        let mut config = test_config("update");
+        config.min_interval_seconds = 10;
+        config.similarity_threshold = 90;
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config.clone());
+        let mut capturer = FrameCapturer::new(config.clone());
+
+        let now = Utc::now();
+        capturer.last_capture_time = Some(now - Duration::seconds(3));
+        assert!(!capturer.capture_due(FocusTransition::TitleChange, now));
+
+        // A bright patch in one corner: ~89% similar, so not reused at 90
+        let frame = |patch: bool| {
+            DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, y| {
+                if patch && x < 16 && y < 12 {
+                    image::Rgb([255, 255, 255])
+                } else {
+                    image::Rgb([(x * 4) as u8, (y * 5) as u8, 200])
+                }
+            }))
+        };
+        manager.store_image(frame(false), "Code".to_string(), "a".to_string()).unwrap();
+        let patched = manager.store_image(frame(true), "Code".to_string(), "a".to_string()).unwrap();
+        assert!(!patched.reused);
+
+        let updated = ScreenshotConfig {
+            min_interval_seconds: 2,
+            similarity_threshold: 80,
+            ..config
+        };
+        manager.set_config(updated.clone());
+        capturer.set_config(updated);
+        assert!(capturer.capture_due(FocusTransition::TitleChange, now));
+        // Drop the stored patched frame so only the original can be reused
+        fs::remove_file(&patched.path).ok();
+        let patched = manager.store_image(frame(true), "Code".to_string(), "a".to_string()).unwrap();
+        assert!(patched.reused);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_capture_now_ignores_interval_and_similarity() {
+        let mut config = test_config("capture_now");
+        config.min_interval_seconds = 60;
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config.clone());
+
+        let now = Utc::now();
+        let mut capturer = FrameCapturer::new(config);
+        capturer.last_capture_time = Some(now - Duration::seconds(5));
+        assert!(!capturer.capture_due(FocusTransition::AppSwitch, now));
+
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, 120])
+        }));
+        let first = manager.store_now(image.clone(), "Code".to_string(), "main.rs".to_string()).unwrap();
+        let second = manager.store_now(image, "Code".to_string(), "main.rs".to_string()).unwrap();
+        assert!(!first.reused && !second.reused);
+        assert!(second.path.exists());
+        assert_eq!(count_jpgs(&root), 2);
+
+        let db = std::sync::Arc::new(crate::db::EventDatabase::in_memory().unwrap());
+        let mut state = crate::AppState::new(db, Vec::new());
+        let event = crate::collector::add_manual_screenshot(&mut state, &second, &Default::default());
+        assert_eq!(event.event_type, "screenshot");
+        assert_eq!(event.data["source"], "manual");
+        assert_eq!(state.events_buffer.len(), 1);
+        assert_eq!(
+            state.events_buffer[0].screenshot_path.as_deref(),
+            Some(second.path.to_string_lossy().as_ref())
+        );
+        assert_eq!(state.db.count().unwrap(), 1);
+
+        // Apps in categories excluded from screenshots can't be captured manually either
+        let only_browsing = vec!["browsing".to_string()];
+        assert!(crate::collector::manual_screenshot_allowed("Visual Studio Code", &only_browsing).is_err());
+        assert!(crate::collector::manual_screenshot_allowed("Visual Studio Code", &[]).is_ok());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(ScreenshotConfig::default().validate().is_ok());
+
+        let invalid = [
+            ScreenshotConfig { jpeg_quality: 101, ..ScreenshotConfig::default() },
+            ScreenshotConfig { scale_factor: 0.0, ..ScreenshotConfig::default() },
+            ScreenshotConfig { scale_factor: 1.5, ..ScreenshotConfig::default() },
+            ScreenshotConfig { scale_factor: f32::NAN, ..ScreenshotConfig::default() },
+            ScreenshotConfig { similarity_threshold: 101, ..ScreenshotConfig::default() },
+            ScreenshotConfig { retention_days: 0, ..ScreenshotConfig::default() },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?} should be rejected", config);
+        }
+        assert!(ScreenshotConfig { scale_factor: 1.0, jpeg_quality: 0, ..ScreenshotConfig::default() }
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_decode_padded_premultiplied_bgra() {
+        // 2x2 image, rows padded from 8 to 12 bytes (padding filled with junk)
+        let data: Vec<u8> = vec![
+            // Opaque red, opaque blue (BGRA)
+            0, 0, 255, 255, 255, 0, 0, 255, 0xAA, 0xAA, 0xAA, 0xAA,
+            // Half-transparent white (premultiplied to 128), transparent
+            128, 128, 128, 128, 9, 9, 9, 0, 0xAA, 0xAA, 0xAA, 0xAA,
+        ];
+        let layout = PixelLayout {
+            width: 2,
+            height: 2,
+            bytes_per_row: 12,
+            order: ChannelOrder::Bgra,
+            premultiplied: true,
+        };
+
+        let image = decode_pixels(&data, layout).unwrap();
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [255, 255, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [0, 0, 0]);
+
+        // Same bytes read as straight RGBA keep their order and values
+        let rgba = decode_pixels(&data, PixelLayout { order: ChannelOrder::Rgba, premultiplied: false, ..layout }).unwrap();
+        assert_eq!(rgba.get_pixel(0, 0).0, [0, 0, 255]);
+        assert_eq!(rgba.get_pixel(0, 1).0, [128, 128, 128]);
+
+        // The last row needs no padding after it, but must be complete
+        assert!(decode_pixels(&data[..20], layout).is_some());
+        assert!(decode_pixels(&data[..19], layout).is_none());
+        assert!(decode_pixels(&data, PixelLayout { bytes_per_row: 7, ..layout }).is_none());
+    }
+
+    #[test]
+    fn test_capture_gated_by_category() {
+        // Empty list = capture everything
+        assert!(category_allows_capture(Some("browsing"), &[]));
+        assert!(category_allows_capture(None, &[]));
+
+        let allowed = vec!["coding".to_string(), "writing".to_string()];
+        assert!(category_allows_capture(Some("coding"), &allowed));
+        assert!(category_allows_capture(Some("Writing"), &allowed));
+        assert!(!category_allows_capture(Some("browsing"), &allowed));
+        assert!(!category_allows_capture(None, &allowed));
+    }
+
+    fn test_config(name: &str) -> ScreenshotConfig {
+        ScreenshotConfig {
+            storage_path: std::env::temp_dir()
+                .join(format!("observer_screenshots_{}_{}", name, Uuid::new_v4())),
+            ..ScreenshotConfig::default()
+        }
+    }
+
+    fn count_jpgs(root: &std::path::Path) -> usize {
+        fs::read_dir(root)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .flat_map(|dir| fs::read_dir(dir.path()).unwrap().flatten())
+            .filter(|file| file.path().extension().and_then(|s| s.to_str()) == Some("jpg"))
+            .count()
+    }
+
+    #[test]
+    fn test_same_content_is_stored_once() {
+        let config = test_config("dedup");
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config);
+
+        let image = DynamicImage::ImageRgb8(image::ImageBuffer::from_fn(64, 64, |x, _| {
+            if x < 32 { image::Rgb([0, 0, 0]) } else { image::Rgb([255, 255, 255]) }
+        }));
+
+        let first = manager
+            .store_image(image.clone(), "Code".to_string(), "main.rs".to_string())
+            .unwrap();
+        let second = manager
+            .store_image(image, "Safari".to_string(), "Docs".to_string())
+            .unwrap();
+
+        assert!(!first.reused);
+        assert!(second.reused);
+        assert_eq!(count_jpgs(&root), 1);
+
+        // Both events point at the single stored file
+        let events: Vec<crate::collector::Event> = [first, second]
+            .into_iter()
+            .map(|shot| {
+                let mut event = crate::collector::Event::new(
+                    "app_focus",
+                    Some(shot.app_name.clone()),
+                    Some(shot.window_title.clone()),
+                );
+                event.screenshot_path = Some(shot.path.to_string_lossy().to_string());
+                event
+            })
+            .collect();
+        assert_eq!(events[0].screenshot_path, events[1].screenshot_path);
+
+        // The index survives a restart
+        let reloaded = ScreenshotManager::new(ScreenshotConfig {
+            storage_path: root.clone(),
+            ..ScreenshotConfig::default()
+        });
+        assert_eq!(reloaded.hash_index.len(), 1);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_capture_writes_thumbnail() {
+        let mut config = test_config("thumbs");
+        config.thumbnails = true;
+        config.thumbnail_width = 40;
+        let root = config.storage_path.clone();
+        let mut manager = ScreenshotManager::new(config);
+
+        let image = DynamicImage::ImageRgb8(image::ImageBuffer::from_fn(200, 100, |x, y| {
+            image::Rgb([x as u8, y as u8, 128])
+        }));
+        let shot = manager
+            .store_image(image, "Code".to_string(), "main.rs".to_string())
+            .unwrap();
+
+        let thumbnail_path = shot.thumbnail_path.clone().unwrap();
+        assert_eq!(thumbnail_path, thumbnail_path_for(&shot.path));
+        assert!(thumbnail_path.to_string_lossy().ends_with("_thumb.jpg"));
+
+        let full = image::open(&shot.path).unwrap();
+        assert_eq!((full.width(), full.height()), (200, 100));
+        let thumbnail = image::open(&thumbnail_path).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (40, 20));
+
+        // Served as-is, and not counted as a separate screenshot
+        let served = load_thumbnail(manager.config(), &shot.path).unwrap();
+        assert_eq!(served, fs::read(&thumbnail_path).unwrap());
+        assert_eq!(manager.get_stats().total_screenshots, 1);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_thumbnail_outside_storage_rejected() {
+        let config = test_config("thumbs_outside");
+        let root = config.storage_path.clone();
+        fs::create_dir_all(&root).unwrap();
+
+        let outside = std::env::temp_dir().join(format!("observer_outside_{}.jpg", Uuid::new_v4()));
+        fs::write(&outside, b"not really a jpeg").unwrap();
+        assert!(load_thumbnail(&config, &outside).is_err());
+
+        fs::remove_file(&outside).ok();
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_parse_filename_timestamp() {
+        let result = parse_filename_timestamp("2026-01-08", "143025_abc123def.jpg");
         assert_eq!(result, Some("2026-01-08T14:30:25Z".to_string()));
     }
+
+    fn dated_dirs(sizes: &[(&str, usize)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("observer_cleanup_{}", Uuid::new_v4()));
+        for (date, size) in sizes {
+            let dir = root.join(date);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("120000_abcdef12.jpg"), vec![0u8; *size]).unwrap();
+        }
+        root
+    }
+
+    fn remaining(root: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(root)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_cleanup_by_age() {
+        let root = dated_dirs(&[
+            ("2026-01-01", 100),
+            ("2026-01-05", 200),
+            ("2026-01-08", 300),
+            ("2026-01-10", 400),
+        ]);
+        fs::create_dir_all(root.join("not-a-date")).unwrap();
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+
+        let summary = cleanup_screenshot_dirs(&root, today, Some(3), 0, None).unwrap();
+        assert_eq!(
+            summary,
+            CleanupSummary {
+                directories_removed: 2,
+                bytes_freed: 300,
+            }
+        );
+        assert_eq!(remaining(&root), vec!["2026-01-08", "2026-01-10", "not-a-date"]);
+
+        // Even an age of zero keeps today's screenshots
+        let summary = cleanup_screenshot_dirs(&root, today, Some(0), 0, None).unwrap();
+        assert_eq!(summary.directories_removed, 1);
+        assert_eq!(remaining(&root), vec!["2026-01-10", "not-a-date"]);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_cleanup_to_free_space_target() {
+        let root = dated_dirs(&[
+            ("2026-01-01", 100),
+            ("2026-01-05", 200),
+            ("2026-01-08", 300),
+            ("2026-01-10", 400),
+        ]);
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+
+        // 1000 free, want 1250: the two oldest directories are enough
+        let summary = cleanup_screenshot_dirs(&root, today, None, 1_000, Some(1_250)).unwrap();
+        assert_eq!(
+            summary,
+            CleanupSummary {
+                directories_removed: 2,
+                bytes_freed: 300,
+            }
+        );
+        assert_eq!(remaining(&root), vec!["2026-01-08", "2026-01-10"]);
+
+        // An unreachable target stops at today's directory
+        let summary = cleanup_screenshot_dirs(&root, today, None, 0, Some(u64::MAX)).unwrap();
+        assert_eq!(summary.bytes_freed, 300);
+        assert_eq!(remaining(&root), vec!["2026-01-10"]);
+
+        // Already enough space: nothing to do
+        let summary = cleanup_screenshot_dirs(&root, today, None, 1_000, Some(500)).unwrap();
+        assert_eq!(summary, CleanupSummary::default());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_on_switch_captures_only_on_focus_change() {
+        let min_interval = Duration::seconds(5);
+        // (what changed this tick, seconds since the last capture)
+        let ticks = [
+            (Some(FocusTransition::AppSwitch), None),
+            (None, Some(1)),
+            (None, Some(30)),
+            (Some(FocusTransition::TitleChange), Some(60)),
+            (Some(FocusTransition::AppSwitch), Some(1)),
+            (None, Some(2)),
+        ];
+        let captures = |trigger: CaptureTrigger| -> Vec<bool> {
+            ticks
+                .iter()
+                .map(|(transition, since)| {
+                    trigger.should_capture(*transition, since.map(Duration::seconds), min_interval)
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            captures(CaptureTrigger::OnSwitch),
+            vec![true, false, false, false, true, false]
+        );
+        // Interval mode waits out the interval even for an app switch
+        assert_eq!(
+            captures(CaptureTrigger::Interval),
+            vec![true, false, false, true, false, false]
+        );
+        assert_eq!(
+            captures(CaptureTrigger::Both),
+            vec![true, false, false, true, true, false]
+        );
+    }
 }
@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use sysinfo::{System, CpuRefreshKind, MemoryRefreshKind, RefreshKind};
 
+/// Default EMA smoothing factor (higher = follows raw samples more closely)
+pub const DEFAULT_SMOOTHING_ALPHA: f32 = 0.3;
+
+/// Default seconds a metrics sample is reused before collecting a new one
+pub const DEFAULT_METRICS_INTERVAL_SECS: u64 = 10;
+
 /// System performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemMetrics {
@@ -13,11 +20,34 @@ pub struct SystemMetrics {
     pub ram_total: u64,
     /// RAM usage percentage (0-100)
     pub ram_usage_percent: f32,
+    /// EMA-smoothed CPU usage (only when smoothing is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_usage_smoothed: Option<f32>,
+    /// EMA-smoothed RAM usage percentage (only when smoothing is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ram_usage_percent_smoothed: Option<f32>,
+}
+
+/// Exponential moving average step
+/// The first sample seeds the average.
+pub fn ema(previous: Option<f32>, sample: f32, alpha: f32) -> f32 {
+    match previous {
+        Some(prev) => alpha * sample + (1.0 - alpha) * prev,
+        None => sample,
+    }
 }
 
 /// System metrics collector
 pub struct SystemMetricsCollector {
     system: Mutex<System>,
+    /// EMA smoothing factor (0-1), None disables smoothing
+    smoothing_alpha: Option<f32>,
+    /// Last smoothed (cpu, ram percent) values
+    smoothed: Mutex<Option<(f32, f32)>>,
+    /// How long a sample is reused; zero collects on every call
+    cache_window: Duration,
+    /// Last sample and when it was taken
+    cached: Mutex<Option<(Instant, SystemMetrics)>>,
 }
 
 impl SystemMetricsCollector {
@@ -31,14 +61,52 @@ impl SystemMetricsCollector {
 
         Self {
             system: Mutex::new(system),
+            smoothing_alpha: None,
+            smoothed: Mutex::new(None),
+            cache_window: Duration::ZERO,
+            cached: Mutex::new(None),
         }
     }
 
-    /// Collect current system metrics
+    /// Enable EMA smoothing with the given alpha (clamped to 0-1)
+    pub fn with_smoothing(mut self, alpha: f32) -> Self {
+        self.smoothing_alpha = Some(alpha.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Reuse each sample for `window` instead of collecting on every call
+    pub fn with_cache(mut self, window: Duration) -> Self {
+        self.cache_window = window;
+        self
+    }
+
+    /// Collect current system metrics, or the cached sample while it is fresh
     ///
     /// # Errors
     /// Returns an error if unable to lock the system instance
     pub fn collect(&self) -> Result<SystemMetrics, String> {
+        self.collect_at(Instant::now())
+    }
+
+    /// `collect` as of `now`
+    pub fn collect_at(&self, now: Instant) -> Result<SystemMetrics, String> {
+        let mut cached = self
+            .cached
+            .lock()
+            .map_err(|e| format!("Failed to lock metrics cache: {}", e))?;
+        if let Some((sampled_at, ref metrics)) = *cached {
+            if now.saturating_duration_since(sampled_at) < self.cache_window {
+                return Ok(metrics.clone());
+            }
+        }
+
+        let metrics = self.sample()?;
+        *cached = Some((now, metrics.clone()));
+        Ok(metrics)
+    }
+
+    /// Take a new sample from the system
+    fn sample(&self) -> Result<SystemMetrics, String> {
         let mut system = self
             .system
             .lock()
@@ -60,11 +128,28 @@ impl SystemMetricsCollector {
             0.0
         };
 
+        // Smooth on top of the raw samples, which are kept as-is
+        let (cpu_usage_smoothed, ram_usage_percent_smoothed) = match self.smoothing_alpha {
+            Some(alpha) => {
+                let mut smoothed = self
+                    .smoothed
+                    .lock()
+                    .map_err(|e| format!("Failed to lock smoothing state: {}", e))?;
+                let cpu = ema(smoothed.map(|(cpu, _)| cpu), cpu_usage, alpha);
+                let ram = ema(smoothed.map(|(_, ram)| ram), ram_usage_percent, alpha);
+                *smoothed = Some((cpu, ram));
+                (Some(cpu), Some(ram))
+            }
+            None => (None, None),
+        };
+
         Ok(SystemMetrics {
             cpu_usage,
             ram_used,
             ram_total,
             ram_usage_percent,
+            cpu_usage_smoothed,
+            ram_usage_percent_smoothed,
         })
     }
 
@@ -110,6 +195,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ema_known_sequence() {
+        let samples = [10.0, 20.0, 20.0, 0.0];
+        let mut value = None;
+        let mut results = Vec::new();
+        for sample in samples {
+            let next = ema(value, sample, 0.5);
+            results.push(next);
+            value = Some(next);
+        }
+
+        // 10 -> 0.5*20+0.5*10=15 -> 0.5*20+0.5*15=17.5 -> 0.5*0+0.5*17.5=8.75
+        assert_eq!(results, vec![10.0, 15.0, 17.5, 8.75]);
+    }
+
+    #[test]
+    fn test_collect_with_smoothing() {
+        let collector = SystemMetricsCollector::new().with_smoothing(DEFAULT_SMOOTHING_ALPHA);
+        let metrics = collector.collect().unwrap();
+        // First sample seeds the average
+        assert_eq!(metrics.cpu_usage_smoothed, Some(metrics.cpu_usage));
+        assert_eq!(metrics.ram_usage_percent_smoothed, Some(metrics.ram_usage_percent));
+
+        let unsmoothed = SystemMetricsCollector::new().collect().unwrap();
+        assert!(unsmoothed.cpu_usage_smoothed.is_none());
+    }
+
+    #[test]
+    fn test_collect_reuses_sample_within_cache_window() {
+        let collector = SystemMetricsCollector::new().with_cache(Duration::from_secs(10));
+        let start = Instant::now();
+
+        let first = collector.collect_at(start).unwrap();
+        // Mark the cached sample so a fresh one is distinguishable
+        collector.cached.lock().unwrap().as_mut().unwrap().1.cpu_usage = -1.0;
+
+        let cached = collector.collect_at(start + Duration::from_secs(9)).unwrap();
+        assert_eq!(cached.cpu_usage, -1.0);
+        assert_eq!(cached.ram_total, first.ram_total);
+
+        let fresh = collector.collect_at(start + Duration::from_secs(10)).unwrap();
+        assert!(fresh.cpu_usage >= 0.0);
+        // The new sample is cached from when it was taken
+        assert!(collector.collect_at(start + Duration::from_secs(15)).unwrap().cpu_usage >= 0.0);
+
+        // Without a cache window every call samples
+        let uncached = SystemMetricsCollector::new();
+        uncached.collect_at(start).unwrap();
+        uncached.cached.lock().unwrap().as_mut().unwrap().1.cpu_usage = -1.0;
+        assert!(uncached.collect_at(start).unwrap().cpu_usage >= 0.0);
+    }
+
     #[tokio::test]
     async fn test_collect_metrics_async() {
         let collector = SystemMetricsCollector::new();
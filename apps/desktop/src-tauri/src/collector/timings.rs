@@ -0,0 +1,167 @@
+// Per-step collector timings
+// Keeps the most recent durations of each expensive collector step (focus
+// lookup, metrics, browser tab, screenshot, OCR, messages) in a small ring so
+// the debug panel can show which one is costing CPU.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Samples kept per step
+pub const TIMING_WINDOW: usize = 200;
+
+static TIMINGS: Lazy<Mutex<CollectorTimings>> = Lazy::new(|| Mutex::new(CollectorTimings::new(TIMING_WINDOW)));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectorStep {
+    Focus,
+    Metrics,
+    BrowserTab,
+    Screenshot,
+    Ocr,
+    Messages,
+}
+
+impl CollectorStep {
+    pub const ALL: [CollectorStep; 6] = [
+        CollectorStep::Focus,
+        CollectorStep::Metrics,
+        CollectorStep::BrowserTab,
+        CollectorStep::Screenshot,
+        CollectorStep::Ocr,
+        CollectorStep::Messages,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Summary of one step's recent durations, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepTimings {
+    pub step: CollectorStep,
+    pub samples: usize,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Rolling window of the last `window` durations per step
+#[derive(Debug)]
+pub struct CollectorTimings {
+    window: usize,
+    samples: Vec<VecDeque<Duration>>,
+}
+
+impl CollectorTimings {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: vec![VecDeque::new(); CollectorStep::ALL.len()],
+        }
+    }
+
+    pub fn record(&mut self, step: CollectorStep, duration: Duration) {
+        let samples = &mut self.samples[step.index()];
+        if samples.len() >= self.window {
+            samples.pop_front();
+        }
+        samples.push_back(duration);
+    }
+
+    /// Summaries of the steps that have samples, in pipeline order
+    pub fn summaries(&self) -> Vec<StepTimings> {
+        CollectorStep::ALL
+            .iter()
+            .filter_map(|&step| summarize(step, &self.samples[step.index()]))
+            .collect()
+    }
+}
+
+/// Average, nearest-rank percentiles and max of `samples`
+pub fn summarize(step: CollectorStep, samples: &VecDeque<Duration>) -> Option<StepTimings> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let mut sorted: Vec<f64> = samples.iter().map(|duration| duration.as_secs_f64() * 1000.0).collect();
+    sorted.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    };
+
+    Some(StepTimings {
+        step,
+        samples: sorted.len(),
+        avg_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p50_ms: percentile(50.0),
+        p95_ms: percentile(95.0),
+        max_ms: sorted[sorted.len() - 1],
+    })
+}
+
+/// Record how long `step` took
+pub fn record_timing(step: CollectorStep, duration: Duration) {
+    TIMINGS.lock().unwrap().record(step, duration);
+}
+
+/// Run `f`, recording its duration under `step`
+pub fn timed<T>(step: CollectorStep, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record_timing(step, started.elapsed());
+    result
+}
+
+/// Recent timings per step
+pub fn collector_timings() -> Vec<StepTimings> {
+    TIMINGS.lock().unwrap().summaries()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_timing_aggregation() {
+        let mut timings = CollectorTimings::new(100);
+        for millis in 1..=100 {
+            timings.record(CollectorStep::Screenshot, ms(millis));
+        }
+        timings.record(CollectorStep::Focus, ms(4));
+
+        let summaries = timings.summaries();
+        // Steps without samples are left out; order follows the pipeline
+        let steps: Vec<CollectorStep> = summaries.iter().map(|summary| summary.step).collect();
+        assert_eq!(steps, vec![CollectorStep::Focus, CollectorStep::Screenshot]);
+
+        let focus = &summaries[0];
+        assert_eq!((focus.samples, focus.p50_ms, focus.p95_ms, focus.max_ms), (1, 4.0, 4.0, 4.0));
+
+        let screenshot = &summaries[1];
+        assert_eq!(screenshot.samples, 100);
+        assert!((screenshot.avg_ms - 50.5).abs() < 1e-9);
+        assert_eq!(screenshot.p50_ms, 50.0);
+        assert_eq!(screenshot.p95_ms, 95.0);
+        assert_eq!(screenshot.max_ms, 100.0);
+
+        // The window rolls: old samples drop out
+        for _ in 0..100 {
+            timings.record(CollectorStep::Screenshot, ms(2));
+        }
+        let screenshot = &timings.summaries()[1];
+        assert_eq!(screenshot.samples, 100);
+        assert_eq!(screenshot.max_ms, 2.0);
+    }
+}
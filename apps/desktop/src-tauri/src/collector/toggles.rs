@@ -0,0 +1,110 @@
+// Per-collector on/off switches
+// Screenshots, messages, browser tabs and system metrics can each be turned
+// off without stopping collection as a whole, either locally or by the server
+// (e.g. no screenshots during a compliance hold). The collector reads them on
+// every tick.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+static TOGGLES: CollectorSwitches = CollectorSwitches::new();
+
+/// Collectors that can be switched off individually
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectorKind {
+    Screenshots,
+    Messages,
+    BrowserTab,
+    Metrics,
+}
+
+/// Which collectors are currently enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorToggles {
+    pub screenshots: bool,
+    pub messages: bool,
+    pub browser_tab: bool,
+    pub metrics: bool,
+}
+
+impl CollectorToggles {
+    pub const ALL_ENABLED: Self = Self {
+        screenshots: true,
+        messages: true,
+        browser_tab: true,
+        metrics: true,
+    };
+
+    pub fn is_enabled(&self, collector: CollectorKind) -> bool {
+        match collector {
+            CollectorKind::Screenshots => self.screenshots,
+            CollectorKind::Messages => self.messages,
+            CollectorKind::BrowserTab => self.browser_tab,
+            CollectorKind::Metrics => self.metrics,
+        }
+    }
+
+    pub fn set(&mut self, collector: CollectorKind, enabled: bool) {
+        let flag = match collector {
+            CollectorKind::Screenshots => &mut self.screenshots,
+            CollectorKind::Messages => &mut self.messages,
+            CollectorKind::BrowserTab => &mut self.browser_tab,
+            CollectorKind::Metrics => &mut self.metrics,
+        };
+        *flag = enabled;
+    }
+}
+
+impl Default for CollectorToggles {
+    fn default() -> Self {
+        Self::ALL_ENABLED
+    }
+}
+
+/// Shared, changeable collector switches
+pub struct CollectorSwitches(RwLock<CollectorToggles>);
+
+impl CollectorSwitches {
+    pub const fn new() -> Self {
+        Self(RwLock::new(CollectorToggles::ALL_ENABLED))
+    }
+
+    /// Current switches
+    pub fn get(&self) -> CollectorToggles {
+        *self.0.read().unwrap()
+    }
+
+    /// Replace all switches at once
+    pub fn replace(&self, toggles: CollectorToggles) {
+        *self.0.write().unwrap() = toggles;
+    }
+
+    /// Turn one collector on or off, returning the updated switches
+    pub fn set(&self, collector: CollectorKind, enabled: bool) -> CollectorToggles {
+        let mut toggles = self.0.write().unwrap();
+        toggles.set(collector, enabled);
+        *toggles
+    }
+}
+
+/// The switches the collector reads
+pub fn global_switches() -> &'static CollectorSwitches {
+    &TOGGLES
+}
+
+/// Current collector switches
+pub fn collector_toggles() -> CollectorToggles {
+    TOGGLES.get()
+}
+
+/// Replace all switches at once
+pub fn replace_collector_toggles(toggles: CollectorToggles) {
+    TOGGLES.replace(toggles);
+}
+
+/// Turn one collector on or off, returning the updated switches
+pub fn set_collector_enabled(collector: CollectorKind, enabled: bool) -> CollectorToggles {
+    TOGGLES.set(collector, enabled)
+}
@@ -22,6 +22,9 @@ pub struct Stats {
     pub buffer_size: usize,
     #[serde(rename = "bufferCapacity")]
     pub buffer_capacity: usize,
+    /// End of the running high-fidelity recording
+    #[serde(rename = "highFidelityUntil")]
+    pub high_fidelity_until: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Serialize)]
@@ -62,6 +65,7 @@ pub async fn get_stats(state: State<'_, Arc<Mutex<AppState>>>) -> Result<Stats,
         },
         buffer_size: state.events_buffer.len(),
         buffer_capacity: crate::MAX_BUFFER_SIZE,
+        high_fidelity_until: crate::collector::recording_status().until,
     })
 }
 
@@ -103,6 +107,15 @@ pub async fn get_detailed_stats(
     })
 }
 
+/// Reset counters and top apps for a fresh tracking period (events are kept)
+#[tauri::command]
+pub async fn reset_stats(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let mut state = state.lock().await;
+    state.reset_stats();
+    crate::logs::info(&format!("[Stats] Reset counters ({} events kept)", state.events_buffer.len()));
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn toggle_collection(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let mut state = state.lock().await;
@@ -114,7 +127,19 @@ pub async fn toggle_collection(state: State<'_, Arc<Mutex<AppState>>>) -> Result
 pub async fn sync_now(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     // Actually sync events to server using the sync module
     let state_arc = state.inner().clone();
-    manual_sync(state_arc).await
+    manual_sync(state_arc).await.map(|_| ())
+}
+
+/// Write any buffered events missing from the database, then sync right away
+/// For a "make sure everything is saved" button, before exports and in tests
+#[tauri::command]
+pub async fn flush_and_sync(state: State<'_, Arc<Mutex<AppState>>>) -> Result<crate::sync::FlushResult, String> {
+    let result = crate::sync::flush_and_sync(&state).await?;
+    crate::logs::info(&format!(
+        "[Sync] Flush: {} events saved, {} synced, {} remaining",
+        result.persisted, result.synced, result.remaining
+    ));
+    Ok(result)
 }
 
 #[tauri::command]
@@ -146,6 +171,28 @@ pub fn get_focus() -> Option<FocusInfo> {
     get_current_focus()
 }
 
+/// Get a snapshot of the current focus, system metrics and browser tab
+/// With `include_ocr`, also OCRs the focused window (bounded in size and time)
+#[tauri::command]
+pub async fn get_current_context(
+    include_ocr: bool,
+) -> Result<crate::collector::CurrentContext, String> {
+    tokio::task::spawn_blocking(move || {
+        crate::collector::compose_context(&crate::collector::SystemContext, include_ocr)
+    })
+    .await
+    .map_err(|e| format!("Failed to get current context: {}", e))
+}
+
+/// Dump the frontmost app's accessibility tree as JSON (for automation debugging)
+/// Bounded by `max_depth` levels and `max_nodes` elements; walked off the main thread
+#[tauri::command]
+pub async fn dump_ax_tree(max_depth: usize, max_nodes: usize) -> Result<serde_json::Value, String> {
+    tokio::task::spawn_blocking(move || crate::collector::dump_focused_app_tree(max_depth, max_nodes))
+        .await
+        .map_err(|e| format!("Failed to dump accessibility tree: {}", e))?
+}
+
 /// Notify that window visibility changed (for tray icon sync)
 #[tauri::command]
 pub fn set_window_visible(visible: bool) {
@@ -207,22 +254,62 @@ pub fn open_settings() -> Result<(), String> {
 // AUTOMATION COMMANDS
 // ============================================================================
 
-/// Check all automation permissions
+/// Check all automation permissions (cached for a few seconds)
 #[tauri::command]
-pub fn check_all_permissions() -> permissions::AllPermissions {
-    permissions::check_all_permissions()
+pub fn check_all_permissions(
+    manager: State<'_, Arc<permissions::PermissionManager>>,
+) -> permissions::AllPermissions {
+    manager.check_all()
 }
 
+/// Force a fresh permission check, bypassing the cache
+#[tauri::command]
+pub fn refresh_permissions(
+    manager: State<'_, Arc<permissions::PermissionManager>>,
+) -> permissions::AllPermissions {
+    manager.refresh()
+}
+
+/// How long to poll for a grant after requesting a permission
+const PERMISSION_POLL_TIMEOUT_SECS: u64 = 60;
+
 /// Request specific permission
+/// If not granted right away, polls in the background and emits
+/// `permissions-changed` once the user grants it in System Settings
 #[tauri::command]
-pub fn request_permission(permission: String) -> Result<bool, String> {
+pub fn request_permission(
+    app: tauri::AppHandle,
+    manager: State<'_, Arc<permissions::PermissionManager>>,
+    permission: String,
+) -> Result<bool, String> {
     let perm_type = match permission.as_str() {
         "accessibility" => permissions::PermissionType::Accessibility,
         "screen_recording" => permissions::PermissionType::ScreenRecording,
         _ => return Err(format!("Unknown permission type: {}", permission)),
     };
 
-    permissions::request_permission(perm_type)
+    let granted = permissions::request_permission(perm_type)?;
+    manager.invalidate_cache();
+
+    if !granted {
+        let manager = manager.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            use tauri::Emitter;
+
+            let granted = crate::startup::wait_until(
+                move || permissions::check_permission(perm_type).granted,
+                std::time::Duration::from_secs(1),
+                std::time::Duration::from_secs(PERMISSION_POLL_TIMEOUT_SECS),
+            )
+            .await;
+
+            if granted {
+                let _ = app.emit(permissions::PERMISSIONS_CHANGED_EVENT, manager.refresh());
+            }
+        });
+    }
+
+    Ok(granted)
 }
 
 /// Open permission settings
@@ -281,10 +368,28 @@ pub fn automation_screenshot() -> Result<String, String> {
 
 /// Capture screenshot as JPEG
 #[tauri::command]
-pub fn automation_screenshot_jpeg(quality: Option<u8>) -> Result<String, String> {
+pub fn automation_screenshot_jpeg(
+    quality: Option<u8>,
+    subsampling: Option<crate::jpeg::ChromaSubsampling>,
+) -> Result<String, String> {
     let image = automation::screen::capture_screenshot()?;
     let q = quality.unwrap_or(85);
-    automation::screen::encode_to_base64_jpeg(&image, q)
+    let options = crate::jpeg::JpegOptions::new(q).with_subsampling(subsampling.unwrap_or_default());
+    automation::screen::encode_to_base64_jpeg(&image, options)
+}
+
+/// Capture one app window (focused or not) as base64 PNG
+#[tauri::command]
+pub async fn automation_screenshot_window(
+    app_name: String,
+    window_title: Option<String>,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let image = automation::screen::capture_window(&app_name, window_title.as_deref().unwrap_or(""))?;
+        automation::screen::encode_to_base64(&image)
+    })
+    .await
+    .map_err(|e| format!("Failed to capture window: {}", e))?
 }
 
 /// Get list of monitors
@@ -293,13 +398,42 @@ pub fn automation_get_monitors() -> Result<Vec<automation::screen::MonitorInfo>,
     automation::screen::get_monitors()
 }
 
+/// Capture a small preview of each monitor (for the monitor picker)
+#[tauri::command]
+pub async fn get_monitor_previews(
+    max_dim: u32,
+) -> Result<Vec<automation::screen::MonitorPreview>, String> {
+    tokio::task::spawn_blocking(move || automation::screen::get_monitor_previews(max_dim))
+        .await
+        .map_err(|e| format!("Failed to capture monitor previews: {}", e))?
+}
+
 /// Extract text from screenshot using OCR
+/// Bounding boxes are in screenshot pixels (top-left origin), not points
 #[tauri::command]
 pub fn automation_ocr() -> Result<automation::ocr::OcrResult, String> {
     let image = automation::screen::capture_screenshot()?;
     automation::ocr::extract_text_from_image(&image)
 }
 
+/// OCR the screen (or a region of it) with bounding boxes in screen pixels
+#[tauri::command]
+pub async fn ocr_with_boxes(
+    region: Option<automation::screen::Rect>,
+) -> Result<automation::ocr::OcrResult, String> {
+    tokio::task::spawn_blocking(move || automation::ocr::ocr_with_boxes(region))
+        .await
+        .map_err(|e| format!("Failed to run OCR: {}", e))?
+}
+
+/// OCR recognition languages available on this machine (BCP-47 codes)
+#[tauri::command]
+pub async fn get_ocr_languages() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(automation::ocr::supported_languages)
+        .await
+        .map_err(|e| format!("Failed to list OCR languages: {}", e))?
+}
+
 /// Get current browser URL
 #[tauri::command]
 pub fn automation_browser_url(browser: String) -> Result<String, String> {
@@ -332,6 +466,23 @@ pub async fn queue_add_task(
     queue.add_task(task).await
 }
 
+/// Pending tasks in execution order, for saving an automation session
+#[tauri::command]
+pub async fn export_queue(
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+) -> Result<Vec<automation::queue::AutomationTask>, String> {
+    Ok(queue.export_tasks().await)
+}
+
+/// Add exported tasks back to the queue (all or nothing)
+#[tauri::command]
+pub async fn import_queue(
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+    tasks: Vec<automation::queue::AutomationTask>,
+) -> Result<Vec<String>, String> {
+    queue.import_tasks(tasks).await
+}
+
 /// Get queue status
 #[tauri::command]
 pub async fn queue_status(
@@ -358,6 +509,102 @@ pub async fn queue_resume(
     Ok(())
 }
 
+/// Recently executed automation tasks with their results, newest first
+#[tauri::command]
+pub async fn get_task_history(
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+    limit: Option<usize>,
+) -> Result<Vec<automation::history::TaskHistoryEntry>, String> {
+    Ok(queue.history(limit.unwrap_or(50)).await)
+}
+
+/// Current trust level and when an active full trust expires
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustState {
+    pub level: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn trust_state(trust: &automation::TrustManager) -> TrustState {
+    TrustState {
+        level: trust.get_level().to_str().to_string(),
+        expires_at: trust.full_trust_expires_at(),
+    }
+}
+
+/// Current automation trust level, reverting an expired full trust
+#[tauri::command]
+pub async fn get_trust_level(
+    trust: State<'_, Arc<automation::TrustManager>>,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+) -> Result<TrustState, String> {
+    let now = chrono::Utc::now();
+    if trust.expire(now) {
+        let entry = automation::history::TaskHistoryEntry::trust_change(trust.get_level(), "full trust expired", now);
+        queue.record_history(entry).await;
+        if let Err(e) = trust.save() {
+            crate::logs::error(&format!("[Automation] Failed to save trust level: {}", e));
+        }
+    }
+    Ok(trust_state(&trust))
+}
+
+/// Change the automation trust level ("ask_always", "ask_dangerous" or "full_trust")
+/// Full trust needs `confirm: true` and expires after `fullTrustExpiryMinutes`
+#[tauri::command]
+pub async fn set_trust_level(
+    trust: State<'_, Arc<automation::TrustManager>>,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+    level: String,
+    confirm: Option<bool>,
+) -> Result<TrustState, String> {
+    let level = automation::TrustLevel::from_str(&level).ok_or_else(|| format!("Unknown trust level: {}", level))?;
+    let expiry_minutes = get_settings().unwrap_or_default().full_trust_expiry_minutes;
+    let expires_after = (expiry_minutes > 0).then(|| chrono::Duration::minutes(expiry_minutes as i64));
+
+    let now = chrono::Utc::now();
+    let expires_at = trust.change_level(level, confirm.unwrap_or(false), expires_after, now)?;
+    trust.save()?;
+
+    let note = match expires_at {
+        Some(at) => format!("confirmed, expires {}", at.to_rfc3339()),
+        None if level == automation::TrustLevel::FullTrust => "confirmed".to_string(),
+        None => "changed".to_string(),
+    };
+    crate::logs::info(&format!("[Automation] Trust level set to {} ({})", level.to_str(), note));
+    queue
+        .record_history(automation::history::TaskHistoryEntry::trust_change(level, &note, now))
+        .await;
+
+    Ok(trust_state(&trust))
+}
+
+/// Combined state returned by `set_automation_paused`
+#[derive(Serialize)]
+pub struct AutomationPauseState {
+    #[serde(rename = "automationPaused")]
+    pub automation_paused: bool,
+    pub collecting: bool,
+}
+
+/// Stop accepting and running automation while activity tracking keeps going
+#[tauri::command]
+pub async fn set_automation_paused(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+    paused: bool,
+) -> Result<AutomationPauseState, String> {
+    queue.set_automation_paused(paused).await;
+    crate::logs::info(&format!("[Automation] {}", if paused { "Paused" } else { "Resumed" }));
+
+    let state = state.lock().await;
+    Ok(AutomationPauseState {
+        automation_paused: queue.is_automation_paused().await,
+        collecting: state.collecting,
+    })
+}
+
 /// Clear queue
 #[tauri::command]
 pub async fn queue_clear(
@@ -381,6 +628,345 @@ pub struct AppSettings {
     pub sync_interval: u32,
     #[serde(rename = "launchAtStartup")]
     pub launch_at_startup: bool,
+    /// Where synced events are delivered
+    #[serde(default)]
+    pub sink: crate::sink::SinkConfig,
+    /// Events less important than this stay local-only ("low" syncs everything)
+    #[serde(rename = "minSyncImportance", default)]
+    pub min_sync_importance: crate::importance::EventImportance,
+    /// Maximum number of AppleScript calls running at once
+    #[serde(rename = "applescriptConcurrency", default = "default_applescript_concurrency")]
+    pub applescript_concurrency: usize,
+    /// Only capture screenshots for these categories (empty = all)
+    #[serde(rename = "screenshotCategories", default)]
+    pub screenshot_categories: Vec<String>,
+    /// Store a small thumbnail next to each screenshot for the review UI
+    #[serde(rename = "screenshotThumbnails", default)]
+    pub screenshot_thumbnails: bool,
+    /// Thumbnail width in pixels
+    #[serde(rename = "screenshotThumbnailWidth", default = "default_screenshot_thumbnail_width")]
+    pub screenshot_thumbnail_width: u32,
+    /// Chroma subsampling of stored screenshots ("4:4:4", "4:2:2" or "4:2:0")
+    #[serde(rename = "screenshotChromaSubsampling", default)]
+    pub screenshot_chroma_subsampling: crate::jpeg::ChromaSubsampling,
+    /// When screenshots are taken ("interval", "on_switch" or "both")
+    #[serde(rename = "screenshotTrigger", default)]
+    pub screenshot_trigger: crate::collector::CaptureTrigger,
+    /// File format of stored screenshots ("jpeg", "webp" or "png")
+    #[serde(rename = "screenshotFormat", default)]
+    pub screenshot_format: crate::collector::ScreenshotFormat,
+    /// JPEG and WebP quality of stored screenshots (0-100)
+    #[serde(rename = "screenshotQuality", default = "default_screenshot_quality")]
+    pub screenshot_quality: u8,
+    /// Minimum seconds between screenshots
+    #[serde(rename = "screenshotIntervalSecs", default = "default_screenshot_interval_secs")]
+    pub screenshot_interval_secs: u64,
+    /// Downscale factor of stored screenshots (0-1)
+    #[serde(rename = "screenshotScale", default = "default_screenshot_scale")]
+    pub screenshot_scale: f32,
+    /// Days screenshots are kept before cleanup
+    #[serde(rename = "screenshotRetentionDays", default = "default_screenshot_retention_days")]
+    pub screenshot_retention_days: i64,
+    /// Similarity (0-100) above which a frame is skipped as a duplicate
+    #[serde(rename = "screenshotSimilarityThreshold", default = "default_screenshot_similarity_threshold")]
+    pub screenshot_similarity_threshold: u8,
+    /// Workers storing (and OCRing) captured screenshots off the collector loop
+    #[serde(rename = "screenshotWorkers", default = "default_screenshot_workers")]
+    pub screenshot_workers: usize,
+    /// Captures waiting for a worker before the oldest is dropped
+    #[serde(rename = "screenshotQueueSize", default = "default_screenshot_queue_size")]
+    pub screenshot_queue_size: usize,
+    /// OCR each stored screenshot in the capture worker
+    #[serde(rename = "screenshotOcr", default)]
+    pub screenshot_ocr: bool,
+    /// Send images to the Observer server for OCR when local OCR can't run (opt-in)
+    #[serde(rename = "cloudOcr", default)]
+    pub cloud_ocr: bool,
+    /// Minutes full trust stays on before reverting to "ask dangerous" (0 = until changed)
+    #[serde(rename = "fullTrustExpiryMinutes", default = "default_full_trust_expiry_minutes")]
+    pub full_trust_expiry_minutes: u64,
+    /// Seconds an unchanged browser tab is not stored again (0 = off)
+    #[serde(rename = "urlDedupSecs", default = "default_url_dedup_secs")]
+    pub url_dedup_secs: u64,
+    /// Seconds a system metrics sample is reused for later events (0 = collect every time)
+    #[serde(rename = "metricsIntervalSecs", default = "default_metrics_interval_secs")]
+    pub metrics_interval_secs: u64,
+    /// Weight of the newest sample in the smoothed CPU and memory values (0-1, 1 = no smoothing)
+    #[serde(rename = "metricsSmoothingAlpha", default = "default_metrics_smoothing_alpha")]
+    pub metrics_smoothing_alpha: f32,
+    /// Store the current text selection on focus events
+    #[serde(rename = "captureSelectedText", default = "default_true")]
+    pub capture_selected_text: bool,
+    /// Record typed text in all apps (opt-in, only changed through `set_global_typed_text`)
+    #[serde(rename = "globalTypedText", default)]
+    pub global_typed_text: bool,
+    /// Apps whose typed text is never recorded, on top of the built-in password managers
+    #[serde(rename = "typedTextDenylist", default)]
+    pub typed_text_denylist: Vec<String>,
+    /// Longer selections are truncated to this many characters
+    #[serde(rename = "maxSelectedTextChars", default = "default_max_selected_text_chars")]
+    pub max_selected_text_chars: usize,
+    /// Automation tasks executed per minute at most
+    #[serde(rename = "automationMaxTasksPerMinute", default = "default_automation_max_tasks_per_minute")]
+    pub automation_max_tasks_per_minute: u32,
+    /// Automation tasks allowed to wait in the queue
+    #[serde(rename = "automationMaxPendingTasks", default = "default_automation_max_pending_tasks")]
+    pub automation_max_pending_tasks: usize,
+    /// Longer window titles are truncated to this many characters
+    #[serde(rename = "maxWindowTitleChars", default = "default_max_window_title_chars")]
+    pub max_window_title_chars: usize,
+    /// Longer URLs are truncated to this many characters
+    #[serde(rename = "maxUrlChars", default = "default_max_url_chars")]
+    pub max_url_chars: usize,
+    /// Nudge the user to take a focus break when switching apps too often
+    #[serde(rename = "focusReminder", default)]
+    pub focus_reminder: crate::focus::FocusReminderConfig,
+    /// What the collector does while accessibility or screen recording is denied
+    #[serde(rename = "permissionDeniedPolicy", default)]
+    pub permission_denied_policy: crate::collector::PermissionDeniedPolicy,
+    /// Ask for Automation permission for installed browsers/messengers at startup
+    #[serde(rename = "prewarmAutomationPermissions", default)]
+    pub prewarm_automation_permissions: bool,
+    /// Record only app, category and timestamp - no screenshots, OCR, messages, titles or URLs
+    #[serde(rename = "compactMode", default)]
+    pub compact_mode: bool,
+    /// Days synced and local-only events are kept in the local database for history and stats
+    #[serde(rename = "eventRetentionDays", default = "default_event_retention_days")]
+    pub event_retention_days: u32,
+    /// Pause screenshots below this much free disk space (MB, 0 = never)
+    #[serde(rename = "minFreeDiskMb", default = "default_min_free_disk_mb")]
+    pub min_free_disk_mb: u64,
+    /// System processes (Dock, login window, helpers) ignored when they take focus
+    #[serde(rename = "systemAppFilter", default)]
+    pub system_app_filter: crate::collector::SystemAppFilter,
+    /// Seconds without keyboard/mouse input before collection pauses as idle (0 = never)
+    #[serde(rename = "idleThresholdSeconds", default = "default_idle_threshold_seconds")]
+    pub idle_threshold_seconds: u64,
+    /// Idle threshold per app category ("browsing", "coding", ...), overriding `idleThresholdSeconds`
+    #[serde(rename = "idleThresholdsByCategory", default = "default_idle_thresholds_by_category")]
+    pub idle_thresholds_by_category: std::collections::HashMap<String, u64>,
+    /// Record the site domain on browser events and fetch site icons for the dashboard
+    #[serde(rename = "favicons", default)]
+    pub favicons: bool,
+    /// Apps (terminals, editors) whose window title changes don't create events
+    #[serde(rename = "titleChurnApps", default = "default_title_churn_apps")]
+    pub title_churn_apps: Vec<String>,
+    /// Webhooks called as soon as a matching event is collected
+    #[serde(rename = "eventHooks", default)]
+    pub event_hooks: Vec<crate::event_hooks::EventHookRule>,
+    /// Ask the Observer server to categorize events the built-in rules can't tell apart
+    #[serde(rename = "serverCategorization", default)]
+    pub server_categorization: bool,
+}
+
+impl AppSettings {
+    /// Automation queue safety limits
+    pub fn queue_limits(&self) -> automation::queue::QueueLimits {
+        automation::queue::QueueLimits {
+            max_tasks_per_minute: self.automation_max_tasks_per_minute,
+            max_pending_tasks: self.automation_max_pending_tasks,
+        }
+    }
+
+    /// `base` with the screenshot options of these settings applied
+    pub fn screenshot_config(&self, base: crate::collector::ScreenshotConfig) -> crate::collector::ScreenshotConfig {
+        crate::collector::ScreenshotConfig {
+            thumbnails: self.screenshot_thumbnails,
+            thumbnail_width: self.screenshot_thumbnail_width,
+            chroma_subsampling: self.screenshot_chroma_subsampling,
+            capture_trigger: self.screenshot_trigger,
+            format: self.screenshot_format,
+            jpeg_quality: self.screenshot_quality,
+            min_interval_seconds: self.screenshot_interval_secs,
+            scale_factor: self.screenshot_scale,
+            retention_days: self.screenshot_retention_days,
+            similarity_threshold: self.screenshot_similarity_threshold,
+            ..base
+        }
+    }
+
+    /// Keep the user-facing options of `config` so they survive a restart
+    pub fn store_screenshot_config(&mut self, config: &crate::collector::ScreenshotConfig) {
+        self.screenshot_thumbnails = config.thumbnails;
+        self.screenshot_thumbnail_width = config.thumbnail_width;
+        self.screenshot_chroma_subsampling = config.chroma_subsampling;
+        self.screenshot_trigger = config.capture_trigger;
+        self.screenshot_format = config.format;
+        self.screenshot_quality = config.jpeg_quality;
+        self.screenshot_interval_secs = config.min_interval_seconds;
+        self.screenshot_scale = config.scale_factor;
+        self.screenshot_retention_days = config.retention_days;
+        self.screenshot_similarity_threshold = config.similarity_threshold;
+    }
+
+    /// Length caps for event titles and URLs
+    pub fn field_limits(&self) -> crate::collector::FieldLimits {
+        crate::collector::FieldLimits {
+            max_title_chars: self.max_window_title_chars,
+            max_url_chars: self.max_url_chars,
+        }
+    }
+
+    /// Check values the rest of the app can't work with
+    pub fn validate(&self) -> Result<(), String> {
+        validate_url(&self.api_url).map_err(|e| format!("apiUrl: {}", e))?;
+        if self.sync_interval == 0 {
+            return Err("syncInterval must be at least 1 second".to_string());
+        }
+        if self.applescript_concurrency == 0 {
+            return Err("applescriptConcurrency must be at least 1".to_string());
+        }
+        if self.screenshot_thumbnail_width == 0 {
+            return Err("screenshotThumbnailWidth must be at least 1 pixel".to_string());
+        }
+        if self.screenshot_quality > 100 {
+            return Err("screenshotQuality must be between 0 and 100".to_string());
+        }
+        if !(self.screenshot_scale > 0.0 && self.screenshot_scale <= 1.0) {
+            return Err("screenshotScale must be greater than 0 and at most 1".to_string());
+        }
+        if self.screenshot_retention_days < 1 {
+            return Err("screenshotRetentionDays must be at least 1".to_string());
+        }
+        if self.screenshot_similarity_threshold > 100 {
+            return Err("screenshotSimilarityThreshold must be between 0 and 100".to_string());
+        }
+        if self.automation_max_tasks_per_minute == 0 || self.automation_max_pending_tasks == 0 {
+            return Err("Automation queue limits must be at least 1".to_string());
+        }
+        if self.max_window_title_chars == 0 || self.max_url_chars == 0 {
+            return Err("Title and URL length limits must be at least 1".to_string());
+        }
+        if !(self.metrics_smoothing_alpha > 0.0 && self.metrics_smoothing_alpha <= 1.0) {
+            return Err("metricsSmoothingAlpha must be greater than 0 and at most 1".to_string());
+        }
+        if self.event_retention_days == 0 {
+            return Err("eventRetentionDays must be at least 1".to_string());
+        }
+        if self.focus_reminder.window_minutes == 0 {
+            return Err("focusReminder: window must be at least 1 minute".to_string());
+        }
+        if let Some(category) = self
+            .screenshot_categories
+            .iter()
+            .find(|category| !crate::collector::is_known_category(category))
+        {
+            return Err(format!("screenshotCategories: unknown category {}", category));
+        }
+        match &self.sink {
+            crate::sink::SinkConfig::Webhook { url, .. } => {
+                validate_url(url).map_err(|e| format!("sink: {}", e))?;
+            }
+            crate::sink::SinkConfig::File { path } if path.as_os_str().is_empty() => {
+                return Err("sink: file path cannot be empty".to_string());
+            }
+            _ => {}
+        }
+        for (index, rule) in self.event_hooks.iter().enumerate() {
+            rule.validate()
+                .map_err(|e| format!("eventHooks[{}]: {}", index, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn default_automation_max_tasks_per_minute() -> u32 {
+    automation::queue::DEFAULT_MAX_TASKS_PER_MINUTE
+}
+
+fn default_automation_max_pending_tasks() -> usize {
+    automation::queue::DEFAULT_MAX_PENDING_TASKS
+}
+
+fn default_max_window_title_chars() -> usize {
+    crate::text::MAX_TITLE_CHARS
+}
+
+fn default_max_url_chars() -> usize {
+    crate::collector::DEFAULT_MAX_URL_CHARS
+}
+
+fn default_event_retention_days() -> u32 {
+    crate::sync::DEFAULT_EVENT_RETENTION_DAYS
+}
+
+fn default_min_free_disk_mb() -> u64 {
+    crate::storage::DEFAULT_MIN_FREE_DISK_MB
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_selected_text_chars() -> usize {
+    crate::collector::DEFAULT_MAX_SELECTED_TEXT_CHARS
+}
+
+fn default_screenshot_thumbnail_width() -> u32 {
+    crate::collector::DEFAULT_THUMBNAIL_WIDTH
+}
+
+fn default_screenshot_quality() -> u8 {
+    crate::collector::DEFAULT_SCREENSHOT_QUALITY
+}
+
+fn default_screenshot_interval_secs() -> u64 {
+    crate::collector::DEFAULT_SCREENSHOT_INTERVAL_SECS
+}
+
+fn default_screenshot_scale() -> f32 {
+    crate::collector::DEFAULT_SCREENSHOT_SCALE
+}
+
+fn default_screenshot_retention_days() -> i64 {
+    crate::collector::DEFAULT_SCREENSHOT_RETENTION_DAYS
+}
+
+fn default_screenshot_similarity_threshold() -> u8 {
+    crate::collector::DEFAULT_SCREENSHOT_SIMILARITY
+}
+
+fn default_screenshot_workers() -> usize {
+    crate::collector::DEFAULT_CAPTURE_WORKERS
+}
+
+fn default_screenshot_queue_size() -> usize {
+    crate::collector::DEFAULT_CAPTURE_QUEUE_SIZE
+}
+
+fn default_full_trust_expiry_minutes() -> u64 {
+    automation::trust::DEFAULT_FULL_TRUST_EXPIRY_MINUTES
+}
+
+fn default_url_dedup_secs() -> u64 {
+    crate::collector::DEFAULT_URL_DEDUP_SECS
+}
+
+fn default_metrics_interval_secs() -> u64 {
+    crate::collector::DEFAULT_METRICS_INTERVAL_SECS
+}
+
+fn default_metrics_smoothing_alpha() -> f32 {
+    crate::collector::DEFAULT_SMOOTHING_ALPHA
+}
+
+fn default_idle_threshold_seconds() -> u64 {
+    crate::collector::DEFAULT_IDLE_THRESHOLD_SECS
+}
+
+fn default_idle_thresholds_by_category() -> std::collections::HashMap<String, u64> {
+    crate::collector::DEFAULT_CATEGORY_IDLE_THRESHOLDS
+        .iter()
+        .map(|(category, seconds)| (category.to_string(), *seconds))
+        .collect()
+}
+
+fn default_title_churn_apps() -> Vec<String> {
+    crate::collector::DEFAULT_TITLE_CHURN_APPS.iter().map(|app| app.to_string()).collect()
+}
+
+fn default_applescript_concurrency() -> usize {
+    crate::native_applescript::DEFAULT_CONCURRENCY_LIMIT
 }
 
 impl Default for AppSettings {
@@ -389,68 +975,263 @@ impl Default for AppSettings {
             api_url: get_dashboard_url(),
             sync_interval: 30,
             launch_at_startup: false,
+            sink: crate::sink::SinkConfig::default(),
+            min_sync_importance: crate::importance::EventImportance::default(),
+            applescript_concurrency: default_applescript_concurrency(),
+            screenshot_categories: Vec::new(),
+            screenshot_thumbnails: false,
+            screenshot_thumbnail_width: default_screenshot_thumbnail_width(),
+            screenshot_chroma_subsampling: crate::jpeg::ChromaSubsampling::default(),
+            screenshot_trigger: crate::collector::CaptureTrigger::default(),
+            screenshot_format: crate::collector::ScreenshotFormat::default(),
+            screenshot_quality: default_screenshot_quality(),
+            screenshot_interval_secs: default_screenshot_interval_secs(),
+            screenshot_scale: default_screenshot_scale(),
+            screenshot_retention_days: default_screenshot_retention_days(),
+            screenshot_similarity_threshold: default_screenshot_similarity_threshold(),
+            screenshot_workers: default_screenshot_workers(),
+            screenshot_queue_size: default_screenshot_queue_size(),
+            screenshot_ocr: false,
+            cloud_ocr: false,
+            full_trust_expiry_minutes: default_full_trust_expiry_minutes(),
+            url_dedup_secs: default_url_dedup_secs(),
+            metrics_interval_secs: default_metrics_interval_secs(),
+            metrics_smoothing_alpha: default_metrics_smoothing_alpha(),
+            capture_selected_text: true,
+            global_typed_text: false,
+            typed_text_denylist: Vec::new(),
+            max_selected_text_chars: default_max_selected_text_chars(),
+            automation_max_tasks_per_minute: default_automation_max_tasks_per_minute(),
+            automation_max_pending_tasks: default_automation_max_pending_tasks(),
+            max_window_title_chars: default_max_window_title_chars(),
+            max_url_chars: default_max_url_chars(),
+            focus_reminder: crate::focus::FocusReminderConfig::default(),
+            permission_denied_policy: crate::collector::PermissionDeniedPolicy::default(),
+            prewarm_automation_permissions: false,
+            compact_mode: false,
+            event_retention_days: default_event_retention_days(),
+            min_free_disk_mb: default_min_free_disk_mb(),
+            system_app_filter: crate::collector::SystemAppFilter::default(),
+            idle_threshold_seconds: default_idle_threshold_seconds(),
+            idle_thresholds_by_category: default_idle_thresholds_by_category(),
+            favicons: false,
+            title_churn_apps: default_title_churn_apps(),
+            event_hooks: Vec::new(),
+            server_categorization: false,
         }
     }
 }
 
-fn get_settings_path() -> std::path::PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("observer")
-        .join("settings.json")
-}
-
 /// Get app settings
 #[tauri::command]
 pub fn get_settings() -> Result<AppSettings, String> {
-    let path = get_settings_path();
-
-    if path.exists() {
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))
-    } else {
-        Ok(AppSettings::default())
+    if let Some(settings) = crate::settings_writer::pending() {
+        return Ok(settings);
     }
+    let config = crate::config::Config::load()?;
+    Ok(config.settings.unwrap_or_default())
 }
 
 /// Save app settings
+/// Takes effect right away; the file is written once saves stop for a moment
 #[tauri::command]
-pub fn save_settings(settings: AppSettings) -> Result<(), String> {
-    let path = get_settings_path();
+pub fn save_settings(
+    settings: AppSettings,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+) -> Result<(), String> {
+    settings.validate()?;
+    let previous = get_settings()?;
+    // Global typed text needs the explicit opt-in of `set_global_typed_text`
+    let mut settings = settings;
+    settings.global_typed_text = previous.global_typed_text;
+    crate::settings_writer::save(settings.clone());
+
+    apply_settings(&previous, &settings, &queue);
+    Ok(())
+}
 
-    // Ensure directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+/// Put saved settings into effect
+/// The login item is only touched when `launch_at_startup` changed
+fn apply_settings(previous: &AppSettings, settings: &AppSettings, queue: &automation::queue::AutomationQueue) {
+    crate::native_applescript::set_concurrency_limit(settings.applescript_concurrency);
+    queue.set_limits(settings.queue_limits());
+
+    // Screenshot options take effect on the collector's next tick. Only
+    // changed ones are pushed, so a running recording keeps its own config.
+    let live = crate::collector::live_screenshot_config();
+    let screenshots = settings.screenshot_config(live.clone());
+    if screenshots != previous.screenshot_config(live) {
+        if let Err(e) = crate::collector::set_live_screenshot_config(screenshots) {
+            crate::logs::error(&format!("[Screenshot] {}", e));
+        }
     }
 
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    std::fs::write(&path, content)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
-
     // Handle launch at startup (macOS)
+    let login_item = crate::settings_writer::login_item_change(previous, settings);
     #[cfg(target_os = "macos")]
-    {
-        if settings.launch_at_startup {
+    if let Some(launch_at_startup) = login_item {
+        if launch_at_startup {
             // Add to Login Items using AppleScript
-            let _ = std::process::Command::new("osascript")
-                .args(["-e", "tell application \"System Events\" to make login item at end with properties {path:\"/Applications/Observer.app\", hidden:false}"])
-                .output();
+            let _ = crate::native_applescript::run(
+                "tell application \"System Events\" to make login item at end with properties {path:\"/Applications/Observer.app\", hidden:false}",
+                crate::native_applescript::PERMISSION_PROMPT_TIMEOUT,
+            );
         } else {
             // Remove from Login Items
-            let _ = std::process::Command::new("osascript")
-                .args(["-e", "tell application \"System Events\" to delete login item \"Observer\""])
-                .output();
+            let _ = crate::native_applescript::run(
+                "tell application \"System Events\" to delete login item \"Observer\"",
+                crate::native_applescript::PERMISSION_PROMPT_TIMEOUT,
+            );
         }
     }
+    #[cfg(not(target_os = "macos"))]
+    let _ = login_item;
+}
 
+/// Get all configurable settings as one object
+#[tauri::command]
+pub fn get_full_config() -> Result<crate::config::FullConfig, String> {
+    crate::config::FullConfig::load()
+}
+
+/// Save all configurable settings at once
+/// The whole config is validated first; nothing is saved if any part is invalid
+#[tauri::command]
+pub fn set_full_config(
+    config: crate::config::FullConfig,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+) -> Result<crate::config::FullConfig, String> {
+    let previous = get_settings()?;
+    crate::settings_writer::discard_pending();
+    let saved = config.save()?;
+    crate::collector::reload_category_rules();
+    apply_settings(&previous, &saved.settings, &queue);
+    Ok(saved)
+}
+
+/// Export all settings except secrets and the device name to a JSON file
+#[tauri::command]
+pub fn export_config(path: String) -> Result<(), String> {
+    crate::config::export_config(std::path::Path::new(&path))
+}
+
+/// Import settings exported by `export_config`, merging with or replacing the current ones
+/// The server and WebSocket URLs in the file are only used with `trustEndpoints: true`
+#[tauri::command]
+pub fn import_config(
+    path: String,
+    mode: crate::config::ImportMode,
+    trust_endpoints: Option<bool>,
+    queue: State<'_, Arc<automation::queue::AutomationQueue>>,
+) -> Result<crate::config::FullConfig, String> {
+    let previous = get_settings()?;
+    crate::settings_writer::discard_pending();
+    let saved = crate::config::import_config(std::path::Path::new(&path), mode, trust_endpoints.unwrap_or(false))?;
+    crate::collector::reload_category_rules();
+    apply_settings(&previous, &saved.settings, &queue);
+    Ok(saved)
+}
+
+/// Get the cloud OCR endpoint and whether an API key is configured
+#[tauri::command]
+pub fn get_cloud_ocr_config() -> automation::ocr::CloudOcrConfig {
+    automation::ocr::cloud_ocr_config()
+}
+
+/// Set the server URL and/or API key used by cloud OCR (and sync)
+/// An empty value clears the setting; environment variables still take precedence
+#[tauri::command]
+pub fn set_cloud_ocr_config(
+    server_url: Option<String>,
+    api_key: Option<String>,
+) -> Result<automation::ocr::CloudOcrConfig, String> {
+    if let Some(url) = server_url {
+        let url = url.trim();
+        let url = if url.is_empty() {
+            None
+        } else {
+            Some(validate_url(url)?.to_string().trim_end_matches('/').to_string())
+        };
+        crate::config::Config::update(|config| {
+            config.server_url = url;
+            Ok(())
+        })?;
+    }
+    if let Some(key) = api_key {
+        if key.trim().is_empty() {
+            crate::credentials::clear_token()?;
+        } else {
+            crate::credentials::store_token(&key)?;
+        }
+    }
+    Ok(automation::ocr::cloud_ocr_config())
+}
+
+/// Run cloud OCR on a built-in test image to verify the server and API key
+#[tauri::command]
+pub async fn test_cloud_ocr() -> Result<automation::ocr::OcrResult, String> {
+    automation::ocr::test_cloud_ocr().await
+}
+
+/// The API token used for sync and the automation WebSocket, masked
+#[tauri::command]
+pub fn get_ws_token_masked() -> crate::credentials::MaskedToken {
+    crate::credentials::masked_token()
+}
+
+/// Replace the API token and reconnect the automation WebSocket with it
+#[tauri::command]
+pub fn set_ws_token(
+    sync: State<'_, Arc<automation::sync::AutomationSync>>,
+    token: String,
+) -> Result<crate::credentials::MaskedToken, String> {
+    replace_ws_token(&sync, &token)
+}
+
+/// Get a fresh API token from the server, store it and reconnect with it
+#[tauri::command]
+pub async fn rotate_ws_token(
+    sync: State<'_, Arc<automation::sync::AutomationSync>>,
+) -> Result<crate::credentials::MaskedToken, String> {
+    ensure_token_not_from_env()?;
+    let current = crate::sync::get_api_key().ok_or("No token configured to rotate")?;
+    let token = crate::credentials::request_rotated_token(&crate::sync::get_server_url(), &current).await?;
+    replace_ws_token(&sync, &token)
+}
+
+fn ensure_token_not_from_env() -> Result<(), String> {
+    if crate::config::env_override(crate::credentials::API_KEY_ENV).is_some() {
+        return Err(format!(
+            "{} is set in the environment and takes precedence over a stored token",
+            crate::credentials::API_KEY_ENV
+        ));
+    }
     Ok(())
 }
 
+fn replace_ws_token(
+    sync: &automation::sync::AutomationSync,
+    token: &str,
+) -> Result<crate::credentials::MaskedToken, String> {
+    ensure_token_not_from_env()?;
+    let source = crate::credentials::store_token(token)?;
+    crate::logs::info(&format!("[Credentials] Token updated ({:?}), reconnecting WebSocket", source));
+    sync.reconnect_to(automation::sync::get_websocket_url());
+    Ok(crate::credentials::masked_token())
+}
+
+/// Get the device's friendly name
+#[tauri::command]
+pub fn get_device_name() -> String {
+    crate::config::device_name()
+}
+
+/// Rename the device (its id stays the same)
+#[tauri::command]
+pub fn set_device_name(name: String) -> Result<String, String> {
+    crate::config::set_device_name(&name)
+}
+
 /// Open system preferences to a specific pane
 #[tauri::command]
 pub fn open_system_preferences(pane: String) -> Result<(), String> {
@@ -482,6 +1263,8 @@ pub struct DebugInfo {
     pub config_path: String,
     pub log_path: String,
     pub platform: String,
+    /// Heartbeat and restart count per background service
+    pub tasks: Vec<crate::supervisor::TaskHealth>,
 }
 
 #[tauri::command]
@@ -490,9 +1273,7 @@ pub fn get_debug_info() -> Result<DebugInfo, String> {
         .map(|p| p.join("observer").to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
-    let log_path = dirs::data_local_dir()
-        .map(|p| p.join("observer").join("logs").to_string_lossy().to_string())
-        .unwrap_or_else(|| "unknown".to_string());
+    let log_path = crate::storage::logs_dir().to_string_lossy().to_string();
 
     Ok(DebugInfo {
         version: env!("CARGO_PKG_VERSION").to_string(),
@@ -501,7 +1282,247 @@ pub fn get_debug_info() -> Result<DebugInfo, String> {
         config_path,
         log_path,
         platform: std::env::consts::OS.to_string(),
+        tasks: crate::supervisor::task_health(),
+    })
+}
+
+/// Get a JPEG thumbnail (base64) of a stored screenshot
+/// Uses the thumbnail saved at capture time when there is one
+#[tauri::command]
+pub async fn get_screenshot_thumbnail(path: String) -> Result<String, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let config = crate::collector::live_screenshot_config();
+    let bytes = tokio::task::spawn_blocking(move || {
+        crate::collector::load_thumbnail(&config, std::path::Path::new(&path))
     })
+    .await
+    .map_err(|e| format!("Failed to load thumbnail: {}", e))??;
+
+    Ok(BASE64.encode(bytes))
+}
+
+/// Get disk usage of the database, screenshots and logs
+#[tauri::command]
+pub async fn get_storage_usage() -> Result<crate::storage::StorageUsage, String> {
+    crate::storage::get_storage_usage().await
+}
+
+/// Delete screenshots now, by age (days) and/or until enough disk space (MB) is free
+/// Without either, the configured retention applies
+#[tauri::command]
+pub async fn cleanup_screenshots(
+    older_than_days: Option<i64>,
+    target_free_mb: Option<u64>,
+) -> Result<crate::collector::CleanupSummary, String> {
+    crate::storage::cleanup_screenshots(older_than_days, target_free_mb).await
+}
+
+/// Project events, screenshot storage and sync upload per day from the last
+/// week of collected data and the current settings
+#[tauri::command]
+pub async fn estimate_daily_footprint(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<crate::storage::DailyEstimate, String> {
+    let db = state.lock().await.db.clone();
+    crate::storage::estimate_daily_footprint(db).await
+}
+
+/// Run SQLite's integrity check on the event database
+#[tauri::command]
+pub async fn check_database_integrity(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<crate::db::IntegrityReport, String> {
+    let db = state.lock().await.db.clone();
+    tokio::task::spawn_blocking(move || db.check_integrity())
+        .await
+        .map_err(|e| format!("Failed to check database integrity: {}", e))?
+        .map_err(|e| format!("Failed to check database integrity: {}", e))
+}
+
+/// Active warnings for the alerts area (buffer near full, sync failing,
+/// disk low, permissions missing), most severe first
+#[tauri::command]
+pub async fn get_warnings(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    manager: State<'_, Arc<permissions::PermissionManager>>,
+) -> Result<Vec<crate::warnings::Warning>, String> {
+    let inputs = crate::warnings::WarningInputs {
+        buffered_events: state.lock().await.events_buffer.len(),
+        sync_failure: crate::sync::current_failure(),
+        disk_low: crate::storage::screenshot_capture_suspended(),
+        applescript_disabled: crate::native_applescript::collectors_disabled(),
+        permissions: cfg!(target_os = "macos").then(|| manager.check_all()),
+    };
+    Ok(crate::warnings::current(&inputs))
+}
+
+/// Hide a warning until its condition clears
+#[tauri::command]
+pub fn dismiss_warning(id: String) {
+    crate::warnings::dismiss(&id);
+}
+
+/// Re-enable AppleScript collectors disabled after repeated osascript failures
+#[tauri::command]
+pub fn retry_applescript() {
+    crate::native_applescript::reset_collectors();
+    crate::logs::info("[AppleScript] Collectors re-enabled");
+}
+
+/// Recent per-step collector timings (average and percentiles), for finding
+/// which step is costing CPU
+#[tauri::command]
+pub fn get_collector_timings() -> Vec<crate::collector::StepTimings> {
+    crate::collector::collector_timings()
+}
+
+/// Screenshot capture config currently used by the collector
+#[tauri::command]
+pub fn get_screenshot_config() -> crate::collector::ScreenshotConfig {
+    crate::collector::live_screenshot_config()
+}
+
+/// Change screenshot quality, interval, scale, retention or similarity threshold
+/// for the running collector (applied on its next tick) and save them to settings
+#[tauri::command]
+pub fn set_screenshot_config(config: crate::collector::ScreenshotConfig) -> Result<(), String> {
+    crate::collector::set_live_screenshot_config(config.clone())?;
+    let mut settings = get_settings()?;
+    settings.store_screenshot_config(&config);
+    crate::settings_writer::save(settings);
+    Ok(())
+}
+
+/// Which collectors (screenshots, messages, browser tab, metrics) are enabled
+#[tauri::command]
+pub fn get_collector_toggles() -> crate::collector::CollectorToggles {
+    crate::collector::collector_toggles()
+}
+
+/// Turn one collector on or off without stopping collection
+#[tauri::command]
+pub fn set_collector_enabled(
+    collector: crate::collector::CollectorKind,
+    enabled: bool,
+) -> crate::collector::CollectorToggles {
+    crate::logs::info(&format!(
+        "[Collector] {:?} {}",
+        collector,
+        if enabled { "enabled" } else { "disabled" }
+    ));
+    crate::collector::set_collector_enabled(collector, enabled)
+}
+
+/// Record typed text in all apps, not just browser fields (opt-in)
+/// Enabling needs `confirm: true`; password fields and denylisted apps are never
+/// recorded, and a menu bar marker stays on while it runs. Returns the new state.
+#[tauri::command]
+pub fn set_global_typed_text(app: tauri::AppHandle, enabled: bool, confirm: Option<bool>) -> Result<bool, String> {
+    if enabled && confirm != Some(true) {
+        return Err("Recording typed text in all apps needs explicit confirmation (confirm: true)".to_string());
+    }
+
+    crate::collector::set_global_typed_text_enabled(&app, enabled)?;
+    let mut settings = get_settings()?;
+    settings.global_typed_text = enabled;
+    crate::settings_writer::save(settings);
+    Ok(crate::collector::global_typed_text_enabled())
+}
+
+/// Record the next `duration_minutes` in high detail: screenshots on every focus
+/// change without similarity reuse and all collectors on. The previous settings
+/// come back on `stop_high_fidelity_recording` or when the time is up.
+/// Calling it again while recording extends the recording.
+#[tauri::command]
+pub fn start_high_fidelity_recording(
+    app: tauri::AppHandle,
+    duration_minutes: u64,
+) -> Result<crate::collector::RecordingStatus, String> {
+    if duration_minutes == 0 || duration_minutes > crate::collector::MAX_RECORDING_MINUTES {
+        return Err(format!(
+            "duration_minutes must be between 1 and {}",
+            crate::collector::MAX_RECORDING_MINUTES
+        ));
+    }
+
+    let (status, generation) = crate::collector::start_recording(chrono::Duration::minutes(duration_minutes as i64))?;
+    crate::tray::set_recording_indicator(&app, true);
+    crate::logs::info(&format!("[Recording] High fidelity for {} minutes", duration_minutes));
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(duration_minutes * 60)).await;
+        match crate::collector::expire_recording(generation) {
+            Ok(true) => {
+                crate::tray::set_recording_indicator(&app, false);
+                crate::logs::info("[Recording] High fidelity ended, settings restored");
+            }
+            Ok(false) => {}
+            Err(e) => crate::logs::error(&format!("[Recording] {}", e)),
+        }
+    });
+
+    Ok(status)
+}
+
+/// End a high-fidelity recording early and restore the previous settings
+#[tauri::command]
+pub fn stop_high_fidelity_recording(app: tauri::AppHandle) -> Result<crate::collector::RecordingStatus, String> {
+    if crate::collector::stop_recording()? {
+        crate::tray::set_recording_indicator(&app, false);
+        crate::logs::info("[Recording] High fidelity stopped, settings restored");
+    }
+    Ok(crate::collector::recording_status())
+}
+
+/// Recently sent notifications, newest first (default 50)
+#[tauri::command]
+pub fn get_notification_history(limit: Option<usize>) -> Vec<crate::notifications::NotificationRecord> {
+    crate::notifications::notification_history(limit.unwrap_or(50))
+}
+
+/// Forget all sent notifications
+#[tauri::command]
+pub fn clear_notification_history() {
+    crate::notifications::clear_notification_history();
+}
+
+/// Take a screenshot of the focused window right away, regardless of the
+/// capture interval or similarity to the last one, and add it to the timeline
+#[tauri::command]
+pub async fn capture_now(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    screenshot_manager: State<'_, crate::collector::SharedScreenshotManager>,
+) -> Result<crate::collector::Screenshot, String> {
+    if !crate::collector::collector_toggles().screenshots {
+        return Err("Screenshots are turned off".to_string());
+    }
+
+    let focus = tokio::task::spawn_blocking(get_current_focus)
+        .await
+        .map_err(|e| format!("Failed to read focused window: {}", e))?
+        .ok_or("No focused window to capture")?;
+
+    // Same privacy rules as automatic captures
+    let settings = get_settings().unwrap_or_default();
+    crate::collector::manual_screenshot_allowed(&focus.app_name, &settings.screenshot_categories)?;
+
+    // The collector's manager, so the capture is in its hash index and used
+    // as its last full frame
+    let manager = screenshot_manager.inner().clone();
+    let screenshot = tokio::task::spawn_blocking(move || {
+        manager
+            .lock()
+            .map_err(|_| "Screenshot manager is unavailable".to_string())?
+            .capture_now(focus.app_name, focus.window_title)
+    })
+    .await
+    .map_err(|e| format!("Failed to capture screenshot: {}", e))??;
+
+    let mut state = state.lock().await;
+    crate::collector::add_manual_screenshot(&mut state, &screenshot, &settings);
+    crate::logs::info(&format!("[Screenshot] Captured manually: {}", screenshot.path.display()));
+    Ok(screenshot)
 }
 
 /// Force check for updates (manual trigger)
@@ -529,14 +1550,24 @@ pub async fn check_updates(app: tauri::AppHandle) -> Result<String, String> {
     }
 }
 
+/// Start/stop streaming log lines to the frontend (`log-line` events)
+/// The debug panel enables this while open to avoid overhead otherwise
+#[tauri::command]
+pub fn set_log_streaming(enabled: bool) {
+    crate::logs::stream().set_enabled(enabled);
+}
+
 // ============================================================================
 // APP AUTOMATION PERMISSIONS
 // ============================================================================
 
 /// Get automation permissions for all required apps
+/// One AppleScript check per app, so this runs off the main thread
 #[tauri::command]
-pub fn get_automation_permissions() -> Vec<permissions::AppPermissionStatus> {
-    permissions::check_automation_permissions()
+pub async fn get_automation_permissions() -> Result<Vec<permissions::AppPermissionStatus>, String> {
+    tokio::task::spawn_blocking(permissions::check_automation_permissions)
+        .await
+        .map_err(|e| format!("Failed to check automation permissions: {}", e))
 }
 
 /// Request automation permission for a specific app
@@ -549,6 +1580,9 @@ pub fn request_app_automation(app_name: String) -> bool {
 /// This uses osascript directly which properly triggers macOS permission dialogs
 #[tauri::command]
 pub fn trigger_automation_permission(app_name: String) -> bool {
+    if !permissions::is_required_app(&app_name) {
+        return false;
+    }
     crate::native_applescript::trigger_permission_for_app(&app_name)
 }
 
@@ -564,6 +1598,328 @@ pub fn open_automation_prefs() -> Result<(), String> {
     permissions::open_automation_settings()
 }
 
+// ============================================================================
+// EVENT COMMANDS
+// ============================================================================
+
+/// Summarize a local day ("YYYY-MM-DD", defaults to today)
+/// Covers the events still held locally (not yet synced)
+#[tauri::command]
+pub async fn get_daily_summary(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    date: Option<String>,
+) -> Result<crate::stats::DailySummary, String> {
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => chrono::Local::now().date_naive(),
+    };
+
+    let state = state.lock().await;
+    Ok(crate::stats::daily_summary(
+        &state.events_buffer,
+        date,
+        &chrono::Local,
+    ))
+}
+
+/// Current and longest run of local days with at least `min_active_minutes` of active time
+/// History goes back as far as `eventRetentionDays`.
+#[tauri::command]
+pub async fn get_streaks(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    min_active_minutes: u32,
+) -> Result<crate::stats::Streaks, String> {
+    let db = state.lock().await.db.clone();
+    let max_gap = chrono::Duration::minutes(crate::stats::DEFAULT_SESSION_GAP_MINUTES);
+    let days = tokio::task::spawn_blocking(move || db.daily_active_seconds(max_gap))
+        .await
+        .map_err(|e| format!("Failed to compute streaks: {}", e))?
+        .map_err(|e| format!("Failed to load daily activity: {}", e))?;
+    let daily = days
+        .into_iter()
+        .map(|(date, seconds)| (date, chrono::Duration::seconds(seconds)))
+        .collect();
+
+    Ok(crate::stats::compute_streaks(
+        &daily,
+        chrono::Duration::minutes(min_active_minutes as i64),
+        chrono::Local::now().date_naive(),
+    ))
+}
+
+/// Events per local hour of a day ("YYYY-MM-DD", defaults to today), for the menu-bar sparkline
+/// Always 24 values; hours still to come are zero
+#[tauri::command]
+pub async fn get_hourly_activity(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    date: Option<String>,
+) -> Result<Vec<u32>, String> {
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => chrono::Local::now().date_naive(),
+    };
+
+    let db = state.lock().await.db.clone();
+    let (from, to) = crate::stats::local_day_bounds(date, &chrono::Local);
+    let timestamps = db
+        .timestamps_between(from, to)
+        .map_err(|e| format!("Failed to load events: {}", e))?;
+    Ok(crate::stats::hourly_activity(&timestamps, date, &chrono::Local))
+}
+
+/// Focused time per category for the local days `from..=to` (YYYY-MM-DD, default today)
+#[tauri::command]
+pub async fn get_category_breakdown(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<crate::stats::CategoryShare>, String> {
+    let parse = |date: Option<String>| match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(chrono::Local::now().date_naive()),
+    };
+    let (from, to) = (parse(from)?, parse(to)?);
+    if from > to {
+        return Err(format!("Invalid range: {} is after {}", from, to));
+    }
+
+    let state = state.lock().await;
+    Ok(crate::stats::category_breakdown(
+        &state.events_buffer,
+        from,
+        to,
+        &chrono::Local,
+    ))
+}
+
+/// Sites returned by `get_top_domains` when no limit is given, and the most ever returned
+const TOP_DOMAINS_DEFAULT_LIMIT: usize = 10;
+const TOP_DOMAINS_MAX_LIMIT: usize = 100;
+
+/// Most visited sites for the local days `from..=to` (YYYY-MM-DD, default today)
+/// `stripSubdomains` groups mail.google.com and docs.google.com as google.com
+#[tauri::command]
+pub async fn get_top_domains(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    strip_subdomains: Option<bool>,
+) -> Result<Vec<crate::stats::DomainVisits>, String> {
+    let parse = |date: Option<String>| match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(chrono::Local::now().date_naive()),
+    };
+    let (from, to) = (parse(from)?, parse(to)?);
+    if from > to {
+        return Err(format!("Invalid range: {} is after {}", from, to));
+    }
+    let limit = limit.unwrap_or(TOP_DOMAINS_DEFAULT_LIMIT).min(TOP_DOMAINS_MAX_LIMIT);
+
+    let db = state.lock().await.db.clone();
+    let (start, _) = crate::stats::local_day_bounds(from, &chrono::Local);
+    let (_, end) = crate::stats::local_day_bounds(to, &chrono::Local);
+    let events = tokio::task::spawn_blocking(move || db.events_between(start, end))
+        .await
+        .map_err(|e| format!("Failed to load events: {}", e))?
+        .map_err(|e| format!("Failed to load events: {}", e))?;
+
+    Ok(crate::stats::top_domains(&events, limit, strip_subdomains.unwrap_or(false)))
+}
+
+const APP_TRANSITIONS_DEFAULT_LIMIT: usize = 20;
+const APP_TRANSITIONS_MAX_LIMIT: usize = 200;
+
+/// Most frequent app-to-app focus transitions for the local days `from..=to`
+/// (YYYY-MM-DD, default today), for a Sankey/graph view of work patterns
+#[tauri::command]
+pub async fn get_app_transitions(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    from: Option<String>,
+    to: Option<String>,
+    top_n: Option<usize>,
+) -> Result<Vec<crate::stats::Transition>, String> {
+    let parse = |date: Option<String>| match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(chrono::Local::now().date_naive()),
+    };
+    let (from, to) = (parse(from)?, parse(to)?);
+    if from > to {
+        return Err(format!("Invalid range: {} is after {}", from, to));
+    }
+    let limit = top_n.unwrap_or(APP_TRANSITIONS_DEFAULT_LIMIT).min(APP_TRANSITIONS_MAX_LIMIT);
+
+    let db = state.lock().await.db.clone();
+    let (start, _) = crate::stats::local_day_bounds(from, &chrono::Local);
+    let (_, end) = crate::stats::local_day_bounds(to, &chrono::Local);
+    let events = tokio::task::spawn_blocking(move || db.events_between(start, end))
+        .await
+        .map_err(|e| format!("Failed to load events: {}", e))?
+        .map_err(|e| format!("Failed to load events: {}", e))?;
+
+    Ok(crate::stats::app_transitions(&events, limit))
+}
+
+/// Re-run OCR for screenshots in the local days `from..=to` that have no text yet
+/// Runs in the background, emitting `ocr-backfill-progress` events
+#[tauri::command]
+pub async fn backfill_ocr(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+    from: String,
+    to: String,
+) -> Result<crate::backfill::BackfillSummary, String> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))
+    };
+    let (from, to) = (parse(&from)?, parse(&to)?);
+    if from > to {
+        return Err(format!("Invalid range: {} is after {}", from, to));
+    }
+
+    crate::backfill::backfill_ocr(app, state.inner().clone(), from, to).await
+}
+
+/// Cancel a running OCR backfill
+#[tauri::command]
+pub fn cancel_ocr_backfill() -> bool {
+    crate::backfill::cancel_backfill()
+}
+
+/// Show exactly what the next sync would send to the configured sink for the
+/// first `limit` pending events
+#[tauri::command]
+pub async fn preview_sync_payload(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    limit: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let settings = get_settings().unwrap_or_default();
+    let sink = crate::sink::create_sink(&settings.sink);
+    let state = state.lock().await;
+    Ok(crate::sync::preview_sync_payload(
+        &state,
+        sink.as_ref(),
+        limit.unwrap_or(20),
+        settings.min_sync_importance,
+    ))
+}
+
+/// Events waiting to sync and the age of the oldest one, for the sync-health badge
+#[tauri::command]
+pub async fn get_sync_lag(state: State<'_, Arc<Mutex<AppState>>>) -> Result<crate::sync::SyncLag, String> {
+    let state = state.lock().await;
+    Ok(crate::sync::sync_lag(&state, chrono::Utc::now()))
+}
+
+/// Latest raw events for a live activity feed, newest first
+/// `limit` is capped at 1000; `category` and `since` narrow the results
+#[tauri::command]
+pub async fn get_recent_events(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    limit: usize,
+    category: Option<String>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<crate::collector::Event>, String> {
+    let (db, buffered) = {
+        let state = state.lock().await;
+        (state.db.clone(), crate::collector::buffered_matching(&state, category.as_deref(), since))
+    };
+
+    tokio::task::spawn_blocking(move || {
+        crate::collector::recent_events(&db, buffered, limit, category.as_deref(), since)
+    })
+    .await
+    .map_err(|e| format!("Failed to load recent events: {}", e))?
+}
+
+/// Site icons (data URIs) fetched so far, by domain
+/// Domains whose icon isn't cached (yet) are left out
+#[tauri::command]
+pub fn get_favicons(domains: Vec<String>) -> std::collections::HashMap<String, String> {
+    crate::collector::cached_favicons(&domains)
+}
+
+/// Find stored events, e.g. screenshots whose OCR text contains a phrase
+/// Newest first; returned events carry their screenshot paths
+#[tauri::command]
+pub async fn query_events(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    query: crate::db::EventQuery,
+) -> Result<Vec<crate::collector::Event>, String> {
+    let db = state.lock().await.db.clone();
+    db.query_events(&query)
+        .map_err(|e| format!("Failed to query events: {}", e))
+}
+
+/// Manually change the category of an event
+/// With `learn`, future events from the same app get the corrected category
+#[tauri::command]
+pub async fn recategorize_event(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    event_id: String,
+    category: String,
+    learn: Option<bool>,
+) -> Result<(), String> {
+    let app_name = {
+        let mut state = state.lock().await;
+        crate::collector::recategorize_event(&mut state, &event_id, &category)?
+    };
+
+    if learn.unwrap_or(false) {
+        if let Some(app_name) = app_name {
+            crate::collector::learn_rule(&app_name, &category)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the complete event (data, messages, browser tab, metrics) for a detail view
+/// Screenshots are referenced by path only - use `get_screenshot_thumbnail` for the image
+#[tauri::command]
+pub async fn get_event(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    event_id: String,
+) -> Result<Option<crate::collector::Event>, String> {
+    let state = state.lock().await;
+    crate::collector::find_event(&state, &event_id)
+}
+
+/// Delete a single event from the buffer and database, along with its screenshot
+/// Returns false if no such event exists
+#[tauri::command]
+pub async fn delete_event(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    event_id: String,
+) -> Result<bool, String> {
+    let mut state = state.lock().await;
+    crate::collector::delete_event(&mut state, &event_id)
+}
+
+/// Add an event to the timeline by hand (integrations, testing, custom markers)
+#[tauri::command]
+pub async fn add_manual_event(
+    state: State<'_, Arc<Mutex<AppState>>>,
+    event_type: String,
+    app_name: Option<String>,
+    data: Option<serde_json::Value>,
+) -> Result<crate::collector::Event, String> {
+    let mut state = state.lock().await;
+    crate::collector::add_manual_event(
+        &mut state,
+        &event_type,
+        app_name,
+        data.unwrap_or(serde_json::Value::Null),
+        &get_settings().unwrap_or_default(),
+    )
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
@@ -0,0 +1,1062 @@
+// Unified configuration module
+// Everything the app persists about its setup (server, dashboard, API key,
+// WebSocket, device id and app settings) lives in config_dir/observer/config.json.
+// The separate files used by older versions are imported once and renamed.
+
+use crate::automation::trust::SavedTrust;
+use crate::collector::{CategoryRule, CategoryRules, CATEGORY_RULES_FILE};
+use crate::commands::AppSettings;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Name of the unified config file inside the config directory
+pub const CONFIG_FILE: &str = "config.json";
+
+/// Suffix given to legacy files once they have been migrated
+const DEPRECATED_SUFFIX: &str = "deprecated";
+
+/// Legacy single-value files in the config directory
+const LEGACY_API_KEY_FILE: &str = "api_key.txt";
+const LEGACY_SERVER_FILE: &str = "server.txt";
+const LEGACY_DASHBOARD_FILE: &str = "dashboard.txt";
+const LEGACY_DEVICE_ID_FILE: &str = "device_id";
+const LEGACY_SETTINGS_FILE: &str = "settings.json";
+
+/// Longest accepted device name (characters)
+pub const MAX_DEVICE_NAME_CHARS: usize = 100;
+
+/// Name used when the hostname can't be read
+const FALLBACK_DEVICE_NAME: &str = "Observer device";
+
+/// Device id, generated and persisted on first use
+static DEVICE_ID: Lazy<String> = Lazy::new(load_or_create_device_id);
+
+/// The app's config.json
+static STORE: Lazy<ConfigStore> = Lazy::new(|| ConfigStore::new(Config::dir(), Config::legacy_ws_path()));
+
+/// Unified app configuration
+/// Unset values fall back to the defaults of the code that reads them
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dashboard_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Automation WebSocket server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Human-readable device name shown on dashboards (defaults to the hostname)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    /// App settings edited in the settings window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<AppSettings>,
+    /// Automation trust level, changed only through `set_trust_level`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust: Option<SavedTrust>,
+}
+
+impl Config {
+    /// config_dir/observer
+    pub fn dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("observer")
+    }
+
+    /// ~/.observer/config.json, the old automation WebSocket config
+    fn legacy_ws_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".observer").join("config.json"))
+    }
+
+    /// Load the config, migrating legacy files on first run
+    /// Parsed once; later loads return the in-memory copy
+    pub fn load() -> Result<Self, String> {
+        STORE.load()
+    }
+
+    /// Load the config from `dir`, migrating legacy files if it doesn't exist yet
+    pub fn load_from(dir: &Path, legacy_ws_path: Option<&Path>) -> Result<Self, String> {
+        let path = dir.join(CONFIG_FILE);
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read config: {}", e))?;
+            return serde_json::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e));
+        }
+
+        Ok(migrate_legacy(dir, legacy_ws_path))
+    }
+
+    /// Configured device name, or the hostname if none was set
+    pub fn device_name(&self) -> String {
+        self.device_name
+            .clone()
+            .or_else(hostname)
+            .unwrap_or_else(|| FALLBACK_DEVICE_NAME.to_string())
+    }
+
+    /// Change config_dir/observer/config.json
+    /// `edit` runs on the current config under the store's write lock, so
+    /// concurrent writers never overwrite each other's changes
+    pub fn update<T>(edit: impl FnOnce(&mut Config) -> Result<T, String>) -> Result<T, String> {
+        STORE.update(edit)
+    }
+
+    /// Write the config to `dir`, replacing the old file atomically
+    pub fn save_to(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        write_atomic(&dir.join(CONFIG_FILE), content.as_bytes())
+            .map_err(|e| format!("Failed to write config: {}", e))
+    }
+}
+
+/// Write `content` to a temporary file next to `path`, then rename it over `path`
+/// Readers (and a crash mid-write) see either the old file or the new one
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    std::fs::write(&temp, content)?;
+    std::fs::rename(&temp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp);
+    })
+}
+
+/// config.json of one directory, parsed on first load and kept in memory
+/// Settings are read several times per collector tick, so loads don't touch the
+/// disk again; writes go through the store to keep the copy current. Its lock
+/// is the one config write lock, and also makes concurrent first loads migrate
+/// the legacy files only once.
+pub struct ConfigStore {
+    dir: PathBuf,
+    legacy_ws_path: Option<PathBuf>,
+    cached: Mutex<Option<Config>>,
+}
+
+impl ConfigStore {
+    pub fn new(dir: PathBuf, legacy_ws_path: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            legacy_ws_path,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn load(&self) -> Result<Config, String> {
+        self.lock().current()
+    }
+
+    /// Load, `edit` and save the config without another writer in between
+    pub fn update<T>(&self, edit: impl FnOnce(&mut Config) -> Result<T, String>) -> Result<T, String> {
+        let mut writer = self.lock();
+        let mut config = writer.current()?;
+        let result = edit(&mut config)?;
+        writer.save(&config)?;
+        Ok(result)
+    }
+
+    /// Hold the write lock, for writers that do more than change the config
+    pub fn lock(&self) -> ConfigWriter<'_> {
+        ConfigWriter {
+            store: self,
+            cached: self.cached.lock().unwrap(),
+        }
+    }
+}
+
+/// Exclusive access to a `ConfigStore` until dropped
+pub struct ConfigWriter<'a> {
+    store: &'a ConfigStore,
+    cached: MutexGuard<'a, Option<Config>>,
+}
+
+impl ConfigWriter<'_> {
+    pub fn dir(&self) -> &Path {
+        &self.store.dir
+    }
+
+    pub fn current(&mut self) -> Result<Config, String> {
+        if let Some(config) = self.cached.as_ref() {
+            return Ok(config.clone());
+        }
+        let config = Config::load_from(&self.store.dir, self.store.legacy_ws_path.as_deref())?;
+        *self.cached = Some(config.clone());
+        Ok(config)
+    }
+
+    pub fn save(&mut self, config: &Config) -> Result<(), String> {
+        config.save_to(&self.store.dir)?;
+        *self.cached = Some(config.clone());
+        Ok(())
+    }
+}
+
+/// Every user-configurable surface in one object, for the settings page
+/// The API key and device id are not part of it
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullConfig {
+    pub settings: AppSettings,
+    pub device_name: String,
+    #[serde(default)]
+    pub server_url: Option<String>,
+    #[serde(default)]
+    pub dashboard_url: Option<String>,
+    /// Automation WebSocket server
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    #[serde(default)]
+    pub category_rules: Vec<CategoryRule>,
+    /// Automation trust level; shown here but only changed through `set_trust_level`
+    #[serde(default)]
+    pub trust: SavedTrust,
+}
+
+impl FullConfig {
+    /// Gather the current configuration
+    pub fn load() -> Result<Self, String> {
+        let mut config = Config::load()?;
+        // Include settings saved but not written yet
+        if let Some(settings) = crate::settings_writer::pending() {
+            config.settings = Some(settings);
+        }
+        Ok(Self::from_parts(config, CategoryRules::load()))
+    }
+
+    #[cfg(test)]
+    pub fn load_from(dir: &Path) -> Result<Self, String> {
+        let config = Config::load_from(dir, None)?;
+        let rules = CategoryRules::load_from(&dir.join(CATEGORY_RULES_FILE));
+        Ok(Self::from_parts(config, rules))
+    }
+
+    fn from_parts(config: Config, rules: CategoryRules) -> Self {
+        Self {
+            device_name: config.device_name(),
+            settings: config.settings.unwrap_or_default(),
+            server_url: config.server_url,
+            dashboard_url: config.dashboard_url,
+            ws_url: config.ws_url,
+            category_rules: rules.rules,
+            trust: config.trust.unwrap_or_default(),
+        }
+    }
+
+    /// Validated copy with names and URLs trimmed (empty URLs become None)
+    pub fn validated(&self) -> Result<Self, String> {
+        self.settings.validate()?;
+        CategoryRules {
+            rules: self.category_rules.clone(),
+        }
+        .validate()?;
+
+        let http_url = |value: &Option<String>, label: &str| -> Result<Option<String>, String> {
+            match value.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+                Some(url) => crate::sync::validate_url(url)
+                    .map(|_| Some(url.to_string()))
+                    .map_err(|e| format!("{}: {}", label, e)),
+                None => Ok(None),
+            }
+        };
+
+        let ws_url = self.ws_url.as_deref().map(str::trim).filter(|url| !url.is_empty());
+        if let Some(ws_url) = ws_url {
+            let parsed = url::Url::parse(ws_url).map_err(|e| format!("wsUrl: Invalid URL: {}", e))?;
+            if !matches!(parsed.scheme(), "ws" | "wss") {
+                return Err("wsUrl: only ws and wss URLs are allowed".to_string());
+            }
+        }
+
+        Ok(Self {
+            settings: self.settings.clone(),
+            device_name: normalize_device_name(&self.device_name)?,
+            server_url: http_url(&self.server_url, "serverUrl")?,
+            dashboard_url: http_url(&self.dashboard_url, "dashboardUrl")?,
+            ws_url: ws_url.map(|url| url.to_string()),
+            category_rules: self
+                .category_rules
+                .iter()
+                .map(|rule| CategoryRule {
+                    app_name: rule.app_name.trim().to_string(),
+                    category: rule.category.clone(),
+                })
+                .collect(),
+            trust: self.trust,
+        })
+    }
+
+    /// Validate everything, then save; nothing is written if any field is invalid
+    /// Returns the saved (normalized) configuration
+    pub fn save(&self) -> Result<Self, String> {
+        self.write(STORE.lock())
+    }
+
+    #[cfg(test)]
+    pub fn save_to(&self, dir: &Path) -> Result<Self, String> {
+        self.write(ConfigStore::new(dir.to_path_buf(), None).lock())
+    }
+
+    fn write(&self, mut writer: ConfigWriter<'_>) -> Result<Self, String> {
+        let mut config = writer.current()?;
+        let mut validated = self.validated()?;
+        // Global typed text is only switched by its own opt-in command, never by a config save or import
+        validated.settings.global_typed_text = config.settings.as_ref().is_some_and(|settings| settings.global_typed_text);
+        // Likewise the trust level, so a saved or imported config can't grant full trust
+        validated.trust = config.trust.unwrap_or_default();
+
+        config.settings = Some(validated.settings.clone());
+        config.device_name = Some(validated.device_name.clone());
+        config.server_url = validated.server_url.clone();
+        config.dashboard_url = validated.dashboard_url.clone();
+        config.ws_url = validated.ws_url.clone();
+
+        let rules_path = writer.dir().join(CATEGORY_RULES_FILE);
+        let previous_rules = std::fs::read(&rules_path).ok();
+        CategoryRules {
+            rules: validated.category_rules.clone(),
+        }
+        .save_to(&rules_path)?;
+
+        if let Err(e) = writer.save(&config) {
+            // Put the old rules back so the two files stay consistent
+            let restored = match previous_rules {
+                Some(content) => write_atomic(&rules_path, &content),
+                None => std::fs::remove_file(&rules_path),
+            };
+            if let Err(restore_error) = restored {
+                crate::logs::error(&format!("[Config] Failed to restore category rules: {}", restore_error));
+            }
+            return Err(e);
+        }
+
+        Ok(validated)
+    }
+}
+
+/// How an imported configuration is combined with the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Imported values win; settings and rules missing from the file are kept
+    Merge,
+    /// The file replaces the configuration; anything missing goes back to defaults
+    Replace,
+}
+
+/// Export fields that describe this machine rather than the setup
+const MACHINE_FIELDS: &[&str] = &["deviceName", "trust"];
+
+/// Servers that receive events and send automation commands; an import only
+/// changes them when the user confirms
+const ENDPOINT_FIELDS: &[&str] = &["serverUrl", "wsUrl"];
+
+/// Shareable form of a configuration: secrets and the device name are left out
+/// Webhook URLs and headers usually carry tokens, so a webhook sink and the
+/// event hooks are not exported; importing the file keeps the current ones.
+pub fn export_json(full: &FullConfig) -> Result<serde_json::Value, String> {
+    let mut value =
+        serde_json::to_value(full).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let Some(object) = value.as_object_mut() else {
+        return Err("Failed to serialize config".to_string());
+    };
+    for field in MACHINE_FIELDS {
+        object.remove(*field);
+    }
+    if let Some(settings) = object.get_mut("settings").and_then(|settings| settings.as_object_mut()) {
+        if matches!(full.settings.sink, crate::sink::SinkConfig::Webhook { .. }) {
+            settings.remove("sink");
+        }
+        settings.remove("eventHooks");
+    }
+    Ok(value)
+}
+
+/// Combine an exported configuration with `current`
+/// Server URLs are only taken from the file with `trust_endpoints`; otherwise
+/// the current ones are kept. The result is validated but not saved.
+pub fn import_json(
+    current: &FullConfig,
+    imported: serde_json::Value,
+    mode: ImportMode,
+    trust_endpoints: bool,
+) -> Result<FullConfig, String> {
+    let serde_json::Value::Object(mut imported) = imported else {
+        return Err("Imported config must be a JSON object".to_string());
+    };
+    for field in MACHINE_FIELDS {
+        imported.remove(*field);
+    }
+    if !trust_endpoints {
+        for field in ENDPOINT_FIELDS {
+            if imported.remove(*field).is_some() {
+                crate::logs::info(&format!("[Config] Import: keeping the current {} (not confirmed)", field));
+            }
+        }
+    }
+
+    let base = match mode {
+        ImportMode::Merge => current.clone(),
+        ImportMode::Replace => FullConfig {
+            settings: AppSettings::default(),
+            device_name: current.device_name.clone(),
+            server_url: if trust_endpoints { None } else { current.server_url.clone() },
+            dashboard_url: None,
+            ws_url: if trust_endpoints { None } else { current.ws_url.clone() },
+            category_rules: Vec::new(),
+            trust: current.trust,
+        },
+    };
+    let serde_json::Value::Object(mut merged) =
+        serde_json::to_value(&base).map_err(|e| format!("Failed to serialize config: {}", e))?
+    else {
+        return Err("Failed to serialize config".to_string());
+    };
+
+    for (key, value) in imported {
+        if key == "settings" {
+            let (Some(serde_json::Value::Object(settings)), serde_json::Value::Object(values)) =
+                (merged.get_mut("settings"), value)
+            else {
+                return Err("settings: expected an object".to_string());
+            };
+            settings.extend(values);
+        } else if mode == ImportMode::Merge && key == "categoryRules" {
+            let rules: Vec<CategoryRule> =
+                serde_json::from_value(value).map_err(|e| format!("categoryRules: {}", e))?;
+            let mut merged_rules = CategoryRules {
+                rules: base.category_rules.clone(),
+            };
+            for rule in rules {
+                merged_rules.learn(&rule.app_name, &rule.category);
+            }
+            let rules = serde_json::to_value(merged_rules.rules)
+                .map_err(|e| format!("Failed to serialize category rules: {}", e))?;
+            merged.insert(key, rules);
+        } else {
+            merged.insert(key, value);
+        }
+    }
+
+    let full: FullConfig = serde_json::from_value(serde_json::Value::Object(merged))
+        .map_err(|e| format!("Invalid config: {}", e))?;
+    full.validated()
+}
+
+/// Write the shareable form of the current configuration to `path`
+pub fn export_config(path: &Path) -> Result<(), String> {
+    let value = export_json(&FullConfig::load()?)?;
+    let content = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    std::fs::write(path, content).map_err(|e| format!("Failed to write config export: {}", e))
+}
+
+/// Import a configuration exported by `export_config` and save it
+/// Returns the saved configuration
+pub fn import_config(path: &Path, mode: ImportMode, trust_endpoints: bool) -> Result<FullConfig, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config export: {}", e))?;
+    let imported: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config export: {}", e))?;
+
+    import_json(&FullConfig::load()?, imported, mode, trust_endpoints)?.save()
+}
+
+/// Non-empty, trimmed value of an environment variable
+/// Environment variables override the config file
+pub fn env_override(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Stable id of this device
+pub fn device_id() -> String {
+    DEVICE_ID.clone()
+}
+
+/// Friendly name of this device
+pub fn device_name() -> String {
+    Config::load().unwrap_or_default().device_name()
+}
+
+/// Rename this device (the device id is unchanged)
+/// Returns the stored name
+pub fn set_device_name(name: &str) -> Result<String, String> {
+    let name = normalize_device_name(name)?;
+    Config::update(|config| {
+        config.device_name = Some(name.clone());
+        Ok(name)
+    })
+}
+
+/// Trimmed, non-empty device name of at most MAX_DEVICE_NAME_CHARS
+fn normalize_device_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Device name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_DEVICE_NAME_CHARS {
+        return Err(format!(
+            "Device name is too long (max {} characters)",
+            MAX_DEVICE_NAME_CHARS
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Hostname without the mDNS ".local" suffix
+fn hostname() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    let raw = {
+        let mut buf = [0u8; 256];
+        let result = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+        if result != 0 {
+            return None;
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        String::from_utf8_lossy(&buf[..len]).to_string()
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    let raw = env_override("COMPUTERNAME")
+        .or_else(|| env_override("HOSTNAME"))
+        .or_else(|| read_trimmed(Path::new("/etc/hostname")))?;
+
+    let name = raw.trim();
+    let name = name.strip_suffix(".local").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn load_or_create_device_id() -> String {
+    let created = Config::update(|config| {
+        Ok(config
+            .device_id
+            .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+            .clone())
+    });
+    // A config we couldn't parse or write is left alone; the id then lasts until exit
+    created.unwrap_or_else(|e| {
+        crate::logs::error(&format!("[Config] {}", e));
+        uuid::Uuid::new_v4().to_string()
+    })
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Build the config from the legacy files, write it, and rename the legacy files
+/// Returns an empty config if there was nothing to migrate
+fn migrate_legacy(dir: &Path, legacy_ws_path: Option<&Path>) -> Config {
+    let mut config = Config::default();
+    let mut migrated: Vec<PathBuf> = Vec::new();
+
+    let text_values: [(&str, &mut Option<String>); 4] = [
+        (LEGACY_API_KEY_FILE, &mut config.api_key),
+        (LEGACY_SERVER_FILE, &mut config.server_url),
+        (LEGACY_DASHBOARD_FILE, &mut config.dashboard_url),
+        (LEGACY_DEVICE_ID_FILE, &mut config.device_id),
+    ];
+    for (name, value) in text_values {
+        let path = dir.join(name);
+        if let Some(content) = read_trimmed(&path) {
+            *value = Some(content);
+            migrated.push(path);
+        }
+    }
+
+    let settings_path = dir.join(LEGACY_SETTINGS_FILE);
+    if let Ok(content) = std::fs::read_to_string(&settings_path) {
+        match serde_json::from_str::<AppSettings>(&content) {
+            Ok(settings) => {
+                config.settings = Some(settings);
+                migrated.push(settings_path);
+            }
+            Err(e) => crate::logs::error(&format!("[Config] Not migrating unreadable settings.json: {}", e)),
+        }
+    }
+
+    if let Some(ws_path) = legacy_ws_path {
+        let ws_config = std::fs::read_to_string(ws_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+        if let Some(json) = ws_config {
+            let field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(|v| v.to_string());
+            config.ws_url = field("ws_url");
+            config.api_key = config.api_key.or_else(|| field("api_key"));
+            config.device_id = config.device_id.or_else(|| field("device_id"));
+            migrated.push(ws_path.to_path_buf());
+        }
+    }
+
+    if migrated.is_empty() {
+        return config;
+    }
+
+    if let Err(e) = config.save_to(dir) {
+        // Leave the legacy files alone so nothing is lost
+        crate::logs::error(&format!("[Config] Migration failed: {}", e));
+        return config;
+    }
+
+    for path in &migrated {
+        let mut deprecated = path.clone().into_os_string();
+        deprecated.push(format!(".{}", DEPRECATED_SUFFIX));
+        if let Err(e) = std::fs::rename(path, &deprecated) {
+            crate::logs::error(&format!("[Config] Failed to deprecate {}: {}", path.display(), e));
+        }
+    }
+    crate::logs::info(&format!(
+        "[Config] Migrated {} legacy config files into {}",
+        migrated.len(),
+        dir.join(CONFIG_FILE).display()
+    ));
+
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("observer_config_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_unified_config() {
+        let dir = temp_dir();
+        std::fs::write(
+            dir.join(CONFIG_FILE),
+            r#"{
+                "server_url": "https://api.example.com",
+                "api_key": "key-123",
+                "settings": {"apiUrl": "https://app.example.com", "syncInterval": 60, "launchAtStartup": true}
+            }"#,
+        )
+        .unwrap();
+        // Ignored once the unified config exists
+        std::fs::write(dir.join(LEGACY_SERVER_FILE), "https://old.example.com").unwrap();
+
+        let config = Config::load_from(&dir, None).unwrap();
+        assert_eq!(config.server_url.as_deref(), Some("https://api.example.com"));
+        assert_eq!(config.api_key.as_deref(), Some("key-123"));
+        assert_eq!(config.dashboard_url, None);
+        assert_eq!(config.settings.unwrap().sync_interval, 60);
+        assert!(dir.join(LEGACY_SERVER_FILE).exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_invalid_config_is_an_error() {
+        let dir = temp_dir();
+        std::fs::write(dir.join(CONFIG_FILE), "{ not json").unwrap();
+
+        assert!(Config::load_from(&dir, None).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_migrates_legacy_files() {
+        let dir = temp_dir();
+        let home = temp_dir();
+        std::fs::write(dir.join(LEGACY_API_KEY_FILE), "legacy-key\n").unwrap();
+        std::fs::write(dir.join(LEGACY_SERVER_FILE), "https://api.example.com").unwrap();
+        std::fs::write(dir.join(LEGACY_DASHBOARD_FILE), "https://app.example.com").unwrap();
+        std::fs::write(dir.join(LEGACY_DEVICE_ID_FILE), "device-1").unwrap();
+        std::fs::write(
+            dir.join(LEGACY_SETTINGS_FILE),
+            r#"{"apiUrl": "https://app.example.com", "syncInterval": 45, "launchAtStartup": false}"#,
+        )
+        .unwrap();
+        let ws_path = home.join("config.json");
+        std::fs::write(
+            &ws_path,
+            r#"{"ws_url": "wss://ws.example.com", "api_key": "ws-key", "device_id": "mac-host"}"#,
+        )
+        .unwrap();
+
+        let config = Config::load_from(&dir, Some(&ws_path)).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("legacy-key"));
+        assert_eq!(config.server_url.as_deref(), Some("https://api.example.com"));
+        assert_eq!(config.dashboard_url.as_deref(), Some("https://app.example.com"));
+        assert_eq!(config.ws_url.as_deref(), Some("wss://ws.example.com"));
+        assert_eq!(config.device_id.as_deref(), Some("device-1"));
+        assert_eq!(config.settings.as_ref().unwrap().sync_interval, 45);
+
+        // Legacy files are renamed, not read again
+        assert!(!dir.join(LEGACY_SERVER_FILE).exists());
+        assert!(dir.join("server.txt.deprecated").exists());
+        assert!(!ws_path.exists());
+
+        // The written config loads back the same
+        let reloaded = Config::load_from(&dir, Some(&ws_path)).unwrap();
+        assert_eq!(reloaded.ws_url.as_deref(), Some("wss://ws.example.com"));
+        assert_eq!(reloaded.settings.unwrap().sync_interval, 45);
+
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::remove_dir_all(&home).ok();
+    }
+
+    #[test]
+    fn test_concurrent_first_loads_migrate_once() {
+        let dir = temp_dir();
+        std::fs::write(dir.join(LEGACY_API_KEY_FILE), "legacy-key").unwrap();
+        let store = std::sync::Arc::new(ConfigStore::new(dir.clone(), None));
+
+        let loads: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || store.load().unwrap())
+            })
+            .collect();
+        // Every load sees the migrated key, none an empty config from a half-done migration
+        for load in loads {
+            assert_eq!(load.join().unwrap().api_key.as_deref(), Some("legacy-key"));
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_store_serves_loads_from_memory() {
+        let dir = temp_dir();
+        let store = ConfigStore::new(dir.clone(), None);
+        store
+            .update(|config| {
+                config.server_url = Some("https://api.example.com".to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            Config::load_from(&dir, None).unwrap().server_url.as_deref(),
+            Some("https://api.example.com")
+        );
+
+        // Not read from disk again
+        std::fs::write(dir.join(CONFIG_FILE), "{ not json").unwrap();
+        assert_eq!(store.load().unwrap().server_url.as_deref(), Some("https://api.example.com"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = temp_dir();
+        let store = std::sync::Arc::new(ConfigStore::new(dir.clone(), None));
+
+        let writers: Vec<_> = (0..16)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    store
+                        .update(|config| {
+                            config.device_name.get_or_insert_with(String::new).push('x');
+                            Ok(())
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let saved = Config::load_from(&dir, None).unwrap();
+        assert_eq!(saved.device_name.as_deref(), Some("x".repeat(16).as_str()));
+        // Only the config itself is left behind, no temporary file
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(files, vec![std::ffi::OsString::from(CONFIG_FILE)]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_device_name_defaults_to_hostname() {
+        let config = Config::default();
+        let expected = hostname().unwrap_or_else(|| FALLBACK_DEVICE_NAME.to_string());
+        assert_eq!(config.device_name(), expected);
+        assert!(!config.device_name().ends_with(".local"));
+    }
+
+    #[test]
+    fn test_device_name_persists_and_keeps_id() {
+        let dir = temp_dir();
+        let config = Config {
+            device_id: Some("device-1".to_string()),
+            ..Config::default()
+        };
+        config.save_to(&dir).unwrap();
+
+        let mut config = Config::load_from(&dir, None).unwrap();
+        config.device_name = Some(normalize_device_name("  Work laptop ").unwrap());
+        config.save_to(&dir).unwrap();
+
+        let reloaded = Config::load_from(&dir, None).unwrap();
+        assert_eq!(reloaded.device_name(), "Work laptop");
+        assert_eq!(reloaded.device_id.as_deref(), Some("device-1"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_invalid_device_names() {
+        assert!(normalize_device_name("   ").is_err());
+        assert!(normalize_device_name(&"x".repeat(MAX_DEVICE_NAME_CHARS + 1)).is_err());
+        assert_eq!(normalize_device_name(&"x".repeat(MAX_DEVICE_NAME_CHARS)).unwrap().len(), 100);
+    }
+
+    fn full_config() -> FullConfig {
+        FullConfig {
+            settings: AppSettings {
+                api_url: "https://app.example.com".to_string(),
+                sync_interval: 60,
+                ..AppSettings::default()
+            },
+            device_name: "Work laptop".to_string(),
+            server_url: Some("https://api.example.com".to_string()),
+            dashboard_url: None,
+            ws_url: Some("wss://ws.example.com".to_string()),
+            category_rules: vec![CategoryRule {
+                app_name: "Figma".to_string(),
+                category: "design".to_string(),
+            }],
+            trust: SavedTrust::default(),
+        }
+    }
+
+    #[test]
+    fn test_full_config_round_trip() {
+        let dir = temp_dir();
+        Config {
+            api_key: Some("key-123".to_string()),
+            device_id: Some("device-1".to_string()),
+            ..Config::default()
+        }
+        .save_to(&dir)
+        .unwrap();
+
+        let mut full = full_config();
+        full.device_name = "  Work laptop ".to_string();
+        full.dashboard_url = Some("  ".to_string());
+        let saved = full.save_to(&dir).unwrap();
+        assert_eq!(saved.device_name, "Work laptop");
+        assert_eq!(saved.dashboard_url, None);
+
+        let loaded = FullConfig::load_from(&dir).unwrap();
+        assert_eq!(loaded.device_name, "Work laptop");
+        assert_eq!(loaded.server_url.as_deref(), Some("https://api.example.com"));
+        assert_eq!(loaded.ws_url.as_deref(), Some("wss://ws.example.com"));
+        assert_eq!(loaded.settings.sync_interval, 60);
+        assert_eq!(loaded.category_rules, full_config().category_rules);
+
+        // Values outside the full config are kept
+        let config = Config::load_from(&dir, None).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("key-123"));
+        assert_eq!(config.device_id.as_deref(), Some("device-1"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_full_config_keeps_saved_trust_level() {
+        use crate::automation::TrustLevel;
+
+        let dir = temp_dir();
+        let asking = SavedTrust {
+            level: TrustLevel::AskAlways,
+            expires_at: None,
+        };
+        Config {
+            trust: Some(asking),
+            ..Config::default()
+        }
+        .save_to(&dir)
+        .unwrap();
+        assert_eq!(FullConfig::load_from(&dir).unwrap().trust, asking);
+
+        // Neither a save nor an import raises it to full trust
+        let mut full = full_config();
+        full.trust.level = TrustLevel::FullTrust;
+        assert_eq!(full.save_to(&dir).unwrap().trust, asking);
+        let imported = import_json(
+            &FullConfig::load_from(&dir).unwrap(),
+            serde_json::json!({"trust": {"level": "FullTrust"}}),
+            ImportMode::Merge,
+            false,
+        )
+        .unwrap();
+        imported.save_to(&dir).unwrap();
+
+        assert_eq!(Config::load_from(&dir, None).unwrap().trust, Some(asking));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_invalid_full_config_persists_nothing() {
+        let dir = temp_dir();
+        full_config().save_to(&dir).unwrap();
+
+        let invalid: Vec<Box<dyn Fn(&mut FullConfig)>> = vec![
+            Box::new(|full| full.category_rules[0].category = "gaming".to_string()),
+            Box::new(|full| full.settings.sync_interval = 0),
+            Box::new(|full| full.settings.screenshot_categories = vec!["nope".to_string()]),
+            Box::new(|full| full.settings.metrics_smoothing_alpha = 0.0),
+            Box::new(|full| full.settings.screenshot_scale = 1.5),
+            Box::new(|full| full.server_url = Some("ftp://api.example.com".to_string())),
+            Box::new(|full| full.ws_url = Some("https://ws.example.com".to_string())),
+            Box::new(|full| full.device_name = " ".to_string()),
+        ];
+        for make_invalid in invalid {
+            // Valid changes alongside the invalid one must not be saved either
+            let mut full = full_config();
+            full.device_name = "Renamed".to_string();
+            full.settings.sync_interval = 120;
+            full.category_rules.push(CategoryRule {
+                app_name: "Slack".to_string(),
+                category: "communication".to_string(),
+            });
+            make_invalid(&mut full);
+
+            assert!(full.save_to(&dir).is_err());
+
+            let loaded = FullConfig::load_from(&dir).unwrap();
+            assert_eq!(loaded.device_name, "Work laptop");
+            assert_eq!(loaded.settings.sync_interval, 60);
+            assert_eq!(loaded.category_rules, full_config().category_rules);
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_export_import_round_trip_strips_secrets() {
+        let mut full = full_config();
+        full.settings.sink = crate::sink::SinkConfig::Webhook {
+            url: "https://hooks.example.com/events?token=secret".to_string(),
+            headers: [("Authorization".to_string(), "Bearer secret".to_string())].into(),
+        };
+        full.settings.event_hooks = vec![crate::event_hooks::EventHookRule {
+            matches: crate::event_hooks::EventMatch {
+                app: Some("Figma".to_string()),
+                ..Default::default()
+            },
+            webhook_url: "https://hooks.example.com/secret-path".to_string(),
+        }];
+        full.settings.screenshot_categories = vec!["coding".to_string()];
+
+        let exported = export_json(&full).unwrap();
+        let text = exported.to_string();
+        assert!(!text.contains("secret"));
+        assert!(!text.contains("Work laptop"));
+
+        let mut other_machine = full_config();
+        other_machine.device_name = "Home desktop".to_string();
+        other_machine.server_url = None;
+        other_machine.category_rules.clear();
+
+        let imported = import_json(&other_machine, exported.clone(), ImportMode::Replace, true).unwrap();
+        assert_eq!(imported.device_name, "Home desktop");
+        assert_eq!(imported.server_url, full.server_url);
+        assert_eq!(imported.ws_url, full.ws_url);
+        assert_eq!(imported.category_rules, full.category_rules);
+        assert_eq!(imported.settings.sync_interval, 60);
+        assert_eq!(imported.settings.screenshot_categories, vec!["coding".to_string()]);
+        assert_eq!(imported.settings.sink, crate::sink::SinkConfig::default());
+        assert!(imported.settings.event_hooks.is_empty());
+
+        // Merging keeps this machine's webhooks
+        let mut with_webhook = other_machine.clone();
+        with_webhook.settings.sink = full.settings.sink.clone();
+        let merged = import_json(&with_webhook, exported, ImportMode::Merge, true).unwrap();
+        assert_eq!(merged.settings.sink, full.settings.sink);
+    }
+
+    #[test]
+    fn test_import_keeps_servers_unless_confirmed() {
+        let current = full_config();
+        let imported = serde_json::json!({
+            "serverUrl": "https://attacker.example.com",
+            "wsUrl": "wss://attacker.example.com",
+            "settings": { "syncInterval": 300 }
+        });
+
+        for mode in [ImportMode::Merge, ImportMode::Replace] {
+            let unconfirmed = import_json(&current, imported.clone(), mode, false).unwrap();
+            assert_eq!(unconfirmed.server_url, current.server_url);
+            assert_eq!(unconfirmed.ws_url, current.ws_url);
+            assert_eq!(unconfirmed.settings.sync_interval, 300);
+
+            let confirmed = import_json(&current, imported.clone(), mode, true).unwrap();
+            assert_eq!(confirmed.server_url.as_deref(), Some("https://attacker.example.com"));
+            assert_eq!(confirmed.ws_url.as_deref(), Some("wss://attacker.example.com"));
+        }
+    }
+
+    #[test]
+    fn test_import_merge_and_replace() {
+        let mut current = full_config();
+        current.settings.url_dedup_secs = 5;
+        let imported = serde_json::json!({
+            "settings": { "syncInterval": 300 },
+            "categoryRules": [
+                { "app_name": "figma", "category": "other" },
+                { "app_name": "Slack", "category": "communication" }
+            ],
+            "deviceName": "Someone else's laptop"
+        });
+
+        let merged = import_json(&current, imported.clone(), ImportMode::Merge, false).unwrap();
+        assert_eq!(merged.settings.sync_interval, 300);
+        assert_eq!(merged.settings.url_dedup_secs, 5);
+        assert_eq!(merged.server_url, current.server_url);
+        assert_eq!(merged.device_name, "Work laptop");
+        let rules: Vec<(&str, &str)> = merged
+            .category_rules
+            .iter()
+            .map(|rule| (rule.app_name.as_str(), rule.category.as_str()))
+            .collect();
+        assert_eq!(rules, vec![("figma", "other"), ("Slack", "communication")]);
+
+        let replaced = import_json(&current, imported, ImportMode::Replace, true).unwrap();
+        assert_eq!(replaced.settings.sync_interval, 300);
+        assert_eq!(replaced.settings.url_dedup_secs, crate::collector::DEFAULT_URL_DEDUP_SECS);
+        assert_eq!(replaced.server_url, None);
+        assert_eq!(replaced.category_rules.len(), 2);
+        assert_eq!(replaced.device_name, "Work laptop");
+
+        let invalid = serde_json::json!({ "categoryRules": [{ "app_name": "X", "category": "gaming" }] });
+        assert!(import_json(&current, invalid, ImportMode::Merge, false).is_err());
+    }
+
+    #[test]
+    fn test_nothing_to_migrate() {
+        let dir = temp_dir();
+
+        let config = Config::load_from(&dir, None).unwrap();
+        assert!(config.api_key.is_none());
+        assert!(!dir.join(CONFIG_FILE).exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
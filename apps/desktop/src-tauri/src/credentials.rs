@@ -0,0 +1,209 @@
+// API token storage
+// The token authenticating this device (sync, cloud OCR and the automation
+// WebSocket) is kept in the macOS keychain when available, otherwise in the
+// config file. OBSERVER_API_KEY in the environment always takes precedence.
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable overriding the stored token
+pub const API_KEY_ENV: &str = "OBSERVER_API_KEY";
+/// Server path that exchanges the token sent for a fresh one
+pub const ROTATE_TOKEN_PATH: &str = "/api/v1/auth/rotate-token";
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const KEYCHAIN_SERVICE: &str = "com.observer.desktop";
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const KEYCHAIN_ACCOUNT: &str = "api_key";
+
+/// Trailing characters `mask_token` leaves visible
+const VISIBLE_TOKEN_CHARS: usize = 4;
+/// Shorter tokens are masked completely
+const MIN_PARTIAL_MASK_CHARS: usize = 12;
+/// The mask has a fixed length so it doesn't give away the token's
+const MASK: &str = "••••••••";
+
+/// Where the token in use comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenSource {
+    Env,
+    Keychain,
+    Config,
+}
+
+/// The token in use, safe to show in the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaskedToken {
+    pub masked: Option<String>,
+    pub source: Option<TokenSource>,
+}
+
+/// Hide all but the last few characters of a token
+pub fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.trim().chars().collect();
+    if chars.len() < MIN_PARTIAL_MASK_CHARS {
+        return MASK.to_string();
+    }
+    let visible: String = chars[chars.len() - VISIBLE_TOKEN_CHARS..].iter().collect();
+    format!("{}{}", MASK, visible)
+}
+
+/// Token in use and where it came from: environment, keychain, then config file
+pub fn current_token() -> Option<(String, TokenSource)> {
+    if let Some(token) = crate::config::env_override(API_KEY_ENV) {
+        return Some((token, TokenSource::Env));
+    }
+    if let Some(token) = keychain::get() {
+        return Some((token, TokenSource::Keychain));
+    }
+
+    crate::config::Config::load()
+        .ok()
+        .and_then(|config| config.api_key)
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .map(|token| (token, TokenSource::Config))
+}
+
+pub fn masked_token() -> MaskedToken {
+    match current_token() {
+        Some((token, source)) => MaskedToken {
+            masked: Some(mask_token(&token)),
+            source: Some(source),
+        },
+        None => MaskedToken { masked: None, source: None },
+    }
+}
+
+/// Store a token in the keychain, or in the config file where there is none
+/// Once the keychain has it, a copy left in the config file is removed.
+pub fn store_token(token: &str) -> Result<TokenSource, String> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err("Token can't be empty".to_string());
+    }
+
+    match keychain::set(token) {
+        Ok(()) => {
+            crate::config::Config::update(|config| {
+                config.api_key = None;
+                Ok(())
+            })?;
+            Ok(TokenSource::Keychain)
+        }
+        Err(e) => {
+            if cfg!(target_os = "macos") {
+                crate::logs::error(&format!("[Credentials] {}, storing token in config file", e));
+            }
+            crate::config::Config::update(|config| {
+                config.api_key = Some(token.to_string());
+                Ok(())
+            })?;
+            Ok(TokenSource::Config)
+        }
+    }
+}
+
+/// Remove the stored token from the keychain and the config file
+pub fn clear_token() -> Result<(), String> {
+    keychain::delete()?;
+    crate::config::Config::update(|config| {
+        config.api_key = None;
+        Ok(())
+    })
+}
+
+#[derive(Deserialize)]
+struct RotatedToken {
+    token: String,
+}
+
+/// Ask the server to replace `current` with a fresh token
+pub async fn request_rotated_token(server_url: &str, current: &str) -> Result<String, String> {
+    let client = crate::sync::shared_http_client().map_err(|e| e.to_string())?;
+    let response = client
+        .post(format!("{}{}", server_url.trim_end_matches('/'), ROTATE_TOKEN_PATH))
+        .header("X-API-Key", current)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach server: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return Err("Token rotation is not supported by this server".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("Failed to rotate token: server returned {}", status));
+    }
+
+    let rotated: RotatedToken = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse rotated token: {}", e))?;
+    let token = rotated.token.trim();
+    if token.is_empty() {
+        return Err("Failed to rotate token: server returned an empty token".to_string());
+    }
+    Ok(token.to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod keychain {
+    use super::{KEYCHAIN_ACCOUNT, KEYCHAIN_SERVICE};
+    use security_framework::passwords;
+
+    const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+    pub fn get() -> Option<String> {
+        let bytes = passwords::get_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).ok()?;
+        String::from_utf8(bytes)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    }
+
+    pub fn set(token: &str) -> Result<(), String> {
+        passwords::set_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, token.as_bytes())
+            .map_err(|e| format!("Failed to store token in keychain: {}", e))
+    }
+
+    pub fn delete() -> Result<(), String> {
+        match passwords::delete_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT) {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(()),
+            Err(e) => Err(format!("Failed to remove token from keychain: {}", e)),
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod keychain {
+    pub fn get() -> Option<String> {
+        None
+    }
+
+    pub fn set(_token: &str) -> Result<(), String> {
+        Err("No keychain on this platform".to_string())
+    }
+
+    pub fn delete() -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_token() {
+        assert_eq!(mask_token("obs_live_4f9a2c71d0e8b3a6"), "••••••••b3a6");
+        assert_eq!(mask_token("  obs_live_4f9a2c71d0e8b3a6\n"), "••••••••b3a6");
+        // Short tokens are hidden entirely, and every mask has the same length
+        assert_eq!(mask_token("abc123"), MASK);
+        assert_eq!(mask_token(""), MASK);
+        assert_eq!(mask_token("ключ-ключ-ключ"), "••••••••ключ");
+        assert!(!mask_token("obs_live_4f9a2c71d0e8b3a6").contains("obs_live"));
+    }
+}
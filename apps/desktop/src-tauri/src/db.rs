@@ -1,25 +1,215 @@
 // Database module for persisting events to disk
 use crate::collector::Event;
+use chrono::{DateTime, NaiveDate, Utc};
 use rusqlite::{params, Connection, Result as SqliteResult};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// Events returned by `query_events` when no limit is given, and the most ever returned
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+pub const MAX_QUERY_LIMIT: usize = 1_000;
+
+/// Filters for `EventDatabase::query_events` (unset fields match everything)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub app_name: Option<String>,
+    /// Category, compared case-insensitively
+    pub category: Option<String>,
+    /// Only events with (true) or without (false) a screenshot
+    pub has_screenshot: Option<bool>,
+    /// Case-insensitive substring of the screenshot's OCR text
+    pub ocr_contains: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Totals for the events in a time window, used to project future usage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActivitySample {
+    pub events: u64,
+    /// Days between the oldest event in the window and the window's end
+    pub span_days: f64,
+    /// Characters in the fields sync sends (excluding JSON keys)
+    pub sync_chars: u64,
+    /// Characters of OCR text stored with screenshots
+    pub ocr_chars: u64,
+    /// Distinct screenshot files referenced
+    pub screenshot_paths: Vec<String>,
+}
+
+/// Most problems `check_integrity` reports
+const MAX_INTEGRITY_PROBLEMS: usize = 100;
+/// IDs bound per `IN (...)` query, well below SQLite's limit on bound parameters
+const MAX_IDS_PER_QUERY: usize = 500;
+
+/// Result of `PRAGMA integrity_check`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// SQLite's descriptions of what is wrong (empty when ok)
+    pub problems: Vec<String>,
+}
+
+/// `%text%` LIKE pattern matching `text` literally
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Columns read back into an `Event`, in the order `event_from_row` expects
+const EVENT_COLUMNS: &str = "id, device_id, event_type, timestamp, app_name, window_title, url,
+    data, category, browser_tab, messages, screenshot_path,
+    system_metrics, typed_text, thumbnail_path";
+
+fn event_from_row(row: &rusqlite::Row) -> SqliteResult<Event> {
+    let timestamp_str: String = row.get(3)?;
+    let data_str: String = row.get(7)?;
+    let browser_tab_str: Option<String> = row.get(9)?;
+    let messages_str: Option<String> = row.get(10)?;
+    let system_metrics_str: Option<String> = row.get(12)?;
+
+    Ok(Event {
+        id: row.get(0)?,
+        device_id: row.get(1)?,
+        event_type: row.get(2)?,
+        timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp_str)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now()),
+        app_name: row.get(4)?,
+        window_title: row.get(5)?,
+        url: row.get(6)?,
+        data: serde_json::from_str(&data_str).unwrap_or(serde_json::json!({})),
+        category: row.get(8)?,
+        browser_tab: browser_tab_str.and_then(|s| serde_json::from_str(&s).ok()),
+        messages: messages_str.and_then(|s| serde_json::from_str(&s).ok()),
+        screenshot_path: row.get(11)?,
+        system_metrics: system_metrics_str.and_then(|s| serde_json::from_str(&s).ok()),
+        typed_text: row.get(13)?,
+        thumbnail_path: row.get(14)?,
+    })
+}
+
 pub struct EventDatabase {
     conn: Arc<Mutex<Connection>>,
 }
 
+/// `path` with `suffix` appended to the file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Marker left next to the database when a background check finds it corrupt,
+/// so the next `open` moves it aside before anything is written to it
+fn recovery_marker(db_path: &Path) -> PathBuf {
+    with_suffix(db_path, ".needs-recovery")
+}
+
+/// Whether SQLite reported the file as damaged or not a database at all
+/// (other errors, e.g. a locked or unreadable file, don't mean the data is lost)
+fn is_corruption(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _)
+            if matches!(e.code, rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+    )
+}
+
+/// Move a corrupt database and its side files to `<name>.corrupt-<timestamp>`
+fn backup_corrupt(db_path: &Path) -> std::io::Result<Option<PathBuf>> {
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let backup = with_suffix(db_path, &format!(".corrupt-{}", Utc::now().format("%Y%m%d-%H%M%S")));
+    std::fs::rename(db_path, &backup)?;
+    // A stale WAL next to the new database would be replayed into it
+    for side in ["-wal", "-shm", "-journal"] {
+        let side_path = with_suffix(db_path, side);
+        if side_path.exists() {
+            if let Err(e) = std::fs::rename(&side_path, with_suffix(&backup, side)) {
+                crate::logs::error(&format!("[DB] Error: Failed to move {}: {}", side_path.display(), e));
+            }
+        }
+    }
+    Ok(Some(backup))
+}
+
 impl EventDatabase {
     /// Create new database connection and initialize schema
     pub fn new() -> SqliteResult<Self> {
-        let db_path = Self::get_db_path();
+        Self::open(&Self::db_path())
+    }
 
+    /// Open the database at `db_path`. A file SQLite reports as corrupt (or one
+    /// `schedule_recovery` flagged) is moved aside and replaced with an empty
+    /// database, so the app still starts. Any other error is returned as is.
+    pub fn open(db_path: &Path) -> SqliteResult<Self> {
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(&db_path)?;
+        let marker = recovery_marker(db_path);
+        if marker.exists() {
+            crate::logs::error("[DB] Warning: Last integrity check failed, starting with an empty database");
+            Self::quarantine(db_path);
+            std::fs::remove_file(&marker).ok();
+        }
 
+        match Self::with_connection(Connection::open(db_path)?) {
+            Err(e) if is_corruption(&e) => {
+                crate::logs::error(&format!(
+                    "[DB] Warning: Database is corrupt ({}), starting with an empty one",
+                    e
+                ));
+                Self::quarantine(db_path);
+                Self::with_connection(Connection::open(db_path)?)
+            }
+            result => result,
+        }
+    }
+
+    /// Move a corrupt database out of the way, logging where it went
+    fn quarantine(db_path: &Path) {
+        match backup_corrupt(db_path) {
+            Ok(Some(backup)) => crate::logs::error(&format!(
+                "[DB] Corrupt database moved to {}",
+                backup.display()
+            )),
+            Ok(None) => {}
+            Err(e) => crate::logs::error(&format!("[DB] Error: Failed to move corrupt database aside: {}", e)),
+        }
+    }
+
+    /// Run SQLite's quick check; slow on large databases, so call it off the main thread
+    pub fn quick_check(&self) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let result: String = conn.query_row("PRAGMA quick_check(1)", [], |row| row.get(0))?;
+        Ok(result == "ok")
+    }
+
+    /// Have the next `open` of `db_path` move the database aside
+    pub fn schedule_recovery(db_path: &Path) -> std::io::Result<()> {
+        std::fs::write(recovery_marker(db_path), Utc::now().to_rfc3339())
+    }
+
+    /// Create an in-memory database (used by tests)
+    #[allow(dead_code)]
+    pub fn in_memory() -> SqliteResult<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Initialize schema on an open connection
+    fn with_connection(conn: Connection) -> SqliteResult<Self> {
         // Create events table if it doesn't exist
         conn.execute(
             "CREATE TABLE IF NOT EXISTS events (
@@ -37,11 +227,34 @@ impl EventDatabase {
                 screenshot_path TEXT,
                 system_metrics TEXT,
                 typed_text TEXT,
-                created_at INTEGER DEFAULT (strftime('%s', 'now'))
+                thumbnail_path TEXT,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                synced INTEGER NOT NULL DEFAULT 0
             )",
             [],
         )?;
 
+        // Databases created before the synced flag existed
+        let has_synced: bool = conn
+            .prepare("SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = 'synced'")?
+            .query_row([], |row| row.get::<_, i64>(0))
+            .map(|count| count > 0)?;
+        if !has_synced {
+            conn.execute(
+                "ALTER TABLE events ADD COLUMN synced INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Databases created before thumbnails were stored
+        let has_thumbnail_path: bool = conn
+            .prepare("SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = 'thumbnail_path'")?
+            .query_row([], |row| row.get::<_, i64>(0))
+            .map(|count| count > 0)?;
+        if !has_thumbnail_path {
+            conn.execute("ALTER TABLE events ADD COLUMN thumbnail_path TEXT", [])?;
+        }
+
         // Create index on created_at for efficient ordering
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at)",
@@ -54,7 +267,7 @@ impl EventDatabase {
     }
 
     /// Get database file path
-    fn get_db_path() -> PathBuf {
+    pub fn db_path() -> PathBuf {
         dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("observer")
@@ -65,14 +278,14 @@ impl EventDatabase {
     pub fn insert_event(&self, event: &Event) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
 
-        println!("[DB::insert_event] Inserting: {} | {}", event.id, event.app_name.as_deref().unwrap_or("?"));
+        crate::logs::debug(&format!("[DB::insert_event] Inserting: {} | {}", event.id, event.app_name.as_deref().unwrap_or("?")));
 
         conn.execute(
             "INSERT INTO events (
                 id, device_id, event_type, timestamp, app_name, window_title, url,
                 data, category, browser_tab, messages, screenshot_path,
-                system_metrics, typed_text
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                system_metrics, typed_text, thumbnail_path
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
             params![
                 event.id,
                 event.device_id,
@@ -88,6 +301,7 @@ impl EventDatabase {
                 event.screenshot_path,
                 event.system_metrics.as_ref().and_then(|s| serde_json::to_string(s).ok()),
                 event.typed_text,
+                event.thumbnail_path,
             ],
         )?;
 
@@ -98,52 +312,122 @@ impl EventDatabase {
     pub fn load_all_events(&self) -> SqliteResult<Vec<Event>> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare(
-            "SELECT id, device_id, event_type, timestamp, app_name, window_title, url,
-                    data, category, browser_tab, messages, screenshot_path,
-                    system_metrics, typed_text
-             FROM events
-             ORDER BY created_at ASC"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events ORDER BY created_at ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt.query_map([], event_from_row)?;
 
-        let events = stmt.query_map([], |row| {
-            let timestamp_str: String = row.get(3)?;
-            let data_str: String = row.get(7)?;
-            let browser_tab_str: Option<String> = row.get(9)?;
-            let messages_str: Option<String> = row.get(10)?;
-            let system_metrics_str: Option<String> = row.get(12)?;
-
-            Ok(Event {
-                id: row.get(0)?,
-                device_id: row.get(1)?,
-                event_type: row.get(2)?,
-                timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp_str)
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .unwrap_or_else(|_| chrono::Utc::now()),
-                app_name: row.get(4)?,
-                window_title: row.get(5)?,
-                url: row.get(6)?,
-                data: serde_json::from_str(&data_str).unwrap_or(serde_json::json!({})),
-                category: row.get(8)?,
-                browser_tab: browser_tab_str.and_then(|s| serde_json::from_str(&s).ok()),
-                messages: messages_str.and_then(|s| serde_json::from_str(&s).ok()),
-                screenshot_path: row.get(11)?,
-                system_metrics: system_metrics_str.and_then(|s| serde_json::from_str(&s).ok()),
-                typed_text: row.get(13)?,
-            })
-        })?;
+        events.collect()
+    }
+
+    /// Load up to `limit` events that haven't been synced yet, oldest first
+    pub fn load_unsynced_events(&self, limit: usize) -> SqliteResult<Vec<Event>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events WHERE synced = 0 ORDER BY created_at ASC, rowid ASC LIMIT ?1",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt.query_map(params![limit as i64], event_from_row)?;
 
         events.collect()
     }
 
-    /// Delete events by their IDs
-    pub fn delete_events(&self, event_ids: &[String]) -> SqliteResult<()> {
+    /// Number of events that haven't been synced yet and the timestamp of the oldest
+    pub fn unsynced_summary(&self) -> SqliteResult<(usize, Option<DateTime<Utc>>)> {
+        let conn = self.conn.lock().unwrap();
+
+        let (count, oldest): (i64, Option<String>) = conn.query_row(
+            "SELECT COUNT(*),
+                (SELECT timestamp FROM events WHERE synced = 0 ORDER BY julianday(timestamp) LIMIT 1)
+             FROM events WHERE synced = 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let oldest = oldest
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(&timestamp).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc));
+        Ok((count as usize, oldest))
+    }
+
+    /// Which of `event_ids` are stored, synced or not
+    pub fn stored_ids(&self, event_ids: &[&str]) -> SqliteResult<std::collections::HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stored = std::collections::HashSet::new();
+        for chunk in event_ids.chunks(MAX_IDS_PER_QUERY) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let mut stmt = conn.prepare(&format!("SELECT id FROM events WHERE id IN ({})", placeholders))?;
+            let ids = stmt.query_map(rusqlite::params_from_iter(chunk.iter()), |row| row.get::<_, String>(0))?;
+            for id in ids {
+                stored.insert(id?);
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Flag events as synced so they are never sent again
+    /// They stay in the database for local history until `prune_synced_events` removes them.
+    pub fn mark_synced(&self, event_ids: &[String]) -> SqliteResult<()> {
+        self.set_synced_flag(event_ids, 1)
+    }
+
+    /// Flag events as local-only: never sent, and kept as long as synced events
+    pub fn mark_local_only(&self, event_ids: &[String]) -> SqliteResult<()> {
+        self.set_synced_flag(event_ids, 2)
+    }
+
+    fn set_synced_flag(&self, event_ids: &[String], flag: i64) -> SqliteResult<()> {
         if event_ids.is_empty() {
             return Ok(());
         }
 
         let conn = self.conn.lock().unwrap();
 
+        for chunk in event_ids.chunks(MAX_IDS_PER_QUERY) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let query = format!("UPDATE events SET synced = ? WHERE id IN ({})", placeholders);
+
+            let mut stmt = conn.prepare(&query)?;
+            let params: Vec<&dyn rusqlite::ToSql> = std::iter::once(&flag as &dyn rusqlite::ToSql)
+                .chain(chunk.iter().map(|id| id as &dyn rusqlite::ToSql))
+                .collect();
+            stmt.execute(&params[..])?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete synced and local-only events that happened before `older_than`
+    /// Unsynced events are never pruned, however old.
+    pub fn prune_synced_events(&self, older_than: DateTime<Utc>) -> SqliteResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM events WHERE synced IN (1, 2) AND julianday(timestamp) < julianday(?1)",
+            params![older_than.to_rfc3339()],
+        )
+    }
+
+    /// Load a single event by ID
+    pub fn get_event(&self, event_id: &str) -> SqliteResult<Option<Event>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM events WHERE id = ?1", EVENT_COLUMNS))?;
+        let mut events = stmt.query_map(params![event_id], event_from_row)?;
+
+        events.next().transpose()
+    }
+
+    /// Delete events by their IDs
+    /// Returns the number of rows deleted
+    pub fn delete_events(&self, event_ids: &[String]) -> SqliteResult<usize> {
+        if event_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.conn.lock().unwrap();
+
         // Build placeholders for SQL IN clause
         let placeholders = event_ids.iter()
             .map(|_| "?")
@@ -158,9 +442,253 @@ impl EventDatabase {
             .map(|id| id as &dyn rusqlite::ToSql)
             .collect();
 
-        stmt.execute(&params[..])?;
+        stmt.execute(&params[..])
+    }
 
-        Ok(())
+    /// Number of stored events pointing at a screenshot file
+    pub fn screenshot_references(&self, screenshot_path: &str) -> SqliteResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM events WHERE screenshot_path = ?1",
+            params![screenshot_path],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Update the category of a stored event
+    /// Returns false if no event with that ID exists
+    pub fn update_category(&self, event_id: &str, category: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE events SET category = ?1 WHERE id = ?2",
+            params![category, event_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Set the screenshot (and thumbnail) of a stored event
+    /// Returns false if no event with that ID exists
+    pub fn update_screenshot_path(
+        &self,
+        event_id: &str,
+        screenshot_path: &str,
+        thumbnail_path: Option<&str>,
+    ) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE events SET screenshot_path = ?1, thumbnail_path = ?2 WHERE id = ?3",
+            params![screenshot_path, thumbnail_path, event_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Replace the `data` JSON of a stored event
+    /// Returns false if no event with that ID exists
+    pub fn update_data(&self, event_id: &str, data: &serde_json::Value) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE events SET data = ?1 WHERE id = ?2",
+            params![serde_json::to_string(data).unwrap_or_default(), event_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Events matching `query`, newest first
+    pub fn query_events(&self, query: &EventQuery) -> SqliteResult<Vec<Event>> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(from) = query.from {
+            conditions.push("julianday(timestamp) >= julianday(?)");
+            values.push(Box::new(from.to_rfc3339()));
+        }
+        if let Some(to) = query.to {
+            conditions.push("julianday(timestamp) <= julianday(?)");
+            values.push(Box::new(to.to_rfc3339()));
+        }
+        if let Some(ref app_name) = query.app_name {
+            conditions.push("app_name = ?");
+            values.push(Box::new(app_name.clone()));
+        }
+        if let Some(ref category) = query.category {
+            conditions.push("category = ? COLLATE NOCASE");
+            values.push(Box::new(category.clone()));
+        }
+        match query.has_screenshot {
+            Some(true) => conditions.push("COALESCE(screenshot_path, '') != ''"),
+            Some(false) => conditions.push("COALESCE(screenshot_path, '') = ''"),
+            None => {}
+        }
+        if let Some(text) = query.ocr_contains.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            conditions.push("json_extract(data, '$.ocr_text') LIKE ? ESCAPE '\\'");
+            values.push(Box::new(like_pattern(text)));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT);
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events {} ORDER BY julianday(timestamp) DESC, rowid DESC LIMIT {}",
+            EVENT_COLUMNS, where_clause, limit
+        ))?;
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|value| value.as_ref()).collect();
+        let events = stmt.query_map(&params[..], event_from_row)?;
+
+        events.collect()
+    }
+
+    /// Timestamps of the events in `[from, to)`
+    pub fn timestamps_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> SqliteResult<Vec<DateTime<Utc>>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT timestamp FROM events
+             WHERE julianday(timestamp) >= julianday(?1) AND julianday(timestamp) < julianday(?2)",
+        )?;
+        let rows = stmt.query_map(params![from.to_rfc3339(), to.to_rfc3339()], |row| {
+            row.get::<_, String>(0)
+        })?;
+
+        let mut timestamps = Vec::new();
+        for row in rows {
+            if let Ok(timestamp) = DateTime::parse_from_rfc3339(&row?) {
+                timestamps.push(timestamp.with_timezone(&Utc));
+            }
+        }
+        Ok(timestamps)
+    }
+
+    /// Events in `[from, to)`, oldest first
+    pub fn events_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> SqliteResult<Vec<Event>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events
+             WHERE julianday(timestamp) >= julianday(?1) AND julianday(timestamp) < julianday(?2)
+             ORDER BY julianday(timestamp) ASC, rowid ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt.query_map(params![from.to_rfc3339(), to.to_rfc3339()], event_from_row)?;
+
+        events.collect()
+    }
+
+    /// Totals for the events in `[from, to)`
+    pub fn activity_sample(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> SqliteResult<ActivitySample> {
+        let conn = self.conn.lock().unwrap();
+        let window = "julianday(timestamp) >= julianday(?1) AND julianday(timestamp) < julianday(?2)";
+        let (from, to) = (from.to_rfc3339(), to.to_rfc3339());
+        let bounds = params![from, to];
+
+        let (events, span_days, sync_chars, ocr_chars) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*),
+                    COALESCE(julianday(?2) - MIN(julianday(timestamp)), 0),
+                    COALESCE(SUM(LENGTH(id) + LENGTH(device_id) + LENGTH(event_type) + LENGTH(timestamp)
+                        + COALESCE(LENGTH(app_name), 0) + COALESCE(LENGTH(window_title), 0)
+                        + COALESCE(LENGTH(url), 0) + LENGTH(data) + COALESCE(LENGTH(category), 0)), 0),
+                    COALESCE(SUM(LENGTH(json_extract(data, '$.ocr_text'))), 0)
+                 FROM events WHERE {}",
+                window
+            ),
+            bounds,
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT screenshot_path FROM events
+             WHERE screenshot_path IS NOT NULL AND screenshot_path != '' AND {}",
+            window
+        ))?;
+        let screenshot_paths = stmt
+            .query_map(bounds, |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(ActivitySample {
+            events: events as u64,
+            span_days,
+            sync_chars: sync_chars as u64,
+            ocr_chars: ocr_chars as u64,
+            screenshot_paths,
+        })
+    }
+
+    /// Run SQLite's full integrity check
+    pub fn check_integrity(&self) -> SqliteResult<IntegrityReport> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!("PRAGMA integrity_check({})", MAX_INTEGRITY_PROBLEMS))?;
+        let problems: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?
+            .into_iter()
+            .filter(|line| line != "ok")
+            .collect();
+
+        Ok(IntegrityReport {
+            ok: problems.is_empty(),
+            problems,
+        })
+    }
+
+    /// Active seconds on each local day, from the gaps between consecutive events
+    /// A gap counts when it is at most `max_gap` and doesn't follow a `system_sleep`
+    /// or `idle_start`, like the sessions of `stats::compute_sessions`. Days without activity are left out.
+    pub fn daily_active_seconds(&self, max_gap: chrono::Duration) -> SqliteResult<Vec<(NaiveDate, i64)>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT day, CAST(ROUND(SUM(gap)) AS INTEGER) FROM (
+                SELECT date(timestamp, 'localtime') AS day,
+                    (julianday(timestamp) - julianday(LAG(timestamp) OVER day_events)) * 86400.0 AS gap,
+                    LAG(event_type) OVER day_events AS previous_type
+                FROM events
+                WINDOW day_events AS (PARTITION BY date(timestamp, 'localtime') ORDER BY julianday(timestamp))
+             )
+             WHERE gap IS NOT NULL AND gap <= ?1 AND previous_type NOT IN ('system_sleep', 'idle_start')
+             GROUP BY day
+             ORDER BY day",
+        )?;
+        let rows = stmt.query_map(params![max_gap.num_seconds()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut days = Vec::new();
+        for row in rows {
+            let (day, seconds) = row?;
+            if let Ok(date) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") {
+                days.push((date, seconds));
+            }
+        }
+        Ok(days)
+    }
+
+    /// Number of stored events at or after `since`, synced or not
+    pub fn count_events_since(&self, since: DateTime<Utc>) -> SqliteResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM events WHERE julianday(timestamp) >= julianday(?1)",
+            params![since.to_rfc3339()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
     }
 
     /// Get count of events in database
@@ -178,3 +706,283 @@ impl EventDatabase {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn event_at(minute: i64, screenshot: Option<&str>, ocr_text: Option<&str>) -> Event {
+        let mut event = Event::new("app_focus", Some("Preview".to_string()), None);
+        event.timestamp = Utc.with_ymd_and_hms(2026, 1, 8, 12, 0, 0).unwrap() + Duration::minutes(minute);
+        event.screenshot_path = screenshot.map(str::to_string);
+        if let Some(text) = ocr_text {
+            event.data = serde_json::json!({ "ocr_text": text });
+        }
+        event
+    }
+
+    fn ids(events: &[Event]) -> Vec<String> {
+        events.iter().map(|event| event.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_query_by_ocr_text() {
+        let db = EventDatabase::in_memory().unwrap();
+        let invoice = event_at(0, Some("/shots/a.jpg"), Some("Invoice #42 due Friday"));
+        let other = event_at(1, Some("/shots/b.jpg"), Some("Quarterly roadmap"));
+        let reminder = event_at(2, Some("/shots/c.jpg"), Some("Pay the INVOICE today"));
+        let no_shot = event_at(3, None, Some("invoice draft"));
+        for event in [&invoice, &other, &reminder, &no_shot] {
+            db.insert_event(event).unwrap();
+        }
+
+        let query = EventQuery {
+            has_screenshot: Some(true),
+            ocr_contains: Some("invoice".to_string()),
+            ..EventQuery::default()
+        };
+        let found = db.query_events(&query).unwrap();
+        // Case-insensitive, newest first, only events with screenshots
+        assert_eq!(ids(&found), vec![reminder.id.clone(), invoice.id.clone()]);
+        assert_eq!(found[0].screenshot_path.as_deref(), Some("/shots/c.jpg"));
+
+        let query = EventQuery {
+            ocr_contains: Some("invoice".to_string()),
+            ..EventQuery::default()
+        };
+        assert_eq!(db.query_events(&query).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_query_screenshot_presence_and_range() {
+        let db = EventDatabase::in_memory().unwrap();
+        let early = event_at(0, Some("/shots/a.jpg"), None);
+        let plain = event_at(10, None, None);
+        let late = event_at(20, Some("/shots/b.jpg"), None);
+        for event in [&early, &plain, &late] {
+            db.insert_event(event).unwrap();
+        }
+
+        let without = EventQuery {
+            has_screenshot: Some(false),
+            ..EventQuery::default()
+        };
+        assert_eq!(ids(&db.query_events(&without).unwrap()), vec![plain.id.clone()]);
+
+        let in_range = EventQuery {
+            from: Some(early.timestamp + Duration::minutes(5)),
+            has_screenshot: Some(true),
+            ..EventQuery::default()
+        };
+        assert_eq!(ids(&db.query_events(&in_range).unwrap()), vec![late.id.clone()]);
+
+        let limited = EventQuery {
+            limit: Some(2),
+            ..EventQuery::default()
+        };
+        assert_eq!(ids(&db.query_events(&limited).unwrap()), vec![late.id, plain.id]);
+    }
+
+    #[test]
+    fn test_timestamps_between_is_half_open() {
+        let db = EventDatabase::in_memory().unwrap();
+        let events = [event_at(0, None, None), event_at(30, None, None), event_at(60, None, None)];
+        for event in &events {
+            db.insert_event(event).unwrap();
+        }
+
+        let found = db
+            .timestamps_between(events[0].timestamp, events[2].timestamp)
+            .unwrap();
+        assert_eq!(found, vec![events[0].timestamp, events[1].timestamp]);
+    }
+
+    #[test]
+    fn test_mark_synced_large_backlog() {
+        let db = EventDatabase::in_memory().unwrap();
+        let ids: Vec<String> = (0..MAX_IDS_PER_QUERY * 2 + 1)
+            .map(|_| {
+                let event = Event::new("app_focus", Some("Code".to_string()), None);
+                db.insert_event(&event).unwrap();
+                event.id
+            })
+            .collect();
+
+        db.mark_synced(&ids).unwrap();
+        assert!(db.load_unsynced_events(ids.len()).unwrap().is_empty());
+        assert_eq!(db.count().unwrap(), ids.len());
+    }
+
+    #[test]
+    fn test_count_events_since() {
+        let db = EventDatabase::in_memory().unwrap();
+        let events = [event_at(-90, None, None), event_at(0, None, None), event_at(45, None, None)];
+        for event in &events {
+            db.insert_event(event).unwrap();
+        }
+        // Sent and local-only events still count
+        db.mark_synced(&[events[1].id.clone()]).unwrap();
+        db.mark_local_only(&[events[2].id.clone()]).unwrap();
+
+        assert_eq!(db.count_events_since(events[1].timestamp).unwrap(), 2);
+        assert_eq!(db.count_events_since(events[0].timestamp - Duration::days(1)).unwrap(), 3);
+        assert_eq!(db.count_events_since(events[2].timestamp + Duration::seconds(1)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_daily_active_seconds_by_local_day() {
+        let db = EventDatabase::in_memory().unwrap();
+        let local = |day: u32, hour: u32, minute: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2026, 1, day, hour, minute, 0)
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let insert = |event_type: &str, timestamp: DateTime<Utc>| {
+            let mut event = Event::new(event_type, Some("Code".to_string()), None);
+            event.timestamp = timestamp;
+            db.insert_event(&event).unwrap();
+            event
+        };
+
+        // Jan 3: 45 minutes, one event a minute
+        for minute in 0..=45 {
+            insert("app_focus", local(3, 10, 0) + Duration::minutes(minute));
+        }
+        // Jan 4: 8 minutes, a 2-hour gap, then 3 more minutes; synced rows count too
+        let synced = insert("app_focus", local(4, 9, 0));
+        db.mark_synced(&[synced.id.clone()]).unwrap();
+        insert("app_focus", local(4, 9, 4));
+        insert("app_focus", local(4, 9, 8));
+        insert("app_focus", local(4, 11, 10));
+        insert("app_focus", local(4, 11, 13));
+        // Jan 5: asleep for an hour between two short stretches
+        insert("app_focus", local(5, 8, 0));
+        insert("system_sleep", local(5, 8, 2));
+        insert("system_wake", local(5, 8, 4));
+        insert("app_focus", local(5, 8, 5));
+        // ... and idle for four minutes between two more
+        insert("app_focus", local(5, 15, 0));
+        insert("idle_start", local(5, 15, 1));
+        insert("idle_end", local(5, 15, 5));
+        insert("app_focus", local(5, 15, 6));
+        // Late on the 5th and early on the 6th: the gap across midnight doesn't count
+        insert("app_focus", local(5, 23, 58));
+        insert("app_focus", local(6, 0, 1));
+
+        let date = |day: u32| NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+        let days = db.daily_active_seconds(Duration::minutes(5)).unwrap();
+        assert_eq!(days, vec![(date(3), 45 * 60), (date(4), 11 * 60), (date(5), 5 * 60)]);
+    }
+
+    #[test]
+    fn test_ocr_query_wildcards_are_literal() {
+        let db = EventDatabase::in_memory().unwrap();
+        let discount = event_at(0, Some("/shots/a.jpg"), Some("Save 100% today"));
+        let plain = event_at(1, Some("/shots/b.jpg"), Some("Save 1000 today"));
+        db.insert_event(&discount).unwrap();
+        db.insert_event(&plain).unwrap();
+
+        let query = EventQuery {
+            ocr_contains: Some("100%".to_string()),
+            ..EventQuery::default()
+        };
+        assert_eq!(ids(&db.query_events(&query).unwrap()), vec![discount.id]);
+    }
+
+    #[test]
+    fn test_garbled_database_is_backed_up_and_recreated() {
+        let dir = std::env::temp_dir().join(format!("observer_db_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.db");
+        let garbage: Vec<u8> = (0..8192u32).map(|i| (i * 31 % 251) as u8).collect();
+        std::fs::write(&path, &garbage).unwrap();
+
+        let db = EventDatabase::open(&path).unwrap();
+        assert_eq!(db.count().unwrap(), 0);
+        let event = event_at(0, None, None);
+        db.insert_event(&event).unwrap();
+        assert_eq!(db.count().unwrap(), 1);
+        assert!(db.check_integrity().unwrap().ok);
+
+        let backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read(&backups[0]).unwrap(), garbage);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_open_errors_other_than_corruption_keep_the_file() {
+        let dir = std::env::temp_dir().join(format!("observer_db_{}", uuid::Uuid::new_v4()));
+        // A directory where the database should be can't be opened, but isn't corrupt
+        let path = dir.join("events.db");
+        std::fs::create_dir_all(&path).unwrap();
+
+        assert!(EventDatabase::open(&path).is_err());
+        assert!(path.is_dir());
+        let backups = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(backups, 0);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_scheduled_recovery_moves_database_aside_on_next_open() {
+        let dir = std::env::temp_dir().join(format!("observer_db_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.db");
+
+        let db = EventDatabase::open(&path).unwrap();
+        db.insert_event(&event_at(0, None, None)).unwrap();
+        assert!(db.quick_check().unwrap());
+        drop(db);
+
+        EventDatabase::schedule_recovery(&path).unwrap();
+        let db = EventDatabase::open(&path).unwrap();
+        assert_eq!(db.count().unwrap(), 0);
+        assert!(!recovery_marker(&path).exists());
+        let backups = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(backups, 1);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_healthy_database_is_kept() {
+        let dir = std::env::temp_dir().join(format!("observer_db_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.db");
+
+        let event = event_at(0, None, None);
+        EventDatabase::open(&path).unwrap().insert_event(&event).unwrap();
+        let db = EventDatabase::open(&path).unwrap();
+        assert_eq!(ids(&db.load_all_events().unwrap()), vec![event.id.clone()]);
+
+        let report = db.check_integrity().unwrap();
+        assert!(report.ok);
+        assert!(report.problems.is_empty());
+        let backups = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(backups, 0);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
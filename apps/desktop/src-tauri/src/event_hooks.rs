@@ -0,0 +1,244 @@
+// Real-time event webhooks
+// Rules that POST an event to a URL as soon as it is collected, e.g. to ping a
+// home-automation endpoint when a specific app or site comes into focus.
+// Separate from the sync sink, which delivers batches on the sync interval.
+
+use crate::collector::Event;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Per-request budget; hooks are fire-and-forget
+const HOOK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Most webhook calls in flight at once (extra calls are dropped, not queued)
+const MAX_CONCURRENT_HOOKS: usize = 4;
+
+static IN_FLIGHT: Lazy<Arc<Semaphore>> = Lazy::new(|| Arc::new(Semaphore::new(MAX_CONCURRENT_HOOKS)));
+
+/// Conditions an event must meet; every field that is set has to match
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventMatch {
+    /// App name, whole name, case-insensitive
+    pub app: Option<String>,
+    /// Substring of the event URL, case-insensitive
+    pub url: Option<String>,
+    /// Category, case-insensitive
+    pub category: Option<String>,
+}
+
+impl EventMatch {
+    fn is_empty(&self) -> bool {
+        self.app.is_none() && self.url.is_none() && self.category.is_none()
+    }
+}
+
+/// One `{ match, webhookUrl }` entry of the `eventHooks` setting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventHookRule {
+    #[serde(rename = "match", default)]
+    pub matches: EventMatch,
+    pub webhook_url: String,
+}
+
+impl EventHookRule {
+    /// Whether `event` triggers this rule (a rule without conditions never fires)
+    pub fn matches(&self, event: &Event) -> bool {
+        let conditions = &self.matches;
+        if conditions.is_empty() {
+            return false;
+        }
+
+        let app_ok = condition(conditions.app.as_deref(), |app| {
+            event
+                .app_name
+                .as_deref()
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case(app.trim()))
+        });
+        let url_ok = condition(conditions.url.as_deref(), |needle| {
+            event
+                .url
+                .as_deref()
+                .is_some_and(|url| url.to_lowercase().contains(&needle.to_lowercase()))
+        });
+        let category_ok = condition(conditions.category.as_deref(), |category| {
+            event
+                .category
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(category.trim()))
+        });
+
+        app_ok && url_ok && category_ok
+    }
+
+    /// Reject rules that could never fire or point somewhere unsafe
+    pub fn validate(&self) -> Result<(), String> {
+        if self.matches.is_empty() {
+            return Err("match needs at least one of app, url or category".to_string());
+        }
+        crate::sync::validate_url(&self.webhook_url)?;
+        Ok(())
+    }
+}
+
+/// An unset condition always holds
+fn condition(expected: Option<&str>, check: impl Fn(&str) -> bool) -> bool {
+    match expected {
+        Some(expected) => check(expected),
+        None => true,
+    }
+}
+
+/// Body POSTed to a webhook. Only identifying fields - no typed text,
+/// clipboard, OCR or message content leaves the machine this way.
+pub fn payload(event: &Event) -> serde_json::Value {
+    serde_json::json!({
+        "type": "event",
+        "event": {
+            "id": event.id,
+            "eventType": event.event_type,
+            "timestamp": event.timestamp,
+            "appName": event.app_name,
+            "windowTitle": event.window_title,
+            "url": event.url,
+            "category": event.category,
+            "deviceId": event.device_id,
+        },
+    })
+}
+
+/// Fire every rule matching `event` in the background
+/// Never blocks the caller; failures are only logged
+pub fn dispatch(rules: &[EventHookRule], event: &Event) {
+    let urls: Vec<String> = rules
+        .iter()
+        .filter(|rule| rule.matches(event))
+        .map(|rule| rule.webhook_url.clone())
+        .collect();
+    if urls.is_empty() {
+        return;
+    }
+
+    let body = payload(event);
+    for url in urls {
+        let permit = match IN_FLIGHT.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                crate::logs::error(&format!("[EventHooks] Too many webhooks in flight, skipping {}", url));
+                continue;
+            }
+        };
+        let body = body.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = post(&url, &body).await {
+                crate::logs::error(&format!("[EventHooks] {}: {}", url, e));
+            }
+            drop(permit);
+        });
+    }
+}
+
+async fn post(url: &str, body: &serde_json::Value) -> Result<(), String> {
+    let url = crate::sync::validate_url(url)?;
+    let client = crate::sync::shared_http_client().map_err(|e| e.to_string())?;
+
+    let response = client
+        .post(url)
+        .timeout(HOOK_TIMEOUT)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Webhook returned status: {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(app: Option<&str>, url: Option<&str>, category: Option<&str>) -> EventHookRule {
+        EventHookRule {
+            matches: EventMatch {
+                app: app.map(str::to_string),
+                url: url.map(str::to_string),
+                category: category.map(str::to_string),
+            },
+            webhook_url: "https://hooks.example.com/observer".to_string(),
+        }
+    }
+
+    fn chrome_event() -> Event {
+        let mut event = Event::new(
+            "app_focus",
+            Some("Google Chrome".to_string()),
+            Some("Pull requests".to_string()),
+        )
+        .with_category("coding");
+        event.url = Some("https://github.com/pulls".to_string());
+        event.typed_text = Some("secret".to_string());
+        event
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let event = chrome_event();
+
+        assert!(rule(Some("google chrome"), None, None).matches(&event));
+        assert!(rule(None, Some("GITHUB.com"), None).matches(&event));
+        assert!(rule(None, None, Some("Coding")).matches(&event));
+        assert!(rule(Some("Google Chrome"), Some("github.com"), Some("coding")).matches(&event));
+
+        // Every set condition has to hold
+        assert!(!rule(Some("Google Chrome"), Some("gitlab.com"), None).matches(&event));
+        // App names match whole, not as substrings
+        assert!(!rule(Some("Chrome"), None, None).matches(&event));
+        assert!(!rule(None, None, Some("browsing")).matches(&event));
+        // Events without a URL never match URL rules
+        let mut no_url = event.clone();
+        no_url.url = None;
+        assert!(!rule(None, Some("github.com"), None).matches(&no_url));
+        // Empty rules never fire
+        assert!(!rule(None, None, None).matches(&event));
+    }
+
+    #[test]
+    fn test_rule_config_shape() {
+        let slack: EventHookRule = serde_json::from_value(serde_json::json!({
+            "match": { "app": "Slack" },
+            "webhookUrl": "https://hooks.example.com/slack"
+        }))
+        .unwrap();
+        assert_eq!(slack.matches.app.as_deref(), Some("Slack"));
+        assert!(slack.validate().is_ok());
+
+        assert!(rule(None, None, None).validate().is_err());
+    }
+
+    #[test]
+    fn test_payload_shape() {
+        let event = chrome_event();
+        let body = payload(&event);
+
+        assert_eq!(body["type"], "event");
+        let fields = body["event"].as_object().unwrap();
+        let mut keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec!["appName", "category", "deviceId", "eventType", "id", "timestamp", "url", "windowTitle"]
+        );
+        assert_eq!(fields["id"], event.id.as_str());
+        assert_eq!(fields["appName"], "Google Chrome");
+        assert_eq!(fields["url"], "https://github.com/pulls");
+        assert_eq!(fields["category"], "coding");
+        assert!(!body.to_string().contains("secret"));
+    }
+}
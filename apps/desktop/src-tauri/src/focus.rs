@@ -0,0 +1,204 @@
+// Focus reminder module
+// Watches how often the user switches apps and, when the rate stays above a
+// configurable threshold, sends a gentle notification suggesting a focus break
+
+use crate::notifications::{NotificationConfig, NotificationManager, NotificationPriority};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Defaults: more than 20 switches in 10 minutes, at most one nudge per 30 minutes
+pub const DEFAULT_MAX_SWITCHES: u32 = 20;
+pub const DEFAULT_WINDOW_MINUTES: u32 = 10;
+pub const DEFAULT_COOLDOWN_MINUTES: u32 = 30;
+
+/// How often the monitor checks the switch rate
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// App switches seen by the collector
+static DETECTOR: Lazy<Mutex<SwitchRateDetector>> =
+    Lazy::new(|| Mutex::new(SwitchRateDetector::default()));
+
+/// Focus reminder thresholds, stored in the app settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusReminderConfig {
+    pub enabled: bool,
+    /// Nudge when the user switches apps more than this many times...
+    pub max_switches: u32,
+    /// ...within this many minutes
+    pub window_minutes: u32,
+    /// Minimum minutes between two nudges
+    pub cooldown_minutes: u32,
+}
+
+impl Default for FocusReminderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_switches: DEFAULT_MAX_SWITCHES,
+            window_minutes: DEFAULT_WINDOW_MINUTES,
+            cooldown_minutes: DEFAULT_COOLDOWN_MINUTES,
+        }
+    }
+}
+
+/// Sliding-window app switch counter with a nudge cooldown
+#[derive(Debug, Default)]
+pub struct SwitchRateDetector {
+    switches: VecDeque<DateTime<Utc>>,
+    last_nudge: Option<DateTime<Utc>>,
+}
+
+impl SwitchRateDetector {
+    /// Record an app switch
+    pub fn record_switch(&mut self, at: DateTime<Utc>) {
+        self.switches.push_back(at);
+    }
+
+    /// Switches in the window ending at `now`
+    pub fn switch_count(&mut self, now: DateTime<Utc>, window: Duration) -> usize {
+        let cutoff = now - window;
+        while self.switches.front().is_some_and(|&at| at <= cutoff) {
+            self.switches.pop_front();
+        }
+        self.switches.len()
+    }
+
+    /// Number of recent switches if a nudge is due at `now`, at most once per cooldown
+    pub fn check(&mut self, now: DateTime<Utc>, config: &FocusReminderConfig) -> Option<usize> {
+        let count = self.switch_count(now, Duration::minutes(config.window_minutes as i64));
+        if !config.enabled || count <= config.max_switches as usize {
+            return None;
+        }
+
+        let cooldown = Duration::minutes(config.cooldown_minutes as i64);
+        if self.last_nudge.is_some_and(|last| now - last < cooldown) {
+            return None;
+        }
+
+        self.last_nudge = Some(now);
+        Some(count)
+    }
+}
+
+/// Record an app switch from the collector
+pub fn record_switch(at: DateTime<Utc>) {
+    DETECTOR.lock().unwrap().record_switch(at);
+}
+
+fn nudge(switches: usize, window_minutes: u32) -> NotificationConfig {
+    NotificationConfig {
+        title: "Time for a focus break?".to_string(),
+        body: format!(
+            "You switched apps {} times in the last {} minutes. Try sticking with one task for a while.",
+            switches, window_minutes
+        ),
+        priority: NotificationPriority::Normal,
+        action: None,
+    }
+}
+
+/// Check the switch rate periodically and send nudges until `shutdown`
+pub async fn start_focus_monitor(app: tauri::AppHandle, shutdown: CancellationToken) {
+    let notifications = NotificationManager::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let config = crate::commands::get_settings()
+            .map(|settings| settings.focus_reminder)
+            .unwrap_or_default();
+        let due = DETECTOR.lock().unwrap().check(Utc::now(), &config);
+
+        if let Some(switches) = due {
+            crate::logs::info(&format!(
+                "[Focus] {} app switches in {} minutes, sending a focus reminder",
+                switches, config.window_minutes
+            ));
+            if let Err(e) = notifications.send_if_allowed(&app, nudge(switches, config.window_minutes)) {
+                crate::logs::error(&format!("[Focus] {}", e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 8, 12, minute, second).unwrap()
+    }
+
+    fn config() -> FocusReminderConfig {
+        FocusReminderConfig {
+            enabled: true,
+            max_switches: 5,
+            window_minutes: 10,
+            cooldown_minutes: 30,
+        }
+    }
+
+    #[test]
+    fn test_triggers_above_threshold() {
+        let mut detector = SwitchRateDetector::default();
+        for second in 0..5 {
+            detector.record_switch(at(0, second));
+        }
+        assert_eq!(detector.check(at(1, 0), &config()), None);
+
+        detector.record_switch(at(0, 30));
+        assert_eq!(detector.check(at(1, 0), &config()), Some(6));
+    }
+
+    #[test]
+    fn test_triggers_once_per_cooldown() {
+        let mut detector = SwitchRateDetector::default();
+        for minute in 0..40 {
+            for second in [0, 20, 40] {
+                detector.record_switch(at(minute, second));
+            }
+        }
+
+        let nudges: Vec<u32> = (0..60)
+            .filter(|&minute| detector.check(at(minute, 50), &config()).is_some())
+            .collect();
+        // The rate stays above the threshold from minute 1 to minute 47
+        assert_eq!(nudges, vec![1, 31]);
+    }
+
+    #[test]
+    fn test_old_switches_leave_the_window() {
+        let mut detector = SwitchRateDetector::default();
+        for second in 0..10 {
+            detector.record_switch(at(0, second));
+        }
+
+        assert_eq!(detector.switch_count(at(10, 5), Duration::minutes(10)), 4);
+        assert_eq!(detector.check(at(11, 0), &config()), None);
+    }
+
+    #[test]
+    fn test_disabled_never_triggers() {
+        let mut detector = SwitchRateDetector::default();
+        for second in 0..20 {
+            detector.record_switch(at(0, second));
+        }
+
+        let disabled = FocusReminderConfig {
+            enabled: false,
+            ..config()
+        };
+        assert_eq!(detector.check(at(1, 0), &disabled), None);
+        assert_eq!(detector.check(at(1, 0), &config()), Some(20));
+    }
+}
@@ -0,0 +1,87 @@
+// Event importance for sync
+// Each event type gets an importance so users can sync only the events that
+// matter (app switches, browser navigations) and keep granular ones local.
+// Events below the `minSyncImportance` threshold stay in the local database
+// but are never sent.
+
+use crate::collector::Event;
+use serde::{Deserialize, Serialize};
+
+/// How much an event is worth sending to the server, lowest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventImportance {
+    /// Granular activity inside an app (typing, input fields)
+    #[default]
+    Low,
+    /// Context within an app (title changes, screenshots, idle)
+    Normal,
+    /// App switches, navigations, idle periods, system and user markers
+    High,
+}
+
+/// Importance of an event, by type
+/// A title change with a URL is a browser navigation and counts as high.
+pub fn classify(event: &Event) -> EventImportance {
+    match event.event_type.as_str() {
+        "app_focus" | "system_sleep" | "system_wake" | "idle_start" | "idle_end" | "permission_lost"
+        | "permission_restored" | "marker" | "note" => EventImportance::High,
+        "window_title_changed" if event.url.is_some() => EventImportance::High,
+        "browser_input" | "typed_text" => EventImportance::Low,
+        _ => EventImportance::Normal,
+    }
+}
+
+/// Split events into those to send and the ids of those kept local-only
+pub fn split_for_sync(events: Vec<Event>, min: EventImportance) -> (Vec<Event>, Vec<String>) {
+    let (send, local): (Vec<Event>, Vec<Event>) = events.into_iter().partition(|event| classify(event) >= min);
+    (send, local.into_iter().map(|event| event.id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, url: Option<&str>) -> Event {
+        let mut event = Event::new(event_type, Some("Safari".to_string()), None);
+        event.url = url.map(str::to_string);
+        event
+    }
+
+    #[test]
+    fn test_classify_event_types() {
+        assert_eq!(classify(&event("app_focus", None)), EventImportance::High);
+        assert_eq!(classify(&event("system_wake", None)), EventImportance::High);
+        assert_eq!(classify(&event("idle_start", None)), EventImportance::High);
+        assert_eq!(classify(&event("window_title_changed", Some("https://a.com"))), EventImportance::High);
+        assert_eq!(classify(&event("window_title_changed", None)), EventImportance::Normal);
+        assert_eq!(classify(&event("screenshot", None)), EventImportance::Normal);
+        assert_eq!(classify(&event("something_new", None)), EventImportance::Normal);
+        assert_eq!(classify(&event("browser_input", Some("https://a.com"))), EventImportance::Low);
+        assert_eq!(classify(&event("typed_text", None)), EventImportance::Low);
+    }
+
+    #[test]
+    fn test_threshold_filters_sync() {
+        let events = vec![
+            event("app_focus", None),
+            event("browser_input", Some("https://a.com")),
+            event("window_title_changed", None),
+            event("window_title_changed", Some("https://b.com")),
+        ];
+        let types = |events: &[Event]| events.iter().map(|e| e.event_type.clone()).collect::<Vec<_>>();
+
+        // Default threshold sends everything
+        let (send, local) = split_for_sync(events.clone(), EventImportance::default());
+        assert_eq!(send.len(), 4);
+        assert!(local.is_empty());
+
+        let (send, local) = split_for_sync(events.clone(), EventImportance::Normal);
+        assert_eq!(types(&send), vec!["app_focus", "window_title_changed", "window_title_changed"]);
+        assert_eq!(local, vec![events[1].id.clone()]);
+
+        let (send, local) = split_for_sync(events.clone(), EventImportance::High);
+        assert_eq!(send.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec![events[0].id.as_str(), events[3].id.as_str()]);
+        assert_eq!(local, vec![events[1].id.clone(), events[2].id.clone()]);
+    }
+}
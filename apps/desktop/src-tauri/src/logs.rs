@@ -0,0 +1,182 @@
+// Log streaming module
+// Mirrors log lines to the frontend debug panel in real time.
+// Streaming is off by default so there is no overhead while the panel is closed.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+/// Tauri event the frontend subscribes to
+pub const LOG_EVENT: &str = "log-line";
+
+/// Lines kept for slow consumers; older lines are dropped once exceeded
+const LOG_CHANNEL_CAPACITY: usize = 500;
+
+static LOG_STREAM: Lazy<LogStream> = Lazy::new(LogStream::new);
+
+/// Log level of a streamed line
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Error,
+}
+
+/// A single streamed log line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Bounded broadcast channel for log lines
+pub struct LogStream {
+    sender: broadcast::Sender<LogLine>,
+    enabled: AtomicBool,
+}
+
+impl LogStream {
+    /// Create a new, disabled stream
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(LOG_CHANNEL_CAPACITY);
+        Self {
+            sender,
+            enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Start or stop streaming
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Publish a line to subscribers (no-op while disabled)
+    pub fn publish(&self, level: LogLevel, message: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        // Errors only mean there are no subscribers yet
+        let _ = self.sender.send(LogLine {
+            timestamp: Utc::now().to_rfc3339(),
+            level,
+            message: message.to_string(),
+        });
+    }
+
+    /// Subscribe to streamed lines
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for LogStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global log stream
+pub fn stream() -> &'static LogStream {
+    &LOG_STREAM
+}
+
+/// Forward a debug line to the stream; only printed in debug builds
+pub fn debug(message: &str) {
+    if cfg!(debug_assertions) {
+        println!("{}", message);
+    }
+    LOG_STREAM.publish(LogLevel::Debug, message);
+}
+
+/// Print an info line and forward it to the stream
+pub fn info(message: &str) {
+    println!("{}", message);
+    LOG_STREAM.publish(LogLevel::Info, message);
+}
+
+/// Print an error line and forward it to the stream
+pub fn error(message: &str) {
+    eprintln!("{}", message);
+    LOG_STREAM.publish(LogLevel::Error, message);
+}
+
+/// Forward streamed lines to the frontend as `log-line` events
+pub async fn forward_to_frontend(app_handle: AppHandle) {
+    let mut rx = LOG_STREAM.subscribe();
+
+    loop {
+        match rx.recv().await {
+            Ok(line) => {
+                let _ = app_handle.emit(LOG_EVENT, line);
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                // Slow consumer - old lines were dropped, keep going
+                let _ = app_handle.emit(
+                    LOG_EVENT,
+                    LogLine {
+                        timestamp: Utc::now().to_rfc3339(),
+                        level: LogLevel::Info,
+                        message: format!("[Logs] Skipped {} lines", skipped),
+                    },
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lines_reach_subscriber_and_stop_when_disabled() {
+        let stream = LogStream::new();
+        let mut rx = stream.subscribe();
+
+        // Disabled by default
+        stream.publish(LogLevel::Info, "[Test] dropped");
+        assert!(rx.try_recv().is_err());
+
+        stream.set_enabled(true);
+        stream.publish(LogLevel::Info, "[Test] first");
+        stream.publish(LogLevel::Error, "[Test] second");
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.message, "[Test] first");
+        assert_eq!(first.level, LogLevel::Info);
+        let second = rx.recv().await.unwrap();
+        assert_eq!(second.level, LogLevel::Error);
+
+        stream.set_enabled(false);
+        stream.publish(LogLevel::Info, "[Test] after disable");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_slow_consumer_drops_old_lines() {
+        let stream = LogStream::new();
+        let mut rx = stream.subscribe();
+        stream.set_enabled(true);
+
+        for i in 0..(LOG_CHANNEL_CAPACITY + 10) {
+            stream.publish(LogLevel::Info, &format!("line {}", i));
+        }
+
+        assert!(matches!(
+            rx.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(10))
+        ));
+        assert_eq!(rx.try_recv().unwrap().message, "line 10");
+    }
+}
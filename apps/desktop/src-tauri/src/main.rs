@@ -1,17 +1,34 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod automation;
+mod backfill;
 mod collector;
 mod commands;
+mod config;
+mod credentials;
 mod db;
+mod event_hooks;
+mod focus;
+mod importance;
+mod jpeg;
+mod logs;
 mod native_applescript;
 mod notifications;
 mod permissions;
+mod settings_writer;
+mod sink;
+mod startup;
+mod stats;
+mod storage;
+mod supervisor;
 mod sync;
+mod text;
 mod tray;
 mod updater;
+mod warnings;
 
 use std::sync::Arc;
+use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
@@ -22,58 +39,132 @@ pub const BUFFER_WARNING_THRESHOLD: usize = 8_000;
 
 pub struct AppState {
     pub collecting: bool,
+    /// Events collected since local midnight
     pub events_today: u32,
+    /// Local date `events_today` counts
+    pub counting_day: chrono::NaiveDate,
     pub last_sync: String,
     pub events_buffer: Vec<collector::Event>,
     pub buffer_warnings_logged: bool,
     pub db: Arc<db::EventDatabase>,
     /// Cached top apps - persists across syncs (updated when events are collected)
     pub top_apps_cache: std::collections::HashMap<String, u32>,
+    /// Events whose screenshot a capture worker hasn't attached yet (not synced until it has)
+    pub pending_captures: std::collections::HashSet<String>,
+}
+
+impl AppState {
+    /// Create app state from the events already persisted in the database
+    pub fn new(db: Arc<db::EventDatabase>, existing_events: Vec<collector::Event>) -> Self {
+        // Build initial top_apps_cache from existing events
+        let mut top_apps_cache: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        for event in &existing_events {
+            if let Some(app_name) = &event.app_name {
+                *top_apps_cache.entry(app_name.clone()).or_insert(0) += 1;
+            }
+        }
+
+        // Only today's events count, not everything still in the database
+        let counting_day = chrono::Local::now().date_naive();
+        let (start_of_today, _) = stats::local_day_bounds(counting_day, &chrono::Local);
+        let events_today = db.count_events_since(start_of_today).unwrap_or_else(|e| {
+            logs::error(&format!("[DB] Warning: Failed to count today's events: {}", e));
+            0
+        });
+
+        Self {
+            collecting: true,
+            events_today: events_today as u32,
+            counting_day,
+            last_sync: "Never".to_string(),
+            events_buffer: existing_events,
+            buffer_warnings_logged: false,
+            db,
+            top_apps_cache,
+            pending_captures: std::collections::HashSet::new(),
+        }
+    }
+
+    /// Start a fresh tracking period: zero the counters and caches shown in the UI
+    /// Buffered and persisted events are left untouched
+    pub fn reset_stats(&mut self) {
+        self.events_today = 0;
+        self.top_apps_cache.clear();
+        self.last_sync = "Never".to_string();
+        self.buffer_warnings_logged = false;
+    }
+
+    /// Start counting a new day once the local date has moved past `counting_day`
+    /// Returns true if the counter was reset
+    pub fn roll_over_day(&mut self, today: chrono::NaiveDate) -> bool {
+        if today <= self.counting_day {
+            return false;
+        }
+        self.events_today = 0;
+        self.counting_day = today;
+        true
+    }
 }
 
 fn main() {
     // Initialize database
+    // A corrupt file is replaced on open; if even that fails, keep running
+    // in memory rather than refusing to start
     let db = Arc::new(
-        db::EventDatabase::new()
-            .expect("Failed to initialize event database")
+        db::EventDatabase::new().unwrap_or_else(|e| {
+            logs::error(&format!(
+                "[DB] Error: Failed to open event database, events will not persist: {}",
+                e
+            ));
+            db::EventDatabase::in_memory().expect("Failed to initialize event database")
+        })
     );
 
-    // Load existing events from database
-    let existing_events = db.load_all_events()
+    // Check the database in the background; if it's damaged, it is moved
+    // aside on the next start instead of blocking this one
+    let check_db = db.clone();
+    std::thread::spawn(move || match check_db.quick_check() {
+        Ok(true) => {}
+        Ok(false) => {
+            logs::error("[DB] Warning: Integrity check failed, the database will be reset on next start");
+            if let Err(e) = db::EventDatabase::schedule_recovery(&db::EventDatabase::db_path()) {
+                logs::error(&format!("[DB] Error: Failed to schedule database recovery: {}", e));
+            }
+        }
+        Err(e) => logs::error(&format!("[DB] Error: Failed to check database integrity: {}", e)),
+    });
+
+    // Load unsynced events from database (the rest is picked up by sync)
+    let existing_events = db.load_unsynced_events(MAX_BUFFER_SIZE)
         .unwrap_or_else(|e| {
-            eprintln!("Warning: Failed to load events from database: {}", e);
+            logs::error(&format!("[DB] Warning: Failed to load events from database: {}", e));
             Vec::new()
         });
 
     let events_count = existing_events.len();
     if events_count > 0 {
-        println!("Loaded {} existing events from database", events_count);
-    }
-
-    // Build initial top_apps_cache from existing events
-    let mut initial_top_apps: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
-    for event in &existing_events {
-        if let Some(app_name) = &event.app_name {
-            *initial_top_apps.entry(app_name.clone()).or_insert(0) += 1;
-        }
+        logs::info(&format!("[DB] Loaded {} existing events from database", events_count));
     }
 
     // Create app state with database
-    let state = Arc::new(Mutex::new(AppState {
-        collecting: true,
-        events_today: existing_events.len() as u32,
-        last_sync: "Never".to_string(),
-        events_buffer: existing_events,
-        buffer_warnings_logged: false,
-        db: db.clone(),
-        top_apps_cache: initial_top_apps,
-    }));
+    let state = Arc::new(Mutex::new(AppState::new(db.clone(), existing_events)));
     let shutdown_token = CancellationToken::new();
 
-    // Create automation queue
-    let (automation_queue, mut result_rx) = automation::queue::AutomationQueue::new();
+    // Create automation queue, gated by the saved trust level
+    let trust = Arc::new(automation::TrustManager::load());
+    let (automation_queue, mut result_rx) = automation::queue::AutomationQueue::new(trust.clone());
     let automation_queue = Arc::new(automation_queue);
 
+    // Apply runtime limits from saved settings
+    if let Ok(settings) = commands::get_settings() {
+        native_applescript::set_concurrency_limit(settings.applescript_concurrency);
+        automation_queue.set_limits(settings.queue_limits());
+    }
+
+    let screenshot_manager = collector::new_screenshot_manager();
+    // Subscribed once; each (re)started collector gets its own receiver
+    let power_events = collector::watch_power();
+
     // Set up signal handlers for graceful shutdown
     let shutdown_token_clone = shutdown_token.clone();
     tauri::async_runtime::spawn(async move {
@@ -90,35 +181,117 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .manage(state.clone())
+        .manage(screenshot_manager.clone())
         .manage(automation_queue.clone())
+        .manage(Arc::new(permissions::PermissionManager::new()))
+        .manage(trust.clone())
         .setup(move |app| {
             // Create system tray
             tray::create_tray(app)?;
 
+            // Forward streamed log lines to the frontend
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                logs::forward_to_frontend(app_handle).await;
+            });
+
             // Check for updates in background
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
+                startup::stagger(startup::UPDATE_CHECK_DELAY_MS).await;
                 updater::check_for_updates(app_handle).await;
             });
 
             // Start collector with shutdown token
             let state_clone = state.clone();
+            let screenshot_manager = screenshot_manager.clone();
+            let power_events = power_events.clone();
             let app_handle = app.handle().clone();
             let shutdown_token_clone = shutdown_token.clone();
             tauri::async_runtime::spawn(async move {
-                collector::start_collector(state_clone, app_handle, shutdown_token_clone).await;
+                startup::stagger(startup::COLLECTOR_DELAY_MS).await;
+                let shutdown = shutdown_token_clone.clone();
+                supervisor::supervise(supervisor::COLLECTOR_TASK, shutdown, move || {
+                    collector::start_collector(
+                        state_clone.clone(),
+                        screenshot_manager.clone(),
+                        power_events.subscribe(),
+                        app_handle.clone(),
+                        shutdown_token_clone.clone(),
+                    )
+                })
+                .await;
+            });
+
+            // Ask for Automation permission up front instead of mid-collection (opt-in, macOS only)
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let enabled = commands::get_settings()
+                    .map(|settings| settings.prewarm_automation_permissions)
+                    .unwrap_or(false);
+                if !cfg!(target_os = "macos") || !enabled {
+                    return;
+                }
+
+                startup::stagger(startup::AUTOMATION_PREWARM_DELAY_MS).await;
+                match tokio::task::spawn_blocking(permissions::prewarm_automation_permissions).await {
+                    Ok(results) => {
+                        let _ = app_handle.emit(permissions::AUTOMATION_PREWARM_EVENT, results);
+                    }
+                    Err(e) => logs::error(&format!("[Permissions] Automation pre-warm failed: {}", e)),
+                }
+            });
+
+            // Pause screenshots while the disk is nearly full
+            let app_handle = app.handle().clone();
+            let shutdown = shutdown_token.clone();
+            tauri::async_runtime::spawn(async move {
+                storage::start_disk_monitor(app_handle, shutdown).await;
+            });
+
+            // Categorize events on the server when enabled
+            let state_clone = state.clone();
+            let shutdown = shutdown_token.clone();
+            tauri::async_runtime::spawn(async move {
+                collector::start_server_categorizer(state_clone, shutdown).await;
+            });
+
+            // Reset the daily event counter at local midnight
+            let state_clone = state.clone();
+            let shutdown = shutdown_token.clone();
+            tauri::async_runtime::spawn(async move {
+                stats::start_day_rollover(state_clone, shutdown).await;
+            });
+
+            // Start focus reminder monitor
+            let app_handle = app.handle().clone();
+            let shutdown = shutdown_token.clone();
+            tauri::async_runtime::spawn(async move {
+                focus::start_focus_monitor(app_handle, shutdown).await;
             });
 
             // Start sync service
             let state_clone = state.clone();
+            let app_handle = app.handle().clone();
+            let shutdown = shutdown_token.clone();
             tauri::async_runtime::spawn(async move {
-                sync::start_sync_service(state_clone).await;
+                startup::stagger(startup::SYNC_DELAY_MS).await;
+                supervisor::supervise(supervisor::SYNC_TASK, shutdown, move || {
+                    sync::start_sync_service(state_clone.clone(), app_handle.clone())
+                })
+                .await;
             });
 
             // Start automation queue processor
             let queue_clone = automation_queue.clone();
+            let shutdown = shutdown_token.clone();
             tauri::async_runtime::spawn(async move {
-                queue_clone.process().await;
+                startup::stagger(startup::QUEUE_DELAY_MS).await;
+                supervisor::supervise(supervisor::QUEUE_TASK, shutdown, move || {
+                    let queue = queue_clone.clone();
+                    async move { queue.process().await }
+                })
+                .await;
             });
 
             // Create WebSocket automation sync
@@ -126,32 +299,39 @@ fn main() {
             let mut sync = automation::sync::AutomationSync::new(
                 ws_url,
                 automation_queue.clone(),
+                trust.clone(),
             );
             sync.set_app_handle(app.handle().clone());
             let sync = Arc::new(sync);
+            app.manage(sync.clone());
 
             // Handle automation task results
             let app_handle = app.handle().clone();
             let sync_for_results = sync.clone();
             tauri::async_runtime::spawn(async move {
                 while let Some(result) = result_rx.recv().await {
-                    println!("Task {} completed: {}", result.task_id, result.success);
+                    logs::info(&format!("[Automation] Task {} completed: {}", result.task_id, result.success));
                     if let Some(error) = &result.error {
-                        eprintln!("Task error: {}", error);
+                        logs::error(&format!("[Automation] Task error: {}", error));
                         // Send notification
                         let _ = notifications::notify_error(&app_handle, error);
                     }
 
                     // Send result back to server via WebSocket
                     if let Err(e) = sync_for_results.send_result(result).await {
-                        eprintln!("Failed to send result to server: {}", e);
+                        logs::error(&format!("[Automation] Failed to send result to server: {}", e));
                     }
                 }
             });
 
             // Start WebSocket automation sync
+            let shutdown = shutdown_token.clone();
             tauri::async_runtime::spawn(async move {
-                sync.start().await;
+                startup::stagger(startup::WEBSOCKET_DELAY_MS).await;
+                supervisor::supervise(supervisor::WEBSOCKET_TASK, shutdown, move || {
+                    sync.clone().start()
+                })
+                .await;
             });
 
             Ok(())
@@ -159,54 +339,124 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::get_stats,
             commands::get_detailed_stats,
+            commands::reset_stats,
             commands::toggle_collection,
             commands::sync_now,
+            commands::flush_and_sync,
+            commands::get_recent_events,
+            commands::get_favicons,
             commands::open_dashboard,
             commands::open_settings,
             commands::check_permissions,
             commands::request_permissions,
             commands::get_focus,
+            commands::get_current_context,
+            commands::dump_ax_tree,
             commands::set_window_visible,
             // Automation commands
             commands::check_all_permissions,
             commands::request_permission,
+            commands::refresh_permissions,
             commands::open_permission_settings,
             commands::automation_click,
             commands::automation_type,
             commands::automation_hotkey,
             commands::automation_screenshot,
             commands::automation_screenshot_jpeg,
+            commands::automation_screenshot_window,
             commands::automation_get_monitors,
+            commands::get_monitor_previews,
             commands::automation_ocr,
+            commands::ocr_with_boxes,
+            commands::get_ocr_languages,
+            commands::get_cloud_ocr_config,
+            commands::set_cloud_ocr_config,
+            commands::test_cloud_ocr,
+            commands::get_ws_token_masked,
+            commands::set_ws_token,
+            commands::rotate_ws_token,
             commands::automation_browser_url,
             commands::automation_browser_navigate,
             commands::automation_detect_browser,
             commands::queue_add_task,
             commands::queue_status,
+            commands::export_queue,
+            commands::import_queue,
             commands::queue_pause,
             commands::queue_resume,
             commands::queue_clear,
+            commands::set_automation_paused,
+            commands::get_task_history,
+            commands::get_trust_level,
+            commands::set_trust_level,
             // Settings commands
             commands::get_settings,
             commands::save_settings,
+            commands::get_full_config,
+            commands::set_full_config,
+            commands::export_config,
+            commands::import_config,
+            commands::get_device_name,
+            commands::set_device_name,
             commands::open_system_preferences,
             // Debug commands
             commands::get_debug_info,
             commands::check_updates,
+            commands::get_storage_usage,
+            commands::estimate_daily_footprint,
+            commands::check_database_integrity,
+            commands::get_warnings,
+            commands::dismiss_warning,
+            commands::retry_applescript,
+            commands::get_collector_timings,
+            commands::get_screenshot_config,
+            commands::set_screenshot_config,
+            commands::get_collector_toggles,
+            commands::set_collector_enabled,
+            commands::capture_now,
+            commands::set_global_typed_text,
+            commands::get_notification_history,
+            commands::start_high_fidelity_recording,
+            commands::stop_high_fidelity_recording,
+            commands::clear_notification_history,
+            commands::cleanup_screenshots,
+            commands::get_screenshot_thumbnail,
+            commands::set_log_streaming,
             // App automation permissions
             commands::get_automation_permissions,
             commands::request_app_automation,
             commands::trigger_automation_permission,
             commands::request_all_automations,
             commands::open_automation_prefs,
+            // Event commands
+            commands::recategorize_event,
+            commands::add_manual_event,
+            commands::get_event,
+            commands::delete_event,
+            commands::query_events,
+            commands::preview_sync_payload,
+            commands::get_sync_lag,
+            commands::get_daily_summary,
+            commands::get_streaks,
+            commands::get_hourly_activity,
+            commands::get_category_breakdown,
+            commands::get_top_domains,
+            commands::get_app_transitions,
+            commands::backfill_ocr,
+            commands::cancel_ocr_backfill,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
-            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+        .run(|_app_handle, event| match event {
+            tauri::RunEvent::ExitRequested { api, .. } => {
                 // Prevent exit - this is a menubar app (LSUIElement)
                 api.prevent_exit();
             }
+            tauri::RunEvent::Exit => {
+                // Don't lose a save still waiting out the debounce
+                settings_writer::flush_pending();
+            }
+            _ => {}
         });
 }
 
@@ -223,11 +473,11 @@ async fn setup_signal_handlers(shutdown_token: CancellationToken) {
 
         tokio::select! {
             _ = sigterm.recv() => {
-                println!("Received SIGTERM signal");
+                logs::info("[App] Received SIGTERM signal");
                 shutdown_token.cancel();
             }
             _ = sigint.recv() => {
-                println!("Received SIGINT signal");
+                logs::info("[App] Received SIGINT signal");
                 shutdown_token.cancel();
             }
         }
@@ -239,9 +489,67 @@ async fn setup_signal_handlers(shutdown_token: CancellationToken) {
 
         tokio::select! {
             _ = signal::ctrl_c() => {
-                println!("Received CTRL+C signal");
+                logs::info("[App] Received CTRL+C signal");
                 shutdown_token.cancel();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_stats_keeps_events() {
+        let db = Arc::new(db::EventDatabase::in_memory().unwrap());
+        let events = vec![
+            collector::Event::new("app_focus", Some("Code".to_string()), None),
+            collector::Event::new("app_focus", Some("Safari".to_string()), None),
+        ];
+        for event in &events {
+            db.insert_event(event).unwrap();
+        }
+
+        let mut state = AppState::new(db.clone(), events);
+        state.last_sync = "Just now".to_string();
+        assert_eq!(state.events_today, 2);
+        assert_eq!(state.top_apps_cache.len(), 2);
+
+        state.reset_stats();
+
+        assert_eq!(state.events_today, 0);
+        assert!(state.top_apps_cache.is_empty());
+        assert_eq!(state.last_sync, "Never");
+        assert_eq!(state.events_buffer.len(), 2);
+        assert_eq!(db.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_events_today_counts_only_today() {
+        let db = Arc::new(db::EventDatabase::in_memory().unwrap());
+        let today = collector::Event::new("app_focus", Some("Code".to_string()), None);
+        let mut yesterday = collector::Event::new("app_focus", Some("Safari".to_string()), None);
+        let (start_of_today, _) = stats::local_day_bounds(chrono::Local::now().date_naive(), &chrono::Local);
+        yesterday.timestamp = start_of_today - chrono::Duration::hours(1);
+        // Sent earlier today, before the restart
+        let synced_today = collector::Event::new("app_focus", Some("Slack".to_string()), None);
+        for event in [&today, &yesterday, &synced_today] {
+            db.insert_event(event).unwrap();
+        }
+        db.mark_synced(&[synced_today.id.clone()]).unwrap();
+
+        let mut state = AppState::new(db, vec![today, yesterday]);
+        assert_eq!(state.events_today, 2);
+
+        // Same day (or a clock set back) keeps the count
+        let day = state.counting_day;
+        assert!(!state.roll_over_day(day));
+        assert!(!state.roll_over_day(day.pred_opt().unwrap()));
+        assert_eq!(state.events_today, 2);
+
+        assert!(state.roll_over_day(day.succ_opt().unwrap()));
+        assert_eq!(state.events_today, 0);
+        assert_eq!(state.counting_day, day.succ_opt().unwrap());
+    }
+}
@@ -1,31 +1,298 @@
 //! AppleScript execution via osascript command
 //! Using osascript ensures Automation permission dialogs are triggered properly.
+//! All osascript calls go through here so a hung target app (beachball, modal
+//! dialog) can't block the caller forever - scripts are killed at a timeout.
 
-use std::process::Command;
+use once_cell::sync::Lazy;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
 
-/// Execute AppleScript using osascript command
-/// Returns the result as a String, or None if execution failed
+/// Default timeout for AppleScript calls made from the collector and automation
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for scripts that may show a permission dialog the user has to answer
+pub const PERMISSION_PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Stdout kept from a script; anything beyond is read and discarded
+/// Bounds memory when a browser with hundreds of tabs returns megabytes
+pub const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// How often a running child is polled for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Default number of osascript processes allowed to run at once
+pub const DEFAULT_CONCURRENCY_LIMIT: usize = 2;
+
+static LIMITER: Lazy<ConcurrencyLimiter> =
+    Lazy::new(|| ConcurrencyLimiter::new(DEFAULT_CONCURRENCY_LIMIT));
+
+/// Consecutive failed collector scripts after which osascript is assumed blocked
+/// (e.g. by a management policy) and AppleScript collection stops for the session
+pub const COLLECTOR_FAILURE_THRESHOLD: u32 = 20;
+
+static COLLECTOR_CIRCUIT: Mutex<FailureCircuit> = Mutex::new(FailureCircuit::new());
+
+/// Error text of failures that mean osascript itself can't run or isn't allowed
+/// to: it couldn't be launched, or Automation (-1743) / assistive access (-1719)
+/// was refused. A script error or a slow app says nothing about osascript.
+const BLOCKED_ERROR_MARKERS: &[&str] = &["Failed to spawn process", "(-1743)", "(-1719)", "Not authorized", "not allowed"];
+
+/// Counting semaphore for blocking callers
+/// Caps how many osascript subprocesses run at once so rapid focus changes
+/// don't stack up processes (and authorization dialogs)
+pub struct ConcurrencyLimiter {
+    limit: Mutex<usize>,
+    active: Mutex<usize>,
+    released: Condvar,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: Mutex::new(limit.max(1)),
+            active: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Change the limit; waiters are re-checked immediately
+    pub fn set_limit(&self, limit: usize) {
+        *self.limit.lock().unwrap() = limit.max(1);
+        self.released.notify_all();
+    }
+
+    /// Run `f` once a slot is free
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        {
+            let mut active = self.active.lock().unwrap();
+            while *active >= *self.limit.lock().unwrap() {
+                active = self.released.wait(active).unwrap();
+            }
+            *active += 1;
+        }
+
+        // Release the slot even if `f` panics
+        struct Release<'a>(&'a ConcurrencyLimiter);
+        impl Drop for Release<'_> {
+            fn drop(&mut self) {
+                *self.0.active.lock().unwrap() -= 1;
+                self.0.released.notify_one();
+            }
+        }
+        let _release = Release(self);
+
+        f()
+    }
+}
+
+/// Consecutive-failure circuit breaker
+/// Any success resets the count; once open it stays open until `reset`
+#[derive(Debug)]
+pub struct FailureCircuit {
+    consecutive_failures: u32,
+    open: bool,
+    /// Opened since the last `take_opened`
+    newly_opened: bool,
+}
+
+impl FailureCircuit {
+    pub const fn new() -> Self {
+        Self {
+            consecutive_failures: 0,
+            open: false,
+            newly_opened: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Returns true if this failure opened the circuit
+    pub fn record_failure(&mut self, threshold: u32) -> bool {
+        self.consecutive_failures += 1;
+        if !self.open && self.consecutive_failures >= threshold {
+            self.open = true;
+            self.newly_opened = true;
+            return true;
+        }
+        false
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    /// Whether the circuit opened since the last call (to notify once)
+    pub fn take_opened(&mut self) -> bool {
+        std::mem::take(&mut self.newly_opened)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Set how many AppleScript calls may run concurrently
+pub fn set_concurrency_limit(limit: usize) {
+    LIMITER.set_limit(limit);
+}
+
+/// Run a command, killing it if it doesn't finish within `timeout`
+pub fn output_with_timeout(mut command: Command, timeout: Duration) -> Result<Output, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn process: {}", e))?;
+
+    // Drain pipes on threads so a chatty child can't block on a full pipe
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let Some(pipe) = stdout_pipe.as_mut() else {
+            return Vec::new();
+        };
+        let (buf, truncated) = read_capped(pipe, MAX_OUTPUT_BYTES);
+        if truncated {
+            crate::logs::error(&format!(
+                "[AppleScript] Warning: Output over {} bytes, truncated",
+                MAX_OUTPUT_BYTES
+            ));
+        }
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Process timed out after {:?}", timeout));
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for process: {}", e)),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    })
+}
+
+/// Read `pipe` to the end, keeping at most `max` bytes
+/// Returns the kept bytes and whether anything was dropped
+pub fn read_capped(pipe: &mut impl Read, max: usize) -> (Vec<u8>, bool) {
+    let mut buf = Vec::new();
+    let _ = pipe.by_ref().take(max as u64).read_to_end(&mut buf);
+    // Keep draining so the child never blocks on a full pipe
+    let dropped = std::io::copy(pipe, &mut std::io::sink()).unwrap_or(0);
+    (buf, dropped > 0)
+}
+
+/// Run AppleScript with a timeout
+/// Returns raw stdout on success, or an error (including timeouts)
 #[cfg(target_os = "macos")]
-pub fn execute(script: &str) -> Option<String> {
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .ok()?;
+pub fn run(script: &str, timeout: Duration) -> Result<String, String> {
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+
+    let output = LIMITER
+        .run(|| output_with_timeout(command, timeout))
+        .map_err(|e| format!("AppleScript failed: {}", e))?;
 
     if output.status.success() {
-        let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Some(result)
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        let err = String::from_utf8_lossy(&output.stderr);
-        eprintln!("[AppleScript] Error: {}", err);
-        None
+        Err(format!(
+            "AppleScript error: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
     }
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn execute(_script: &str) -> Option<String> {
-    None
+pub fn run(_script: &str, _timeout: Duration) -> Result<String, String> {
+    Err("AppleScript only supported on macOS".to_string())
+}
+
+/// Execute AppleScript using osascript command
+/// Returns the trimmed result as a String, or None if execution failed or timed out
+pub fn execute(script: &str) -> Option<String> {
+    match run(script, DEFAULT_TIMEOUT) {
+        Ok(result) => Some(result.trim().to_string()),
+        Err(e) => {
+            if cfg!(target_os = "macos") {
+                crate::logs::error(&format!("[AppleScript] Error: {}", e));
+            }
+            None
+        }
+    }
+}
+
+/// Whether a `run` error means osascript is blocked rather than the script failing
+pub fn is_blocked_error(error: &str) -> bool {
+    BLOCKED_ERROR_MARKERS.iter().any(|marker| error.contains(marker))
+}
+
+/// Run a collector script (browser, messenger, window info) with `DEFAULT_TIMEOUT`
+/// After `COLLECTOR_FAILURE_THRESHOLD` launch or permission failures in a row
+/// nothing is run until `reset_collectors`, so a blocked osascript doesn't cost
+/// a process per tick. Other errors (app not running, timeouts) don't count.
+pub fn collect(script: &str) -> Option<String> {
+    if COLLECTOR_CIRCUIT.lock().unwrap().is_open() {
+        return None;
+    }
+
+    match run(script, DEFAULT_TIMEOUT) {
+        Ok(result) => {
+            COLLECTOR_CIRCUIT.lock().unwrap().record_success();
+            Some(result.trim().to_string())
+        }
+        Err(e) => {
+            if cfg!(target_os = "macos") {
+                crate::logs::error(&format!("[AppleScript] Error: {}", e));
+                if is_blocked_error(&e)
+                    && COLLECTOR_CIRCUIT.lock().unwrap().record_failure(COLLECTOR_FAILURE_THRESHOLD)
+                {
+                    crate::logs::error(&format!(
+                        "[AppleScript] osascript was blocked {} times in a row, disabling AppleScript collectors for this session",
+                        COLLECTOR_FAILURE_THRESHOLD
+                    ));
+                }
+            }
+            None
+        }
+    }
+}
+
+/// Whether AppleScript collectors were disabled after repeated failures
+pub fn collectors_disabled() -> bool {
+    COLLECTOR_CIRCUIT.lock().unwrap().is_open()
+}
+
+/// True once after the collectors were disabled
+pub fn take_collectors_disabled() -> bool {
+    COLLECTOR_CIRCUIT.lock().unwrap().take_opened()
+}
+
+/// Re-enable AppleScript collectors (manual retry)
+pub fn reset_collectors() {
+    COLLECTOR_CIRCUIT.lock().unwrap().reset();
 }
 
 /// Execute AppleScript and return true/false result
@@ -56,7 +323,7 @@ pub fn request_app_permission(app_name: &str) -> bool {
         end tell"#,
         app_name
     );
-    execute(&script).is_some()
+    run(&script, PERMISSION_PROMPT_TIMEOUT).is_ok()
 }
 
 /// Trigger automation permission request for a specific app
@@ -64,10 +331,131 @@ pub fn request_app_permission(app_name: &str) -> bool {
 /// macOS permission dialogs
 pub fn trigger_permission_for_app(app_name: &str) -> bool {
     let script = format!(r#"tell application "{}" to name"#, app_name);
-    Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    run(&script, PERMISSION_PROMPT_TIMEOUT).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_long_running_process_is_killed_at_timeout() {
+        let mut command = Command::new("sleep");
+        command.arg("10");
+
+        let start = Instant::now();
+        let result = output_with_timeout(command, Duration::from_millis(200));
+
+        assert!(result.unwrap_err().contains("timed out"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fast_process_returns_output() {
+        let mut command = Command::new("echo");
+        command.arg("hello");
+
+        let output = output_with_timeout(command, Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_oversized_output_is_capped() {
+        let mut command = Command::new("head");
+        command.args(["-c", &(MAX_OUTPUT_BYTES * 3).to_string(), "/dev/zero"]);
+
+        let output = output_with_timeout(command, Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout.len(), MAX_OUTPUT_BYTES);
+    }
+
+    #[test]
+    fn test_limiter_caps_concurrent_executions() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let limiter = Arc::new(ConcurrencyLimiter::new(2));
+        let current = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let current = current.clone();
+                let peak = peak.clone();
+                std::thread::spawn(move || {
+                    // Stubbed executor: record concurrency instead of running osascript
+                    limiter.run(|| {
+                        let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(30));
+                        current.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak >= 1 && peak <= 2, "peak concurrency was {}", peak);
+        assert_eq!(*limiter.active.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let mut circuit = FailureCircuit::new();
+
+        // A success in between resets the count
+        for _ in 0..4 {
+            assert!(!circuit.record_failure(5));
+        }
+        circuit.record_success();
+        for _ in 0..4 {
+            assert!(!circuit.record_failure(5));
+        }
+        assert!(!circuit.is_open());
+
+        // The fifth failure in a row opens it, exactly once
+        assert!(circuit.record_failure(5));
+        assert!(circuit.is_open());
+        assert!(!circuit.record_failure(5));
+        assert!(circuit.take_opened());
+        assert!(!circuit.take_opened());
+
+        // Stays open until reset
+        circuit.record_success();
+        assert!(circuit.is_open());
+        circuit.reset();
+        assert!(!circuit.is_open());
+        assert!(!circuit.record_failure(5));
+    }
+
+    #[test]
+    fn test_only_launch_and_permission_errors_count_as_blocked() {
+        assert!(is_blocked_error("AppleScript failed: Failed to spawn process: Permission denied (os error 13)"));
+        assert!(is_blocked_error(
+            "AppleScript error: execution error: Not authorized to send Apple events to Safari. (-1743)"
+        ));
+        assert!(is_blocked_error(
+            "AppleScript error: System Events got an error: osascript is not allowed assistive access. (-1719)"
+        ));
+
+        assert!(!is_blocked_error("AppleScript failed: Process timed out after 5s"));
+        assert!(!is_blocked_error("AppleScript error: execution error: Safari got an error: Application isn't running. (-600)"));
+        assert!(!is_blocked_error("AppleScript error: syntax error: Expected end of line. (-2741)"));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_hung_applescript_times_out() {
+        let result = run("delay 10", Duration::from_millis(500));
+        assert!(result.unwrap_err().contains("timed out"));
+    }
 }
@@ -1,8 +1,18 @@
 /// Notification management module
 /// Uses tauri_plugin_notification for cross-platform notifications
 
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use tauri_plugin_notification::NotificationExt;
 use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Sent notifications kept for the in-app notification center
+pub const NOTIFICATION_HISTORY_CAPACITY: usize = 200;
+
+static HISTORY: Lazy<Mutex<NotificationHistory>> =
+    Lazy::new(|| Mutex::new(NotificationHistory::new(NOTIFICATION_HISTORY_CAPACITY)));
 
 /// Notification priority
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,6 +33,58 @@ pub struct NotificationConfig {
     pub action: Option<String>,
 }
 
+/// A notification that was sent
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub title: String,
+    pub body: String,
+    pub priority: NotificationPriority,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Most recent sent notifications, oldest dropped first
+#[derive(Debug)]
+pub struct NotificationHistory {
+    capacity: usize,
+    records: VecDeque<NotificationRecord>,
+}
+
+impl NotificationHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            records: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, record: NotificationRecord) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Up to `limit` notifications, newest first
+    pub fn recent(&self, limit: usize) -> Vec<NotificationRecord> {
+        self.records.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Recently sent notifications, newest first
+pub fn notification_history(limit: usize) -> Vec<NotificationRecord> {
+    HISTORY.lock().unwrap().recent(limit)
+}
+
+/// Forget all sent notifications
+pub fn clear_notification_history() {
+    HISTORY.lock().unwrap().clear();
+}
+
 /// Send a notification
 pub fn send_notification(
     app: &tauri::AppHandle,
@@ -34,12 +96,20 @@ pub fn send_notification(
     // The action field in config is stored for potential future use
     let _action = config.action; // Suppress unused warning
 
+    let record = NotificationRecord {
+        title: config.title.clone(),
+        body: config.body.clone(),
+        priority: config.priority,
+        timestamp: Utc::now(),
+    };
+
     notification.builder()
         .title(config.title)
         .body(config.body)
         .show()
         .map_err(|e| format!("Failed to show notification: {}", e))?;
 
+    HISTORY.lock().unwrap().record(record);
     Ok(())
 }
 
@@ -58,6 +128,20 @@ pub fn notify_permission_required(
     send_notification(app, config)
 }
 
+/// Tell the user AppleScript collectors were turned off after repeated failures
+pub fn notify_applescript_disabled(app: &tauri::AppHandle) -> Result<(), String> {
+    let config = NotificationConfig {
+        title: "AppleScript Unavailable".to_string(),
+        body: "osascript keeps failing (it may be blocked by a policy). Browser, messenger and \
+            window details are paused for this session; retry from settings."
+            .to_string(),
+        priority: NotificationPriority::High,
+        action: Some("Retry".to_string()),
+    };
+
+    send_notification(app, config)
+}
+
 /// Send automation task started notification
 pub fn notify_task_started(
     app: &tauri::AppHandle,
@@ -154,7 +238,7 @@ pub fn show_suggestion_notification(
     description: &str,
     suggestion_id: &str,
 ) -> Result<(), String> {
-    println!("[Notification] Showing suggestion: {} (ID: {})", title, suggestion_id);
+    crate::logs::info(&format!("[Notification] Showing suggestion: {} (ID: {})", title, suggestion_id));
 
     let config = NotificationConfig {
         title: format!("Suggestion: {}", title),
@@ -263,4 +347,26 @@ mod tests {
         assert!(!manager.should_show(NotificationPriority::Normal));
         assert!(manager.should_show(NotificationPriority::High));
     }
+
+    #[test]
+    fn test_history_newest_first_and_bounded() {
+        let mut history = NotificationHistory::new(3);
+        let start = Utc::now();
+        for i in 0..5 {
+            history.record(NotificationRecord {
+                title: format!("Title {}", i),
+                body: format!("Body {}", i),
+                priority: NotificationPriority::Normal,
+                timestamp: start + chrono::Duration::seconds(i),
+            });
+        }
+
+        // Only the last 3 are kept, newest first
+        let titles: Vec<String> = history.recent(10).into_iter().map(|record| record.title).collect();
+        assert_eq!(titles, vec!["Title 4", "Title 3", "Title 2"]);
+        assert_eq!(history.recent(1)[0].body, "Body 4");
+
+        history.clear();
+        assert!(history.recent(10).is_empty());
+    }
 }
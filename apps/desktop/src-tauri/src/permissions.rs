@@ -2,6 +2,8 @@
 /// Handles accessibility and screen recording permissions
 
 use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[cfg(target_os = "macos")]
 use crate::automation::accessibility_ffi;
@@ -153,20 +155,30 @@ pub fn is_permission_required_for_feature(
     }
 }
 
+/// Event emitted when permission status changes after a request
+pub const PERMISSIONS_CHANGED_EVENT: &str = "permissions-changed";
+
 /// Permission manager
 pub struct PermissionManager {
     last_check: std::sync::RwLock<std::time::Instant>,
     cache_duration: std::time::Duration,
     cached_permissions: std::sync::RwLock<Option<AllPermissions>>,
+    checker: fn() -> AllPermissions,
 }
 
 impl PermissionManager {
     /// Create new permission manager
     pub fn new() -> Self {
+        Self::with_checker(check_all_permissions)
+    }
+
+    /// Create a permission manager with a custom status source
+    pub fn with_checker(checker: fn() -> AllPermissions) -> Self {
         Self {
             last_check: std::sync::RwLock::new(std::time::Instant::now()),
             cache_duration: std::time::Duration::from_secs(5),
             cached_permissions: std::sync::RwLock::new(None),
+            checker,
         }
     }
 
@@ -183,7 +195,7 @@ impl PermissionManager {
         }
 
         // Check permissions
-        let permissions = check_all_permissions();
+        let permissions = (self.checker)();
 
         // Update cache
         {
@@ -203,6 +215,12 @@ impl PermissionManager {
         let mut cached = self.cached_permissions.write().unwrap();
         *cached = None;
     }
+
+    /// Bust the cache and return fresh permission status
+    pub fn refresh(&self) -> AllPermissions {
+        self.invalidate_cache();
+        self.check_all()
+    }
 }
 
 impl Default for PermissionManager {
@@ -243,45 +261,126 @@ pub fn get_required_apps() -> Vec<(&'static str, &'static str)> {
 
 /// Check automation permission for all required apps
 pub fn check_automation_permissions() -> Vec<AppPermissionStatus> {
+    automation_statuses(crate::native_applescript::check_app_permission)
+}
+
+/// One status per required app, in `get_required_apps` order
+fn automation_statuses(check: impl Fn(&str) -> bool) -> Vec<AppPermissionStatus> {
     get_required_apps()
         .iter()
-        .map(|(name, bundle_id)| {
-            let granted = crate::native_applescript::check_app_permission(name);
-            AppPermissionStatus {
-                app_name: name.to_string(),
-                bundle_id: bundle_id.to_string(),
-                granted,
-            }
+        .map(|(name, bundle_id)| AppPermissionStatus {
+            app_name: name.to_string(),
+            bundle_id: bundle_id.to_string(),
+            granted: check(name),
         })
         .collect()
 }
 
+/// Check if Observer asks for Automation access to this app
+/// The name ends up inside an AppleScript, so anything else is rejected
+pub fn is_required_app(app_name: &str) -> bool {
+    get_required_apps().iter().any(|(name, _)| *name == app_name)
+}
+
 /// Request automation permission for a specific app
 /// Returns true if permission was granted
 pub fn request_automation_permission(app_name: &str) -> bool {
+    if !is_required_app(app_name) {
+        crate::logs::error(&format!("[Permissions] Refusing Automation request for unknown app: {}", app_name));
+        return false;
+    }
     crate::native_applescript::request_app_permission(app_name)
 }
 
 /// Request automation permissions for all apps sequentially
 /// Returns updated status for all apps
 pub fn request_all_automation_permissions() -> Vec<AppPermissionStatus> {
-    let apps = get_required_apps();
+    request_sequentially(
+        &get_required_apps(),
+        crate::native_applescript::request_app_permission,
+        std::thread::sleep,
+        Duration::from_millis(500),
+    )
+}
+
+/// Event emitted with the results of the startup automation permission pre-warm
+pub const AUTOMATION_PREWARM_EVENT: &str = "automation-permissions-prewarmed";
+
+/// Pause between startup prompts so the user can answer each dialog
+pub const PREWARM_PROMPT_DELAY: Duration = Duration::from_secs(3);
+
+/// Request permission for each app in order, pausing `delay` between requests
+/// (not after the last one)
+pub fn request_sequentially(
+    apps: &[(&'static str, &'static str)],
+    mut request: impl FnMut(&str) -> bool,
+    mut pause: impl FnMut(Duration),
+    delay: Duration,
+) -> Vec<AppPermissionStatus> {
     let mut results = Vec::new();
 
-    for (name, bundle_id) in apps {
-        // Try to trigger permission request
-        let granted = crate::native_applescript::request_app_permission(name);
+    for (index, (name, bundle_id)) in apps.iter().enumerate() {
+        if index > 0 {
+            pause(delay);
+        }
 
         results.push(AppPermissionStatus {
             app_name: name.to_string(),
             bundle_id: bundle_id.to_string(),
-            granted,
+            granted: request(name),
         });
+    }
+
+    results
+}
 
-        // Small delay between requests so user can respond to dialogs
-        std::thread::sleep(std::time::Duration::from_millis(500));
+/// Check if an app bundle exists in the usual install locations
+/// Scripting an app that isn't installed makes macOS ask where it is
+pub fn is_app_installed(app_name: &str) -> bool {
+    let bundle = format!("{}.app", app_name);
+    let mut locations = vec![
+        PathBuf::from("/Applications"),
+        PathBuf::from("/System/Applications"),
+        PathBuf::from("/System/Library/CoreServices"),
+    ];
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join("Applications"));
     }
 
+    locations.iter().any(|dir| dir.join(&bundle).exists())
+}
+
+/// Ask for Automation permission for every installed app up front, one dialog at a time,
+/// so the prompts don't interrupt collection later (blocking)
+pub fn prewarm_automation_permissions() -> Vec<AppPermissionStatus> {
+    let installed: Vec<_> = get_required_apps()
+        .into_iter()
+        .filter(|(name, _)| is_app_installed(name))
+        .collect();
+
+    let results = request_sequentially(
+        &installed,
+        crate::native_applescript::request_app_permission,
+        std::thread::sleep,
+        PREWARM_PROMPT_DELAY,
+    );
+
+    let granted: Vec<&str> = results
+        .iter()
+        .filter(|status| status.granted)
+        .map(|status| status.app_name.as_str())
+        .collect();
+    let denied: Vec<&str> = results
+        .iter()
+        .filter(|status| !status.granted)
+        .map(|status| status.app_name.as_str())
+        .collect();
+    crate::logs::info(&format!(
+        "[Permissions] Automation pre-warm: granted [{}], not granted [{}]",
+        granted.join(", "),
+        denied.join(", ")
+    ));
+
     results
 }
 
@@ -334,4 +433,86 @@ mod tests {
         let _ = manager.check_all();
         manager.invalidate_cache();
     }
+
+    #[test]
+    fn test_one_automation_status_per_required_app() {
+        let statuses = automation_statuses(|name| name == "Safari");
+        let required = get_required_apps();
+
+        assert_eq!(statuses.len(), required.len());
+        for (status, (name, bundle_id)) in statuses.iter().zip(&required) {
+            assert_eq!(status.app_name, *name);
+            assert_eq!(status.bundle_id, *bundle_id);
+            assert_eq!(status.granted, *name == "Safari");
+        }
+    }
+
+    #[test]
+    fn test_unknown_app_is_not_scripted() {
+        assert!(is_required_app("Google Chrome"));
+        assert!(!is_required_app("Safari\" to do shell script \"id"));
+        assert!(!request_automation_permission("Not An App"));
+    }
+
+    #[test]
+    fn test_requests_are_sequential_with_pauses_between() {
+        let apps = [
+            ("Safari", "com.apple.Safari"),
+            ("Slack", "com.tinyspeck.slackmacgap"),
+            ("Notes", "com.apple.Notes"),
+        ];
+        let log = std::cell::RefCell::new(Vec::new());
+
+        let results = request_sequentially(
+            &apps,
+            |name| {
+                log.borrow_mut().push(format!("request {}", name));
+                name != "Slack"
+            },
+            |delay| log.borrow_mut().push(format!("pause {}s", delay.as_secs())),
+            Duration::from_secs(3),
+        );
+
+        assert_eq!(
+            log.into_inner(),
+            vec![
+                "request Safari",
+                "pause 3s",
+                "request Slack",
+                "pause 3s",
+                "request Notes"
+            ]
+        );
+        let granted: Vec<bool> = results.iter().map(|status| status.granted).collect();
+        assert_eq!(granted, vec![true, false, true]);
+        assert_eq!(results[1].bundle_id, "com.tinyspeck.slackmacgap");
+
+        let none = request_sequentially(&[], |_| panic!("nothing to request"), |_| panic!("no pause"), Duration::ZERO);
+        assert!(none.is_empty());
+    }
+
+    static GRANTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+    fn fake_checker() -> AllPermissions {
+        let granted = GRANTED.load(std::sync::atomic::Ordering::SeqCst);
+        AllPermissions {
+            accessibility: granted,
+            screen_recording: granted,
+            all_granted: granted,
+        }
+    }
+
+    #[test]
+    fn test_invalidate_forces_recheck() {
+        let manager = PermissionManager::with_checker(fake_checker);
+        assert!(!manager.check_all().all_granted);
+
+        // User grants permission in System Settings; cache is still stale
+        GRANTED.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(!manager.check_all().all_granted);
+
+        // Refreshing bypasses the cache
+        assert!(manager.refresh().all_granted);
+        assert!(manager.check_all().all_granted);
+    }
 }
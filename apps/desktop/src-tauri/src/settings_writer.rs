@@ -0,0 +1,188 @@
+// Debounced settings persistence
+// A settings UI may call `save_settings` on every keystroke or toggle. Changes
+// are applied right away but written to disk at most once per quiet period,
+// with the newest value winning; reads see the unsaved value in the meantime.
+
+use crate::commands::AppSettings;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Quiet period after the last save before settings are written
+pub const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+static PENDING: Lazy<Mutex<Debouncer<AppSettings>>> = Lazy::new(|| Mutex::new(Debouncer::default()));
+
+/// Orders flushes, so an older value is never written after a newer one
+/// (config.json itself is guarded by `Config::update`)
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Latest staged value and a generation counter to tell whether it was superseded
+#[derive(Debug)]
+pub struct Debouncer<T> {
+    pending: Option<T>,
+    generation: u64,
+}
+
+impl<T> Default for Debouncer<T> {
+    fn default() -> Self {
+        Self {
+            pending: None,
+            generation: 0,
+        }
+    }
+}
+
+impl<T: Clone> Debouncer<T> {
+    /// Replace the pending value, returning its generation
+    pub fn stage(&mut self, value: T) -> u64 {
+        self.generation += 1;
+        self.pending = Some(value);
+        self.generation
+    }
+
+    pub fn pending(&self) -> Option<&T> {
+        self.pending.as_ref()
+    }
+
+    /// The value to write, if `generation` is still the newest one staged
+    pub fn due(&self, generation: u64) -> Option<T> {
+        if generation == self.generation {
+            self.pending.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Forget the value once written, unless a newer one was staged meanwhile
+    pub fn written(&mut self, generation: u64) {
+        if generation == self.generation {
+            self.pending = None;
+        }
+    }
+
+    /// Take whatever is pending, regardless of generation
+    pub fn take(&mut self) -> Option<T> {
+        self.pending.take()
+    }
+}
+
+/// Wait `delay`, then `write` the value staged as `generation` unless a newer
+/// one has been staged since (its own flush will write it)
+pub async fn flush_after<T, W>(debouncer: &Mutex<Debouncer<T>>, generation: u64, delay: Duration, write: W)
+where
+    T: Clone,
+    W: FnOnce(T),
+{
+    tokio::time::sleep(delay).await;
+
+    let _write = WRITE_LOCK.lock().unwrap();
+    let Some(value) = debouncer.lock().unwrap().due(generation) else {
+        return;
+    };
+    write(value);
+    debouncer.lock().unwrap().written(generation);
+}
+
+/// Settings saved but not written yet
+pub fn pending() -> Option<AppSettings> {
+    PENDING.lock().unwrap().pending().cloned()
+}
+
+/// Stage `settings` and write them once no newer save arrives for `SAVE_DEBOUNCE`
+pub fn save(settings: AppSettings) {
+    let generation = PENDING.lock().unwrap().stage(settings);
+    tauri::async_runtime::spawn(async move {
+        flush_after(&PENDING, generation, SAVE_DEBOUNCE, |settings| {
+            if let Err(e) = write(settings) {
+                crate::logs::error(&format!("[Settings] {}", e));
+            }
+        })
+        .await;
+    });
+}
+
+/// Drop unwritten settings; used before the whole config is replaced
+pub fn discard_pending() {
+    PENDING.lock().unwrap().take();
+}
+
+/// Write unwritten settings now (on exit)
+pub fn flush_pending() {
+    let _write = WRITE_LOCK.lock().unwrap();
+    if let Some(settings) = PENDING.lock().unwrap().take() {
+        if let Err(e) = write(settings) {
+            crate::logs::error(&format!("[Settings] {}", e));
+        }
+    }
+}
+
+fn write(settings: AppSettings) -> Result<(), String> {
+    crate::config::Config::update(|config| {
+        config.settings = Some(settings);
+        Ok(())
+    })
+}
+
+/// New login item state if `launch_at_startup` changed
+pub fn login_item_change(previous: &AppSettings, next: &AppSettings) -> Option<bool> {
+    (previous.launch_at_startup != next.launch_at_startup).then_some(next.launch_at_startup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rapid_saves_coalesce_into_one_write() {
+        let debouncer = Mutex::new(Debouncer::default());
+        let writes = Mutex::new(Vec::new());
+
+        // Every save is staged before any quiet period has passed
+        let mut generations = Vec::new();
+        for value in 1..=5 {
+            generations.push(debouncer.lock().unwrap().stage(value));
+            // Unwritten values are still readable
+            assert_eq!(debouncer.lock().unwrap().pending(), Some(&value));
+        }
+        // The flushes of superseded saves write nothing, whatever order they run in
+        for generation in generations.into_iter().rev() {
+            flush_after(&debouncer, generation, Duration::ZERO, |value| {
+                writes.lock().unwrap().push(value)
+            })
+            .await;
+        }
+
+        assert_eq!(*writes.lock().unwrap(), vec![5]);
+        assert_eq!(debouncer.lock().unwrap().pending(), None);
+
+        // A later save is written on its own
+        let generation = debouncer.lock().unwrap().stage(6);
+        flush_after(&debouncer, generation, Duration::ZERO, |value| {
+            writes.lock().unwrap().push(value)
+        })
+        .await;
+        assert_eq!(*writes.lock().unwrap(), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_login_item_changes_only_on_change() {
+        let off = AppSettings {
+            launch_at_startup: false,
+            ..AppSettings::default()
+        };
+        let on = AppSettings {
+            launch_at_startup: true,
+            ..AppSettings::default()
+        };
+        let other_change = AppSettings {
+            sync_interval: on.sync_interval + 1,
+            ..on.clone()
+        };
+
+        assert_eq!(login_item_change(&off, &on), Some(true));
+        assert_eq!(login_item_change(&on, &off), Some(false));
+        assert_eq!(login_item_change(&on, &on), None);
+        assert_eq!(login_item_change(&on, &other_change), None);
+    }
+}
@@ -0,0 +1,228 @@
+// Event sink module
+// Destinations the sync service can deliver events to: the Observer server,
+// a generic webhook, a local JSONL file or stdout
+
+use crate::collector::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+/// Boxed future returned by sinks (keeps the trait object-safe)
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<String>, String>> + Send + 'a>>;
+
+/// A destination for collected events
+pub trait EventSink: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Body this sink sends for `events` - the filtered sync payload by default
+    fn payload(&self, events: &[Event]) -> serde_json::Value {
+        crate::sync::build_sync_payload(events)
+    }
+
+    /// Deliver events, returning the IDs that were accepted
+    /// Accepted events leave the buffer and are marked synced in the database
+    fn send<'a>(&'a self, events: &'a [Event]) -> SinkFuture<'a>;
+}
+
+/// Sink selection, stored as the `sink` setting
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Observer server (`/api/v1/events`)
+    #[default]
+    Observer,
+    /// POST events as JSON to an arbitrary URL
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Append events to a JSONL file
+    File { path: PathBuf },
+    /// Print events as JSONL to stdout
+    Stdout,
+}
+
+/// Build the sink for a config
+pub fn create_sink(config: &SinkConfig) -> Box<dyn EventSink> {
+    match config {
+        SinkConfig::Observer => Box::new(ObserverSink),
+        SinkConfig::Webhook { url, headers } => Box::new(WebhookSink {
+            url: url.clone(),
+            headers: headers.clone(),
+        }),
+        SinkConfig::File { path } => Box::new(FileSink { path: Some(path.clone()) }),
+        SinkConfig::Stdout => Box::new(FileSink { path: None }),
+    }
+}
+
+/// All event IDs (sinks without per-event ACKs accept everything)
+fn all_ids(events: &[Event]) -> Vec<String> {
+    events.iter().map(|e| e.id.clone()).collect()
+}
+
+/// Observer server sink (the original sync path)
+pub struct ObserverSink;
+
+impl EventSink for ObserverSink {
+    fn name(&self) -> &'static str {
+        "observer"
+    }
+
+    fn send<'a>(&'a self, events: &'a [Event]) -> SinkFuture<'a> {
+        Box::pin(async move {
+            crate::sync::sync_events(events)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Generic webhook sink
+pub struct WebhookSink {
+    pub url: String,
+    pub headers: HashMap<String, String>,
+}
+
+impl EventSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send<'a>(&'a self, events: &'a [Event]) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let url = crate::sync::validate_url(&self.url)?;
+            let client = crate::sync::shared_http_client().map_err(|e| e.to_string())?;
+
+            let mut request = client
+                .post(url)
+                .json(&self.payload(events));
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Webhook request failed: {}", e))?;
+
+            if !response.status().is_success() {
+                return Err(format!("Webhook returned status: {}", response.status()));
+            }
+
+            Ok(all_ids(events))
+        })
+    }
+}
+
+/// JSONL sink - appends one event per line to a file, or stdout when no path is set
+pub struct FileSink {
+    pub path: Option<PathBuf>,
+}
+
+impl FileSink {
+    fn write_lines(&self, events: &[Event]) -> Result<(), String> {
+        let mut lines = String::new();
+        for event in self.payload(events).as_array().into_iter().flatten() {
+            lines.push_str(&event.to_string());
+            lines.push('\n');
+        }
+
+        match &self.path {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create sink directory: {}", e))?;
+                }
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Failed to open sink file: {}", e))?;
+                file.write_all(lines.as_bytes())
+                    .map_err(|e| format!("Failed to write sink file: {}", e))
+            }
+            None => {
+                print!("{}", lines);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl EventSink for FileSink {
+    fn name(&self) -> &'static str {
+        match self.path {
+            Some(_) => "file",
+            None => "stdout",
+        }
+    }
+
+    /// The events of the filtered sync payload, each written as one line
+    fn payload(&self, events: &[Event]) -> serde_json::Value {
+        crate::sync::build_sync_payload(events)["events"].take()
+    }
+
+    fn send<'a>(&'a self, events: &'a [Event]) -> SinkFuture<'a> {
+        Box::pin(async move {
+            self.write_lines(events)?;
+            Ok(all_ids(events))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_sink_writes_jsonl() {
+        let path = std::env::temp_dir().join(format!("observer_sink_{}.jsonl", uuid::Uuid::new_v4()));
+        let sink = FileSink { path: Some(path.clone()) };
+
+        let mut first = Event::new("app_focus", Some("Code".to_string()), Some("main.rs".to_string()));
+        first.typed_text = Some("secret".to_string());
+        first.screenshot_path = Some("/tmp/shot.jpg".to_string());
+        let second = Event::new("app_focus", Some("Safari".to_string()), None);
+
+        let acked = sink.send(&[first.clone()]).await.unwrap();
+        assert_eq!(acked, vec![first.id.clone()]);
+        sink.send(&[second.clone()]).await.unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let parsed: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed["event_id"], first.id.as_str());
+        assert_eq!(parsed["window_title"], "main.rs");
+        // Local-only content never reaches the sink
+        assert!(parsed.get("typed_text").is_none());
+        assert!(parsed.get("screenshot_path").is_none());
+        let parsed: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(parsed["event_id"], second.id.as_str());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_sink_selection_from_config() {
+        let config: SinkConfig = serde_json::from_str(r#"{"type": "file", "path": "/tmp/events.jsonl"}"#).unwrap();
+        assert_eq!(create_sink(&config).name(), "file");
+
+        let config: SinkConfig = serde_json::from_str(
+            r#"{"type": "webhook", "url": "https://example.com/hook", "headers": {"Authorization": "Bearer x"}}"#,
+        )
+        .unwrap();
+        assert_eq!(create_sink(&config).name(), "webhook");
+
+        let config: SinkConfig = serde_json::from_str(r#"{"type": "stdout"}"#).unwrap();
+        assert_eq!(create_sink(&config).name(), "stdout");
+
+        assert_eq!(create_sink(&SinkConfig::default()).name(), "observer");
+    }
+}
@@ -0,0 +1,98 @@
+// Startup scheduling module
+// Staggers background services at launch and gates them on permissions/config
+
+use std::time::Duration;
+
+/// Base startup delays for background services (milliseconds)
+pub const COLLECTOR_DELAY_MS: u64 = 500;
+pub const QUEUE_DELAY_MS: u64 = 1_000;
+pub const WEBSOCKET_DELAY_MS: u64 = 2_000;
+pub const SYNC_DELAY_MS: u64 = 3_000;
+pub const UPDATE_CHECK_DELAY_MS: u64 = 10_000;
+/// After the collector's own accessibility/screen recording prompts
+pub const AUTOMATION_PREWARM_DELAY_MS: u64 = 5_000;
+
+/// Maximum random jitter added on top of each base delay
+const MAX_JITTER_MS: u64 = 500;
+
+/// How often gated services re-check their condition
+pub const GATE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Base delay plus a random jitter so services don't start in lockstep
+pub fn jittered_delay(base_ms: u64) -> Duration {
+    let jitter = (uuid::Uuid::new_v4().as_u128() % (MAX_JITTER_MS as u128 + 1)) as u64;
+    Duration::from_millis(base_ms + jitter)
+}
+
+/// Sleep for a jittered startup delay
+pub async fn stagger(base_ms: u64) {
+    tokio::time::sleep(jittered_delay(base_ms)).await;
+}
+
+/// Block until `check` returns true or `timeout` elapses
+/// Returns true if the condition was met, false on timeout
+pub async fn wait_until<F>(check: F, poll_interval: Duration, timeout: Duration) -> bool
+where
+    F: Fn() -> bool,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        if check() {
+            return true;
+        }
+
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return false;
+        }
+
+        tokio::time::sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_jittered_delay_bounds() {
+        for _ in 0..100 {
+            let delay = jittered_delay(1_000);
+            assert!(delay >= Duration::from_millis(1_000));
+            assert!(delay <= Duration::from_millis(1_000 + MAX_JITTER_MS));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_granted() {
+        let granted = Arc::new(AtomicBool::new(false));
+
+        let granted_clone = granted.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            granted_clone.store(true, Ordering::SeqCst);
+        });
+
+        let granted_check = granted.clone();
+        let result = wait_until(
+            move || granted_check.load(Ordering::SeqCst),
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+        )
+        .await;
+
+        assert!(result);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_times_out() {
+        let start = tokio::time::Instant::now();
+        let result = wait_until(|| false, Duration::from_millis(10), Duration::from_millis(100)).await;
+
+        assert!(!result);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,882 @@
+// Stats aggregation module
+// Derives sessions and active time from the collected event timeline
+
+use crate::collector::Event;
+use crate::AppState;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Gap between consecutive events after which a session is considered finished
+pub const DEFAULT_SESSION_GAP_MINUTES: i64 = 5;
+
+/// How often the local date is checked for the daily counter reset
+const DAY_ROLLOVER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Reset `events_today` when the local date changes
+/// The wall clock is polled instead of sleeping until midnight, so a machine
+/// asleep at midnight still rolls over as soon as it wakes
+pub async fn start_day_rollover(state: Arc<Mutex<AppState>>, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(DAY_ROLLOVER_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let today = chrono::Local::now().date_naive();
+        if state.lock().await.roll_over_day(today) {
+            crate::logs::info(&format!("[Stats] New day {}, events today reset", today));
+        }
+    }
+}
+
+/// A contiguous stretch of activity
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub event_count: usize,
+}
+
+impl Session {
+    fn starting_at(timestamp: DateTime<Utc>) -> Self {
+        Self {
+            start: timestamp,
+            end: timestamp,
+            event_count: 1,
+        }
+    }
+
+    /// Length of the session
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+}
+
+/// Events that start a stretch of inactivity (the machine asleep or the user idle)
+const PAUSE_START_EVENTS: &[&str] = &["system_sleep", "idle_start"];
+/// Events that end such a stretch
+const PAUSE_END_EVENTS: &[&str] = &["system_wake", "idle_end"];
+
+/// Split the event timeline into sessions
+///
+/// A session ends when the gap to the next event exceeds `max_gap`, when the
+/// machine goes to sleep or when the user goes idle. The time between
+/// `system_sleep` and `system_wake`, or `idle_start` and `idle_end`, is never
+/// counted as part of a session.
+pub fn compute_sessions(events: &[Event], max_gap: Duration) -> Vec<Session> {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut sessions = Vec::new();
+    let mut current: Option<Session> = None;
+    let mut paused = false;
+
+    for event in sorted {
+        match event.event_type.as_str() {
+            pause if PAUSE_START_EVENTS.contains(&pause) => {
+                // Close the running session at the moment the machine slept or the user left
+                if let Some(mut session) = current.take() {
+                    session.end = event.timestamp;
+                    session.event_count += 1;
+                    sessions.push(session);
+                }
+                paused = true;
+            }
+            resume if paused && PAUSE_END_EVENTS.contains(&resume) => {
+                paused = false;
+                current = Some(Session::starting_at(event.timestamp));
+            }
+            _ if paused => {
+                // Stray events recorded while asleep or idle are ignored
+            }
+            _ => match current.as_mut() {
+                Some(session) if event.timestamp.signed_duration_since(session.end) <= max_gap => {
+                    session.end = event.timestamp;
+                    session.event_count += 1;
+                }
+                _ => {
+                    if let Some(session) = current.take() {
+                        sessions.push(session);
+                    }
+                    current = Some(Session::starting_at(event.timestamp));
+                }
+            },
+        }
+    }
+
+    if let Some(session) = current {
+        sessions.push(session);
+    }
+
+    sessions
+}
+
+/// Active time attributed to each event: the gap until the next event,
+/// as long as it doesn't cross a session boundary (long gap, sleep or idle)
+fn attributed_durations(events: &[Event], max_gap: Duration) -> Vec<(&Event, Duration)> {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut result = Vec::new();
+    let mut paused = false;
+
+    for pair in sorted.windows(2) {
+        let (event, next) = (pair[0], pair[1]);
+        let event_type = event.event_type.as_str();
+        if PAUSE_START_EVENTS.contains(&event_type) {
+            paused = true;
+        } else if PAUSE_END_EVENTS.contains(&event_type) {
+            paused = false;
+        }
+        if paused {
+            continue;
+        }
+
+        let gap = next.timestamp.signed_duration_since(event.timestamp);
+        if gap <= max_gap {
+            result.push((event, gap));
+        }
+    }
+
+    result
+}
+
+/// Time spent in a single app
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AppUsage {
+    pub app_name: String,
+    pub seconds: i64,
+}
+
+/// Share of active time in a category
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CategoryShare {
+    pub category: String,
+    pub seconds: i64,
+    pub percent: f64,
+}
+
+/// Summary of one local day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailySummary {
+    pub date: String,
+    pub total_active_seconds: i64,
+    pub session_count: usize,
+    pub top_apps: Vec<AppUsage>,
+    pub categories: Vec<CategoryShare>,
+    pub context_switches: u32,
+    /// Local hour (0-23) with the most active time
+    pub busiest_hour: Option<u32>,
+}
+
+/// Number of apps listed in the daily summary
+pub const TOP_APPS_LIMIT: usize = 5;
+
+/// Summarize the events of `date` (a day in timezone `tz`)
+pub fn daily_summary<Tz: TimeZone>(events: &[Event], date: NaiveDate, tz: &Tz) -> DailySummary {
+    let day_events: Vec<Event> = events
+        .iter()
+        .filter(|e| e.timestamp.with_timezone(tz).date_naive() == date)
+        .cloned()
+        .collect();
+
+    let max_gap = Duration::minutes(DEFAULT_SESSION_GAP_MINUTES);
+    let sessions = compute_sessions(&day_events, max_gap);
+    let total = total_active_duration(&sessions);
+
+    let mut app_time: HashMap<String, i64> = HashMap::new();
+    let mut hour_time: HashMap<u32, i64> = HashMap::new();
+
+    let durations = attributed_durations(&day_events, max_gap);
+    for (event, duration) in &durations {
+        let seconds = duration.num_seconds();
+        if let Some(ref app_name) = event.app_name {
+            *app_time.entry(app_name.clone()).or_insert(0) += seconds;
+        }
+        let hour = event.timestamp.with_timezone(tz).hour();
+        *hour_time.entry(hour).or_insert(0) += seconds;
+    }
+
+    let mut top_apps: Vec<AppUsage> = app_time
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0)
+        .map(|(app_name, seconds)| AppUsage { app_name, seconds })
+        .collect();
+    top_apps.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.app_name.cmp(&b.app_name)));
+    top_apps.truncate(TOP_APPS_LIMIT);
+
+    let busiest_hour = hour_time
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(hour, _)| hour);
+
+    DailySummary {
+        date: date.format("%Y-%m-%d").to_string(),
+        total_active_seconds: total.num_seconds(),
+        session_count: sessions.len(),
+        top_apps,
+        categories: category_shares(&durations, Duration::zero()),
+        context_switches: count_context_switches(&day_events),
+        busiest_hour,
+    }
+}
+
+/// Split attributed time by category, largest first
+/// A non-zero `idle` time is reported as an extra "idle" slice
+fn category_shares(durations: &[(&Event, Duration)], idle: Duration) -> Vec<CategoryShare> {
+    let mut category_time: HashMap<String, i64> = HashMap::new();
+    for (event, duration) in durations {
+        let category = event.category.clone().unwrap_or_else(|| "other".to_string());
+        *category_time.entry(category).or_insert(0) += duration.num_seconds();
+    }
+    if idle > Duration::zero() {
+        *category_time.entry("idle".to_string()).or_insert(0) += idle.num_seconds();
+    }
+
+    let category_total: i64 = category_time.values().sum();
+    let mut categories: Vec<CategoryShare> = category_time
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0)
+        .map(|(category, seconds)| CategoryShare {
+            category,
+            seconds,
+            percent: seconds as f64 * 100.0 / category_total as f64,
+        })
+        .collect();
+    categories.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.category.cmp(&b.category)));
+    categories
+}
+
+/// Total time between each `idle_start` and the `idle_end` that follows it
+/// An idle stretch interrupted by sleep only counts up to the `system_sleep`.
+fn idle_duration(events: &[Event]) -> Duration {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut total = Duration::zero();
+    let mut idle_since: Option<DateTime<Utc>> = None;
+    for event in sorted {
+        match event.event_type.as_str() {
+            "idle_start" => idle_since = idle_since.or(Some(event.timestamp)),
+            "idle_end" | "system_sleep" => {
+                if let Some(since) = idle_since.take() {
+                    total = total + event.timestamp.signed_duration_since(since);
+                }
+            }
+            _ => {}
+        }
+    }
+    total
+}
+
+/// Focused time per category for the local days `from..=to` (timezone `tz`),
+/// plus an "idle" slice for the time between `idle_start` and `idle_end`
+pub fn category_breakdown<Tz: TimeZone>(
+    events: &[Event],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Vec<CategoryShare> {
+    let range_events: Vec<Event> = events
+        .iter()
+        .filter(|e| {
+            let date = e.timestamp.with_timezone(tz).date_naive();
+            date >= from && date <= to
+        })
+        .cloned()
+        .collect();
+
+    let max_gap = Duration::minutes(DEFAULT_SESSION_GAP_MINUTES);
+    category_shares(&attributed_durations(&range_events, max_gap), idle_duration(&range_events))
+}
+
+/// UTC bounds `[start, end)` of the local day `date` in timezone `tz`
+pub fn local_day_bounds<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start_of = |day: NaiveDate| {
+        let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        // Midnight can fall in a DST gap; fall back to treating it as UTC
+        tz.from_local_datetime(&midnight)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+    };
+    let next_day = date.succ_opt().unwrap_or(date);
+    (start_of(date), start_of(next_day))
+}
+
+/// Number of events in each local hour (0-23) of `date`
+///
+/// Always 24 buckets: the current hour counts the events so far and later hours
+/// stay at zero. On DST days a repeated hour shares one bucket and a skipped hour
+/// stays empty.
+pub fn hourly_activity<Tz: TimeZone>(
+    timestamps: &[DateTime<Utc>],
+    date: NaiveDate,
+    tz: &Tz,
+) -> Vec<u32> {
+    let mut buckets = vec![0u32; 24];
+    for timestamp in timestamps {
+        let local = timestamp.with_timezone(tz);
+        if local.date_naive() == date {
+            buckets[local.hour() as usize] += 1;
+        }
+    }
+    buckets
+}
+
+/// Visits to one site
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DomainVisits {
+    pub domain: String,
+    /// Events recorded on the site
+    pub visits: u32,
+    /// Active time attributed to those events
+    pub seconds: i64,
+}
+
+/// Second-level labels under which registrations happen one level deeper (example.co.uk)
+const SECOND_LEVEL_LABELS: &[&str] = &["co", "com", "org", "net", "ac", "gov", "edu"];
+
+/// Lowercased host of an http(s) URL without "www.", optionally reduced to the
+/// registrable domain. None for redacted, internal (about:, file:) or malformed URLs.
+pub fn url_domain(url: &str, strip_subdomains: bool) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = match parsed.host()? {
+        url::Host::Domain(domain) => domain.to_lowercase(),
+        // IP addresses have no subdomains
+        ip => return Some(ip.to_string()),
+    };
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    if !strip_subdomains {
+        return Some(host);
+    }
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let keep = match labels.as_slice() {
+        [.., second, top] if top.len() == 2 && SECOND_LEVEL_LABELS.contains(second) => 3,
+        _ => 2,
+    };
+    Some(labels[labels.len().saturating_sub(keep)..].join("."))
+}
+
+/// Most visited sites by number of events, with the time spent on each
+pub fn top_domains(events: &[Event], limit: usize, strip_subdomains: bool) -> Vec<DomainVisits> {
+    let mut by_domain: HashMap<String, DomainVisits> = HashMap::new();
+    let domain_of = |event: &Event| {
+        event
+            .url
+            .as_deref()
+            .and_then(|url| url_domain(url, strip_subdomains))
+    };
+
+    for event in events {
+        if let Some(domain) = domain_of(event) {
+            by_domain
+                .entry(domain.clone())
+                .or_insert(DomainVisits {
+                    domain,
+                    visits: 0,
+                    seconds: 0,
+                })
+                .visits += 1;
+        }
+    }
+
+    let max_gap = Duration::minutes(DEFAULT_SESSION_GAP_MINUTES);
+    for (event, duration) in attributed_durations(events, max_gap) {
+        if let Some(visits) = domain_of(event).and_then(|domain| by_domain.get_mut(&domain)) {
+            visits.seconds += duration.num_seconds();
+        }
+    }
+
+    let mut domains: Vec<DomainVisits> = by_domain.into_values().collect();
+    domains.sort_by(|a, b| {
+        b.visits
+            .cmp(&a.visits)
+            .then(b.seconds.cmp(&a.seconds))
+            .then(a.domain.cmp(&b.domain))
+    });
+    domains.truncate(limit);
+    domains
+}
+
+/// Count switches between different apps
+pub fn count_context_switches(events: &[Event]) -> u32 {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut last_app: Option<&str> = None;
+    let mut switches = 0;
+    for event in sorted {
+        if event.event_type != "app_focus" {
+            continue;
+        }
+        let app = event.app_name.as_deref();
+        if last_app.is_some() && app != last_app {
+            switches += 1;
+        }
+        last_app = app;
+    }
+
+    switches
+}
+
+/// How often focus moved from one app straight to another
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Transition {
+    pub from_app: String,
+    pub to_app: String,
+    pub count: u32,
+}
+
+/// Most frequent app-to-app focus transitions, most common first
+/// Consecutive `app_focus` events on the same app don't count as a transition
+pub fn app_transitions(events: &[Event], limit: usize) -> Vec<Transition> {
+    let mut sorted: Vec<&Event> = events
+        .iter()
+        .filter(|e| e.event_type == "app_focus" && e.app_name.is_some())
+        .collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut counts: HashMap<(&str, &str), u32> = HashMap::new();
+    for pair in sorted.windows(2) {
+        let (from, to) = (pair[0].app_name.as_deref(), pair[1].app_name.as_deref());
+        if let (Some(from), Some(to)) = (from, to) {
+            if from != to {
+                *counts.entry((from, to)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut transitions: Vec<Transition> = counts
+        .into_iter()
+        .map(|((from_app, to_app), count)| Transition {
+            from_app: from_app.to_string(),
+            to_app: to_app.to_string(),
+            count,
+        })
+        .collect();
+    transitions.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.from_app.cmp(&b.from_app))
+            .then_with(|| a.to_app.cmp(&b.to_app))
+    });
+    transitions.truncate(limit);
+    transitions
+}
+
+/// Runs of consecutive days with enough active time
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Streaks {
+    /// Run ending today, or yesterday while today doesn't count yet
+    pub current_days: u32,
+    pub longest_days: u32,
+    /// Latest day with enough active time (YYYY-MM-DD)
+    pub last_active_date: Option<String>,
+}
+
+/// Streaks of days with at least `min_active` active time, as of local day `today`
+pub fn compute_streaks(
+    daily: &BTreeMap<NaiveDate, Duration>,
+    min_active: Duration,
+    today: NaiveDate,
+) -> Streaks {
+    let active_days: Vec<NaiveDate> = daily
+        .iter()
+        .filter(|(date, active)| **date <= today && **active >= min_active)
+        .map(|(date, _)| *date)
+        .collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in &active_days {
+        run = match previous {
+            Some(previous) if previous.succ_opt() == Some(*date) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*date);
+    }
+
+    // The run still going: its last day is today, or yesterday if today isn't done
+    let last_active = active_days.last().copied();
+    let current = match last_active {
+        Some(last) if last == today || last.succ_opt() == Some(today) => run,
+        _ => 0,
+    };
+
+    Streaks {
+        current_days: current,
+        longest_days: longest,
+        last_active_date: last_active.map(|date| date.format("%Y-%m-%d").to_string()),
+    }
+}
+
+/// Total active time across sessions
+pub fn total_active_duration(sessions: &[Session]) -> Duration {
+    sessions
+        .iter()
+        .fold(Duration::zero(), |total, s| total + s.duration())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event_at(event_type: &str, hour: u32, minute: u32) -> Event {
+        let mut event = Event::new(event_type, Some("Code".to_string()), None);
+        event.timestamp = Utc.with_ymd_and_hms(2026, 1, 8, hour, minute, 0).unwrap();
+        event
+    }
+
+    fn focus_at(app: &str, category: &str, hour: u32, minute: u32) -> Event {
+        let mut event = event_at("app_focus", hour, minute).with_category(category);
+        event.app_name = Some(app.to_string());
+        event
+    }
+
+    #[test]
+    fn test_hourly_activity_buckets_by_local_hour() {
+        let tz = chrono::FixedOffset::east_opt(3 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let at = |day: u32, hour: u32, minute: u32| Utc.with_ymd_and_hms(2026, 1, day, hour, minute, 0).unwrap();
+
+        let timestamps = vec![
+            // 21:30 UTC on the 7th is 00:30 local on the 8th
+            at(7, 21, 30),
+            // 06:xx UTC is 09:xx local
+            at(8, 6, 0),
+            at(8, 6, 59),
+            at(8, 7, 0),
+            // 20:59 UTC is 23:59 local; 21:00 UTC is already the 9th
+            at(8, 20, 59),
+            at(8, 21, 0),
+            // Previous local day
+            at(7, 20, 59),
+        ];
+
+        let buckets = hourly_activity(&timestamps, date, &tz);
+        assert_eq!(buckets.len(), 24);
+        assert_eq!(buckets[0], 1);
+        assert_eq!(buckets[9], 2);
+        assert_eq!(buckets[10], 1);
+        assert_eq!(buckets[23], 1);
+        assert_eq!(buckets.iter().sum::<u32>(), 5);
+
+        assert_eq!(hourly_activity(&[], date, &tz), vec![0; 24]);
+    }
+
+    #[test]
+    fn test_local_day_bounds() {
+        let tz = chrono::FixedOffset::east_opt(3 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let (start, end) = local_day_bounds(date, &tz);
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 1, 7, 21, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2026, 1, 8, 21, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_daily_summary() {
+        let mut yesterday = focus_at("Code", "coding", 23, 0);
+        yesterday.timestamp = Utc.with_ymd_and_hms(2026, 1, 7, 23, 0, 0).unwrap();
+
+        let events = vec![
+            yesterday,
+            // 09:00-09:04 coding, 09:04-09:06 browsing, 09:06-09:07 coding
+            focus_at("Code", "coding", 9, 0),
+            focus_at("Code", "coding", 9, 2),
+            focus_at("Safari", "browsing", 9, 4),
+            focus_at("Code", "coding", 9, 6),
+            focus_at("Slack", "communication", 9, 7),
+            // Two hours idle, then 14:00-14:03 writing
+            focus_at("Notes", "writing", 14, 0),
+            focus_at("Notes", "writing", 14, 3),
+        ];
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let summary = daily_summary(&events, date, &Utc);
+
+        assert_eq!(summary.date, "2026-01-08");
+        assert_eq!(summary.session_count, 2);
+        assert_eq!(summary.total_active_seconds, 10 * 60);
+        assert_eq!(
+            summary.top_apps,
+            vec![
+                AppUsage { app_name: "Code".to_string(), seconds: 5 * 60 },
+                AppUsage { app_name: "Notes".to_string(), seconds: 3 * 60 },
+                AppUsage { app_name: "Safari".to_string(), seconds: 2 * 60 },
+            ]
+        );
+        assert_eq!(summary.categories[0].category, "coding");
+        assert!((summary.categories[0].percent - 50.0).abs() < 1e-9);
+        assert!((summary.categories[1].percent - 30.0).abs() < 1e-9);
+        // Code -> Safari -> Code -> Slack -> Notes
+        assert_eq!(summary.context_switches, 4);
+        assert_eq!(summary.busiest_hour, Some(9));
+    }
+
+    #[test]
+    fn test_app_transitions() {
+        let events = vec![
+            focus_at("Code", "coding", 9, 0),
+            focus_at("Safari", "browsing", 9, 5),
+            focus_at("Code", "coding", 9, 7),
+            // Repeated focus on the same app isn't a transition
+            focus_at("Code", "coding", 9, 8),
+            focus_at("Safari", "browsing", 9, 10),
+            focus_at("Slack", "communication", 9, 12),
+            focus_at("Code", "coding", 9, 20),
+            // Only app_focus events count
+            event_at("window_title_changed", 9, 21),
+        ];
+        // Out of order input is sorted by time first
+        let mut shuffled = events.clone();
+        shuffled.reverse();
+
+        let transitions = app_transitions(&shuffled, 10);
+        let pairs: Vec<(&str, &str, u32)> = transitions
+            .iter()
+            .map(|t| (t.from_app.as_str(), t.to_app.as_str(), t.count))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Code", "Safari", 2),
+                ("Safari", "Code", 1),
+                ("Safari", "Slack", 1),
+                ("Slack", "Code", 1),
+            ]
+        );
+
+        assert_eq!(app_transitions(&events, 1).len(), 1);
+        assert!(app_transitions(&events[..1], 10).is_empty());
+    }
+
+    #[test]
+    fn test_category_breakdown() {
+        let mut outside = focus_at("Code", "coding", 10, 0);
+        outside.timestamp = Utc.with_ymd_and_hms(2026, 1, 10, 10, 0, 0).unwrap();
+
+        let events = vec![
+            // 09:00-09:06 coding, 09:06-09:09 browsing, 09:09-09:10 communication
+            focus_at("Code", "coding", 9, 0),
+            focus_at("Code", "coding", 9, 3),
+            focus_at("Safari", "browsing", 9, 6),
+            focus_at("Slack", "communication", 9, 9),
+            focus_at("Code", "coding", 9, 10),
+            // Long gap without idle tracking is not attributed to anything
+            focus_at("Code", "coding", 15, 0),
+            // 15:00-15:02 coding, idle until 15:09, then 15:09-15:10 coding
+            event_at("idle_start", 15, 2),
+            event_at("idle_end", 15, 9),
+            focus_at("Code", "coding", 15, 9),
+            focus_at("Code", "coding", 15, 10),
+            outside.clone(),
+            outside,
+        ];
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let slices = category_breakdown(&events, date, date, &Utc);
+
+        let seconds: Vec<(&str, i64)> = slices
+            .iter()
+            .map(|slice| (slice.category.as_str(), slice.seconds))
+            .collect();
+        assert_eq!(
+            seconds,
+            vec![("coding", 540), ("idle", 420), ("browsing", 180), ("communication", 60)]
+        );
+
+        let total_percent: f64 = slices.iter().map(|slice| slice.percent).sum();
+        assert!((total_percent - 100.0).abs() < 1e-6);
+        assert!((slices[0].percent - 45.0).abs() < 1e-9);
+        assert!((slices[1].percent - 35.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_daily_summary_empty_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 9).unwrap();
+        let summary = daily_summary(&[focus_at("Code", "coding", 9, 0)], date, &Utc);
+
+        assert_eq!(summary.total_active_seconds, 0);
+        assert!(summary.top_apps.is_empty());
+        assert_eq!(summary.busiest_hour, None);
+    }
+
+    #[test]
+    fn test_streaks_across_days() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+        // Active minutes per day of January
+        let daily: BTreeMap<NaiveDate, Duration> = [
+            // Jan 1-3: a three-day run
+            (1, 40),
+            (2, 30),
+            (3, 45),
+            // Jan 4: too short to count, which breaks the run
+            (4, 10),
+            // Jan 5-6, then a day off on the 7th
+            (5, 60),
+            (6, 30),
+            // Jan 8-9
+            (8, 30),
+            (9, 35),
+        ]
+        .into_iter()
+        .map(|(day, minutes)| (date(day), Duration::minutes(minutes)))
+        .collect();
+        let min_active = Duration::minutes(30);
+
+        // On the 9th the Jan 8-9 run is current; Jan 1-3 is the longest
+        let streaks = compute_streaks(&daily, min_active, date(9));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current_days: 2,
+                longest_days: 3,
+                last_active_date: Some("2026-01-09".to_string()),
+            }
+        );
+        // Today not reached yet: the streak through yesterday still counts
+        assert_eq!(compute_streaks(&daily, min_active, date(10)).current_days, 2);
+        // A whole day without enough activity ends it
+        assert_eq!(compute_streaks(&daily, min_active, date(11)).current_days, 0);
+        assert_eq!(compute_streaks(&daily, min_active, date(11)).longest_days, 3);
+        // Looking back from the 6th, later days are ignored
+        let streaks = compute_streaks(&daily, min_active, date(6));
+        assert_eq!((streaks.current_days, streaks.longest_days), (2, 3));
+        assert_eq!(streaks.last_active_date.as_deref(), Some("2026-01-06"));
+
+        // A lower bar joins Jan 1-6 into one run
+        assert_eq!(compute_streaks(&daily, Duration::minutes(10), date(6)).longest_days, 6);
+        assert_eq!(
+            compute_streaks(&BTreeMap::new(), min_active, date(9)),
+            Streaks { current_days: 0, longest_days: 0, last_active_date: None }
+        );
+    }
+
+    #[test]
+    fn test_sessions_split_on_gap() {
+        let events = vec![
+            event_at("app_focus", 10, 0),
+            event_at("app_focus", 10, 3),
+            event_at("app_focus", 11, 0),
+        ];
+
+        let sessions = compute_sessions(&events, Duration::minutes(DEFAULT_SESSION_GAP_MINUTES));
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].duration(), Duration::minutes(3));
+        assert_eq!(sessions[1].duration(), Duration::zero());
+    }
+
+    #[test]
+    fn test_sleep_wake_gap_is_excluded() {
+        let events = vec![
+            event_at("app_focus", 10, 0),
+            event_at("app_focus", 10, 1),
+            event_at("system_sleep", 10, 2),
+            event_at("system_wake", 12, 0),
+            event_at("app_focus", 12, 1),
+        ];
+
+        // Even with a huge allowed gap, sleep must end the session
+        let sessions = compute_sessions(&events, Duration::hours(24));
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].end, events[2].timestamp);
+        assert_eq!(sessions[1].start, events[3].timestamp);
+        assert_eq!(total_active_duration(&sessions), Duration::minutes(3));
+    }
+
+    #[test]
+    fn test_idle_gap_is_excluded() {
+        let events = vec![
+            focus_at("Code", "coding", 10, 0),
+            focus_at("Code", "coding", 10, 1),
+            // Idle for four minutes: shorter than the session gap, still not active time
+            event_at("idle_start", 10, 2),
+            event_at("idle_end", 10, 6),
+            focus_at("Code", "coding", 10, 7),
+        ];
+
+        let sessions = compute_sessions(&events, Duration::minutes(DEFAULT_SESSION_GAP_MINUTES));
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].end, events[2].timestamp);
+        assert_eq!(sessions[1].start, events[3].timestamp);
+        assert_eq!(total_active_duration(&sessions), Duration::minutes(3));
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        let summary = daily_summary(&events, date, &Utc);
+        assert_eq!(summary.total_active_seconds, 3 * 60);
+        assert_eq!(summary.top_apps[0].seconds, 3 * 60);
+    }
+
+    fn visit_at(url: &str, hour: u32, minute: u32) -> Event {
+        let mut event = focus_at("Google Chrome", "browsing", hour, minute);
+        event.url = Some(url.to_string());
+        event
+    }
+
+    #[test]
+    fn test_top_domains_grouped_by_host_and_ranked() {
+        let events = vec![
+            visit_at("https://github.com/o/r/pull/1", 10, 0),
+            visit_at("https://www.github.com/o/r/pull/2?tab=files", 10, 2),
+            visit_at("https://docs.rs/chrono", 10, 3),
+            visit_at("https://GitHub.com/notifications", 10, 4),
+            visit_at("https://news.ycombinator.com/", 10, 5),
+            visit_at("https://docs.rs/serde", 10, 6),
+            // Redacted and internal URLs are left out
+            visit_at("[redacted]", 10, 7),
+            visit_at("about:blank", 10, 8),
+            focus_at("Code", "coding", 10, 10),
+        ];
+
+        let top = top_domains(&events, 10, false);
+        let ranked: Vec<(&str, u32, i64)> = top
+            .iter()
+            .map(|d| (d.domain.as_str(), d.visits, d.seconds))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("github.com", 3, 4 * 60),
+                ("docs.rs", 2, 2 * 60),
+                ("news.ycombinator.com", 1, 60),
+            ]
+        );
+
+        assert_eq!(top_domains(&events, 1, false).len(), 1);
+    }
+
+    #[test]
+    fn test_domains_without_subdomains() {
+        assert_eq!(url_domain("https://mail.google.com/u/0", true).as_deref(), Some("google.com"));
+        assert_eq!(url_domain("https://mail.google.com/u/0", false).as_deref(), Some("mail.google.com"));
+        assert_eq!(url_domain("https://www.bbc.co.uk/news", true).as_deref(), Some("bbc.co.uk"));
+        assert_eq!(url_domain("https://news.bbc.co.uk/", true).as_deref(), Some("bbc.co.uk"));
+        assert_eq!(url_domain("http://127.0.0.1:8080/", true).as_deref(), Some("127.0.0.1"));
+        assert_eq!(url_domain("http://localhost:3000/", true).as_deref(), Some("localhost"));
+        assert_eq!(url_domain("file:///Users/me/notes.txt", false), None);
+
+        let events = vec![
+            visit_at("https://mail.google.com/", 9, 0),
+            visit_at("https://docs.google.com/", 9, 1),
+            visit_at("https://github.com/", 9, 2),
+        ];
+        let top = top_domains(&events, 10, true);
+        assert_eq!(top[0].domain, "google.com");
+        assert_eq!(top[0].visits, 2);
+    }
+}
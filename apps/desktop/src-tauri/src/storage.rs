@@ -0,0 +1,548 @@
+// Storage usage module
+// Reports how much disk the database, screenshots and logs are using, projects
+// how much a day of collection adds, and stops screenshot capture while the
+// disk is nearly full
+
+use crate::collector::{cleanup_screenshot_dirs, screenshot_stats, CleanupSummary, Event};
+use crate::commands::AppSettings;
+use crate::db::{ActivitySample, EventDatabase};
+use crate::notifications::{NotificationConfig, NotificationManager, NotificationPriority};
+use crate::sink::SinkConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Default free space below which screenshot capture stops (0 disables the guard)
+pub const DEFAULT_MIN_FREE_DISK_MB: u64 = 1024;
+
+/// Extra free space needed before capture resumes, so it doesn't flap at the threshold
+const RESUME_MARGIN_MB: u64 = 256;
+
+/// How often free space is checked
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Days of screenshots kept by the cleanup that runs when space runs low,
+/// on top of today's (which is never removed)
+const LOW_DISK_RETENTION_DAYS: i64 = 0;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Days of recent history the daily footprint estimate is based on
+pub const ESTIMATE_HISTORY_DAYS: i64 = 7;
+
+/// JSON keys and punctuation around each event in a sync payload
+const SYNC_EVENT_OVERHEAD_BYTES: u64 = 120;
+
+/// Set while free space is below the threshold
+static CAPTURE_SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Disk usage breakdown in bytes, with the resolved paths
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageUsage {
+    pub database_bytes: u64,
+    pub screenshots_bytes: u64,
+    pub logs_bytes: u64,
+    pub total_bytes: u64,
+    pub database_path: String,
+    pub screenshots_path: String,
+    pub logs_path: String,
+}
+
+/// Projected data collected per day with the current settings
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DailyEstimate {
+    /// Days of history the projection is based on (0 without any events)
+    pub sample_days: f64,
+    pub events_per_day: u64,
+    pub screenshots_per_day: u64,
+    pub screenshot_bytes_per_day: u64,
+    pub ocr_text_bytes_per_day: u64,
+    pub upload_bytes_per_day: u64,
+}
+
+/// Directory where logs are written
+pub fn logs_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("observer")
+        .join("logs")
+}
+
+/// Size of the SQLite database including its WAL/journal side files
+fn database_size(db_path: &Path) -> u64 {
+    ["", "-wal", "-shm", "-journal"]
+        .iter()
+        .filter_map(|suffix| {
+            let mut path = db_path.as_os_str().to_owned();
+            path.push(suffix);
+            std::fs::metadata(PathBuf::from(path)).ok()
+        })
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Total size of all files under a directory (0 if missing)
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Compute usage for the given locations (blocking - walks directories)
+pub fn compute_usage(db_path: &Path, screenshots_path: &Path, logs_path: &Path) -> StorageUsage {
+    let database_bytes = database_size(db_path);
+    let screenshots_bytes = screenshot_stats(screenshots_path).total_size_bytes;
+    let logs_bytes = dir_size(logs_path);
+
+    StorageUsage {
+        database_bytes,
+        screenshots_bytes,
+        logs_bytes,
+        total_bytes: database_bytes + screenshots_bytes + logs_bytes,
+        database_path: db_path.to_string_lossy().to_string(),
+        screenshots_path: screenshots_path.to_string_lossy().to_string(),
+        logs_path: logs_path.to_string_lossy().to_string(),
+    }
+}
+
+/// Whether screenshot capture is stopped because the disk is nearly full
+pub fn screenshot_capture_suspended() -> bool {
+    CAPTURE_SUSPENDED.load(Ordering::Relaxed)
+}
+
+/// Change in disk space state between checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskSpaceChange {
+    Low,
+    Recovered,
+}
+
+/// Low-disk state with hysteresis: low below `min_free`, recovered above `min_free + margin`
+#[derive(Debug, Default)]
+pub struct DiskSpaceGuard {
+    low: bool,
+}
+
+impl DiskSpaceGuard {
+    pub fn is_low(&self) -> bool {
+        self.low
+    }
+
+    /// Record a free space reading against a threshold (0 = disabled)
+    pub fn record(&mut self, free_bytes: u64, min_free_mb: u64) -> Option<DiskSpaceChange> {
+        let min_free = min_free_mb.saturating_mul(BYTES_PER_MB);
+        let resume_at = min_free.saturating_add(RESUME_MARGIN_MB * BYTES_PER_MB);
+
+        if !self.low && min_free_mb > 0 && free_bytes < min_free {
+            self.low = true;
+            Some(DiskSpaceChange::Low)
+        } else if self.low && (min_free_mb == 0 || free_bytes >= resume_at) {
+            self.low = false;
+            Some(DiskSpaceChange::Recovered)
+        } else {
+            None
+        }
+    }
+}
+
+/// Free bytes on the disk holding `path`, given (mount point, available bytes) pairs
+/// The most specific mount point wins
+pub fn free_space_on(path: &Path, disks: &[(PathBuf, u64)]) -> Option<u64> {
+    disks
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, available)| *available)
+}
+
+/// Free bytes on the disk holding `path` (blocking)
+fn free_space(path: &Path) -> Option<u64> {
+    let disks: Vec<(PathBuf, u64)> = sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|disk| (disk.mount_point().to_path_buf(), disk.available_space()))
+        .collect();
+    free_space_on(path, &disks)
+}
+
+fn low_disk_notification(free_bytes: u64) -> NotificationConfig {
+    NotificationConfig {
+        title: "Low disk space".to_string(),
+        body: format!(
+            "Only {} MB free. Screenshots are paused and older ones were cleaned up; \
+            activity tracking continues.",
+            free_bytes / BYTES_PER_MB
+        ),
+        priority: NotificationPriority::High,
+        action: None,
+    }
+}
+
+/// Check free space periodically; stop screenshot capture and clean up old
+/// screenshots while it is low, and resume once space is recovered
+pub async fn start_disk_monitor(app: tauri::AppHandle, shutdown: CancellationToken) {
+    let notifications = NotificationManager::new();
+    let mut guard = DiskSpaceGuard::default();
+    let mut interval = tokio::time::interval(DISK_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let min_free_mb = crate::commands::get_settings()
+            .map(|settings| settings.min_free_disk_mb)
+            .unwrap_or(DEFAULT_MIN_FREE_DISK_MB);
+        let screenshots_path = crate::collector::live_screenshot_config().storage_path;
+        let free = tokio::task::spawn_blocking(move || free_space(&screenshots_path))
+            .await
+            .ok()
+            .flatten();
+        let Some(free) = free else {
+            continue;
+        };
+
+        match guard.record(free, min_free_mb) {
+            Some(DiskSpaceChange::Low) => {
+                CAPTURE_SUSPENDED.store(true, Ordering::Relaxed);
+                crate::logs::error(&format!(
+                    "[Storage] Low disk space ({} MB free), pausing screenshots",
+                    free / BYTES_PER_MB
+                ));
+
+                if let Err(e) = cleanup_screenshots(Some(LOW_DISK_RETENTION_DAYS), None).await {
+                    crate::logs::error(&format!("[Storage] Screenshot cleanup failed: {}", e));
+                }
+
+                if let Err(e) = notifications.send_if_allowed(&app, low_disk_notification(free)) {
+                    crate::logs::error(&format!("[Storage] {}", e));
+                }
+            }
+            Some(DiskSpaceChange::Recovered) => {
+                CAPTURE_SUSPENDED.store(false, Ordering::Relaxed);
+                crate::logs::info(&format!(
+                    "[Storage] Disk space recovered ({} MB free), resuming screenshots",
+                    free / BYTES_PER_MB
+                ));
+            }
+            None => {}
+        }
+    }
+}
+
+/// Project a day of collection from `sample`, where `screenshot_sizes` are the
+/// sizes of the sample's screenshots still on disk, adjusted for the current settings
+pub fn estimate_daily(sample: &ActivitySample, screenshot_sizes: &[u64], settings: &AppSettings) -> DailyEstimate {
+    if sample.events == 0 {
+        return DailyEstimate {
+            sample_days: 0.0,
+            events_per_day: 0,
+            screenshots_per_day: 0,
+            screenshot_bytes_per_day: 0,
+            ocr_text_bytes_per_day: 0,
+            upload_bytes_per_day: 0,
+        };
+    }
+
+    // Less than a day of history still counts as a day, so a fresh install isn't extrapolated wildly
+    let days = sample.span_days.clamp(1.0, ESTIMATE_HISTORY_DAYS as f64);
+    let per_day = |total: u64| (total as f64 / days).round() as u64;
+    let events_per_day = per_day(sample.events);
+
+    let (screenshots_per_day, screenshot_bytes_per_day, ocr_text_bytes_per_day) = if settings.compact_mode {
+        (0, 0, 0)
+    } else {
+        let screenshots_per_day = per_day(sample.screenshot_paths.len() as u64);
+        let average_size = if screenshot_sizes.is_empty() {
+            0
+        } else {
+            screenshot_sizes.iter().sum::<u64>() / screenshot_sizes.len() as u64
+        };
+        (
+            screenshots_per_day,
+            screenshots_per_day * average_size,
+            per_day(sample.ocr_chars),
+        )
+    };
+
+    let upload_bytes_per_event = if settings.compact_mode {
+        compact_event_sync_bytes()
+    } else {
+        sample.sync_chars / sample.events + SYNC_EVENT_OVERHEAD_BYTES
+    };
+    let upload_bytes_per_day = match settings.sink {
+        // Local sinks never upload
+        SinkConfig::File { .. } | SinkConfig::Stdout => 0,
+        SinkConfig::Observer | SinkConfig::Webhook { .. } => events_per_day * upload_bytes_per_event,
+    };
+
+    DailyEstimate {
+        sample_days: days,
+        events_per_day,
+        screenshots_per_day,
+        screenshot_bytes_per_day,
+        ocr_text_bytes_per_day,
+        upload_bytes_per_day,
+    }
+}
+
+/// Sync size of one compact-mode event
+fn compact_event_sync_bytes() -> u64 {
+    let event = Event::compact("app_focus", "Application", "productivity");
+    crate::sync::build_sync_payload(&[event])["events"][0].to_string().len() as u64
+}
+
+/// Estimate daily collection from the last `ESTIMATE_HISTORY_DAYS` of events
+pub async fn estimate_daily_footprint(db: Arc<EventDatabase>) -> Result<DailyEstimate, String> {
+    let settings = crate::commands::get_settings().unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let now = chrono::Utc::now();
+        let sample = db
+            .activity_sample(now - chrono::Duration::days(ESTIMATE_HISTORY_DAYS), now)
+            .map_err(|e| format!("Failed to load recent events: {}", e))?;
+        let screenshot_sizes: Vec<u64> = sample
+            .screenshot_paths
+            .iter()
+            .filter_map(|path| std::fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .collect();
+        Ok(estimate_daily(&sample, &screenshot_sizes, &settings))
+    })
+    .await
+    .map_err(|e| format!("Failed to estimate daily footprint: {}", e))?
+}
+
+/// Clean up screenshots now: those older than `older_than_days`, then the
+/// oldest until `target_free_mb` is free. Without either, the configured
+/// retention applies.
+pub async fn cleanup_screenshots(
+    older_than_days: Option<i64>,
+    target_free_mb: Option<u64>,
+) -> Result<CleanupSummary, String> {
+    if older_than_days.is_some_and(|days| days < 0) {
+        return Err("olderThanDays cannot be negative".to_string());
+    }
+
+    let config = crate::collector::live_screenshot_config();
+    tokio::task::spawn_blocking(move || {
+        let older_than_days = match (older_than_days, target_free_mb) {
+            (None, None) => Some(config.retention_days),
+            (days, _) => days,
+        };
+        let free_bytes = match target_free_mb {
+            Some(_) => free_space(&config.storage_path).ok_or("Failed to read free disk space")?,
+            None => 0,
+        };
+
+        let summary = cleanup_screenshot_dirs(
+            &config.storage_path,
+            chrono::Utc::now().date_naive(),
+            older_than_days,
+            free_bytes,
+            target_free_mb.map(|mb| mb.saturating_mul(BYTES_PER_MB)),
+        )?;
+        crate::logs::info(&format!(
+            "[Storage] Cleanup removed {} screenshot directories ({} MB)",
+            summary.directories_removed,
+            summary.bytes_freed / BYTES_PER_MB
+        ));
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Failed to clean up screenshots: {}", e))?
+}
+
+/// Compute usage for the default locations and the collector's screenshot directory
+pub async fn get_storage_usage() -> Result<StorageUsage, String> {
+    let screenshots_path = crate::collector::live_screenshot_config().storage_path;
+    tokio::task::spawn_blocking(move || {
+        compute_usage(&crate::db::EventDatabase::db_path(), &screenshots_path, &logs_dir())
+    })
+    .await
+    .map_err(|e| format!("Failed to compute storage usage: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_file(path: &Path, size: usize) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![0u8; size]).unwrap();
+    }
+
+    #[test]
+    fn test_sizes_match_created_files() {
+        let root = std::env::temp_dir().join(format!("observer_storage_{}", uuid::Uuid::new_v4()));
+        let db_path = root.join("events.db");
+        let screenshots_path = root.join("screenshots");
+        let logs_path = root.join("logs");
+
+        write_file(&db_path, 4096);
+        write_file(&root.join("events.db-wal"), 100);
+        write_file(&screenshots_path.join("2026-01-08").join("143025_abcdef12.jpg"), 1000);
+        write_file(&screenshots_path.join("2026-01-08").join("143030_12345678.jpg"), 500);
+        write_file(&logs_path.join("observer.log"), 300);
+        write_file(&logs_path.join("old").join("observer.1.log"), 200);
+
+        let usage = compute_usage(&db_path, &screenshots_path, &logs_path);
+
+        assert_eq!(usage.database_bytes, 4196);
+        assert_eq!(usage.screenshots_bytes, 1500);
+        assert_eq!(usage.logs_bytes, 500);
+        assert_eq!(usage.total_bytes, 6196);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    const MB: u64 = BYTES_PER_MB;
+
+    #[test]
+    fn test_capture_toggles_with_free_space() {
+        let mut guard = DiskSpaceGuard::default();
+        let readings = [5_000, 1_100, 900, 800, 1_100, 1_300, 1_000];
+        let changes: Vec<_> = readings
+            .iter()
+            .map(|free_mb| (guard.record(free_mb * MB, 1_024), guard.is_low()))
+            .collect();
+
+        assert_eq!(
+            changes,
+            vec![
+                (None, false),
+                (None, false),
+                (Some(DiskSpaceChange::Low), true),
+                (None, true),
+                // Above the threshold but within the resume margin
+                (None, true),
+                (Some(DiskSpaceChange::Recovered), false),
+                (Some(DiskSpaceChange::Low), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_guard() {
+        let mut guard = DiskSpaceGuard::default();
+        assert_eq!(guard.record(0, 0), None);
+
+        assert_eq!(guard.record(10 * MB, 100), Some(DiskSpaceChange::Low));
+        // Turning the guard off resumes capture right away
+        assert_eq!(guard.record(10 * MB, 0), Some(DiskSpaceChange::Recovered));
+    }
+
+    #[test]
+    fn test_free_space_uses_most_specific_mount() {
+        let disks = vec![
+            (PathBuf::from("/"), 100),
+            (PathBuf::from("/Volumes/Data"), 5),
+        ];
+        assert_eq!(free_space_on(Path::new("/Volumes/Data/observer"), &disks), Some(5));
+        assert_eq!(free_space_on(Path::new("/Users/me/observer"), &disks), Some(100));
+        assert_eq!(free_space_on(Path::new("relative"), &disks), None);
+    }
+
+    #[test]
+    fn test_missing_directories_are_empty() {
+        let root = std::env::temp_dir().join(format!("observer_storage_{}", uuid::Uuid::new_v4()));
+        assert_eq!(dir_size(&root.join("nope")), 0);
+        assert_eq!(database_size(&root.join("nope.db")), 0);
+    }
+
+    #[test]
+    fn test_daily_estimate_from_recent_history() {
+        let root = std::env::temp_dir().join(format!("observer_estimate_{}", uuid::Uuid::new_v4()));
+        let db = EventDatabase::in_memory().unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let event_at = |hours_ago: i64, screenshot: Option<(&str, usize)>| {
+            let mut event = Event::new("app_focus", Some("Code".to_string()), Some("main.rs".to_string()));
+            event.timestamp = now - chrono::Duration::hours(hours_ago);
+            if let Some((name, size)) = screenshot {
+                let path = root.join(name);
+                write_file(&path, size);
+                event.screenshot_path = Some(path.to_string_lossy().to_string());
+                event.data = serde_json::json!({ "ocr_text": "x".repeat(100) });
+            }
+            db.insert_event(&event).unwrap();
+        };
+        // Outside the history window
+        event_at(24 * 10, None);
+        event_at(48, Some(("a.jpg", 1_000)));
+        event_at(24, None);
+        event_at(23, Some(("b.jpg", 3_000)));
+        event_at(1, None);
+
+        let sample = db
+            .activity_sample(now - chrono::Duration::days(ESTIMATE_HISTORY_DAYS), now)
+            .unwrap();
+        assert_eq!(sample.events, 4);
+        assert_eq!(sample.span_days, 2.0);
+        assert_eq!(sample.ocr_chars, 200);
+        assert_eq!(sample.screenshot_paths.len(), 2);
+
+        let sizes: Vec<u64> = sample
+            .screenshot_paths
+            .iter()
+            .map(|path| std::fs::metadata(path).unwrap().len())
+            .collect();
+        let estimate = estimate_daily(&sample, &sizes, &AppSettings::default());
+        assert_eq!(estimate.sample_days, 2.0);
+        assert_eq!(estimate.events_per_day, 2);
+        assert_eq!(estimate.screenshots_per_day, 1);
+        assert_eq!(estimate.screenshot_bytes_per_day, 2_000);
+        assert_eq!(estimate.ocr_text_bytes_per_day, 100);
+        assert_eq!(
+            estimate.upload_bytes_per_day,
+            2 * (sample.sync_chars / 4 + SYNC_EVENT_OVERHEAD_BYTES)
+        );
+
+        // Compact mode drops screenshots and OCR; local sinks upload nothing
+        let compact = estimate_daily(
+            &sample,
+            &sizes,
+            &AppSettings {
+                compact_mode: true,
+                ..AppSettings::default()
+            },
+        );
+        assert_eq!(compact.events_per_day, 2);
+        assert_eq!(compact.screenshot_bytes_per_day, 0);
+        assert_eq!(compact.ocr_text_bytes_per_day, 0);
+        assert_eq!(compact.upload_bytes_per_day, 2 * compact_event_sync_bytes());
+
+        let local = estimate_daily(
+            &sample,
+            &sizes,
+            &AppSettings {
+                sink: SinkConfig::Stdout,
+                ..AppSettings::default()
+            },
+        );
+        assert_eq!(local.upload_bytes_per_day, 0);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_daily_estimate_without_history() {
+        let estimate = estimate_daily(&ActivitySample::default(), &[], &AppSettings::default());
+        assert_eq!(estimate.events_per_day, 0);
+        assert_eq!(estimate.upload_bytes_per_day, 0);
+    }
+}
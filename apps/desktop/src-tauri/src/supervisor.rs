@@ -0,0 +1,217 @@
+// Task supervisor module
+// Restarts background services (collector, sync, queue, WebSocket) that panic or
+// exit unexpectedly, and tracks a heartbeat per service for debug info
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Names of the supervised services
+pub const COLLECTOR_TASK: &str = "collector";
+pub const SYNC_TASK: &str = "sync";
+pub const QUEUE_TASK: &str = "queue";
+pub const WEBSOCKET_TASK: &str = "websocket";
+
+/// Restart backoff: starts at INITIAL_BACKOFF and doubles up to MAX_BACKOFF
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A task that ran at least this long before dying starts over at the initial backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(300);
+
+static TASKS: Lazy<RwLock<HashMap<&'static str, TaskRecord>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy)]
+struct TaskRecord {
+    last_heartbeat: Option<Instant>,
+    restarts: u32,
+}
+
+/// Health of a supervised task, reported in debug info
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    /// Seconds since the task last ticked (None if it never did)
+    pub last_heartbeat_secs_ago: Option<u64>,
+    pub restarts: u32,
+}
+
+/// Record that a task is alive (call once per loop iteration)
+pub fn heartbeat(task: &'static str) {
+    let mut tasks = TASKS.write().unwrap();
+    tasks
+        .entry(task)
+        .or_insert(TaskRecord { last_heartbeat: None, restarts: 0 })
+        .last_heartbeat = Some(Instant::now());
+}
+
+fn record_restart(task: &'static str) {
+    let mut tasks = TASKS.write().unwrap();
+    tasks
+        .entry(task)
+        .or_insert(TaskRecord { last_heartbeat: None, restarts: 0 })
+        .restarts += 1;
+}
+
+/// Health of all supervised tasks, sorted by name
+pub fn task_health() -> Vec<TaskHealth> {
+    let tasks = TASKS.read().unwrap();
+    let mut health: Vec<TaskHealth> = tasks
+        .iter()
+        .map(|(name, record)| TaskHealth {
+            name: name.to_string(),
+            last_heartbeat_secs_ago: record.last_heartbeat.map(|at| at.elapsed().as_secs()),
+            restarts: record.restarts,
+        })
+        .collect();
+    health.sort_by(|a, b| a.name.cmp(&b.name));
+    health
+}
+
+/// Restart delay bounds
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: INITIAL_BACKOFF,
+            max: MAX_BACKOFF,
+        }
+    }
+}
+
+/// Run a task, restarting it with backoff whenever it panics or returns
+/// Stops once `shutdown` is cancelled
+pub async fn supervise<F, Fut>(name: &'static str, shutdown: CancellationToken, make_task: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    supervise_with_backoff(name, shutdown, Backoff::default(), make_task).await
+}
+
+/// `supervise` with explicit backoff bounds
+pub async fn supervise_with_backoff<F, Fut>(
+    name: &'static str,
+    shutdown: CancellationToken,
+    backoff: Backoff,
+    make_task: F,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut delay = backoff.initial;
+
+    loop {
+        let started = Instant::now();
+        let outcome = tokio::spawn(make_task()).await;
+
+        if shutdown.is_cancelled() {
+            return;
+        }
+
+        match outcome {
+            Ok(()) => crate::logs::error(&format!("[Supervisor] {} exited unexpectedly", name)),
+            Err(e) if e.is_panic() => {
+                crate::logs::error(&format!("[Supervisor] {} panicked", name))
+            }
+            Err(e) => crate::logs::error(&format!("[Supervisor] {} stopped: {}", name, e)),
+        }
+
+        if started.elapsed() >= HEALTHY_RUN {
+            delay = backoff.initial;
+        }
+
+        crate::logs::info(&format!(
+            "[Supervisor] Restarting {} in {}ms",
+            name,
+            delay.as_millis()
+        ));
+        record_restart(name);
+
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = (delay * 2).min(backoff.max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    const FAST: Backoff = Backoff {
+        initial: Duration::from_millis(1),
+        max: Duration::from_millis(5),
+    };
+
+    fn restarts(name: &str) -> u32 {
+        task_health()
+            .into_iter()
+            .find(|health| health.name == name)
+            .map(|health| health.restarts)
+            .unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn test_restarts_panicking_and_returning_task() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let shutdown = CancellationToken::new();
+
+        let runs_clone = runs.clone();
+        let shutdown_clone = shutdown.clone();
+        supervise_with_backoff("test_flaky", shutdown.clone(), FAST, move || {
+            let runs = runs_clone.clone();
+            let shutdown = shutdown_clone.clone();
+            async move {
+                match runs.fetch_add(1, Ordering::SeqCst) {
+                    0 => panic!("simulated FFI failure"),
+                    1 => {} // returns unexpectedly
+                    _ => {
+                        heartbeat("test_flaky");
+                        shutdown.cancel();
+                    }
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(restarts("test_flaky"), 2);
+
+        let health = task_health();
+        let flaky = health.iter().find(|h| h.name == "test_flaky").unwrap();
+        assert_eq!(flaky.last_heartbeat_secs_ago, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_no_restart_after_shutdown() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+
+        let runs_clone = runs.clone();
+        supervise_with_backoff("test_shutdown", shutdown, FAST, move || {
+            let runs = runs_clone.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(restarts("test_shutdown"), 0);
+    }
+}
@@ -4,6 +4,7 @@
 use crate::collector::Event;
 use crate::AppState;
 use chrono::Utc;
+use once_cell::sync::Lazy;
 use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -15,35 +16,65 @@ const CONNECT_TIMEOUT_SECS: u64 = 10;
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 const MAX_RETRIES: u32 = 3;
 const INITIAL_RETRY_DELAY_MS: u64 = 1000;
+// How long the first sync waits for the user to configure the server
+const CONFIG_WAIT_SECS: u64 = 600;
+// Most persisted events picked up per sync on top of the buffer
+const DB_SYNC_BATCH_SIZE: usize = 500;
+/// Events sent per request, so a large backlog drains in several small posts
+pub const SYNC_BATCH_SIZE: usize = 500;
+/// Environment variable overriding `SYNC_BATCH_SIZE`
+const SYNC_BATCH_SIZE_ENV: &str = "OBSERVER_SYNC_BATCH_SIZE";
+/// How often synced events past the retention period are deleted
+const EVENT_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Days synced events are kept for local history by default
+pub const DEFAULT_EVENT_RETENTION_DAYS: u32 = 30;
 
-/// Get API key from environment or config file
+/// Minutes of continuous sync failure after which the user is notified,
+/// then again every `FAILURE_NOTIFY_REPEAT_MINUTES` after the last entry
+pub const FAILURE_NOTIFY_AFTER_MINUTES: &[i64] = &[5, 15, 60, 240];
+const FAILURE_NOTIFY_REPEAT_MINUTES: i64 = 240;
+// Proxy environment variables reqwest picks up when a client is built
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+];
+
+/// Ongoing run of sync failures, shown as a warning in the UI
+static CURRENT_FAILURE: std::sync::Mutex<Option<SyncFailure>> = std::sync::Mutex::new(None);
+
+/// Since when sync has been failing, and the latest error
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncFailure {
+    pub since: chrono::DateTime<Utc>,
+    pub error: String,
+}
+
+/// The ongoing sync failure, if the last attempt failed
+pub fn current_failure() -> Option<SyncFailure> {
+    CURRENT_FAILURE.lock().unwrap().clone()
+}
+
+/// Cached client together with the settings it was built with
+type ClientCache = std::sync::Mutex<Option<(ClientSettings, Arc<reqwest::Client>)>>;
+
+/// Shared HTTP client, kept across syncs so pooled keep-alive connections are reused
+static HTTP_CLIENT: Lazy<ClientCache> = Lazy::new(|| std::sync::Mutex::new(None));
+
+/// Get API key from environment, keychain or config file
 ///
 /// Priority:
 /// 1. OBSERVER_API_KEY environment variable
-/// 2. ~/.config/observer/api_key.txt config file
-/// 3. Empty string (no authentication - development only)
-fn get_api_key() -> Option<String> {
-    // 1. Environment variable
-    if let Ok(key) = std::env::var("OBSERVER_API_KEY") {
-        let key = key.trim();
-        if !key.is_empty() {
-            return Some(key.to_string());
-        }
-    }
-
-    // 2. Config file
-    if let Some(config_dir) = dirs::config_dir() {
-        let config_file = config_dir.join("observer").join("api_key.txt");
-        if let Ok(key) = std::fs::read_to_string(&config_file) {
-            let key = key.trim();
-            if !key.is_empty() {
-                return Some(key.to_string());
-            }
-        }
-    }
-
-    // 3. No API key (development mode)
-    None
+/// 2. macOS keychain
+/// 3. `api_key` in ~/.config/observer/config.json
+/// 4. None (no authentication - development only)
+pub(crate) fn get_api_key() -> Option<String> {
+    crate::credentials::current_token().map(|(key, _)| key)
 }
 
 /// Check if we're running in development mode
@@ -168,14 +199,14 @@ pub fn validate_url(url_str: &str) -> Result<Url, String> {
 ///
 /// Priority:
 /// 1. OBSERVER_SERVER_URL environment variable
-/// 2. ~/.config/observer/server.txt config file
+/// 2. `server_url` in ~/.config/observer/config.json
 /// 3. Default fallback (localhost for dev, Railway URL for production)
 ///
 /// Environment variable:
 ///   OBSERVER_SERVER_URL - Server API URL (e.g., https://your-server.railway.app)
 ///
 /// Note: Production deployments should set OBSERVER_SERVER_URL environment variable
-/// or configure server URL via config file (~/.config/observer/server.txt)
+/// or configure `server_url` in the config file (~/.config/observer/config.json)
 pub fn get_server_url() -> String {
     // Default URL: localhost for dev, Railway URL for production (fallback)
     let default_url = if is_dev_mode() {
@@ -185,53 +216,26 @@ pub fn get_server_url() -> String {
         "https://server-production-0b14.up.railway.app"
     };
 
-    // 1. Environment variable
-    if let Ok(url) = std::env::var("OBSERVER_SERVER_URL") {
-        let url = url.trim();
-        if !url.is_empty() {
-            match validate_url(url) {
-                Ok(validated) => return validated.to_string(),
-                Err(e) => {
-                    eprintln!("Invalid OBSERVER_SERVER_URL: {}. Using default.", e);
-                    return default_url.to_string();
-                }
-            }
-        }
-    }
-
-    // 2. Config file
-    if let Some(config_dir) = dirs::config_dir() {
-        let config_file = config_dir.join("observer").join("server.txt");
-        if let Ok(url) = std::fs::read_to_string(&config_file) {
-            let url = url.trim();
-            if !url.is_empty() {
-                match validate_url(url) {
-                    Ok(validated) => return validated.to_string(),
-                    Err(e) => {
-                        eprintln!("Invalid server URL in config file: {}. Using default.", e);
-                        return default_url.to_string();
-                    }
-                }
-            }
-        }
-    }
-
-    // 3. Default fallback
-    default_url.to_string()
+    configured_url(
+        "OBSERVER_SERVER_URL",
+        |config| config.server_url,
+        "server",
+        default_url,
+    )
 }
 
 /// Get dashboard URL from environment or config file
 ///
 /// Priority:
 /// 1. OBSERVER_DASHBOARD_URL environment variable
-/// 2. ~/.config/observer/dashboard.txt config file
+/// 2. `dashboard_url` in ~/.config/observer/config.json
 /// 3. Default fallback (localhost for dev, Railway URL for production)
 ///
 /// Environment variable:
 ///   OBSERVER_DASHBOARD_URL - Web dashboard URL (e.g., https://your-web-app.railway.app)
 ///
 /// Note: Production deployments should set OBSERVER_DASHBOARD_URL environment variable
-/// or configure dashboard URL via config file (~/.config/observer/dashboard.txt)
+/// or configure `dashboard_url` in the config file (~/.config/observer/config.json)
 pub fn get_dashboard_url() -> String {
     // Default URL: localhost for dev, Railway URL for production (fallback)
     let default_url = if is_dev_mode() {
@@ -241,37 +245,43 @@ pub fn get_dashboard_url() -> String {
         "https://web-production-20d71.up.railway.app"
     };
 
+    configured_url(
+        "OBSERVER_DASHBOARD_URL",
+        |config| config.dashboard_url,
+        "dashboard",
+        default_url,
+    )
+}
+
+/// Validated URL from the environment variable, then the config file, then the default
+fn configured_url(
+    env_var: &str,
+    from_config: impl FnOnce(crate::config::Config) -> Option<String>,
+    label: &str,
+    default_url: &str,
+) -> String {
     // 1. Environment variable
-    if let Ok(url) = std::env::var("OBSERVER_DASHBOARD_URL") {
-        let url = url.trim();
-        if !url.is_empty() {
-            match validate_url(url) {
-                Ok(validated) => return validated.to_string(),
-                Err(e) => {
-                    eprintln!("Invalid OBSERVER_DASHBOARD_URL: {}. Using default.", e);
-                    return default_url.to_string();
-                }
+    if let Some(url) = crate::config::env_override(env_var) {
+        return match validate_url(&url) {
+            Ok(validated) => validated.to_string(),
+            Err(e) => {
+                crate::logs::error(&format!("[Sync] Invalid {}: {}. Using default.", env_var, e));
+                default_url.to_string()
             }
-        }
+        };
     }
 
     // 2. Config file
-    if let Some(config_dir) = dirs::config_dir() {
-        let config_file = config_dir.join("observer").join("dashboard.txt");
-        if let Ok(url) = std::fs::read_to_string(&config_file) {
-            let url = url.trim();
-            if !url.is_empty() {
-                match validate_url(url) {
-                    Ok(validated) => return validated.to_string(),
-                    Err(e) => {
-                        eprintln!(
-                            "Invalid dashboard URL in config file: {}. Using default.",
-                            e
-                        );
-                        return default_url.to_string();
-                    }
+    if let Some(url) = crate::config::Config::load().ok().and_then(from_config) {
+        let url = url.trim();
+        if !url.is_empty() {
+            return match validate_url(url) {
+                Ok(validated) => validated.to_string(),
+                Err(e) => {
+                    crate::logs::error(&format!("[Sync] Invalid {} URL in config file: {}. Using default.", label, e));
+                    default_url.to_string()
                 }
-            }
+            };
         }
     }
 
@@ -279,62 +289,342 @@ pub fn get_dashboard_url() -> String {
     default_url.to_string()
 }
 
-pub async fn start_sync_service(state: Arc<Mutex<AppState>>) {
+/// Check whether the user has configured syncing (API key or server URL)
+fn is_sync_configured() -> bool {
+    if is_dev_mode() || get_api_key().is_some() || std::env::var("OBSERVER_SERVER_URL").is_ok() {
+        return true;
+    }
+
+    crate::config::Config::load()
+        .map(|config| config.server_url.is_some())
+        .unwrap_or(false)
+}
+
+/// Events per sync request: `OBSERVER_SYNC_BATCH_SIZE` if set to a positive number
+fn sync_batch_size(value: Option<String>) -> usize {
+    match value {
+        Some(value) => match value.parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                crate::logs::error(&format!(
+                    "[Sync] Ignoring invalid {}={}, using {}",
+                    SYNC_BATCH_SIZE_ENV,
+                    value,
+                    SYNC_BATCH_SIZE
+                ));
+                SYNC_BATCH_SIZE
+            }
+        },
+        None => SYNC_BATCH_SIZE,
+    }
+}
+
+pub async fn start_sync_service(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
+    let notifications = crate::notifications::NotificationManager::new();
+    let mut failures = SyncFailureTracker::default();
+    let batch_size = sync_batch_size(crate::config::env_override(SYNC_BATCH_SIZE_ENV));
+    crate::logs::info(&format!("[Sync] Sending up to {} events per request", batch_size));
+
+    // Don't fire the first sync before anything is configured
+    if !crate::startup::wait_until(
+        is_sync_configured,
+        crate::startup::GATE_POLL_INTERVAL,
+        Duration::from_secs(CONFIG_WAIT_SECS),
+    )
+    .await
+    {
+        crate::logs::info("[Sync] Sync not configured yet, syncing with defaults");
+    }
+
+    let mut last_prune: Option<std::time::Instant> = None;
+
     loop {
         tokio::time::sleep(tokio::time::Duration::from_secs(SYNC_INTERVAL_SECS)).await;
+        crate::supervisor::heartbeat(crate::supervisor::SYNC_TASK);
+
+        if last_prune.map_or(true, |last| last.elapsed() >= EVENT_PRUNE_INTERVAL) {
+            last_prune = Some(std::time::Instant::now());
+            let retention_days = crate::commands::get_settings()
+                .map(|settings| settings.event_retention_days)
+                .unwrap_or(DEFAULT_EVENT_RETENTION_DAYS);
+            let db = state.lock().await.db.clone();
+            prune_old_events(&db, retention_days, Utc::now());
+        }
 
         // Get events to sync (clone instead of drain to keep in buffer until ACKed)
-        let events: Vec<Event>;
-        {
-            let state = state.lock().await;
-            if state.events_buffer.is_empty() {
-                continue;
-            }
-            events = state.events_buffer.clone();
+        let events = take_sendable_events(&mut *state.lock().await);
+        if events.is_empty() {
+            continue;
         }
 
-        // Try to sync - convert error to String immediately to make future Send
-        match sync_events(&events).await.map_err(|e| e.to_string()) {
-            Ok(acked_event_ids) => {
+        // Deliver to the configured sink (Observer server by default)
+        let sink = configured_sink();
+        match send_in_batches(sink.as_ref(), &state, &events, batch_size).await {
+            Ok(acked) => {
+                *CURRENT_FAILURE.lock().unwrap() = None;
+                if let Some(minutes) = failures.record_success(Utc::now()) {
+                    crate::logs::info(&format!("Sync recovered after failing for {} minutes", minutes));
+                }
+
                 let mut state = state.lock().await;
                 state.last_sync = format_relative_time(Utc::now());
 
-                // Remove only ACKed events from buffer
-                let acked_set: std::collections::HashSet<String> =
-                    acked_event_ids.into_iter().collect();
-                state.events_buffer.retain(|e| !acked_set.contains(&e.id));
-
-                // Delete successfully synced events from database
-                let acked_ids: Vec<String> = acked_set.into_iter().collect();
-                if let Err(e) = state.db.delete_events(&acked_ids) {
-                    eprintln!("Warning: Failed to delete synced events from database: {}", e);
-                }
-
-                println!(
-                    "Sync successful: {} events ACKed, {} remaining in buffer",
-                    acked_ids.len(),
+                crate::logs::info(&format!(
+                    "Sync successful ({}): {} events ACKed, {} remaining in buffer",
+                    sink.name(),
+                    acked,
                     state.events_buffer.len()
-                );
+                ));
             }
             Err(error_msg) => {
-                eprintln!("Sync failed: {}", error_msg);
-                // Events remain in buffer for retry
+                // The failed batch and later ones remain in buffer for retry
+                let report = failures.record_failure(Utc::now(), &error_msg);
+                *CURRENT_FAILURE.lock().unwrap() = failures.failing_since().map(|since| SyncFailure {
+                    since,
+                    error: error_msg.clone(),
+                });
+                if report.repeated {
+                    crate::logs::debug(&format!("Sync failed: {}", error_msg));
+                } else {
+                    crate::logs::error(&format!("Sync failed: {}", error_msg));
+                }
+                if let Some(minutes) = report.notify_after_minutes {
+                    crate::logs::error(&format!("Sync has been failing for {} minutes", minutes));
+                    if let Err(e) = notifications.send_if_allowed(&app, sync_failing_notification(minutes, &error_msg)) {
+                        crate::logs::error(&format!("[Sync] {}", e));
+                    }
+                }
 
                 // Set warning flag if buffer is over threshold
                 let state = state.lock().await;
                 if state.events_buffer.len() >= crate::BUFFER_WARNING_THRESHOLD {
-                    eprintln!(
-                        "Warning: Event buffer is {}% full ({}/{} events). Events may be lost if sync continues to fail.",
+                    crate::logs::error(&format!(
+                        "[Sync] Warning: Event buffer is {}% full ({}/{} events). Events may be lost if sync continues to fail.",
                         (state.events_buffer.len() * 100) / crate::MAX_BUFFER_SIZE,
                         state.events_buffer.len(),
                         crate::MAX_BUFFER_SIZE
-                    );
+                    ));
                 }
             }
         }
     }
 }
 
+/// Outcome of recording a failed sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailureReport {
+    /// Same error as the previous attempt (logged quietly)
+    pub repeated: bool,
+    /// Notify the user that sync has been failing for this many minutes
+    pub notify_after_minutes: Option<i64>,
+}
+
+/// Tracks a run of consecutive sync failures so an offline period produces one
+/// error line and a few notifications at escalating intervals, not one per attempt
+#[derive(Debug, Default)]
+pub struct SyncFailureTracker {
+    failing_since: Option<chrono::DateTime<Utc>>,
+    last_error: Option<String>,
+    notifications_sent: usize,
+}
+
+impl SyncFailureTracker {
+    pub fn record_failure(&mut self, now: chrono::DateTime<Utc>, error: &str) -> FailureReport {
+        let failing_since = *self.failing_since.get_or_insert(now);
+        let repeated = self.last_error.as_deref() == Some(error);
+        self.last_error = Some(error.to_string());
+
+        let minutes = (now - failing_since).num_minutes();
+        let notify_after_minutes = if minutes >= failure_notify_threshold(self.notifications_sent) {
+            self.notifications_sent += 1;
+            Some(minutes)
+        } else {
+            None
+        };
+
+        FailureReport {
+            repeated,
+            notify_after_minutes,
+        }
+    }
+
+    pub fn failing_since(&self) -> Option<chrono::DateTime<Utc>> {
+        self.failing_since
+    }
+
+    /// Reset after a successful sync; returns how long it had been failing
+    pub fn record_success(&mut self, now: chrono::DateTime<Utc>) -> Option<i64> {
+        let failing_since = self.failing_since.take()?;
+        self.last_error = None;
+        self.notifications_sent = 0;
+        Some((now - failing_since).num_minutes())
+    }
+}
+
+/// Minutes of failure before notification number `sent` (0-based)
+fn failure_notify_threshold(sent: usize) -> i64 {
+    match FAILURE_NOTIFY_AFTER_MINUTES.get(sent) {
+        Some(minutes) => *minutes,
+        None => {
+            let last = FAILURE_NOTIFY_AFTER_MINUTES[FAILURE_NOTIFY_AFTER_MINUTES.len() - 1];
+            let extra = (sent - FAILURE_NOTIFY_AFTER_MINUTES.len() + 1) as i64;
+            last + extra * FAILURE_NOTIFY_REPEAT_MINUTES
+        }
+    }
+}
+
+fn sync_failing_notification(minutes: i64, error: &str) -> crate::notifications::NotificationConfig {
+    crate::notifications::NotificationConfig {
+        title: "Sync is failing".to_string(),
+        body: format!(
+            "Events haven't synced for {} minutes ({}). They are kept on this device and will be sent once the server is reachable.",
+            minutes, error
+        ),
+        priority: crate::notifications::NotificationPriority::Normal,
+        action: None,
+    }
+}
+
+/// Events to send: the buffer plus persisted events that are no longer in it
+/// (dropped when the buffer filled up while syncs were failing). Events still
+/// waiting for their screenshot are left for a later sync.
+fn pending_events(state: &AppState) -> Vec<Event> {
+    let mut events: Vec<Event> = state
+        .events_buffer
+        .iter()
+        .filter(|e| !state.pending_captures.contains(&e.id))
+        .cloned()
+        .collect();
+    let buffered: std::collections::HashSet<&str> =
+        state.events_buffer.iter().map(|e| e.id.as_str()).collect();
+
+    match state.db.load_unsynced_events(DB_SYNC_BATCH_SIZE + buffered.len() + state.pending_captures.len()) {
+        Ok(stored) => {
+            let dropped: Vec<Event> = stored
+                .into_iter()
+                .filter(|e| !buffered.contains(e.id.as_str()) && !state.pending_captures.contains(&e.id))
+                .take(DB_SYNC_BATCH_SIZE)
+                .collect();
+            if !dropped.is_empty() {
+                crate::logs::info(&format!("[Sync] Including {} persisted events missing from the buffer", dropped.len()));
+            }
+            events.extend(dropped);
+        }
+        Err(e) => crate::logs::error(&format!("[Sync] Warning: Failed to load unsynced events from database: {}", e)),
+    }
+
+    events
+}
+
+/// Pending events at or above the `minSyncImportance` setting
+/// The rest are dropped from the buffer and kept in the database as local-only
+fn take_sendable_events(state: &mut AppState) -> Vec<Event> {
+    let min_importance = crate::commands::get_settings()
+        .map(|settings| settings.min_sync_importance)
+        .unwrap_or_default();
+    let (events, local_only) = crate::importance::split_for_sync(pending_events(state), min_importance);
+    keep_local_only(state, &local_only);
+    events
+}
+
+/// Remove events from the buffer and flag them local-only in the database
+fn keep_local_only(state: &mut AppState, event_ids: &[String]) {
+    if event_ids.is_empty() {
+        return;
+    }
+    let ids: std::collections::HashSet<&str> = event_ids.iter().map(String::as_str).collect();
+    state.events_buffer.retain(|e| !ids.contains(e.id.as_str()));
+    if let Err(e) = state.db.mark_local_only(event_ids) {
+        crate::logs::error(&format!("[Sync] Warning: Failed to mark local-only events in database: {}", e));
+    }
+}
+
+/// Send `events` to `sink` `batch_size` at a time, removing each batch's ACKed
+/// events right away. Stops at the first failed batch, leaving it and the later
+/// ones for the next sync. Returns the number of ACKed events.
+async fn send_in_batches(
+    sink: &dyn crate::sink::EventSink,
+    state: &Mutex<AppState>,
+    events: &[Event],
+    batch_size: usize,
+) -> Result<usize, String> {
+    let batches = events.chunks(batch_size.max(1));
+    let batch_count = batches.len();
+    let mut acked = 0;
+
+    for (index, batch) in batches.enumerate() {
+        match sink.send(batch).await {
+            Ok(acked_event_ids) => acked += apply_acks(&mut *state.lock().await, acked_event_ids),
+            Err(e) if acked > 0 => {
+                return Err(format!(
+                    "{} (batch {}/{}, {} events ACKed before it)",
+                    e,
+                    index + 1,
+                    batch_count,
+                    acked
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(acked)
+}
+
+/// Remove ACKed events from the buffer and flag them synced in the database,
+/// where they are kept for local history until the retention period ends
+/// Returns the number of ACKed events
+fn apply_acks(state: &mut AppState, acked_event_ids: Vec<String>) -> usize {
+    // Remove only ACKed events from buffer
+    let acked_set: std::collections::HashSet<String> = acked_event_ids.into_iter().collect();
+    state.events_buffer.retain(|e| !acked_set.contains(&e.id));
+
+    let acked_ids: Vec<String> = acked_set.into_iter().collect();
+    if let Err(e) = state.db.mark_synced(&acked_ids) {
+        crate::logs::error(&format!("[Sync] Warning: Failed to mark synced events in database: {}", e));
+    }
+
+    acked_ids.len()
+}
+
+/// Delete synced and local-only events older than `retention_days` (at least one day is kept)
+fn prune_old_events(db: &crate::db::EventDatabase, retention_days: u32, now: chrono::DateTime<Utc>) {
+    let cutoff = now - chrono::Duration::days(retention_days.max(1) as i64);
+    match db.prune_synced_events(cutoff) {
+        Ok(0) => {}
+        Ok(pruned) => crate::logs::info(&format!(
+            "[Sync] Pruned {} synced and local-only events older than {} days",
+            pruned, retention_days
+        )),
+        Err(e) => crate::logs::error(&format!("[Sync] Failed to prune synced events: {}", e)),
+    }
+}
+
+/// Sink selected by the `sink` setting
+fn configured_sink() -> Box<dyn crate::sink::EventSink> {
+    let config = crate::commands::get_settings()
+        .map(|settings| settings.sink)
+        .unwrap_or_default();
+    crate::sink::create_sink(&config)
+}
+
+/// Settings baked into a client at build time - a change requires a new client
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ClientSettings {
+    proxy: Vec<(String, String)>,
+}
+
+impl ClientSettings {
+    fn current() -> Self {
+        Self {
+            proxy: PROXY_ENV_VARS
+                .iter()
+                .filter_map(|name| std::env::var(name).ok().map(|value| (name.to_string(), value)))
+                .collect(),
+        }
+    }
+}
+
 /// Create HTTP client with configured timeouts
 fn create_http_client() -> Result<reqwest::Client, Box<dyn std::error::Error>> {
     let client = reqwest::Client::builder()
@@ -344,6 +634,29 @@ fn create_http_client() -> Result<reqwest::Client, Box<dyn std::error::Error>> {
     Ok(client)
 }
 
+/// Client from `cache` for the given settings, rebuilt only when they change
+fn http_client_for(
+    cache: &ClientCache,
+    settings: ClientSettings,
+) -> Result<Arc<reqwest::Client>, Box<dyn std::error::Error>> {
+    let mut cached = cache.lock().unwrap();
+    if let Some((cached_settings, client)) = cached.as_ref() {
+        if *cached_settings == settings {
+            return Ok(client.clone());
+        }
+        crate::logs::info("[Sync] Proxy settings changed, recreating HTTP client");
+    }
+
+    let client = Arc::new(create_http_client()?);
+    *cached = Some((settings, client.clone()));
+    Ok(client)
+}
+
+/// HTTP client shared by all syncs (connections are pooled and kept alive)
+pub(crate) fn shared_http_client() -> Result<Arc<reqwest::Client>, Box<dyn std::error::Error>> {
+    http_client_for(&HTTP_CLIENT, ClientSettings::current())
+}
+
 /// Check if error is transient and should be retried
 fn is_transient_error(error: &reqwest::Error) -> bool {
     // Retry on timeout, connection errors, or server errors (5xx)
@@ -358,11 +671,9 @@ struct SyncResponse {
     acked_event_ids: Vec<String>,
 }
 
-async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    let client = create_http_client()?;
-    let server_url = get_server_url();
-    let api_key = get_api_key();
-
+/// Build the JSON body sent to the server for a batch of events
+/// Only the fields listed here ever leave the machine
+pub fn build_sync_payload(events: &[Event]) -> serde_json::Value {
     // Map events to include event_id field
     let events_payload: Vec<serde_json::Value> = events
         .iter()
@@ -381,9 +692,111 @@ async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error
         })
         .collect();
 
-    let payload = serde_json::json!({
+    serde_json::json!({
+        "device_name": crate::config::device_name(),
         "events": events_payload
+    })
+}
+
+/// Idempotency key for a batch: derived from its event IDs, so retries of the
+/// same batch (even from a later sync cycle) carry the same key and the server
+/// can drop a batch it already stored, while any new batch gets a fresh one
+pub fn batch_idempotency_key(events: &[Event]) -> String {
+    // FNV-1a (128-bit) - deterministic across runs, unlike std's hasher
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let mut hash = OFFSET_BASIS;
+    for event in events {
+        // Separator keeps ["ab", "c"] and ["a", "bc"] apart
+        for byte in event.id.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u128;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    uuid::Uuid::from_u128(hash).to_string()
+}
+
+/// POST request for one sync attempt
+fn sync_request(
+    client: &reqwest::Client,
+    server_url: &str,
+    api_key: Option<&str>,
+    payload: &serde_json::Value,
+    idempotency_key: &str,
+) -> reqwest::RequestBuilder {
+    let mut request = client
+        .post(format!("{}/api/v1/events", server_url))
+        .header("Idempotency-Key", idempotency_key)
+        .json(payload);
+
+    if let Some(key) = api_key {
+        request = request.header("X-API-Key", key);
+    }
+
+    request
+}
+
+/// How far behind sync is: events waiting to be sent and how old the oldest one is
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncLag {
+    pub unsynced_events: usize,
+    pub oldest_unsynced: Option<chrono::DateTime<Utc>>,
+    pub age_seconds: Option<i64>,
+}
+
+/// Sync lag over the buffer and unsynced database rows; buffered events are
+/// persisted too, so only those that failed to save are added to the database count
+pub fn sync_lag(state: &AppState, now: chrono::DateTime<Utc>) -> SyncLag {
+    let (stored_count, stored_oldest) = state.db.unsynced_summary().unwrap_or_else(|e| {
+        crate::logs::error(&format!("[Sync] Warning: Failed to count unsynced events in database: {}", e));
+        (0, None)
+    });
+
+    let buffered_ids: Vec<&str> = state.events_buffer.iter().map(|e| e.id.as_str()).collect();
+    let stored_ids = state.db.stored_ids(&buffered_ids).unwrap_or_else(|e| {
+        crate::logs::error(&format!("[Sync] Warning: Failed to look up buffered events in database: {}", e));
+        Default::default()
     });
+    let unpersisted: Vec<&Event> = state
+        .events_buffer
+        .iter()
+        .filter(|e| !stored_ids.contains(&e.id))
+        .collect();
+
+    let oldest_unsynced = unpersisted.iter().map(|e| e.timestamp).chain(stored_oldest).min();
+    SyncLag {
+        unsynced_events: stored_count + unpersisted.len(),
+        oldest_unsynced,
+        age_seconds: oldest_unsynced.map(|oldest| (now - oldest).num_seconds().max(0)),
+    }
+}
+
+/// Payload that the next sync would send to `sink` for the first `limit` pending
+/// events at or above `min_importance`
+pub fn preview_sync_payload(
+    state: &AppState,
+    sink: &dyn crate::sink::EventSink,
+    limit: usize,
+    min_importance: crate::importance::EventImportance,
+) -> serde_json::Value {
+    let events: Vec<Event> = pending_events(state)
+        .into_iter()
+        .filter(|e| crate::importance::classify(e) >= min_importance)
+        .take(limit)
+        .collect();
+    sink.payload(&events)
+}
+
+pub(crate) async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let client = shared_http_client()?;
+    let server_url = get_server_url();
+    let api_key = get_api_key();
+
+    let payload = build_sync_payload(events);
+    let idempotency_key = batch_idempotency_key(events);
 
     let mut last_error = None;
 
@@ -391,19 +804,17 @@ async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error
     for attempt in 0..=MAX_RETRIES {
         if attempt > 0 {
             let delay_ms = INITIAL_RETRY_DELAY_MS * 2_u64.pow(attempt - 1);
-            eprintln!("Retrying request (attempt {}/{}) after {}ms delay",
-                     attempt, MAX_RETRIES, delay_ms);
+            crate::logs::error(&format!(
+                "[Sync] Retrying request (attempt {}/{}) after {}ms delay",
+                attempt,
+                MAX_RETRIES,
+                delay_ms
+            ));
             tokio::time::sleep(Duration::from_millis(delay_ms)).await;
         }
 
-        // Build request with optional API key authentication
-        let mut request = client
-            .post(format!("{}/api/v1/events", server_url))
-            .json(&payload);
-
-        if let Some(ref key) = api_key {
-            request = request.header("X-API-Key", key);
-        }
+        // Same key on every attempt so the server can dedupe a retried batch
+        let request = sync_request(&client, &server_url, api_key.as_deref(), &payload, &idempotency_key);
 
         match request.send().await
         {
@@ -417,7 +828,7 @@ async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error
                             return Ok(sync_response.acked_event_ids);
                         }
                         Err(e) => {
-                            eprintln!("Failed to parse response: {}", e);
+                            crate::logs::error(&format!("[Sync] Failed to parse response: {}", e));
                             // If we can't parse the response, assume all events were ACKed
                             // to maintain backward compatibility
                             return Ok(events.iter().map(|e| e.id.clone()).collect());
@@ -427,7 +838,7 @@ async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error
 
                 // Check if we should retry based on status code
                 if status.is_server_error() && attempt < MAX_RETRIES {
-                    eprintln!("Server error: {} - will retry", status);
+                    crate::logs::error(&format!("[Sync] Server error: {} - will retry", status));
                     last_error = Some(format!("Server returned status: {}", status));
                     continue;
                 }
@@ -436,7 +847,7 @@ async fn sync_events(events: &[Event]) -> Result<Vec<String>, Box<dyn std::error
                 return Err(format!("Server returned status: {}", status).into());
             }
             Err(e) => {
-                eprintln!("Request failed: {}", e);
+                crate::logs::error(&format!("[Sync] Request failed: {}", e));
 
                 // Check if error is transient and we have retries left
                 if is_transient_error(&e) && attempt < MAX_RETRIES {
@@ -471,46 +882,447 @@ fn format_relative_time(time: chrono::DateTime<Utc>) -> String {
     }
 }
 
-#[allow(dead_code)]
-pub async fn manual_sync(state: Arc<Mutex<AppState>>) -> Result<(), String> {
-    let events: Vec<Event>;
-    {
-        let state = state.lock().await;
-        if state.events_buffer.is_empty() {
-            return Ok(());
-        }
-        events = state.events_buffer.clone();
+/// Sync right away; returns the number of ACKed events
+pub async fn manual_sync(state: Arc<Mutex<AppState>>) -> Result<usize, String> {
+    sync_to(&state, configured_sink().as_ref()).await
+}
+
+async fn sync_to(state: &Mutex<AppState>, sink: &dyn crate::sink::EventSink) -> Result<usize, String> {
+    let events = take_sendable_events(&mut *state.lock().await);
+    if events.is_empty() {
+        return Ok(0);
     }
 
-    // Convert error to String immediately to make future Send
-    match sync_events(&events).await.map_err(|e| e.to_string()) {
-        Ok(acked_event_ids) => {
+    let batch_size = sync_batch_size(crate::config::env_override(SYNC_BATCH_SIZE_ENV));
+    match send_in_batches(sink, state, &events, batch_size).await {
+        Ok(acked) => {
             let mut state = state.lock().await;
             state.last_sync = "Just now".to_string();
 
-            // Remove only ACKed events from buffer
-            let acked_set: std::collections::HashSet<String> =
-                acked_event_ids.into_iter().collect();
-            state.events_buffer.retain(|e| !acked_set.contains(&e.id));
-
-            // Delete successfully synced events from database
-            let acked_ids: Vec<String> = acked_set.into_iter().collect();
-            if let Err(e) = state.db.delete_events(&acked_ids) {
-                eprintln!("Warning: Failed to delete synced events from database: {}", e);
-            }
-
-            println!(
-                "Manual sync successful: {} events ACKed, {} remaining in buffer",
-                acked_ids.len(),
+            crate::logs::info(&format!(
+                "[Sync] Manual sync successful: {} events ACKed, {} remaining in buffer",
+                acked,
                 state.events_buffer.len()
-            );
+            ));
 
-            Ok(())
+            Ok(acked)
         }
         Err(error_msg) => {
             // Events remain in buffer for retry
-            eprintln!("Manual sync failed: {}", error_msg);
+            crate::logs::error(&format!("[Sync] Manual sync failed: {}", error_msg));
             Err(error_msg)
         }
     }
 }
+
+/// Outcome of `flush_and_sync`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlushResult {
+    /// Buffered events that were missing from the database and got written
+    pub persisted: usize,
+    /// Events ACKed by the sink
+    pub synced: usize,
+    /// Events still waiting to be synced
+    pub remaining: usize,
+    /// Why the sync failed, if it did
+    pub sync_error: Option<String>,
+}
+
+/// Make sure every buffered event is in the database, then sync right away
+/// A failed sync is reported in the result; only a database error is an `Err`
+pub async fn flush_and_sync(state: &Mutex<AppState>) -> Result<FlushResult, String> {
+    flush_and_sync_to(state, configured_sink().as_ref()).await
+}
+
+async fn flush_and_sync_to(
+    state: &Mutex<AppState>,
+    sink: &dyn crate::sink::EventSink,
+) -> Result<FlushResult, String> {
+    let persisted = crate::collector::persist_buffer(&*state.lock().await)?;
+    let (synced, sync_error) = match sync_to(state, sink).await {
+        Ok(synced) => (synced, None),
+        Err(e) => (0, Some(e)),
+    };
+    let remaining = sync_lag(&*state.lock().await, Utc::now()).unsynced_events;
+
+    Ok(FlushResult {
+        persisted,
+        synced,
+        remaining,
+        sync_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventDatabase;
+
+    #[test]
+    fn test_preview_matches_payload_builder() {
+        let events: Vec<Event> = (0..3)
+            .map(|i| Event::new("app_focus", Some(format!("App {}", i)), None))
+            .collect();
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        let mut state = AppState::new(db.clone(), events.clone());
+        let observer = crate::sink::ObserverSink;
+
+        let everything = crate::importance::EventImportance::Low;
+        let preview = preview_sync_payload(&state, &observer, 2, everything);
+        assert_eq!(preview, build_sync_payload(&events[..2]));
+        assert_eq!(preview["events"].as_array().unwrap().len(), 2);
+        assert_eq!(preview["events"][0]["event_id"], events[0].id);
+
+        // Events only in the database are sent too; ones waiting for a screenshot are not
+        let stored = Event::new("app_focus", Some("Stored".to_string()), None);
+        db.insert_event(&stored).unwrap();
+        state.pending_captures.insert(events[2].id.clone());
+        let expected = vec![events[0].clone(), events[1].clone(), stored];
+        assert_eq!(preview_sync_payload(&state, &observer, 100, everything), build_sync_payload(&expected));
+
+        // A file sink previews the lines it would write
+        let file = crate::sink::FileSink { path: None };
+        let lines = preview_sync_payload(&state, &file, 100, everything);
+        assert_eq!(lines, build_sync_payload(&expected)["events"]);
+    }
+
+    #[test]
+    fn test_payload_excludes_local_only_fields() {
+        let mut event = Event::new("browser_input", Some("Safari".to_string()), None);
+        event.typed_text = Some("secret".to_string());
+        event.screenshot_path = Some("/tmp/shot.jpg".to_string());
+        event.thumbnail_path = Some("/tmp/shot_thumb.jpg".to_string());
+
+        let payload = build_sync_payload(&[event]);
+        let synced = payload["events"][0].as_object().unwrap();
+        assert!(!synced.contains_key("typed_text"));
+        assert!(!synced.contains_key("screenshot_path"));
+        assert!(!synced.contains_key("thumbnail_path"));
+    }
+
+    #[test]
+    fn test_dropped_buffer_event_still_synced() {
+        let dropped = Event::new("app_focus", Some("Dropped".to_string()), None);
+        let buffered = Event::new("app_focus", Some("Buffered".to_string()), None);
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(&dropped).unwrap();
+        db.insert_event(&buffered).unwrap();
+
+        // The buffer overflowed and lost `dropped`, but it is still persisted
+        let mut state = AppState::new(db.clone(), vec![buffered.clone()]);
+
+        let pending = pending_events(&state);
+        let ids: Vec<&str> = pending.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec![buffered.id.as_str(), dropped.id.as_str()]);
+
+        // Server ACKs both
+        let acked = apply_acks(&mut state, vec![buffered.id.clone(), dropped.id.clone()]);
+        assert_eq!(acked, 2);
+        assert!(state.events_buffer.is_empty());
+        assert!(db.load_unsynced_events(10).unwrap().is_empty());
+        // Both stay in the database as history
+        assert_eq!(db.count().unwrap(), 2);
+        assert!(pending_events(&state).is_empty());
+    }
+
+    #[test]
+    fn test_events_waiting_for_capture_held_back() {
+        let captured = Event::new("app_focus", Some("Code".to_string()), None);
+        let plain = Event::new("app_focus", Some("Slack".to_string()), None);
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(&captured).unwrap();
+        db.insert_event(&plain).unwrap();
+        let mut state = AppState::new(db.clone(), vec![captured.clone(), plain.clone()]);
+        state.pending_captures.insert(captured.id.clone());
+
+        let ids = |state: &AppState| pending_events(state).into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(&state), vec![plain.id.clone()]);
+        // Also when it was dropped from the buffer
+        state.events_buffer.retain(|e| e.id != captured.id);
+        assert_eq!(ids(&state), vec![plain.id.clone()]);
+
+        // The worker attached the screenshot and released the event
+        db.update_screenshot_path(&captured.id, "/shots/a.jpg", None).unwrap();
+        state.pending_captures.remove(&captured.id);
+        let pending = pending_events(&state);
+        assert_eq!(pending.len(), 2);
+        let sent = pending.iter().find(|e| e.id == captured.id).unwrap();
+        assert_eq!(sent.screenshot_path.as_deref(), Some("/shots/a.jpg"));
+    }
+
+    #[test]
+    fn test_local_only_events_kept_but_not_sent() {
+        let focus = Event::new("app_focus", Some("Safari".to_string()), None);
+        let input = Event::new("browser_input", Some("Safari".to_string()), None);
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(&focus).unwrap();
+        db.insert_event(&input).unwrap();
+        let mut state = AppState::new(db.clone(), vec![focus.clone(), input.clone()]);
+
+        let (send, local_only) =
+            crate::importance::split_for_sync(pending_events(&state), crate::importance::EventImportance::Normal);
+        keep_local_only(&mut state, &local_only);
+        assert_eq!(send.len(), 1);
+        assert_eq!(send[0].id, focus.id);
+
+        // The input event left the buffer and the sync queue but stays in the database
+        assert_eq!(state.events_buffer.len(), 1);
+        let pending: Vec<String> = pending_events(&state).into_iter().map(|e| e.id).collect();
+        assert_eq!(pending, vec![focus.id.clone()]);
+        apply_acks(&mut state, vec![focus.id.clone()]);
+        assert_eq!(db.count().unwrap(), 2);
+        assert!(db.get_event(&input.id).unwrap().is_some());
+    }
+
+    /// Sink that ACKs everything except the `fail_on`-th request
+    struct FlakySink {
+        fail_on: usize,
+        calls: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl crate::sink::EventSink for FlakySink {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn send<'a>(&'a self, events: &'a [Event]) -> crate::sink::SinkFuture<'a> {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(events.len());
+            let failed = calls.len() == self.fail_on;
+            Box::pin(async move {
+                if failed {
+                    Err("Server returned status: 503".to_string())
+                } else {
+                    Ok(events.iter().map(|e| e.id.clone()).collect())
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_after_failure_stay_buffered() {
+        let events: Vec<Event> = (0..12)
+            .map(|i| Event::new("app_focus", Some(format!("App {}", i)), None))
+            .collect();
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        for event in &events {
+            db.insert_event(event).unwrap();
+        }
+        let state = Mutex::new(AppState::new(db.clone(), events.clone()));
+
+        // Batches of 5, 5 and 2; the second one fails
+        let sink = FlakySink { fail_on: 2, calls: std::sync::Mutex::new(Vec::new()) };
+        let error = send_in_batches(&sink, &state, &events, 5).await.unwrap_err();
+        assert!(error.contains("503") && error.contains("batch 2/3"));
+        assert_eq!(*sink.calls.lock().unwrap(), vec![5, 5]);
+
+        // Only the first batch was removed; the failed and later batches wait for the retry
+        let remaining: Vec<String> = state.lock().await.events_buffer.iter().map(|e| e.id.clone()).collect();
+        let expected: Vec<String> = events[5..].iter().map(|e| e.id.clone()).collect();
+        assert_eq!(remaining, expected);
+        assert_eq!(db.load_unsynced_events(100).unwrap().len(), 7);
+
+        let sink = FlakySink { fail_on: 0, calls: std::sync::Mutex::new(Vec::new()) };
+        assert_eq!(send_in_batches(&sink, &state, &events[5..], 5).await, Ok(7));
+        assert!(state.lock().await.events_buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_flush_and_sync_persists_buffer_then_syncs() {
+        let saved = Event::new("app_focus", Some("Saved".to_string()), None);
+        let unsaved = Event::new("app_focus", Some("Unsaved".to_string()), None);
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        db.insert_event(&saved).unwrap();
+        // `unsaved` only made it into the buffer (its insert failed)
+        let state = Mutex::new(AppState::new(db.clone(), vec![saved.clone(), unsaved.clone()]));
+
+        // Sync down: both events end up persisted and wait for the next sync
+        let sink = FlakySink { fail_on: 1, calls: std::sync::Mutex::new(Vec::new()) };
+        let result = flush_and_sync_to(&state, &sink).await.unwrap();
+        assert_eq!(sink.calls.lock().unwrap().len(), 1);
+        assert_eq!((result.persisted, result.synced, result.remaining), (1, 0, 2));
+        assert!(result.sync_error.unwrap().contains("503"));
+        assert_eq!(db.load_unsynced_events(10).unwrap().len(), 2);
+
+        // Nothing left to write the second time; the sync ACKs everything
+        let sink = FlakySink { fail_on: 0, calls: std::sync::Mutex::new(Vec::new()) };
+        let result = flush_and_sync_to(&state, &sink).await.unwrap();
+        assert_eq!(
+            result,
+            FlushResult { persisted: 0, synced: 2, remaining: 0, sync_error: None }
+        );
+        assert!(state.lock().await.events_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_sync_batch_size_override() {
+        assert_eq!(sync_batch_size(None), SYNC_BATCH_SIZE);
+        assert_eq!(sync_batch_size(Some("50".to_string())), 50);
+        assert_eq!(sync_batch_size(Some("0".to_string())), SYNC_BATCH_SIZE);
+        assert_eq!(sync_batch_size(Some("lots".to_string())), SYNC_BATCH_SIZE);
+    }
+
+    #[test]
+    fn test_sync_lag_counts_buffer_and_database_once() {
+        let now = Utc::now();
+        let at = |minutes_ago: i64, app: &str| {
+            let mut event = Event::new("app_focus", Some(app.to_string()), None);
+            event.timestamp = now - chrono::Duration::minutes(minutes_ago);
+            event
+        };
+        let dropped = at(30, "Dropped");
+        let synced = at(60, "Synced");
+        let buffered = at(5, "Buffered");
+        let unpersisted = at(1, "Unpersisted");
+
+        let db = Arc::new(EventDatabase::in_memory().unwrap());
+        let empty = AppState::new(db.clone(), Vec::new());
+        assert_eq!(
+            sync_lag(&empty, now),
+            SyncLag { unsynced_events: 0, oldest_unsynced: None, age_seconds: None }
+        );
+
+        for event in [&dropped, &synced, &buffered] {
+            db.insert_event(event).unwrap();
+        }
+        db.mark_synced(&[synced.id.clone()]).unwrap();
+
+        // `buffered` is both in the buffer and the database; `unpersisted` failed to save
+        let state = AppState::new(db, vec![buffered, unpersisted]);
+        let lag = sync_lag(&state, now);
+        assert_eq!(lag.unsynced_events, 3);
+        assert_eq!(lag.oldest_unsynced.map(|t| t.timestamp()), Some(dropped.timestamp.timestamp()));
+        assert_eq!(lag.age_seconds, Some(30 * 60));
+    }
+
+    #[test]
+    fn test_synced_events_not_resent() {
+        let event = Event::new("app_focus", Some("Code".to_string()), None);
+        let db = EventDatabase::in_memory().unwrap();
+        db.insert_event(&event).unwrap();
+
+        db.mark_synced(&[event.id.clone()]).unwrap();
+        assert!(db.load_unsynced_events(10).unwrap().is_empty());
+        assert_eq!(db.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_synced_events_pruned_after_retention() {
+        let now = Utc::now();
+        let at = |days_ago: i64| {
+            let mut event = Event::new("app_focus", Some("Code".to_string()), None);
+            event.timestamp = now - chrono::Duration::days(days_ago);
+            event
+        };
+        let (old_synced, recent_synced, old_unsynced) = (at(40), at(2), at(40));
+        let (old_local, recent_local) = (at(40), at(2));
+        let db = EventDatabase::in_memory().unwrap();
+        for event in [&old_synced, &recent_synced, &old_unsynced, &old_local, &recent_local] {
+            db.insert_event(event).unwrap();
+        }
+        db.mark_synced(&[old_synced.id.clone(), recent_synced.id.clone()]).unwrap();
+        db.mark_local_only(&[old_local.id.clone(), recent_local.id.clone()]).unwrap();
+
+        prune_old_events(&db, DEFAULT_EVENT_RETENTION_DAYS, now);
+        assert!(db.get_event(&old_synced.id).unwrap().is_none());
+        assert!(db.get_event(&recent_synced.id).unwrap().is_some());
+        // Local-only events follow the same retention
+        assert!(db.get_event(&old_local.id).unwrap().is_none());
+        assert!(db.get_event(&recent_local.id).unwrap().is_some());
+        // Not sent yet, so never pruned
+        assert!(db.get_event(&old_unsynced.id).unwrap().is_some());
+
+        prune_old_events(&db, 1, now);
+        assert!(db.get_event(&recent_synced.id).unwrap().is_none());
+        assert!(db.get_event(&recent_local.id).unwrap().is_none());
+        assert_eq!(db.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_http_client_reused_across_syncs() {
+        let cache = ClientCache::default();
+        let settings = ClientSettings { proxy: Vec::new() };
+        let first = http_client_for(&cache, settings.clone()).unwrap();
+        let second = http_client_for(&cache, settings).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // A proxy change builds a new client, which is then reused
+        let proxied = ClientSettings {
+            proxy: vec![("HTTPS_PROXY".to_string(), "http://proxy.local:3128".to_string())],
+        };
+        let third = http_client_for(&cache, proxied.clone()).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert!(Arc::ptr_eq(&third, &http_client_for(&cache, proxied).unwrap()));
+    }
+
+    #[test]
+    fn test_idempotency_key_stable_per_batch() {
+        let batch: Vec<Event> = (0..3)
+            .map(|i| Event::new("app_focus", Some(format!("App {}", i)), None))
+            .collect();
+        let client = reqwest::Client::new();
+        let payload = build_sync_payload(&batch);
+        let key = batch_idempotency_key(&batch);
+
+        // Every retry attempt of the batch sends the same key
+        let attempts: Vec<String> = (0..=MAX_RETRIES)
+            .map(|_| {
+                let request = sync_request(&client, "https://observer.example.com", None, &payload, &key)
+                    .build()
+                    .unwrap();
+                request.headers()["Idempotency-Key"].to_str().unwrap().to_string()
+            })
+            .collect();
+        assert!(attempts.iter().all(|sent| *sent == key));
+        // Re-sending the batch on a later sync cycle reuses it too
+        assert_eq!(batch_idempotency_key(&batch.clone()), key);
+
+        // A new batch gets a fresh key
+        let next: Vec<Event> = (0..3)
+            .map(|i| Event::new("app_focus", Some(format!("App {}", i)), None))
+            .collect();
+        assert_ne!(batch_idempotency_key(&next), key);
+        assert_ne!(batch_idempotency_key(&batch[..2]), key);
+    }
+
+    #[test]
+    fn test_failure_notifications_escalate() {
+        let start = chrono::DateTime::parse_from_rfc3339("2026-01-10T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut tracker = SyncFailureTracker::default();
+
+        // Nine hours offline, one attempt every 30 seconds
+        let mut notified = Vec::new();
+        let mut loud = 0;
+        for attempt in 0..(9 * 60 * 2) {
+            let now = start + chrono::Duration::seconds(attempt * SYNC_INTERVAL_SECS as i64);
+            let report = tracker.record_failure(now, "Request failed: connection refused");
+            if !report.repeated {
+                loud += 1;
+            }
+            notified.extend(report.notify_after_minutes);
+        }
+        assert_eq!(loud, 1);
+        assert_eq!(notified, vec![5, 15, 60, 240, 480]);
+
+        // A different error is logged loudly again without restarting the schedule
+        let later = start + chrono::Duration::hours(9);
+        let report = tracker.record_failure(later, "Server returned status: 502");
+        assert!(!report.repeated);
+        assert_eq!(report.notify_after_minutes, None);
+
+        // Success resets everything
+        assert_eq!(tracker.record_success(later), Some(540));
+        assert_eq!(tracker.record_success(later), None);
+        let report = tracker.record_failure(later, "Server returned status: 502");
+        assert!(!report.repeated);
+        assert_eq!(
+            tracker
+                .record_failure(later + chrono::Duration::minutes(5), "Server returned status: 502")
+                .notify_after_minutes,
+            Some(5)
+        );
+    }
+}
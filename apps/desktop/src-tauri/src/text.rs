@@ -0,0 +1,62 @@
+// Text helpers
+// String truncation that never splits a UTF-8 character
+
+/// Default cap on window titles stored on an event (characters)
+pub const MAX_TITLE_CHARS: usize = 1_000;
+
+/// Longest message content captured from a messenger (characters)
+pub const MAX_MESSAGE_CHARS: usize = 5_000;
+
+/// Longest OCR text kept per screenshot (characters)
+pub const MAX_OCR_CHARS: usize = 20_000;
+
+/// First `max` characters of `s`
+/// Unlike byte slicing (`&s[..max]`) this cannot panic on multi-byte characters
+pub fn truncate_chars(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((byte_index, _)) => &s[..byte_index],
+        None => s,
+    }
+}
+
+/// Owned variant of `truncate_chars` that avoids reallocating when nothing is cut
+pub fn truncate_string(mut s: String, max: usize) -> String {
+    let len = truncate_chars(&s, max).len();
+    s.truncate(len);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_ascii() {
+        assert_eq!(truncate_chars("hello", 3), "hel");
+        assert_eq!(truncate_chars("hello", 5), "hello");
+        assert_eq!(truncate_chars("hello", 10), "hello");
+        assert_eq!(truncate_chars("hello", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_multibyte() {
+        // Byte index 1 is inside the first emoji - `&s[..1]` would panic
+        let emoji = "😀😃😄";
+        assert_eq!(truncate_chars(emoji, 1), "😀");
+        assert_eq!(truncate_chars(emoji, 2), "😀😃");
+
+        // CJK characters are 3 bytes each
+        let cjk = "你好世界";
+        assert_eq!(truncate_chars(cjk, 3), "你好世");
+        assert_eq!(truncate_chars(cjk, 3).len(), 9);
+
+        let mixed = "Привет 👋 мир";
+        assert_eq!(truncate_chars(mixed, 8), "Привет 👋");
+    }
+
+    #[test]
+    fn test_truncate_string_owned() {
+        assert_eq!(truncate_string("日本語テキスト".to_string(), 3), "日本語");
+        assert_eq!(truncate_string("short".to_string(), 100), "short");
+    }
+}
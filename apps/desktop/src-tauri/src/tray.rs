@@ -1,5 +1,6 @@
 use crate::sync::get_dashboard_url;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tauri::{
     image::Image,
     menu::{Menu, MenuItem},
@@ -10,6 +11,19 @@ use tauri::{
 /// Global flag to track window visibility (more reliable than is_visible() on macOS)
 static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(true);
 
+/// Menu bar markers currently shown next to the icon
+static INDICATORS: Mutex<Indicators> = Mutex::new(Indicators {
+    typing: false,
+    recording: false,
+});
+
+struct Indicators {
+    /// Typed text is captured in all apps
+    typing: bool,
+    /// A high-fidelity recording is running
+    recording: bool,
+}
+
 /// Create a minimal outline eye icon for macOS menu bar
 /// Simple black lines only - no fill, just outline like other menu bar icons
 fn create_eye_icon() -> (Vec<u8>, u32, u32) {
@@ -115,19 +129,19 @@ pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                     position,
                     ..
                 } => {
-                    println!("Tray icon clicked at position: {:?}", position);
+                    crate::logs::debug(&format!("[Tray] Tray icon clicked at position: {:?}", position));
                     let app = tray.app_handle();
                     if let Some(window) = app.get_webview_window("main") {
                         // Use our atomic flag instead of is_visible() for reliable state tracking
                         let is_visible = WINDOW_VISIBLE.load(Ordering::SeqCst);
-                        println!("Window visible (tracked): {}", is_visible);
+                        crate::logs::debug(&format!("[Tray] Window visible (tracked): {}", is_visible));
 
                         if is_visible {
-                            println!("Hiding window");
+                            crate::logs::debug("[Tray] Hiding window");
                             let _ = window.hide();
                             WINDOW_VISIBLE.store(false, Ordering::SeqCst);
                         } else {
-                            println!("Showing window");
+                            crate::logs::debug("[Tray] Showing window");
 
                             // Position window near tray icon on macOS
                             #[cfg(target_os = "macos")]
@@ -140,21 +154,21 @@ pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                                 // Position below tray icon, centered
                                 let x = position.x as i32 - (size.width as i32 / 2);
                                 let y = position.y as i32 + 5; // Small offset below tray
-                                println!("Setting window position to ({}, {})", x, y);
+                                crate::logs::debug(&format!("[Tray] Setting window position to ({}, {})", x, y));
                                 let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
                             }
 
                             let _ = window.show();
                             let _ = window.set_focus();
                             WINDOW_VISIBLE.store(true, Ordering::SeqCst);
-                            println!("Window show and focus called");
+                            crate::logs::debug("[Tray] Window show and focus called");
                         }
                     } else {
-                        println!("ERROR: Could not find 'main' window!");
+                        crate::logs::error("[Tray] Could not find 'main' window");
                     }
                 }
                 TrayIconEvent::DoubleClick { .. } => {
-                    println!("Tray icon double clicked");
+                    crate::logs::debug("[Tray] Tray icon double clicked");
                 }
                 _ => {}
             }
@@ -162,9 +176,7 @@ pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .build(app)?;
 
     // Store tray in app state to prevent it from being dropped
-    app.manage(TrayState {
-        _tray: tray.clone(),
-    });
+    app.manage(TrayState { tray: tray.clone() });
 
     // Show window near tray icon on first launch
     if let Some(window) = app.get_webview_window("main") {
@@ -200,11 +212,49 @@ pub fn create_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
 
 /// State to keep tray icon alive
 pub struct TrayState {
-    _tray: tauri::tray::TrayIcon,
+    tray: tauri::tray::TrayIcon,
+}
+
+/// Show or hide the menu bar marker that stays on while typed text is captured in all apps
+pub fn set_typing_indicator(app: &tauri::AppHandle, capturing: bool) {
+    INDICATORS.lock().unwrap().typing = capturing;
+    refresh_indicators(app);
+}
+
+/// Show or hide the menu bar marker of a high-fidelity recording
+pub fn set_recording_indicator(app: &tauri::AppHandle, recording: bool) {
+    INDICATORS.lock().unwrap().recording = recording;
+    refresh_indicators(app);
+}
+
+fn refresh_indicators(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let indicators = INDICATORS.lock().unwrap();
+    let mut titles = Vec::new();
+    let mut notes = Vec::new();
+    if indicators.recording {
+        titles.push("● REC");
+        notes.push("high-fidelity recording");
+    }
+    if indicators.typing {
+        titles.push("⌨");
+        notes.push("recording typed text");
+    }
+
+    let title = (!titles.is_empty()).then(|| titles.join(" "));
+    let tooltip = if notes.is_empty() {
+        "Observer - Activity Tracker".to_string()
+    } else {
+        format!("Observer - Activity Tracker ({})", notes.join(", "))
+    };
+    let _ = state.tray.set_title(title);
+    let _ = state.tray.set_tooltip(Some(tooltip));
 }
 
 /// Set window visibility flag (call this when window is shown/hidden from frontend)
 pub fn set_window_visible(visible: bool) {
     WINDOW_VISIBLE.store(visible, Ordering::SeqCst);
-    println!("Window visibility flag set to: {}", visible);
+    crate::logs::debug(&format!("[Tray] Window visibility flag set to: {}", visible));
 }
@@ -6,7 +6,7 @@ use tauri_plugin_updater::UpdaterExt;
 /// Apply post-update fixes and restart the application
 fn restart_app() {
     if let Ok(exe) = std::env::current_exe() {
-        println!("Applying post-update fixes...");
+        crate::logs::info("[Updater] Applying post-update fixes...");
 
         // Get the .app bundle path (go up from executable)
         // /Applications/Observer.app/Contents/MacOS/observer-desktop -> /Applications/Observer.app
@@ -21,25 +21,25 @@ fn restart_app() {
                 let app_path_str = app_path.to_string_lossy();
 
                 // Remove quarantine attribute
-                println!("Removing quarantine: xattr -cr {}", app_path_str);
+                crate::logs::info(&format!("[Updater] Removing quarantine: xattr -cr {}", app_path_str));
                 let _ = std::process::Command::new("xattr")
                     .args(["-cr", &app_path_str])
                     .output();
 
                 // Re-sign the app with ad-hoc signature
-                println!(
-                    "Re-signing app: codesign --force --deep --sign - {}",
+                crate::logs::info(&format!(
+                    "[Updater] Re-signing app: codesign --force --deep --sign - {}",
                     app_path_str
-                );
+                ));
                 let _ = std::process::Command::new("codesign")
                     .args(["--force", "--deep", "--sign", "-", &app_path_str])
                     .output();
 
-                println!("Post-update fixes applied!");
+                crate::logs::info("[Updater] Post-update fixes applied!");
             }
         }
 
-        println!("Restarting app from: {:?}", exe);
+        crate::logs::info(&format!("[Updater] Restarting app from: {:?}", exe));
 
         // Small delay to ensure signing completes
         std::thread::sleep(std::time::Duration::from_secs(1));
@@ -57,60 +57,60 @@ pub async fn check_for_updates(app: AppHandle) {
     // Wait a bit before checking to let app fully initialize
     tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
 
-    println!("=== Checking for updates ===");
-    println!("Current version: {}", env!("CARGO_PKG_VERSION"));
+    crate::logs::info("[Updater] === Checking for updates ===");
+    crate::logs::info(&format!("[Updater] Current version: {}", env!("CARGO_PKG_VERSION")));
 
     match app.updater() {
         Ok(updater) => {
-            println!("Updater initialized, fetching latest.json from GitHub...");
+            crate::logs::info("[Updater] Updater initialized, fetching latest.json from GitHub...");
             match updater.check().await {
                 Ok(Some(update)) => {
                     let version = update.version.clone();
-                    println!("UPDATE AVAILABLE: v{}", version);
-                    println!("Current: v{} -> New: v{}", env!("CARGO_PKG_VERSION"), version);
+                    crate::logs::info(&format!("[Updater] UPDATE AVAILABLE: v{}", version));
+                    crate::logs::info(&format!("[Updater] Current: v{} -> New: v{}", env!("CARGO_PKG_VERSION"), version));
 
                     // Show notification about update
                     if let Err(e) = show_update_notification(&app, &version).await {
-                        eprintln!("Failed to show update notification: {}", e);
+                        crate::logs::error(&format!("[Updater] Failed to show update notification: {}", e));
                     }
 
                     // Auto-download and install
-                    println!("Starting download...");
+                    crate::logs::info("[Updater] Starting download...");
                     match download_and_install(update).await {
                         Ok(_) => {
-                            println!("Update installed successfully!");
-                            println!("Restarting app to apply update...");
+                            crate::logs::info("[Updater] Update installed successfully!");
+                            crate::logs::info("[Updater] Restarting app to apply update...");
                             restart_app();
                         }
                         Err(e) => {
-                            eprintln!("FAILED to download/install update: {}", e);
-                            eprintln!("Please download manually from GitHub Releases");
+                            crate::logs::error(&format!("[Updater] FAILED to download/install update: {}", e));
+                            crate::logs::error("[Updater] Please download manually from GitHub Releases");
                         }
                     }
                 }
                 Ok(None) => {
-                    println!("App is up to date (v{})", env!("CARGO_PKG_VERSION"));
+                    crate::logs::info(&format!("[Updater] App is up to date (v{})", env!("CARGO_PKG_VERSION")));
                 }
                 Err(e) => {
-                    eprintln!("Failed to check for updates: {}", e);
+                    crate::logs::error(&format!("[Updater] Failed to check for updates: {}", e));
                     // Common errors:
                     // - Network error: can't reach github.com
                     // - Parse error: latest.json doesn't exist or is malformed
                     // - Signature mismatch: update signed with different key
                     if e.to_string().contains("404") || e.to_string().contains("Not Found") {
-                        eprintln!("Hint: No release found on GitHub. Check if releases are published.");
+                        crate::logs::error("[Updater] Hint: No release found on GitHub. Check if releases are published.");
                     } else if e.to_string().contains("signature") {
-                        eprintln!("Hint: Signature mismatch. The update may be signed with a different key.");
+                        crate::logs::error("[Updater] Hint: Signature mismatch. The update may be signed with a different key.");
                     }
                 }
             }
         }
         Err(e) => {
-            eprintln!("Updater not available: {}", e);
-            eprintln!("Hint: Make sure 'updater' plugin is configured in tauri.conf.json");
+            crate::logs::error(&format!("[Updater] Updater not available: {}", e));
+            crate::logs::error("[Updater] Hint: Make sure 'updater' plugin is configured in tauri.conf.json");
         }
     }
-    println!("=== Update check complete ===");
+    crate::logs::info("[Updater] === Update check complete ===");
 }
 
 /// Show notification about available update
@@ -149,16 +149,16 @@ async fn download_and_install(
                 if let Some(total) = content_length {
                     if total > 0 {
                         let percent = (current as f64 / total as f64) * 100.0;
-                        println!("Downloading update: {:.1}%", percent);
+                        crate::logs::debug(&format!("[Updater] Downloading update: {:.1}%", percent));
                     }
                 }
             },
             || {
-                println!("Download complete, preparing to install...");
+                crate::logs::info("[Updater] Download complete, preparing to install...");
             },
         )
         .await?;
 
-    println!("Update installed. Restart app to apply changes.");
+    crate::logs::info("[Updater] Update installed. Restart app to apply changes.");
     Ok(())
 }
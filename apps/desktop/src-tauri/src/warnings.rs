@@ -0,0 +1,241 @@
+// Active warnings for the UI's alerts area
+// Conditions that can lead to lost or missing data (buffer filling up, sync
+// failing, low disk, blocked AppleScript, missing permissions) are otherwise
+// only visible in logs.
+// Warnings are recomputed from the current state on every request; a dismissed
+// warning stays hidden until its condition clears, and shows again if it recurs.
+
+use crate::permissions::AllPermissions;
+use crate::sync::SyncFailure;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+pub const BUFFER_NEAR_FULL: &str = "buffer_near_full";
+pub const SYNC_FAILING: &str = "sync_failing";
+pub const DISK_LOW: &str = "disk_low";
+pub const APPLESCRIPT_DISABLED: &str = "applescript_disabled";
+pub const ACCESSIBILITY_MISSING: &str = "accessibility_permission_missing";
+pub const SCREEN_RECORDING_MISSING: &str = "screen_recording_permission_missing";
+
+static DISMISSED: Lazy<Mutex<Dismissals>> = Lazy::new(|| Mutex::new(Dismissals::default()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningSeverity {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// Stable identifier, used to dismiss the warning
+    pub id: String,
+    pub severity: WarningSeverity,
+    pub message: String,
+}
+
+impl Warning {
+    fn new(id: &str, severity: WarningSeverity, message: String) -> Self {
+        Self {
+            id: id.to_string(),
+            severity,
+            message,
+        }
+    }
+}
+
+/// State the warnings are derived from
+#[derive(Debug, Clone, Default)]
+pub struct WarningInputs {
+    pub buffered_events: usize,
+    pub sync_failure: Option<SyncFailure>,
+    pub disk_low: bool,
+    pub applescript_disabled: bool,
+    /// `None` where permissions don't apply (non-macOS)
+    pub permissions: Option<AllPermissions>,
+}
+
+/// Warnings for the current state, most severe first
+pub fn active_warnings(inputs: &WarningInputs, now: DateTime<Utc>) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    if inputs.buffered_events >= crate::BUFFER_WARNING_THRESHOLD {
+        warnings.push(Warning::new(
+            BUFFER_NEAR_FULL,
+            WarningSeverity::Critical,
+            format!(
+                "Event buffer is {}% full ({}/{} events). The oldest events will be dropped if it fills up.",
+                inputs.buffered_events * 100 / crate::MAX_BUFFER_SIZE,
+                inputs.buffered_events,
+                crate::MAX_BUFFER_SIZE
+            ),
+        ));
+    }
+
+    // Short outages are retried quietly; warn once they last as long as the first notification
+    if let Some(failure) = &inputs.sync_failure {
+        let minutes = (now - failure.since).num_minutes();
+        if minutes >= crate::sync::FAILURE_NOTIFY_AFTER_MINUTES[0] {
+            warnings.push(Warning::new(
+                SYNC_FAILING,
+                WarningSeverity::Warning,
+                format!(
+                    "Sync has been failing for {} minutes: {}. Events are kept locally until it recovers.",
+                    minutes, failure.error
+                ),
+            ));
+        }
+    }
+
+    if inputs.disk_low {
+        warnings.push(Warning::new(
+            DISK_LOW,
+            WarningSeverity::Warning,
+            "Disk space is low. Screenshots are paused until space is freed.".to_string(),
+        ));
+    }
+
+    if inputs.applescript_disabled {
+        warnings.push(Warning::new(
+            APPLESCRIPT_DISABLED,
+            WarningSeverity::Warning,
+            "AppleScript keeps failing, so browser, messenger and window details are paused. Retry once it is allowed.".to_string(),
+        ));
+    }
+
+    if let Some(permissions) = &inputs.permissions {
+        if !permissions.accessibility {
+            warnings.push(Warning::new(
+                ACCESSIBILITY_MISSING,
+                WarningSeverity::Critical,
+                "Accessibility permission is missing. Window titles and automation won't work.".to_string(),
+            ));
+        }
+        if !permissions.screen_recording {
+            warnings.push(Warning::new(
+                SCREEN_RECORDING_MISSING,
+                WarningSeverity::Warning,
+                "Screen Recording permission is missing. Screenshots can't be captured.".to_string(),
+            ));
+        }
+    }
+
+    warnings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    warnings
+}
+
+/// Dismissed warning ids, kept only while the warning is active
+#[derive(Debug, Default)]
+pub struct Dismissals {
+    ids: HashSet<String>,
+}
+
+impl Dismissals {
+    pub fn dismiss(&mut self, id: &str) {
+        self.ids.insert(id.to_string());
+    }
+
+    /// Drop dismissals whose condition cleared and hide the rest
+    pub fn visible(&mut self, active: Vec<Warning>) -> Vec<Warning> {
+        self.ids.retain(|id| active.iter().any(|warning| &warning.id == id));
+        active
+            .into_iter()
+            .filter(|warning| !self.ids.contains(&warning.id))
+            .collect()
+    }
+}
+
+/// Active, undismissed warnings for `inputs`
+pub fn current(inputs: &WarningInputs) -> Vec<Warning> {
+    DISMISSED.lock().unwrap().visible(active_warnings(inputs, Utc::now()))
+}
+
+/// Hide a warning until its condition clears
+pub fn dismiss(id: &str) {
+    DISMISSED.lock().unwrap().dismiss(id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn ids(warnings: &[Warning]) -> Vec<&str> {
+        warnings.iter().map(|warning| warning.id.as_str()).collect()
+    }
+
+    fn permissions(accessibility: bool, screen_recording: bool) -> AllPermissions {
+        AllPermissions {
+            accessibility,
+            screen_recording,
+            all_granted: accessibility && screen_recording,
+        }
+    }
+
+    #[test]
+    fn test_warnings_follow_state() {
+        let now = Utc::now();
+        let mut inputs = WarningInputs {
+            permissions: Some(permissions(true, true)),
+            ..WarningInputs::default()
+        };
+        assert!(active_warnings(&inputs, now).is_empty());
+
+        inputs.buffered_events = crate::BUFFER_WARNING_THRESHOLD - 1;
+        assert!(active_warnings(&inputs, now).is_empty());
+        inputs.buffered_events = crate::BUFFER_WARNING_THRESHOLD;
+        let warnings = active_warnings(&inputs, now);
+        assert_eq!(ids(&warnings), vec![BUFFER_NEAR_FULL]);
+        assert_eq!(warnings[0].severity, WarningSeverity::Critical);
+        assert!(warnings[0].message.contains("80% full"));
+
+        // A sync failure only warns once it has lasted a while
+        inputs.sync_failure = Some(SyncFailure {
+            since: now - Duration::minutes(1),
+            error: "connection refused".to_string(),
+        });
+        assert_eq!(ids(&active_warnings(&inputs, now)), vec![BUFFER_NEAR_FULL]);
+        inputs.sync_failure.as_mut().unwrap().since = now - Duration::minutes(10);
+        let warnings = active_warnings(&inputs, now);
+        assert_eq!(ids(&warnings), vec![BUFFER_NEAR_FULL, SYNC_FAILING]);
+        assert!(warnings[1].message.contains("connection refused"));
+
+        inputs.disk_low = true;
+        inputs.permissions = Some(permissions(false, false));
+        // Most severe first
+        assert_eq!(
+            ids(&active_warnings(&inputs, now)),
+            vec![BUFFER_NEAR_FULL, ACCESSIBILITY_MISSING, SYNC_FAILING, DISK_LOW, SCREEN_RECORDING_MISSING]
+        );
+
+        // Everything clears with the underlying state
+        let cleared = WarningInputs {
+            permissions: Some(permissions(true, true)),
+            ..WarningInputs::default()
+        };
+        assert!(active_warnings(&cleared, now).is_empty());
+        // Permissions aren't checked where they don't apply
+        assert!(active_warnings(&WarningInputs::default(), now).is_empty());
+    }
+
+    #[test]
+    fn test_dismissed_warning_returns_after_it_clears() {
+        let now = Utc::now();
+        let low_disk = WarningInputs {
+            disk_low: true,
+            ..WarningInputs::default()
+        };
+        let mut dismissals = Dismissals::default();
+
+        assert_eq!(ids(&dismissals.visible(active_warnings(&low_disk, now))), vec![DISK_LOW]);
+        dismissals.dismiss(DISK_LOW);
+        assert!(dismissals.visible(active_warnings(&low_disk, now)).is_empty());
+
+        // The condition clears, then comes back: the warning is shown again
+        assert!(dismissals.visible(active_warnings(&WarningInputs::default(), now)).is_empty());
+        assert_eq!(ids(&dismissals.visible(active_warnings(&low_disk, now))), vec![DISK_LOW]);
+    }
+}